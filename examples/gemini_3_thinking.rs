@@ -3,7 +3,8 @@ use rainy_sdk::{
     error::RainyError,
     models::{
         model_constants::GOOGLE_GEMINI_3_PRO, ChatCompletionRequest, ChatMessage, ContentPart,
-        EnhancedChatMessage, FunctionDefinition, ThinkingConfig, ThinkingLevel, Tool, ToolType,
+        EnhancedChatMessage, FunctionDefinition, OpenAIChatCompletionRequest, ThinkingConfig,
+        ThinkingLevel, Tool, ToolType,
     },
 };
 use serde_json::json;
@@ -140,6 +141,19 @@ async fn main() -> Result<(), RainyError> {
         enhanced_messages[1].parts.len()
     );
 
+    // Enhanced history can be sent through the OpenAI-compatible endpoint directly.
+    let request =
+        OpenAIChatCompletionRequest::from_enhanced(GOOGLE_GEMINI_3_PRO, &enhanced_messages)
+            .with_thinking_config(ThinkingConfig::gemini_3(ThinkingLevel::High, true));
+    match client.create_openai_chat_completion(request).await {
+        Ok(response) => {
+            if let Some(usage) = &response.usage {
+                println!("Thinking tokens: {:?}", usage.thoughts_token_count());
+            }
+        }
+        Err(e) => println!("Enhanced request failed: {e}"),
+    }
+
     // Example 5: Model validation
     println!("\n5. Model capability validation:");
 
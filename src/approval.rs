@@ -0,0 +1,279 @@
+//! Human-in-the-loop approval of tool calls before they run.
+//!
+//! When the model returns `tool_calls`, [`ToolApprovals::new`] sorts them by an
+//! [`ApprovalPolicy`]: tools the policy trusts are approved right away, the rest wait for an
+//! operator. Each waiting [`PendingApproval`] carries the parsed arguments; the operator
+//! answers with an [`ApprovalDecision`] either through an [`ApprovalHook`] or by calling
+//! [`ToolApprovals::resolve`]. The whole state serializes with serde, so the decision can be
+//! made later, in another process.
+//!
+//! [`Conversation::request_approvals`](crate::Conversation::request_approvals) keeps the queue
+//! with the conversation and [`Conversation::take_approved_calls`](crate::Conversation::take_approved_calls)
+//! applies the outcome to the history.
+
+use crate::error::{RainyError, Result};
+use crate::models::{OpenAIChatMessage, OpenAIToolCall};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+
+/// Which tools run without an operator's approval.
+///
+/// By default every tool needs approval. Tools listed with
+/// [`with_required`](Self::with_required) always need it, even under
+/// [`auto_approve_all`](Self::auto_approve_all).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApprovalPolicy {
+    #[serde(default)]
+    approve_all: bool,
+    #[serde(default)]
+    auto_approve: BTreeSet<String>,
+    #[serde(default)]
+    required: BTreeSet<String>,
+}
+
+impl ApprovalPolicy {
+    /// Creates a policy that asks for approval of every tool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a policy that approves every tool except those marked as required.
+    pub fn auto_approve_all() -> Self {
+        Self {
+            approve_all: true,
+            ..Self::default()
+        }
+    }
+
+    /// Approves calls to `name` automatically.
+    pub fn with_auto_approve(mut self, name: impl Into<String>) -> Self {
+        self.auto_approve.insert(name.into());
+        self
+    }
+
+    /// Always asks for approval of calls to `name`, e.g. destructive tools.
+    pub fn with_required(mut self, name: impl Into<String>) -> Self {
+        self.required.insert(name.into());
+        self
+    }
+
+    /// Returns `true` when calls to `name` wait for an operator.
+    pub fn requires_approval(&self, name: &str) -> bool {
+        if self.required.contains(name) {
+            return true;
+        }
+        !(self.approve_all || self.auto_approve.contains(name))
+    }
+}
+
+/// An operator's answer to a pending tool call.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    /// Run the call as the model requested it.
+    Approve,
+    /// Do not run the call; `reason` is sent back to the model as the tool result.
+    Deny {
+        /// Why the call was denied.
+        reason: String,
+    },
+    /// Run the call with replaced arguments.
+    Edit {
+        /// The arguments to use instead.
+        arguments: Value,
+    },
+}
+
+/// Where a tool call stands in the approval flow.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    /// Waiting for an operator.
+    Pending,
+    /// Approved by the policy without asking.
+    AutoApproved,
+    /// Approved by an operator.
+    Approved,
+    /// Approved by an operator with edited arguments.
+    Edited,
+    /// Denied by an operator.
+    Denied {
+        /// Why the call was denied.
+        reason: String,
+    },
+}
+
+impl ApprovalStatus {
+    /// Returns `true` when the call may run.
+    pub fn is_approved(&self) -> bool {
+        matches!(self, Self::AutoApproved | Self::Approved | Self::Edited)
+    }
+}
+
+/// A tool call in the approval flow, with its parsed arguments.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PendingApproval {
+    /// The call as it will run (arguments reflect any edit).
+    pub tool_call: OpenAIToolCall,
+    /// Parsed arguments; arguments that are not valid JSON are kept as a string.
+    pub arguments: Value,
+    /// Current status.
+    pub status: ApprovalStatus,
+}
+
+impl PendingApproval {
+    fn new(tool_call: OpenAIToolCall, status: ApprovalStatus) -> Self {
+        let raw = tool_call.function.arguments.trim();
+        let arguments = if raw.is_empty() {
+            json!({})
+        } else {
+            serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+        };
+        Self {
+            tool_call,
+            arguments,
+            status,
+        }
+    }
+
+    fn apply(&mut self, decision: ApprovalDecision) {
+        self.status = match decision {
+            ApprovalDecision::Approve => ApprovalStatus::Approved,
+            ApprovalDecision::Deny { reason } => ApprovalStatus::Denied { reason },
+            ApprovalDecision::Edit { arguments } => {
+                self.tool_call.function.arguments = arguments.to_string();
+                self.arguments = arguments;
+                ApprovalStatus::Edited
+            }
+        };
+    }
+
+    /// Tool call ID.
+    pub fn id(&self) -> &str {
+        &self.tool_call.id
+    }
+
+    /// Function name.
+    pub fn name(&self) -> &str {
+        &self.tool_call.function.name
+    }
+}
+
+/// Decides on pending tool calls, e.g. by prompting an operator.
+pub trait ApprovalHook {
+    /// Returns the decision for one pending call.
+    fn review(&self, pending: &PendingApproval) -> ApprovalDecision;
+}
+
+impl<F> ApprovalHook for F
+where
+    F: Fn(&PendingApproval) -> ApprovalDecision,
+{
+    fn review(&self, pending: &PendingApproval) -> ApprovalDecision {
+        self(pending)
+    }
+}
+
+/// Approval state for the tool calls of one assistant message.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ToolApprovals {
+    calls: Vec<PendingApproval>,
+}
+
+impl ToolApprovals {
+    /// Sorts `tool_calls` into auto-approved and pending calls according to `policy`.
+    pub fn new(tool_calls: &[OpenAIToolCall], policy: &ApprovalPolicy) -> Self {
+        Self {
+            calls: tool_calls
+                .iter()
+                .map(|call| {
+                    let status = if policy.requires_approval(&call.function.name) {
+                        ApprovalStatus::Pending
+                    } else {
+                        ApprovalStatus::AutoApproved
+                    };
+                    PendingApproval::new(call.clone(), status)
+                })
+                .collect(),
+        }
+    }
+
+    /// Every call, in the order the model made them.
+    pub fn calls(&self) -> &[PendingApproval] {
+        &self.calls
+    }
+
+    /// Calls still waiting for a decision.
+    pub fn pending(&self) -> impl Iterator<Item = &PendingApproval> {
+        self.calls
+            .iter()
+            .filter(|call| call.status == ApprovalStatus::Pending)
+    }
+
+    /// Returns `true` once every call has a decision.
+    pub fn is_resolved(&self) -> bool {
+        self.pending().next().is_none()
+    }
+
+    /// Records the decision for the call with `tool_call_id`.
+    ///
+    /// Fails with `UNKNOWN_TOOL_CALL` for an unknown ID and `APPROVAL_ALREADY_RESOLVED` when
+    /// the call already has a decision.
+    pub fn resolve(&mut self, tool_call_id: &str, decision: ApprovalDecision) -> Result<()> {
+        let call = self
+            .calls
+            .iter_mut()
+            .find(|call| call.tool_call.id == tool_call_id)
+            .ok_or_else(|| RainyError::InvalidRequest {
+                code: "UNKNOWN_TOOL_CALL".to_string(),
+                message: format!("no tool call with id '{tool_call_id}' awaits approval"),
+                details: None,
+            })?;
+        if call.status != ApprovalStatus::Pending {
+            return Err(RainyError::InvalidRequest {
+                code: "APPROVAL_ALREADY_RESOLVED".to_string(),
+                message: format!("tool call '{tool_call_id}' is already {:?}", call.status),
+                details: None,
+            });
+        }
+        call.apply(decision);
+        Ok(())
+    }
+
+    /// Asks `hook` for a decision on every pending call.
+    pub fn review(&mut self, hook: &impl ApprovalHook) {
+        for call in self
+            .calls
+            .iter_mut()
+            .filter(|call| call.status == ApprovalStatus::Pending)
+        {
+            let decision = hook.review(call);
+            call.apply(decision);
+        }
+    }
+
+    /// Calls that may run, with edited arguments applied.
+    pub fn approved_calls(&self) -> Vec<OpenAIToolCall> {
+        self.calls
+            .iter()
+            .filter(|call| call.status.is_approved())
+            .map(|call| call.tool_call.clone())
+            .collect()
+    }
+
+    /// Tool messages telling the model which calls were denied and why.
+    pub fn denial_messages(&self) -> Vec<OpenAIChatMessage> {
+        self.calls
+            .iter()
+            .filter_map(|call| match &call.status {
+                ApprovalStatus::Denied { reason } => Some(OpenAIChatMessage::tool(
+                    call.tool_call.id.clone(),
+                    json!({ "error": "tool call denied by operator", "reason": reason })
+                        .to_string(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,496 @@
+//! Batch execution of chat completion requests with bounded concurrency.
+//!
+//! [`BatchRunner`] sends many [`OpenAIChatCompletionRequest`]s through a [`RainyClient`],
+//! at most `concurrency` at a time. Every call goes through the client's normal path, so the
+//! rate limiter, budget, ledger and thought-signature handling all apply.
+//!
+//! Input and output use the OpenAI batch JSONL format: each input line carries a
+//! `custom_id` and a chat completion `body`, and each output line carries the same
+//! `custom_id` with either a `response` or an `error`. With a checkpoint file, completed
+//! `custom_id`s are recorded as they finish so an interrupted run resumes where it stopped.
+//! [`BatchStats`] aggregates credits, tokens and latency from each call's
+//! [`RequestMetadata`].
+
+use crate::client::RainyClient;
+use crate::error::{RainyError, Result};
+use crate::models::{OpenAIChatCompletionRequest, OpenAIChatCompletionResponse, RequestMetadata};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Default number of requests in flight at once.
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 8;
+
+const CHAT_COMPLETIONS_URLS: [&str; 3] = [
+    "/v1/chat/completions",
+    "/api/v1/chat/completions",
+    "/chat/completions",
+];
+
+fn default_method() -> String {
+    "POST".to_string()
+}
+
+fn default_url() -> String {
+    CHAT_COMPLETIONS_URLS[0].to_string()
+}
+
+/// One input line in the OpenAI batch format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchRequestLine {
+    /// Caller-chosen identifier, echoed in the result line.
+    pub custom_id: String,
+    /// HTTP method; only `POST` is supported.
+    #[serde(default = "default_method")]
+    pub method: String,
+    /// Target endpoint; only chat completions are supported.
+    #[serde(default = "default_url")]
+    pub url: String,
+    /// The chat completion request.
+    pub body: OpenAIChatCompletionRequest,
+}
+
+impl BatchRequestLine {
+    /// Creates a chat completion line.
+    pub fn new(custom_id: impl Into<String>, body: OpenAIChatCompletionRequest) -> Self {
+        Self {
+            custom_id: custom_id.into(),
+            method: default_method(),
+            url: default_url(),
+            body,
+        }
+    }
+
+    fn check_target(&self) -> Result<()> {
+        if !self.method.eq_ignore_ascii_case("POST")
+            || !CHAT_COMPLETIONS_URLS.contains(&self.url.as_str())
+        {
+            return Err(RainyError::InvalidRequest {
+                code: "UNSUPPORTED_BATCH_ENDPOINT".to_string(),
+                message: format!(
+                    "{} {} is not supported; batches run chat completions only",
+                    self.method, self.url
+                ),
+                details: None,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Successful response of one batch line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResponse {
+    /// HTTP status code.
+    pub status_code: u16,
+    /// Rainy request ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// The chat completion.
+    pub body: OpenAIChatCompletionResponse,
+}
+
+/// Error of one batch line.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BatchError {
+    /// Machine-readable error code.
+    pub code: String,
+    /// Human-readable error message.
+    pub message: String,
+    /// Whether the call may succeed if retried; retryable lines are not checkpointed.
+    #[serde(default)]
+    pub retryable: bool,
+}
+
+impl From<&RainyError> for BatchError {
+    fn from(error: &RainyError) -> Self {
+        Self {
+            code: error.code().unwrap_or("ERROR").to_string(),
+            message: error.to_string(),
+            retryable: error.is_retryable(),
+        }
+    }
+}
+
+/// One output line in the OpenAI batch format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResultLine {
+    /// The `custom_id` of the input line.
+    pub custom_id: String,
+    /// The response, when the call succeeded.
+    pub response: Option<BatchResponse>,
+    /// The error, when the call failed.
+    pub error: Option<BatchError>,
+    /// Credits charged for the call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credits_charged: Option<f64>,
+    /// Call latency in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+}
+
+impl BatchResultLine {
+    fn from_result(
+        custom_id: String,
+        result: Result<(OpenAIChatCompletionResponse, RequestMetadata)>,
+        elapsed_ms: u64,
+    ) -> Self {
+        match result {
+            Ok((body, metadata)) => Self {
+                custom_id,
+                credits_charged: metadata.rainy_credits_charged.or(metadata.credits_used),
+                latency_ms: Some(metadata.response_time.unwrap_or(elapsed_ms)),
+                response: Some(BatchResponse {
+                    status_code: 200,
+                    request_id: metadata.request_id,
+                    body,
+                }),
+                error: None,
+            },
+            Err(error) => Self {
+                custom_id,
+                response: None,
+                error: Some(BatchError::from(&error)),
+                credits_charged: None,
+                latency_ms: Some(elapsed_ms),
+            },
+        }
+    }
+
+    /// Returns `true` when the call succeeded.
+    pub fn is_success(&self) -> bool {
+        self.response.is_some()
+    }
+
+    /// Returns `true` when the line needs no further attempt: it succeeded or failed with a
+    /// non-retryable error.
+    pub fn is_final(&self) -> bool {
+        self.error.as_ref().is_none_or(|error| !error.retryable)
+    }
+}
+
+/// Aggregate statistics of a batch run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchStats {
+    /// Lines executed in this run.
+    pub completed: usize,
+    /// Lines that succeeded.
+    pub succeeded: usize,
+    /// Lines that failed.
+    pub failed: usize,
+    /// Lines skipped because the checkpoint already recorded them.
+    pub skipped: usize,
+    /// Credits charged across successful calls.
+    pub credits_charged: f64,
+    /// Prompt tokens across successful calls.
+    pub prompt_tokens: u64,
+    /// Completion tokens across successful calls.
+    pub completion_tokens: u64,
+    /// Latencies of executed calls, in completion order.
+    pub latencies_ms: Vec<u64>,
+    /// Wall-clock duration of the run.
+    pub wall_time_ms: u64,
+}
+
+impl BatchStats {
+    fn record(&mut self, line: &BatchResultLine) {
+        self.completed += 1;
+        match &line.response {
+            Some(response) => {
+                self.succeeded += 1;
+                if let Some(usage) = &response.body.usage {
+                    self.prompt_tokens += u64::from(usage.prompt_tokens);
+                    self.completion_tokens += u64::from(usage.completion_tokens);
+                }
+            }
+            None => self.failed += 1,
+        }
+        self.credits_charged += line.credits_charged.unwrap_or(0.0);
+        self.latencies_ms.extend(line.latency_ms);
+    }
+
+    /// Mean call latency in milliseconds.
+    pub fn mean_latency_ms(&self) -> Option<f64> {
+        (!self.latencies_ms.is_empty())
+            .then(|| self.latencies_ms.iter().sum::<u64>() as f64 / self.latencies_ms.len() as f64)
+    }
+
+    /// Latency percentile (`0.0..=1.0`, nearest rank) in milliseconds.
+    pub fn latency_percentile_ms(&self, percentile: f64) -> Option<u64> {
+        let mut sorted = self.latencies_ms.clone();
+        sorted.sort_unstable();
+        let last = sorted.len().checked_sub(1)?;
+        let rank = (percentile.clamp(0.0, 1.0) * last as f64).round() as usize;
+        sorted.get(rank).copied()
+    }
+}
+
+/// Results of an in-memory batch run.
+#[derive(Debug, Clone, Default)]
+pub struct BatchReport {
+    /// Result lines in input order.
+    pub results: Vec<BatchResultLine>,
+    /// Aggregate statistics.
+    pub stats: BatchStats,
+}
+
+/// Completed `custom_id`s, appended one per line.
+struct Checkpoint {
+    done: HashSet<String>,
+    writer: Option<BufWriter<File>>,
+}
+
+impl Checkpoint {
+    fn open(path: Option<&Path>) -> Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self {
+                done: HashSet::new(),
+                writer: None,
+            });
+        };
+        let done = match File::open(path) {
+            Ok(file) => BufReader::new(file)
+                .lines()
+                .collect::<std::io::Result<Vec<_>>>()?
+                .into_iter()
+                .filter(|line| !line.trim().is_empty())
+                .collect(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => HashSet::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            done,
+            writer: Some(BufWriter::new(file)),
+        })
+    }
+
+    fn mark(&mut self, custom_id: &str) -> Result<()> {
+        if let Some(writer) = &mut self.writer {
+            writeln!(writer, "{custom_id}")?;
+            writer.flush()?;
+        }
+        self.done.insert(custom_id.to_string());
+        Ok(())
+    }
+}
+
+/// Runs chat completion requests with bounded concurrency.
+///
+/// Created with [`RainyClient::batch_runner`].
+#[derive(Debug)]
+pub struct BatchRunner<'a> {
+    client: &'a RainyClient,
+    concurrency: usize,
+    checkpoint: Option<PathBuf>,
+    error_output: Option<PathBuf>,
+}
+
+impl<'a> BatchRunner<'a> {
+    /// Creates a runner with [`DEFAULT_BATCH_CONCURRENCY`] and no checkpoint.
+    pub fn new(client: &'a RainyClient) -> Self {
+        Self {
+            client,
+            concurrency: DEFAULT_BATCH_CONCURRENCY,
+            checkpoint: None,
+            error_output: None,
+        }
+    }
+
+    /// Sets the maximum number of requests in flight.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Records finished `custom_id`s in `path` and skips them on later runs.
+    ///
+    /// Successes and non-retryable failures count as finished; lines that failed with a
+    /// retryable error (rate limits, timeouts, server errors) run again on the next run.
+    pub fn with_checkpoint(mut self, path: impl Into<PathBuf>) -> Self {
+        self.checkpoint = Some(path.into());
+        self
+    }
+
+    /// Writes failed lines to a separate JSONL file instead of the results file.
+    pub fn with_error_output(mut self, path: impl Into<PathBuf>) -> Self {
+        self.error_output = Some(path.into());
+        self
+    }
+
+    async fn execute(&self, line: BatchRequestLine) -> BatchResultLine {
+        let start = Instant::now();
+        let result = match line.check_target() {
+            Ok(()) => {
+                self.client
+                    .openai_chat_completion_with_metadata(line.body)
+                    .await
+            }
+            Err(error) => Err(error),
+        };
+        BatchResultLine::from_result(line.custom_id, result, start.elapsed().as_millis() as u64)
+    }
+
+    /// Runs lines and invokes `on_result` as each one finishes, skipping checkpointed ids.
+    async fn drive<F>(&self, lines: Vec<BatchRequestLine>, mut on_result: F) -> Result<BatchStats>
+    where
+        F: FnMut(&BatchResultLine) -> Result<()>,
+    {
+        let started = Instant::now();
+        check_unique_ids(&lines)?;
+        let mut checkpoint = Checkpoint::open(self.checkpoint.as_deref())?;
+        let mut stats = BatchStats::default();
+
+        let total = lines.len();
+        let pending: Vec<BatchRequestLine> = lines
+            .into_iter()
+            .filter(|line| !checkpoint.done.contains(&line.custom_id))
+            .collect();
+        stats.skipped = total - pending.len();
+
+        let mut results = futures::stream::iter(pending)
+            .map(|line| self.execute(line))
+            .buffer_unordered(self.concurrency);
+        while let Some(result) = results.next().await {
+            on_result(&result)?;
+            if result.is_final() {
+                checkpoint.mark(&result.custom_id)?;
+            }
+            stats.record(&result);
+        }
+        stats.wall_time_ms = started.elapsed().as_millis() as u64;
+        Ok(stats)
+    }
+
+    /// Runs batch lines and returns their results in input order.
+    ///
+    /// Fails with `DUPLICATE_CUSTOM_ID` before any request is sent when two lines share a
+    /// `custom_id`.
+    pub async fn run(&self, lines: Vec<BatchRequestLine>) -> Result<BatchReport> {
+        let order: HashMap<String, usize> = lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| (line.custom_id.clone(), index))
+            .collect();
+        let mut results = Vec::with_capacity(lines.len());
+        let stats = self
+            .drive(lines, |result| {
+                results.push(result.clone());
+                Ok(())
+            })
+            .await?;
+        results.sort_by_key(|result| order.get(&result.custom_id).copied());
+        Ok(BatchReport { results, stats })
+    }
+
+    /// Runs plain requests, tagging them `request-0`, `request-1`, ... in order.
+    pub async fn run_requests(
+        &self,
+        requests: Vec<OpenAIChatCompletionRequest>,
+    ) -> Result<BatchReport> {
+        let lines = requests
+            .into_iter()
+            .enumerate()
+            .map(|(index, request)| BatchRequestLine::new(format!("request-{index}"), request))
+            .collect();
+        self.run(lines).await
+    }
+
+    /// Runs an input JSONL file and appends result lines to `output` as calls finish.
+    ///
+    /// Malformed input lines and duplicate `custom_id`s fail the run before any request is
+    /// sent. Results are appended, so re-running with the same checkpoint and output completes
+    /// an interrupted batch. With a checkpoint, retryable failures go only to the error output
+    /// (when set), since the next run retries them; the results file then holds one final line
+    /// per `custom_id`.
+    pub async fn run_jsonl(
+        &self,
+        input: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+    ) -> Result<BatchStats> {
+        let lines = read_batch_lines(input)?;
+        let open = |path: &Path| -> Result<BufWriter<File>> {
+            Ok(BufWriter::new(
+                OpenOptions::new().create(true).append(true).open(path)?,
+            ))
+        };
+        let mut results = open(output.as_ref())?;
+        let mut errors = self.error_output.as_deref().map(open).transpose()?;
+        let resumable = self.checkpoint.is_some();
+
+        self.drive(lines, |result| {
+            let writer = match (&mut errors, result.is_success()) {
+                (Some(errors), false) => errors,
+                (None, false) if resumable && !result.is_final() => return Ok(()),
+                _ => &mut results,
+            };
+            writeln!(writer, "{}", serde_json::to_string(result)?)?;
+            writer.flush()?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+fn check_unique_ids(lines: &[BatchRequestLine]) -> Result<()> {
+    let mut seen = HashSet::with_capacity(lines.len());
+    match lines
+        .iter()
+        .find(|line| !seen.insert(line.custom_id.as_str()))
+    {
+        Some(line) => Err(RainyError::InvalidRequest {
+            code: "DUPLICATE_CUSTOM_ID".to_string(),
+            message: format!("custom_id {} appears more than once", line.custom_id),
+            details: None,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Reads OpenAI batch-format input lines from a JSONL file.
+pub fn read_batch_lines(path: impl AsRef<Path>) -> Result<Vec<BatchRequestLine>> {
+    let raw = std::fs::read_to_string(path)?;
+    raw.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            serde_json::from_str(line).map_err(|err| RainyError::Serialization {
+                message: format!("invalid batch line {}", number + 1),
+                source_error: Some(err.to_string()),
+            })
+        })
+        .collect()
+}
+
+impl RainyClient {
+    /// Creates a [`BatchRunner`] that sends requests through this client.
+    ///
+    /// # Returns
+    ///
+    /// A runner with the default concurrency and no checkpoint.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use rainy_sdk::RainyClient;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = RainyClient::with_api_key("your-api-key")?;
+    ///
+    /// let stats = client
+    ///     .batch_runner()
+    ///     .with_concurrency(16)
+    ///     .with_checkpoint("batch.checkpoint")
+    ///     .run_jsonl("requests.jsonl", "results.jsonl")
+    ///     .await?;
+    /// println!("{} ok, {} failed, {:.2} credits", stats.succeeded, stats.failed, stats.credits_charged);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn batch_runner(&self) -> BatchRunner<'_> {
+        BatchRunner::new(self)
+    }
+}
@@ -0,0 +1,133 @@
+//! Minimal argument parsing for the `rainy` binary.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+
+/// Options that take a value (`--name value` or `--name=value`).
+const VALUE_OPTIONS: &[&str] = &[
+    "budget",
+    "days",
+    "depth",
+    "email",
+    "input-modality",
+    "listen",
+    "max-completion-price",
+    "max-output-tokens",
+    "max-prompt-price",
+    "max-sources",
+    "max-tokens",
+    "min-context",
+    "model",
+    "output-modality",
+    "provider",
+    "reasoning-effort",
+    "system",
+    "temperature",
+    "type",
+    "user-budget",
+];
+
+/// Options that take no value.
+const FLAGS: &[&str] = &[
+    "detailed",
+    "help",
+    "json",
+    "stream",
+    "structured-output",
+    "tools",
+    "version",
+];
+
+/// Parsed command line: positionals in order, plus options by name.
+#[derive(Debug, Default)]
+pub struct Args {
+    positionals: VecDeque<String>,
+    values: HashMap<String, Vec<String>>,
+    flags: HashSet<String>,
+}
+
+impl Args {
+    /// Parses `args` (without the program name).
+    pub fn parse<I>(args: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                parsed.positionals.extend(args.by_ref());
+                break;
+            }
+            if arg == "-h" {
+                parsed.flags.insert("help".to_string());
+                continue;
+            }
+            let Some(option) = arg.strip_prefix("--") else {
+                parsed.positionals.push_back(arg);
+                continue;
+            };
+            let (name, inline) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (option, None),
+            };
+            if VALUE_OPTIONS.contains(&name) {
+                let value = match inline {
+                    Some(value) => value,
+                    None => args
+                        .next()
+                        .ok_or_else(|| format!("--{name} needs a value"))?,
+                };
+                parsed
+                    .values
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(value);
+            } else if FLAGS.contains(&name) && inline.is_none() {
+                parsed.flags.insert(name.to_string());
+            } else {
+                return Err(format!("unknown option '--{name}'"));
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Removes and returns the next positional argument.
+    pub fn next_positional(&mut self) -> Option<String> {
+        self.positionals.pop_front()
+    }
+
+    /// Removes and returns the remaining positional arguments.
+    pub fn rest(&mut self) -> Vec<String> {
+        self.positionals.drain(..).collect()
+    }
+
+    /// Returns `true` when flag `--name` was given.
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+
+    /// The last value given for `--name`.
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values
+            .get(name)
+            .and_then(|values| values.last())
+            .map(String::as_str)
+    }
+
+    /// Every value given for `--name`.
+    pub fn values(&self, name: &str) -> Vec<String> {
+        self.values.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Parses the value of `--name`.
+    pub fn parse_value<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.value(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("invalid value '{value}' for --{name}"))
+            })
+            .transpose()
+    }
+}
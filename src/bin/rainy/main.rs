@@ -0,0 +1,756 @@
+//! `rainy`: the Rainy API from the command line.
+//!
+//! API-key commands use [`RainyClient`] with `RAINY_API_KEY`; account commands use
+//! [`RainySessionClient`] with the tokens saved by `rainy login` (or `RAINY_ACCESS_TOKEN`).
+//! `RAINY_BASE_URL` overrides the API host for both. `--json` switches every command to
+//! machine-readable output.
+
+mod args;
+mod store;
+
+use args::Args;
+use futures::StreamExt;
+use rainy_sdk::{
+    AuthConfig, Budget, ChatCompletionRequest, ChatMessage, ModelCatalogItem,
+    ModelSelectionCriteria, ProxyLogEntry, RainyClient, RainyError, RainyProxy, RainySessionClient,
+    RequestMetadata, ResearchConfig, ResearchDepth, ResponsesRequest, SessionConfig, TextDelta,
+    DEFAULT_PROXY_ADDR,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::io::{IsTerminal, Read, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: rainy <command> [options]
+
+Commands:
+  chat [PROMPT]                 Chat completion (PROMPT or stdin)
+      --model M --system S --temperature T --max-tokens N --stream
+  responses [INPUT]             Responses API completion (INPUT or stdin)
+      --model M --max-output-tokens N --reasoning-effort E --stream
+  models list                   Available models by provider
+  models catalog                Model catalog with context and pricing
+  models select                 Catalog entries matching criteria
+      --input-modality X --output-modality X --tools --structured-output
+      --min-context N --max-prompt-price P --max-completion-price P --provider P
+  search QUERY                  Web search
+      --depth basic|advanced --max-sources N
+  health [--detailed]           API health
+  login [--email E]             Sign in (password from RAINY_PASSWORD or stdin, not echoed)
+  keys list                     List API keys
+  keys create NAME [--type T]   Create an API key (standard or platform)
+  keys delete ID                Delete an API key
+  usage [--days N]              Credit balance and usage statistics
+  proxy                         Local OpenAI-compatible server (/v1/chat/completions,
+                                /v1/responses, /v1/models)
+      --listen ADDR --budget CREDITS --user-budget CREDITS
+
+Options:
+  --json                        Machine-readable JSON output
+  -h, --help                    Show this help
+  --version                     Show the version
+
+Environment:
+  RAINY_API_KEY                 API key for chat, responses, models, search and health
+  RAINY_BASE_URL                API host (default: the Rainy API)
+  RAINY_MODEL                   Default model for chat and responses
+  RAINY_ACCESS_TOKEN            Session token for keys and usage (instead of `rainy login`)
+  RAINY_CONFIG_DIR              Where `rainy login` saves the session
+  RAINY_PROXY_TOKEN             Bearer token local clients must send to `rainy proxy`
+";
+
+/// Why a command failed; usage errors exit with 2, everything else with 1.
+#[derive(Debug)]
+enum CliError {
+    Usage(String),
+    Failed(String),
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Usage(message) | Self::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        Self::Usage(message)
+    }
+}
+
+impl From<RainyError> for CliError {
+    fn from(err: RainyError) -> Self {
+        match err.code() {
+            Some(code) => Self::Failed(format!("{err} [{code}]")),
+            None => Self::Failed(err.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Failed(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for CliError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Failed(err.to_string())
+    }
+}
+
+type CliResult<T = ()> = std::result::Result<T, CliError>;
+
+/// Human-readable or JSON output.
+#[derive(Debug, Clone, Copy)]
+struct Output {
+    json: bool,
+}
+
+impl Output {
+    /// Prints `value` as pretty JSON, or calls `human` to print it for people.
+    fn emit<T: Serialize + ?Sized>(&self, value: &T, human: impl FnOnce(&T)) -> CliResult {
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(value)?);
+        } else {
+            human(value);
+        }
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let result = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => run(args).await,
+        Err(message) => Err(CliError::Usage(message)),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err @ CliError::Usage(_)) => {
+            eprintln!("error: {err}\n\nRun `rainy --help` for usage.");
+            ExitCode::from(2)
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(mut args: Args) -> CliResult {
+    if args.flag("version") {
+        println!("rainy {}", rainy_sdk::VERSION);
+        return Ok(());
+    }
+    let command = args.next_positional();
+    if args.flag("help") || command.as_deref() == Some("help") {
+        print!("{USAGE}");
+        return Ok(());
+    }
+    let out = Output {
+        json: args.flag("json"),
+    };
+    match command.as_deref() {
+        Some("chat") => chat(args, out).await,
+        Some("responses") => responses(args, out).await,
+        Some("models") => models(args, out).await,
+        Some("search") => search(args, out).await,
+        Some("health") => health(args, out).await,
+        Some("login") => login(args, out).await,
+        Some("keys") => keys(args, out).await,
+        Some("usage") => usage(args, out).await,
+        Some("proxy") => proxy(args, out).await,
+        Some(other) => Err(CliError::Usage(format!("unknown command '{other}'"))),
+        None => Err(CliError::Usage("no command given".to_string())),
+    }
+}
+
+fn base_url() -> Option<String> {
+    std::env::var("RAINY_BASE_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+}
+
+fn api_client() -> CliResult<RainyClient> {
+    let api_key = std::env::var("RAINY_API_KEY")
+        .ok()
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| CliError::Usage("RAINY_API_KEY is not set".to_string()))?;
+    let mut config = AuthConfig::new(api_key);
+    if let Some(url) = base_url() {
+        config = config.with_base_url(url);
+    }
+    Ok(RainyClient::with_config(config)?)
+}
+
+fn session_client(stored_base_url: Option<&str>) -> CliResult<RainySessionClient> {
+    let mut config = SessionConfig::new();
+    if let Some(url) = base_url().or_else(|| stored_base_url.map(str::to_string)) {
+        config = config.with_base_url(url);
+    }
+    Ok(RainySessionClient::with_config(config)?)
+}
+
+/// Runs `call` with the session from `RAINY_ACCESS_TOKEN` or `rainy login`, refreshing a
+/// saved session once if the access token has expired.
+async fn with_session<T, F, Fut>(call: F) -> CliResult<T>
+where
+    F: Fn(RainySessionClient) -> Fut,
+    Fut: Future<Output = rainy_sdk::Result<T>>,
+{
+    if let Ok(token) = std::env::var("RAINY_ACCESS_TOKEN") {
+        let mut client = session_client(None)?;
+        client.set_access_token(token);
+        return Ok(call(client).await?);
+    }
+    let mut stored = store::load().ok_or_else(|| {
+        CliError::Usage("not logged in; run `rainy login` or set RAINY_ACCESS_TOKEN".to_string())
+    })?;
+    let mut client = session_client(Some(&stored.base_url))?;
+    client.set_access_token(stored.access_token.clone());
+    match call(client.clone()).await {
+        Err(err) if is_unauthorized(&err) => {
+            let refreshed = client.refresh(&stored.refresh_token).await.map_err(|_| {
+                CliError::Failed("session expired; run `rainy login` again".to_string())
+            })?;
+            stored.access_token = refreshed.access_token;
+            stored.refresh_token = refreshed.refresh_token;
+            store::save(&stored)?;
+            Ok(call(client).await?)
+        }
+        result => Ok(result?),
+    }
+}
+
+fn is_unauthorized(err: &RainyError) -> bool {
+    matches!(
+        err,
+        RainyError::Authentication { .. }
+            | RainyError::Api {
+                status_code: 401,
+                ..
+            }
+    )
+}
+
+fn model(args: &Args) -> CliResult<String> {
+    args.value("model")
+        .map(str::to_string)
+        .or_else(|| std::env::var("RAINY_MODEL").ok())
+        .filter(|model| !model.is_empty())
+        .ok_or_else(|| CliError::Usage("--model is required (or set RAINY_MODEL)".to_string()))
+}
+
+/// The positional text, or stdin when it is absent or `-`.
+fn prompt(args: &mut Args) -> CliResult<String> {
+    let words = args.rest();
+    if !words.is_empty() && words != ["-"] {
+        return Ok(words.join(" "));
+    }
+    let mut stdin = std::io::stdin();
+    if words.is_empty() && stdin.is_terminal() {
+        return Err(CliError::Usage(
+            "no prompt given; pass it as an argument or on stdin".to_string(),
+        ));
+    }
+    let mut text = String::new();
+    stdin.read_to_string(&mut text)?;
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err(CliError::Usage("the prompt is empty".to_string()));
+    }
+    Ok(text)
+}
+
+fn metadata_json(metadata: &RequestMetadata) -> Value {
+    json!({
+        "request_id": metadata.request_id,
+        "provider": metadata.provider,
+        "served_model": metadata.served_model,
+        "response_time_ms": metadata.response_time,
+        "tokens_used": metadata.tokens_used,
+        "credits_used": metadata.credits_used,
+        "credits_remaining": metadata.credits_remaining,
+    })
+}
+
+/// One-line request summary on stderr, so stdout stays pipeable.
+fn print_footer(model: &str, metadata: &RequestMetadata) {
+    let mut parts = vec![metadata
+        .served_model
+        .as_deref()
+        .unwrap_or(model)
+        .to_string()];
+    parts.extend(metadata.provider.clone());
+    parts.extend(metadata.response_time.map(|ms| format!("{ms} ms")));
+    parts.extend(
+        metadata
+            .tokens_used
+            .map(|tokens| format!("{tokens} tokens")),
+    );
+    parts.extend(
+        metadata
+            .credits_used
+            .map(|credits| format!("{credits} credits")),
+    );
+    eprintln!("[{}]", parts.join(" · "));
+}
+
+fn print_delta(text: &str) -> CliResult {
+    let mut stdout = std::io::stdout();
+    stdout.write_all(text.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+async fn chat(mut args: Args, out: Output) -> CliResult {
+    let model = model(&args)?;
+    let prompt = prompt(&mut args)?;
+    let mut messages = Vec::new();
+    if let Some(system) = args.value("system") {
+        messages.push(ChatMessage::system(system));
+    }
+    messages.push(ChatMessage::user(prompt));
+    let mut request = ChatCompletionRequest::new(model.clone(), messages);
+    if let Some(temperature) = args.parse_value("temperature")? {
+        request = request.with_temperature(temperature);
+    }
+    if let Some(max_tokens) = args.parse_value("max-tokens")? {
+        request = request.with_max_tokens(max_tokens);
+    }
+    let client = api_client()?;
+
+    if args.flag("stream") {
+        let mut stream = client.chat_completion_stream(request).await?;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            if out.json {
+                println!("{}", serde_json::to_string(&chunk)?);
+                continue;
+            }
+            for choice in chunk.choices.iter().filter(|choice| choice.index == 0) {
+                if let Some(content) = &choice.delta.content {
+                    print_delta(content)?;
+                }
+            }
+        }
+        if !out.json {
+            println!();
+        }
+        return Ok(());
+    }
+
+    let (response, metadata) = client.chat_completion(request).await?;
+    if out.json {
+        let value = json!({ "response": response, "metadata": metadata_json(&metadata) });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else {
+        if let Some(choice) = response.choices.first() {
+            println!("{}", choice.message.content);
+        }
+        print_footer(&model, &metadata);
+    }
+    Ok(())
+}
+
+async fn responses(mut args: Args, out: Output) -> CliResult {
+    let model = model(&args)?;
+    let input = prompt(&mut args)?;
+    let mut request = ResponsesRequest::text(model.clone(), input);
+    if let Some(max_output_tokens) = args.parse_value("max-output-tokens")? {
+        request = request.with_max_output_tokens(max_output_tokens);
+    }
+    if let Some(effort) = args.value("reasoning-effort") {
+        request = request.with_reasoning_effort(effort);
+    }
+    let client = api_client()?;
+
+    if args.flag("stream") {
+        let mut stream = client.create_response_stream(request).await?;
+        while let Some(event) = stream.next().await {
+            let event = event?;
+            if out.json {
+                println!("{}", serde_json::to_string(&event)?);
+            } else if let Some(delta) = event.text_delta() {
+                print_delta(delta)?;
+            }
+        }
+        if !out.json {
+            println!();
+        }
+        return Ok(());
+    }
+
+    let (response, metadata) = client.create_response(request).await?;
+    if out.json {
+        let value = json!({ "response": response, "metadata": metadata_json(&metadata) });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else {
+        match (&response.output_text, &response.output) {
+            (Some(text), _) => println!("{text}"),
+            (None, Some(output)) => println!("{}", serde_json::to_string_pretty(output)?),
+            (None, None) => {}
+        }
+        print_footer(&model, &metadata);
+    }
+    Ok(())
+}
+
+async fn models(mut args: Args, out: Output) -> CliResult {
+    let client = api_client()?;
+    match args.next_positional().as_deref() {
+        Some("list") | None => {
+            let models = client.get_available_models().await?;
+            out.emit(&models, |models| {
+                let mut providers: Vec<_> = models.providers.iter().collect();
+                providers.sort();
+                for (provider, ids) in providers {
+                    println!("{provider} ({})", ids.len());
+                    let mut ids = ids.clone();
+                    ids.sort();
+                    for id in ids {
+                        println!("  {id}");
+                    }
+                }
+            })
+        }
+        Some("catalog") => {
+            let catalog = client.get_models_catalog().await?;
+            out.emit(&catalog, |catalog| print_catalog(catalog))
+        }
+        Some("select") => {
+            let criteria = ModelSelectionCriteria {
+                required_input_modalities: args.values("input-modality"),
+                required_output_modalities: args.values("output-modality"),
+                require_tools: args.flag("tools").then_some(true),
+                require_structured_output: args.flag("structured-output").then_some(true),
+                min_context_length: args.parse_value("min-context")?,
+                max_prompt_price: args.parse_value("max-prompt-price")?,
+                max_completion_price: args.parse_value("max-completion-price")?,
+                allowed_providers: args.values("provider"),
+                ..ModelSelectionCriteria::default()
+            };
+            let selected = client.select_models(criteria).await?;
+            out.emit(&selected, |selected| print_catalog(selected))
+        }
+        Some(other) => Err(CliError::Usage(format!(
+            "unknown models subcommand '{other}' (expected list, catalog or select)"
+        ))),
+    }
+}
+
+fn print_catalog(models: &[ModelCatalogItem]) {
+    let width = models
+        .iter()
+        .map(|model| model.id.len())
+        .max()
+        .unwrap_or(0)
+        .max(5);
+    println!(
+        "{:<width$}  {:>9}  {:>12}  {:>12}",
+        "MODEL", "CONTEXT", "PROMPT", "COMPLETION"
+    );
+    for model in models {
+        let pricing = model.pricing.as_ref();
+        println!(
+            "{:<width$}  {:>9}  {:>12}  {:>12}",
+            model.id,
+            model
+                .context_length
+                .map_or_else(|| "-".to_string(), |tokens| tokens.to_string()),
+            pricing
+                .and_then(|pricing| pricing.prompt.as_deref())
+                .unwrap_or("-"),
+            pricing
+                .and_then(|pricing| pricing.completion.as_deref())
+                .unwrap_or("-"),
+        );
+    }
+}
+
+async fn search(mut args: Args, out: Output) -> CliResult {
+    let query = args.rest().join(" ");
+    if query.is_empty() {
+        return Err(CliError::Usage("search needs a QUERY".to_string()));
+    }
+    let mut config = ResearchConfig::new();
+    match args.value("depth") {
+        Some("basic") | None => {}
+        Some("advanced") => config = config.with_depth(ResearchDepth::Advanced),
+        Some(other) => {
+            return Err(CliError::Usage(format!(
+                "invalid value '{other}' for --depth (expected basic or advanced)"
+            )))
+        }
+    }
+    if let Some(max_sources) = args.parse_value("max-sources")? {
+        config = config.with_max_sources(max_sources);
+    }
+    let response = api_client()?.research(query, Some(config)).await?;
+    out.emit(&response, |response| {
+        match response
+            .result
+            .as_ref()
+            .and_then(|result| result.get("content"))
+        {
+            Some(Value::String(content)) => println!("{content}"),
+            _ => {
+                if let Some(message) = &response.message {
+                    println!("{message}");
+                }
+            }
+        }
+    })
+}
+
+async fn health(args: Args, out: Output) -> CliResult {
+    let client = api_client()?;
+    let health = if args.flag("detailed") {
+        client.detailed_health_check().await?
+    } else {
+        client.health_check().await?
+    };
+    out.emit(&health, |health| {
+        let check = |ok: bool| if ok { "ok" } else { "down" };
+        println!("status:    {} ({})", health.status, health.timestamp);
+        println!("uptime:    {:.0}s", health.uptime);
+        println!("database:  {}", check(health.services.database));
+        if let Some(redis) = health.services.redis {
+            println!("redis:     {}", check(redis));
+        }
+        println!("providers: {}", check(health.services.providers));
+    })
+}
+
+async fn login(args: Args, out: Output) -> CliResult {
+    let stdin = std::io::stdin();
+    let read_line = |label: &str| -> CliResult<String> {
+        if stdin.is_terminal() {
+            eprint!("{label}: ");
+            std::io::stderr().flush()?;
+        }
+        let mut line = String::new();
+        stdin.read_line(&mut line)?;
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    };
+    let email = match args.value("email") {
+        Some(email) => email.to_string(),
+        None => read_line("Email")?,
+    };
+    let password = match std::env::var("RAINY_PASSWORD") {
+        Ok(password) => password,
+        Err(_) if stdin.is_terminal() => read_hidden_line("Password")?,
+        Err(_) => read_line("Password")?,
+    };
+    if email.is_empty() || password.is_empty() {
+        return Err(CliError::Usage(
+            "email and password are required".to_string(),
+        ));
+    }
+
+    let mut client = session_client(None)?;
+    let response = client.login(&email, &password).await?;
+    let path = store::save(&store::StoredSession {
+        base_url: client.base_url().to_string(),
+        email: email.clone(),
+        access_token: response.access_token,
+        refresh_token: response.refresh_token,
+    })?;
+    let value = json!({ "user": response.user, "session_file": path });
+    out.emit(&value, |_| {
+        println!(
+            "Logged in as {} ({}); session saved to {}",
+            response.user.email,
+            response.user.role,
+            path.display()
+        );
+    })
+}
+
+/// Reads a line from the terminal with echo turned off.
+#[cfg(unix)]
+fn read_hidden_line(label: &str) -> CliResult<String> {
+    use std::process::Command;
+
+    let unavailable = || {
+        CliError::Failed(
+            "cannot turn off terminal echo; set RAINY_PASSWORD or pipe the password on stdin"
+                .to_string(),
+        )
+    };
+    let stty = |arg: &str| Command::new("stty").arg(arg).status();
+    if !stty("-echo").map_err(|_| unavailable())?.success() {
+        return Err(unavailable());
+    }
+    eprint!("{label}: ");
+    let mut line = String::new();
+    let read = std::io::stderr()
+        .flush()
+        .and_then(|()| std::io::stdin().read_line(&mut line));
+    let _ = stty("echo");
+    eprintln!();
+    read?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Reading a password from an interactive terminal needs `stty`; elsewhere it must be piped.
+#[cfg(not(unix))]
+fn read_hidden_line(_label: &str) -> CliResult<String> {
+    Err(CliError::Usage(
+        "set RAINY_PASSWORD or pipe the password on stdin".to_string(),
+    ))
+}
+
+async fn keys(mut args: Args, out: Output) -> CliResult {
+    match args.next_positional().as_deref() {
+        Some("list") | None => {
+            let keys = with_session(|client| async move { client.list_api_keys().await }).await?;
+            out.emit(&keys, |keys| {
+                for key in keys {
+                    println!(
+                        "{}  {:<24}  {:<8}  {:<8}  created {}{}",
+                        key.id,
+                        key.name,
+                        key.r#type.as_deref().unwrap_or("-"),
+                        if key.is_active { "active" } else { "inactive" },
+                        key.created_at,
+                        key.last_used
+                            .as_deref()
+                            .map(|used| format!(", last used {used}"))
+                            .unwrap_or_default(),
+                    );
+                }
+            })
+        }
+        Some("create") => {
+            let name = args
+                .next_positional()
+                .ok_or_else(|| CliError::Usage("keys create needs a NAME".to_string()))?;
+            let key_type = args.value("type").map(str::to_string);
+            let created = with_session(|client| {
+                let name = name.clone();
+                let key_type = key_type.clone();
+                async move { client.create_api_key(&name, key_type.as_deref()).await }
+            })
+            .await?;
+            out.emit(&created, |created| {
+                println!("{}", created.key);
+                eprintln!(
+                    "Created {} key '{}' ({}). It is shown only once; store it now.",
+                    created.r#type, created.name, created.id
+                );
+            })
+        }
+        Some("delete") => {
+            let id = args
+                .next_positional()
+                .ok_or_else(|| CliError::Usage("keys delete needs an ID".to_string()))?;
+            let response = with_session(|client| {
+                let id = id.clone();
+                async move { client.delete_api_key(&id).await }
+            })
+            .await?;
+            out.emit(&response, |_| println!("Deleted key {id}"))
+        }
+        Some(other) => Err(CliError::Usage(format!(
+            "unknown keys subcommand '{other}' (expected list, create or delete)"
+        ))),
+    }
+}
+
+async fn usage(args: Args, out: Output) -> CliResult {
+    let days: Option<u32> = args.parse_value("days")?;
+    let credits = with_session(|client| async move { client.usage_credits().await }).await?;
+    let stats = with_session(|client| async move { client.usage_stats(days).await }).await?;
+    let value = json!({ "credits": credits, "stats": stats });
+    out.emit(&value, |_| {
+        println!("balance:  {} {}", credits.balance, credits.currency);
+        println!(
+            "last {} days: {} requests, {} credits",
+            stats.period_days, stats.total_requests, stats.total_credits_deducted
+        );
+    })
+}
+
+async fn proxy(args: Args, out: Output) -> CliResult {
+    let addr = args
+        .value("listen")
+        .unwrap_or(DEFAULT_PROXY_ADDR)
+        .to_string();
+    let client_limit: Option<f64> = args.parse_value("budget")?;
+    let user_limit: Option<f64> = args.parse_value("user-budget")?;
+    let mut client = api_client()?;
+    if client_limit.is_some() || user_limit.is_some() {
+        let mut budget = Budget::new();
+        if let Some(limit) = client_limit {
+            budget = budget.with_client_limit(limit);
+        }
+        if let Some(limit) = user_limit {
+            budget = budget.with_per_user_limit(limit);
+        }
+        client = client.with_budget(budget);
+    }
+
+    let mut proxy = RainyProxy::new(client).on_request(move |entry| print_proxy_entry(entry, out));
+    if let Ok(token) = std::env::var("RAINY_PROXY_TOKEN") {
+        proxy = proxy.with_access_token(token);
+    }
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    eprintln!(
+        "Serving the OpenAI API at http://{}/v1 (Ctrl-C to stop)",
+        listener.local_addr()?
+    );
+    proxy
+        .serve_with_shutdown(listener, async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+/// One line per proxied request: JSON on stdout with `--json`, otherwise text on stderr.
+fn print_proxy_entry(entry: &ProxyLogEntry, out: Output) {
+    let metadata = entry.metadata.as_ref();
+    if out.json {
+        let mut line = json!({
+            "method": entry.method,
+            "path": entry.path,
+            "model": entry.model,
+            "stream": entry.stream,
+            "status": entry.status,
+            "duration_ms": entry.duration_ms,
+            "error_code": entry.error_code,
+        });
+        if let Some(metadata) = metadata {
+            line["metadata"] = metadata_json(metadata);
+        }
+        println!("{line}");
+        return;
+    }
+    let mut parts = vec![
+        entry.method.clone(),
+        entry.path.clone(),
+        entry.model.clone().unwrap_or_else(|| "-".to_string()),
+        entry.status.to_string(),
+        format!("{} ms", entry.duration_ms),
+    ];
+    if entry.stream {
+        parts.push("stream".to_string());
+    }
+    parts.extend(entry.error_code.clone());
+    if let Some(metadata) = metadata {
+        parts.extend(metadata.provider.clone());
+        parts.extend(metadata.request_id.clone());
+        parts.extend(
+            metadata
+                .credits_used
+                .map(|credits| format!("{credits} credits")),
+        );
+    }
+    eprintln!("{}", parts.join(" "));
+}
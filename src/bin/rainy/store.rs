@@ -0,0 +1,59 @@
+//! Session tokens saved by `rainy login`.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Tokens persisted between invocations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredSession {
+    /// API base URL the tokens were issued for.
+    pub base_url: String,
+    /// Account email.
+    pub email: String,
+    /// Access token for session requests.
+    pub access_token: String,
+    /// Refresh token used when the access token expires.
+    pub refresh_token: String,
+}
+
+/// `$RAINY_CONFIG_DIR/session.json`, falling back to `$XDG_CONFIG_HOME/rainy` and
+/// `~/.config/rainy`.
+pub fn session_path() -> Option<PathBuf> {
+    let dir = match std::env::var_os("RAINY_CONFIG_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?
+            .join("rainy"),
+    };
+    Some(dir.join("session.json"))
+}
+
+/// Loads the saved session, if any.
+pub fn load() -> Option<StoredSession> {
+    let text = std::fs::read_to_string(session_path()?).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Saves `session`, readable only by the current user on Unix.
+pub fn save(session: &StoredSession) -> std::io::Result<PathBuf> {
+    let path = session_path().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no config directory; set RAINY_CONFIG_DIR",
+        )
+    })?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&path)?;
+    // `mode` only applies when the file is created; tighten an existing one too.
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    std::io::Write::write_all(&mut file, serde_json::to_string_pretty(session)?.as_bytes())?;
+    Ok(path)
+}
@@ -0,0 +1,609 @@
+//! Client-side spend budgets and credit guardrails.
+//!
+//! A [`Budget`] is attached to a [`RainyClient`](crate::RainyClient) with
+//! [`with_budget`](crate::RainyClient::with_budget). Before a billable request is sent, the
+//! client holds back the request's worst-case cost (see [`estimate_cost`](crate::estimate_cost))
+//! under the same lock that checks the caps, and refuses the call with
+//! [`RainyError::BudgetExceeded`] when spend plus holds would overrun one. When the call
+//! finishes, the hold is settled to the credits reported in [`RequestMetadata`]; failed
+//! calls release it.
+
+use crate::error::{RainyError, Result};
+use crate::models::RequestMetadata;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The scope a budget limit or warning applies to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BudgetScope {
+    /// Total spend of the client since the budget was attached.
+    Client,
+    /// Spend attributed to a single end-user (the request `user` field).
+    User {
+        /// End-user identifier.
+        user: String,
+    },
+    /// Spend within a rolling time window.
+    Window {
+        /// Window length in seconds.
+        seconds: u64,
+    },
+    /// Remaining account credits reported by Rainy (`credits_remaining`).
+    AccountReserve,
+    /// Remaining daily credits reported by Rainy (`rainy_daily_credits_remaining`).
+    DailyReserve,
+}
+
+impl fmt::Display for BudgetScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetScope::Client => write!(f, "client"),
+            BudgetScope::User { user } => write!(f, "user:{user}"),
+            BudgetScope::Window { seconds } => write!(f, "window:{seconds}s"),
+            BudgetScope::AccountReserve => write!(f, "account_reserve"),
+            BudgetScope::DailyReserve => write!(f, "daily_reserve"),
+        }
+    }
+}
+
+/// Warning emitted when spend in a scope crosses a configured threshold.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetWarning {
+    /// The scope whose spend crossed the threshold.
+    pub scope: BudgetScope,
+    /// The threshold that was crossed, as a fraction of the limit (e.g. `0.8`).
+    pub threshold: f64,
+    /// Spend recorded in the scope after the triggering request.
+    pub spent: f64,
+    /// The configured limit for the scope.
+    pub limit: f64,
+}
+
+/// Callback invoked when a [`BudgetWarning`] is raised.
+pub type BudgetWarningCallback = Arc<dyn Fn(&BudgetWarning) + Send + Sync>;
+
+/// Spend limit over a rolling time window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetWindow {
+    /// Length of the rolling window.
+    pub duration: Duration,
+    /// Maximum credits that may be spent within the window.
+    pub limit: f64,
+}
+
+/// Spending policy enforced by `RainyClient` before requests go out.
+///
+/// # Examples
+///
+/// ```rust
+/// use rainy_sdk::budget::Budget;
+/// use std::time::Duration;
+///
+/// let budget = Budget::new()
+///     .with_client_limit(25.0)
+///     .with_per_user_limit(1.0)
+///     .with_window_limit(Duration::from_secs(3600), 5.0)
+///     .with_warning_threshold(0.8)
+///     .with_credit_reserve(2.0)
+///     .on_warning(|warning| eprintln!("budget warning: {warning:?}"));
+///
+/// assert_eq!(budget.client_limit, Some(25.0));
+/// ```
+#[derive(Clone, Default)]
+pub struct Budget {
+    /// Maximum total credits the client may spend.
+    pub client_limit: Option<f64>,
+    /// Default maximum credits per end-user (`user` request field).
+    pub per_user_limit: Option<f64>,
+    /// Per-user overrides of `per_user_limit`.
+    pub user_limits: HashMap<String, f64>,
+    /// Rolling window limits.
+    pub windows: Vec<BudgetWindow>,
+    /// Fractions of a limit (e.g. `0.5`, `0.8`) at which warnings are raised.
+    pub warning_thresholds: Vec<f64>,
+    /// Traffic is paused once `credits_remaining` drops to or below this value.
+    pub min_credits_remaining: Option<f64>,
+    /// Traffic is paused once `rainy_daily_credits_remaining` drops to or below this value.
+    pub min_daily_credits_remaining: Option<f64>,
+    on_warning: Option<BudgetWarningCallback>,
+}
+
+impl Budget {
+    /// Creates an empty budget with no limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum total credits the client may spend.
+    pub fn with_client_limit(mut self, limit: f64) -> Self {
+        self.client_limit = Some(limit);
+        self
+    }
+
+    /// Sets the default maximum credits per end-user.
+    pub fn with_per_user_limit(mut self, limit: f64) -> Self {
+        self.per_user_limit = Some(limit);
+        self
+    }
+
+    /// Sets a limit for a specific end-user, overriding the per-user default.
+    pub fn with_user_limit(mut self, user: impl Into<String>, limit: f64) -> Self {
+        self.user_limits.insert(user.into(), limit);
+        self
+    }
+
+    /// Adds a rolling window limit (e.g. 5 credits per hour).
+    pub fn with_window_limit(mut self, duration: Duration, limit: f64) -> Self {
+        self.windows.push(BudgetWindow { duration, limit });
+        self
+    }
+
+    /// Adds a warning threshold as a fraction of each limit.
+    pub fn with_warning_threshold(mut self, fraction: f64) -> Self {
+        self.warning_thresholds.push(fraction);
+        self
+    }
+
+    /// Pauses traffic once the account's remaining credits reach this reserve.
+    pub fn with_credit_reserve(mut self, reserve: f64) -> Self {
+        self.min_credits_remaining = Some(reserve);
+        self
+    }
+
+    /// Pauses traffic once the remaining daily credits reach this reserve.
+    pub fn with_daily_credit_reserve(mut self, reserve: f64) -> Self {
+        self.min_daily_credits_remaining = Some(reserve);
+        self
+    }
+
+    /// Registers a callback invoked whenever a warning threshold is crossed.
+    pub fn on_warning<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&BudgetWarning) + Send + Sync + 'static,
+    {
+        self.on_warning = Some(Arc::new(callback));
+        self
+    }
+
+    fn user_limit(&self, user: &str) -> Option<f64> {
+        self.user_limits.get(user).copied().or(self.per_user_limit)
+    }
+}
+
+impl fmt::Debug for Budget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Budget")
+            .field("client_limit", &self.client_limit)
+            .field("per_user_limit", &self.per_user_limit)
+            .field("user_limits", &self.user_limits)
+            .field("windows", &self.windows)
+            .field("warning_thresholds", &self.warning_thresholds)
+            .field("min_credits_remaining", &self.min_credits_remaining)
+            .field(
+                "min_daily_credits_remaining",
+                &self.min_daily_credits_remaining,
+            )
+            .field("on_warning", &self.on_warning.is_some())
+            .finish()
+    }
+}
+
+/// Point-in-time view of the spend tracked by a [`BudgetTracker`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BudgetSnapshot {
+    /// Total credits spent by the client.
+    pub total_spent: f64,
+    /// Credits spent per end-user.
+    pub spent_by_user: HashMap<String, f64>,
+    /// Credits spent within each configured window, keyed by window length in seconds.
+    pub spent_by_window: Vec<(u64, f64)>,
+    /// Credits held back for requests still in flight.
+    pub reserved: f64,
+    /// Last `credits_remaining` value reported by Rainy.
+    pub credits_remaining: Option<f64>,
+    /// Last `rainy_daily_credits_remaining` value reported by Rainy.
+    pub daily_credits_remaining: Option<f64>,
+}
+
+#[derive(Debug, Default)]
+struct BudgetState {
+    total: f64,
+    per_user: HashMap<String, f64>,
+    events: VecDeque<(Instant, f64)>,
+    held: f64,
+    held_by_user: HashMap<String, f64>,
+    credits_remaining: Option<f64>,
+    daily_credits_remaining: Option<f64>,
+}
+
+impl BudgetState {
+    fn prune(&mut self, longest: Option<Duration>, now: Instant) {
+        let Some(longest) = longest else {
+            self.events.clear();
+            return;
+        };
+        while let Some((at, _)) = self.events.front() {
+            if now.duration_since(*at) > longest {
+                self.events.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn window_spend(&self, duration: Duration, now: Instant) -> f64 {
+        self.events
+            .iter()
+            .filter(|(at, _)| now.duration_since(*at) <= duration)
+            .map(|(_, cost)| cost)
+            .sum()
+    }
+
+    fn hold(&mut self, user: Option<&str>, amount: f64) {
+        self.held += amount;
+        if let Some(user) = user {
+            *self.held_by_user.entry(user.to_string()).or_insert(0.0) += amount;
+        }
+    }
+
+    fn release(&mut self, user: Option<&str>, amount: f64) {
+        self.held = (self.held - amount).max(0.0);
+        if let Some(user) = user {
+            if let Some(held) = self.held_by_user.get_mut(user) {
+                *held = (*held - amount).max(0.0);
+                if *held == 0.0 {
+                    self.held_by_user.remove(user);
+                }
+            }
+        }
+    }
+}
+
+/// Credits held back against a budget while a request is in flight.
+///
+/// Returned by [`BudgetTracker::reserve`]. The hold counts towards every limit until it is
+/// settled with the request's reported cost; dropping an unsettled reservation releases it.
+#[derive(Debug)]
+pub struct BudgetReservation {
+    tracker: Arc<BudgetTracker>,
+    user: Option<String>,
+    amount: f64,
+    settled: bool,
+}
+
+impl BudgetReservation {
+    /// Credits held back by this reservation.
+    pub fn amount(&self) -> f64 {
+        self.amount
+    }
+
+    /// Releases the hold and records the spend reported in `metadata`.
+    pub fn settle(mut self, metadata: &RequestMetadata) {
+        self.settled = true;
+        self.tracker
+            .record_releasing(self.user.as_deref(), metadata, self.amount);
+    }
+}
+
+impl Drop for BudgetReservation {
+    fn drop(&mut self) {
+        if !self.settled {
+            let mut state = self.tracker.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.release(self.user.as_deref(), self.amount);
+        }
+    }
+}
+
+/// Tracks spend against a [`Budget`] and enforces its limits.
+///
+/// The tracker is shared by the client and may also be used directly, for example to
+/// check a [`CostEstimate`](crate::cost::CostEstimate) before building a request.
+#[derive(Debug)]
+pub struct BudgetTracker {
+    budget: Budget,
+    state: Mutex<BudgetState>,
+}
+
+fn exceeded(scope: BudgetScope, limit: f64, spent: f64, message: String) -> RainyError {
+    let code = match scope {
+        BudgetScope::AccountReserve | BudgetScope::DailyReserve => "CREDIT_RESERVE_REACHED",
+        _ => "BUDGET_EXCEEDED",
+    };
+    RainyError::BudgetExceeded {
+        code: code.to_string(),
+        message,
+        scope: scope.to_string(),
+        limit,
+        spent,
+    }
+}
+
+impl BudgetTracker {
+    /// Creates a tracker for the given budget with no recorded spend.
+    pub fn new(budget: Budget) -> Self {
+        Self {
+            budget,
+            state: Mutex::new(BudgetState::default()),
+        }
+    }
+
+    /// Returns the budget policy being enforced.
+    pub fn budget(&self) -> &Budget {
+        &self.budget
+    }
+
+    fn longest_window(&self) -> Option<Duration> {
+        self.budget.windows.iter().map(|w| w.duration).max()
+    }
+
+    /// Checks whether a request with the given estimated cost may be sent.
+    ///
+    /// A limit is considered reached when recorded spend plus the credits held for requests
+    /// in flight is already at the limit, or when adding `estimated_cost` would exceed it.
+    pub fn check(&self, user: Option<&str>, estimated_cost: f64) -> Result<()> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.prune(self.longest_window(), now);
+        self.check_locked(&state, user, estimated_cost, now)
+    }
+
+    /// Checks the budget and, if the request may be sent, holds back `estimated_cost`.
+    ///
+    /// The check and the hold happen under one lock, so concurrent callers cannot all pass
+    /// the check before any of them is accounted for. Settle the returned reservation with
+    /// the request's metadata once it finishes.
+    pub fn reserve(
+        self: &Arc<Self>,
+        user: Option<&str>,
+        estimated_cost: f64,
+    ) -> Result<BudgetReservation> {
+        let estimated_cost = estimated_cost.max(0.0);
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.prune(self.longest_window(), now);
+        self.check_locked(&state, user, estimated_cost, now)?;
+        state.hold(user, estimated_cost);
+        Ok(BudgetReservation {
+            tracker: Arc::clone(self),
+            user: user.map(str::to_string),
+            amount: estimated_cost,
+            settled: false,
+        })
+    }
+
+    fn check_locked(
+        &self,
+        state: &BudgetState,
+        user: Option<&str>,
+        estimated_cost: f64,
+        now: Instant,
+    ) -> Result<()> {
+        let over = |spent: f64, held: f64, limit: f64| {
+            spent + held >= limit || spent + held + estimated_cost > limit
+        };
+
+        if let (Some(reserve), Some(remaining)) =
+            (self.budget.min_credits_remaining, state.credits_remaining)
+        {
+            if remaining <= reserve {
+                return Err(exceeded(
+                    BudgetScope::AccountReserve,
+                    reserve,
+                    remaining,
+                    format!(
+                        "Account credits remaining ({remaining}) reached the configured reserve ({reserve})"
+                    ),
+                ));
+            }
+        }
+
+        if let (Some(reserve), Some(remaining)) = (
+            self.budget.min_daily_credits_remaining,
+            state.daily_credits_remaining,
+        ) {
+            if remaining <= reserve {
+                return Err(exceeded(
+                    BudgetScope::DailyReserve,
+                    reserve,
+                    remaining,
+                    format!(
+                        "Daily credits remaining ({remaining}) reached the configured reserve ({reserve})"
+                    ),
+                ));
+            }
+        }
+
+        if let Some(limit) = self.budget.client_limit {
+            if over(state.total, state.held, limit) {
+                return Err(exceeded(
+                    BudgetScope::Client,
+                    limit,
+                    state.total,
+                    format!("Client budget of {limit} credits reached"),
+                ));
+            }
+        }
+
+        if let Some(user) = user {
+            if let Some(limit) = self.budget.user_limit(user) {
+                let spent = state.per_user.get(user).copied().unwrap_or(0.0);
+                let held = state.held_by_user.get(user).copied().unwrap_or(0.0);
+                if over(spent, held, limit) {
+                    return Err(exceeded(
+                        BudgetScope::User {
+                            user: user.to_string(),
+                        },
+                        limit,
+                        spent,
+                        format!("Budget of {limit} credits for user '{user}' reached"),
+                    ));
+                }
+            }
+        }
+
+        for window in &self.budget.windows {
+            let spent = state.window_spend(window.duration, now);
+            if over(spent, state.held, window.limit) {
+                return Err(exceeded(
+                    BudgetScope::Window {
+                        seconds: window.duration.as_secs(),
+                    },
+                    window.limit,
+                    spent,
+                    format!(
+                        "Budget of {} credits per {}s window reached",
+                        window.limit,
+                        window.duration.as_secs()
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records the spend and balances reported in a response's metadata.
+    ///
+    /// Spend is taken from `rainy_credits_charged`, falling back to `credits_used`.
+    /// Warning callbacks run after the internal state has been updated. Requests sent with a
+    /// [`BudgetReservation`] are recorded by [`BudgetReservation::settle`] instead.
+    pub fn record(&self, user: Option<&str>, metadata: &RequestMetadata) {
+        self.record_releasing(user, metadata, 0.0);
+    }
+
+    fn record_releasing(&self, user: Option<&str>, metadata: &RequestMetadata, held: f64) {
+        let cost = metadata
+            .rainy_credits_charged
+            .or(metadata.credits_used)
+            .unwrap_or(0.0);
+        let daily_remaining = metadata
+            .rainy_daily_credits_remaining
+            .as_deref()
+            .and_then(|raw| raw.trim().parse::<f64>().ok());
+
+        let now = Instant::now();
+        let mut warnings = Vec::new();
+        {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.prune(self.longest_window(), now);
+            state.release(user, held);
+
+            if let Some(remaining) = metadata.credits_remaining {
+                state.credits_remaining = Some(remaining);
+            }
+            if daily_remaining.is_some() {
+                state.daily_credits_remaining = daily_remaining;
+            }
+
+            if cost > 0.0 {
+                let mut scopes = Vec::new();
+
+                let before = state.total;
+                state.total += cost;
+                if let Some(limit) = self.budget.client_limit {
+                    scopes.push((BudgetScope::Client, before, state.total, limit));
+                }
+
+                if let Some(user) = user {
+                    let entry = state.per_user.entry(user.to_string()).or_insert(0.0);
+                    let before = *entry;
+                    *entry += cost;
+                    let after = *entry;
+                    if let Some(limit) = self.budget.user_limit(user) {
+                        scopes.push((
+                            BudgetScope::User {
+                                user: user.to_string(),
+                            },
+                            before,
+                            after,
+                            limit,
+                        ));
+                    }
+                }
+
+                if !self.budget.windows.is_empty() {
+                    let befores: Vec<f64> = self
+                        .budget
+                        .windows
+                        .iter()
+                        .map(|w| state.window_spend(w.duration, now))
+                        .collect();
+                    state.events.push_back((now, cost));
+                    for (window, before) in self.budget.windows.iter().zip(befores) {
+                        scopes.push((
+                            BudgetScope::Window {
+                                seconds: window.duration.as_secs(),
+                            },
+                            before,
+                            before + cost,
+                            window.limit,
+                        ));
+                    }
+                }
+
+                for (scope, before, after, limit) in scopes {
+                    if limit <= 0.0 {
+                        continue;
+                    }
+                    for threshold in &self.budget.warning_thresholds {
+                        let mark = limit * threshold;
+                        if before < mark && after >= mark {
+                            warnings.push(BudgetWarning {
+                                scope: scope.clone(),
+                                threshold: *threshold,
+                                spent: after,
+                                limit,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(callback) = &self.budget.on_warning {
+            for warning in &warnings {
+                callback(warning);
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        for warning in &warnings {
+            tracing::warn!(
+                "Budget warning for {}: {:.6} of {:.6} credits spent (threshold {:.0}%)",
+                warning.scope,
+                warning.spent,
+                warning.limit,
+                warning.threshold * 100.0
+            );
+        }
+    }
+
+    /// Returns the spend and balances tracked so far.
+    pub fn snapshot(&self) -> BudgetSnapshot {
+        let now = Instant::now();
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        BudgetSnapshot {
+            total_spent: state.total,
+            spent_by_user: state.per_user.clone(),
+            spent_by_window: self
+                .budget
+                .windows
+                .iter()
+                .map(|w| (w.duration.as_secs(), state.window_spend(w.duration, now)))
+                .collect(),
+            reserved: state.held,
+            credits_remaining: state.credits_remaining,
+            daily_credits_remaining: state.daily_credits_remaining,
+        }
+    }
+
+    /// Clears all recorded spend and reported balances.
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        *state = BudgetState::default();
+    }
+}
@@ -1,8 +1,13 @@
 use crate::{
     auth::AuthConfig,
+    budget::{Budget, BudgetSnapshot, BudgetTracker},
+    cost::{CostEstimable, CostEstimate},
     error::{ApiErrorResponse, RainyError, Result},
+    ledger::{LedgerEntry, LedgerSink},
+    metering::{MeteredStream, SpendHold, StreamFinish, StreamMeter, StreamUsage},
     models::*,
     retry::{retry_with_backoff, RetryConfig},
+    thought_signatures::ThoughtSignatureCache,
 };
 use eventsource_stream::Eventsource;
 use futures::{Stream, StreamExt};
@@ -13,7 +18,8 @@ use reqwest::{
 use secrecy::ExposeSecret;
 use serde::Deserialize;
 use std::pin::Pin;
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "rate-limiting")]
 use governor::{
@@ -58,6 +64,34 @@ pub struct RainyClient {
     /// This is only available when the `rate-limiting` feature is enabled.
     #[cfg(feature = "rate-limiting")]
     rate_limiter: Option<RateLimiter<NotKeyed, InMemoryState, DefaultClock>>,
+
+    /// Optional client-side spend budget enforced before billable requests.
+    budget: Option<Arc<BudgetTracker>>,
+
+    /// Optional usage ledger that records one entry per billable call.
+    ledger: Option<Arc<dyn LedgerSink>>,
+
+    /// Thought signatures captured from tool calls, replayed on later turns.
+    thought_signatures: Arc<ThoughtSignatureCache>,
+
+    /// Model catalog reused by budget estimates, fallbacks and capability checks.
+    catalog: Mutex<Option<(Instant, Arc<Vec<ModelCatalogItem>>)>>,
+
+    /// How long a fetched catalog is reused.
+    catalog_ttl: Duration,
+}
+
+/// Plain HTTP is only allowed for loopback hosts, e.g. a local gateway or test server.
+fn is_loopback(base_url: &str) -> bool {
+    match url::Url::parse(base_url)
+        .ok()
+        .and_then(|url| url.host().map(|h| h.to_owned()))
+    {
+        Some(url::Host::Domain(domain)) => domain == "localhost",
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
 }
 
 impl RainyClient {
@@ -142,7 +176,7 @@ impl RainyClient {
         let client = Client::builder()
             .use_rustls_tls()
             .min_tls_version(reqwest::tls::Version::TLS_1_2)
-            .https_only(true)
+            .https_only(!is_loopback(&auth_config.base_url))
             .timeout(auth_config.timeout())
             .default_headers(headers)
             .build()
@@ -165,6 +199,11 @@ impl RainyClient {
             retry_config,
             #[cfg(feature = "rate-limiting")]
             rate_limiter,
+            budget: None,
+            ledger: None,
+            thought_signatures: Arc::new(ThoughtSignatureCache::new()),
+            catalog: Mutex::new(None),
+            catalog_ttl: Self::DEFAULT_CATALOG_TTL,
         })
     }
 
@@ -184,6 +223,212 @@ impl RainyClient {
         self
     }
 
+    /// Attaches a client-side spend budget.
+    ///
+    /// Before a billable request (chat completion, Responses API or embeddings call) is sent,
+    /// its worst-case cost from [`estimate_cost`](Self::estimate_cost) is held back against
+    /// the budget, using the model's pricing from the cached catalog. The request is refused
+    /// with [`RainyError::BudgetExceeded`] when spend plus holds would overrun a limit, or once
+    /// a credit reserve is reached. When the call finishes, the hold is settled to the credits
+    /// reported in its `RequestMetadata`; failed calls release it. Streaming calls are settled
+    /// from the final usage chunk (chat streams request it with
+    /// `stream_options.include_usage`). Models without catalog pricing are not held back.
+    ///
+    /// # Arguments
+    ///
+    /// * `budget` - The spending policy to enforce.
+    ///
+    /// # Returns
+    ///
+    /// The `RainyClient` instance with the budget attached.
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(Arc::new(BudgetTracker::new(budget)));
+        self
+    }
+
+    /// Returns the budget tracker attached with [`with_budget`](Self::with_budget), if any.
+    pub fn budget(&self) -> Option<&BudgetTracker> {
+        self.budget.as_deref()
+    }
+
+    /// Returns the spend tracked by the attached budget, if any.
+    pub fn budget_snapshot(&self) -> Option<BudgetSnapshot> {
+        self.budget.as_ref().map(|tracker| tracker.snapshot())
+    }
+
+    /// Checks the attached budget for a request with a known estimated cost.
+    ///
+    /// Pair this with [`estimate_cost`](Self::estimate_cost) to refuse a request whose
+    /// worst-case cost would overrun a limit. Always succeeds when no budget is attached.
+    pub fn check_budget(&self, user: Option<&str>, estimated_cost: f64) -> Result<()> {
+        match &self.budget {
+            Some(tracker) => tracker.check(user, estimated_cost),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks the attached budget and holds back the worst-case cost of `request`.
+    pub(crate) async fn reserve_budget<R: CostEstimable + ?Sized>(
+        &self,
+        model: &str,
+        user: Option<&str>,
+        request: &R,
+    ) -> Result<SpendHold> {
+        let Some(tracker) = &self.budget else {
+            return Ok(SpendHold::default());
+        };
+        // Refuse without a catalog round trip when a limit has already been reached.
+        tracker.check(user, 0.0)?;
+        let entry = self.catalog_entry(model).await.ok().flatten();
+        let estimate = entry
+            .as_ref()
+            .and_then(|entry| crate::cost::estimate_cost(request, entry))
+            .map_or(0.0, |estimate| estimate.max_cost);
+        Ok(SpendHold {
+            reservation: Some(tracker.reserve(user, estimate)?),
+            entry,
+        })
+    }
+
+    fn meters_streams(&self) -> bool {
+        self.budget.is_some() || self.ledger.is_some()
+    }
+
+    /// Serializes a chat request for streaming, asking for the final usage chunk when spend
+    /// has to be recorded.
+    pub(crate) fn chat_stream_body<T: serde::Serialize>(
+        &self,
+        request: &T,
+    ) -> Result<serde_json::Value> {
+        let mut body = serde_json::to_value(request)?;
+        if self.meters_streams() {
+            if let Some(object) = body.as_object_mut() {
+                let options = object
+                    .entry("stream_options")
+                    .or_insert_with(|| serde_json::json!({}));
+                if let Some(options) = options.as_object_mut() {
+                    options.insert("include_usage".to_string(), serde_json::Value::Bool(true));
+                }
+            }
+        }
+        Ok(body)
+    }
+
+    /// Wraps a stream so its spend is settled and recorded when it ends.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn meter_stream<T: StreamUsage + Send + 'static>(
+        &self,
+        stream: Pin<Box<dyn Stream<Item = Result<T>> + Send>>,
+        mut hold: SpendHold,
+        endpoint: &'static str,
+        model: &str,
+        user: Option<&str>,
+        start_time: Instant,
+        metadata: RequestMetadata,
+        on_finish: Option<StreamFinish>,
+    ) -> Pin<Box<dyn Stream<Item = Result<T>> + Send>> {
+        if !self.meters_streams() && on_finish.is_none() {
+            return stream;
+        }
+        if hold.entry.is_none() && self.ledger.is_some() {
+            hold.entry = self.catalog_entry(model).await.ok().flatten();
+        }
+        let mut meter = StreamMeter::new(hold, endpoint, model, user, start_time, metadata);
+        meter.ledger = self.ledger.clone();
+        meter.on_finish = on_finish;
+        Box::pin(MeteredStream::new(stream, meter))
+    }
+
+    /// Attaches a usage ledger sink.
+    ///
+    /// Every chat completion, Responses API and embeddings call is recorded in the sink with
+    /// its endpoint, model, provider, request ID, token usage, credits, latency and, for failed
+    /// calls, error code. Streams are recorded when they end, with the usage of their final
+    /// chunk (chat streams request it with `stream_options.include_usage`). Keep a clone of the
+    /// `Arc` to read the ledger back.
+    ///
+    /// # Arguments
+    ///
+    /// * `sink` - The ledger sink, e.g. a `MemoryLedger`, `JsonlLedger` or `CsvLedger`.
+    ///
+    /// # Returns
+    ///
+    /// The `RainyClient` instance with the ledger attached.
+    pub fn with_ledger<S: LedgerSink + 'static>(mut self, sink: Arc<S>) -> Self {
+        self.ledger = Some(sink);
+        self
+    }
+
+    /// Shares a thought-signature cache with this client.
+    ///
+    /// Every client keeps its own cache by default; share one when several clients take part
+    /// in the same conversations.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache` - The cache to capture signatures into and replay them from.
+    ///
+    /// # Returns
+    ///
+    /// The `RainyClient` instance using the shared cache.
+    pub fn with_thought_signature_cache(mut self, cache: Arc<ThoughtSignatureCache>) -> Self {
+        self.thought_signatures = cache;
+        self
+    }
+
+    /// Returns the cache of thought signatures captured from tool calls.
+    pub fn thought_signatures(&self) -> &ThoughtSignatureCache {
+        &self.thought_signatures
+    }
+
+    pub(crate) fn shared_thought_signatures(&self) -> Arc<ThoughtSignatureCache> {
+        Arc::clone(&self.thought_signatures)
+    }
+
+    pub(crate) fn record_ledger(&self, entry: impl FnOnce() -> LedgerEntry) {
+        if let Some(ledger) = &self.ledger {
+            if let Err(_e) = ledger.record(&entry()) {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("Failed to record ledger entry: {}", _e);
+            }
+        }
+    }
+
+    /// Records the outcome of a billable call in the attached budget and ledger.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn finish_call<T>(
+        &self,
+        endpoint: &str,
+        model: &str,
+        user: Option<&str>,
+        start_time: Instant,
+        hold: SpendHold,
+        result: Result<(T, RequestMetadata)>,
+        with_usage: impl FnOnce(&T, LedgerEntry) -> LedgerEntry,
+    ) -> Result<(T, RequestMetadata)> {
+        match &result {
+            Ok((value, metadata)) => {
+                hold.settle(metadata);
+                self.record_ledger(|| {
+                    with_usage(
+                        value,
+                        LedgerEntry::from_metadata(endpoint, Some(model), user, metadata),
+                    )
+                });
+            }
+            Err(error) => self.record_ledger(|| {
+                LedgerEntry::from_error(
+                    endpoint,
+                    Some(model),
+                    user,
+                    error,
+                    Some(start_time.elapsed().as_millis() as u64),
+                )
+            }),
+        }
+        result
+    }
+
     /// Retrieves the list of available models and providers from the API.
     ///
     /// # Returns
@@ -251,6 +496,10 @@ impl RainyClient {
         &self,
         request: ChatCompletionRequest,
     ) -> Result<(ChatCompletionResponse, RequestMetadata)> {
+        let hold = self
+            .reserve_budget(&request.model, request.user.as_deref(), &request)
+            .await?;
+
         #[cfg(feature = "rate-limiting")]
         if let Some(ref limiter) = self.rate_limiter {
             limiter.until_ready().await;
@@ -268,11 +517,20 @@ impl RainyClient {
             Ok((chat_response, metadata))
         };
 
-        if self.auth_config.enable_retry {
+        let result = if self.auth_config.enable_retry {
             retry_with_backoff(&self.retry_config, operation).await
         } else {
             operation().await
-        }
+        };
+        self.finish_call(
+            "/chat/completions",
+            &request.model,
+            request.user.as_deref(),
+            start_time,
+            hold,
+            result,
+            |response, entry| entry.with_usage(response.usage.as_ref()),
+        )
     }
 
     /// Creates a streaming chat completion based on the provided request.
@@ -290,6 +548,11 @@ impl RainyClient {
     ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatCompletionChunk>> + Send>>> {
         // Ensure stream is set to true
         request.stream = Some(true);
+        self.ensure_input_modalities(&request.model, &request)
+            .await?;
+        let hold = self
+            .reserve_budget(&request.model, request.user.as_deref(), &request)
+            .await?;
 
         #[cfg(feature = "rate-limiting")]
         if let Some(ref limiter) = self.rate_limiter {
@@ -297,13 +560,15 @@ impl RainyClient {
         }
 
         let url = self.api_v1_url("/chat/completions");
+        let body = self.chat_stream_body(&request)?;
+        let start_time = Instant::now();
 
         // Note: Retries are more complex with streams, so we only retry the initial connection
         let operation = || async {
             let response = self
                 .client
                 .post(&url)
-                .json(&request)
+                .json(&body)
                 .send()
                 .await
                 .map_err(|e| RainyError::Network {
@@ -312,14 +577,34 @@ impl RainyClient {
                     source_error: Some(e.to_string()),
                 })?;
 
-            self.handle_stream_response(response).await
+            let metadata = self.extract_metadata(&response, start_time);
+            Ok((self.handle_stream_response(response).await?, metadata))
         };
 
-        if self.auth_config.enable_retry {
-            retry_with_backoff(&self.retry_config, operation).await
+        let (stream, metadata) = if self.auth_config.enable_retry {
+            retry_with_backoff(&self.retry_config, operation).await?
         } else {
-            operation().await
-        }
+            operation().await?
+        };
+        let stream = self
+            .meter_stream(
+                stream,
+                hold,
+                "/chat/completions",
+                &request.model,
+                request.user.as_deref(),
+                start_time,
+                metadata,
+                None,
+            )
+            .await;
+        // The usage-only chunk requested for metering carries no choices for the caller.
+        Ok(Box::pin(stream.filter_map(|item| async move {
+            match item {
+                Ok((chunk, Some(_))) if chunk.choices.is_empty() => None,
+                item => Some(item.map(|(chunk, _)| chunk)),
+            }
+        })))
     }
 
     /// Creates a Responses API completion (`POST /api/v1/responses`) in raw mode.
@@ -327,6 +612,12 @@ impl RainyClient {
         &self,
         request: ResponsesRequest,
     ) -> Result<(ResponsesApiResponse, RequestMetadata)> {
+        self.ensure_input_modalities(&request.model, &request)
+            .await?;
+        let hold = self
+            .reserve_budget(&request.model, request.user.as_deref(), &request)
+            .await?;
+
         #[cfg(feature = "rate-limiting")]
         if let Some(ref limiter) = self.rate_limiter {
             limiter.until_ready().await;
@@ -342,11 +633,20 @@ impl RainyClient {
             Ok((api_response, metadata))
         };
 
-        if self.auth_config.enable_retry {
+        let result = if self.auth_config.enable_retry {
             retry_with_backoff(&self.retry_config, operation).await
         } else {
             operation().await
-        }
+        };
+        self.finish_call(
+            "/responses",
+            &request.model,
+            request.user.as_deref(),
+            start_time,
+            hold,
+            result,
+            |response, entry| entry.with_responses_usage(response.usage.as_ref()),
+        )
     }
 
     /// Creates a Responses API completion in envelope mode (`X-Rainy-Response-Mode: envelope`).
@@ -354,6 +654,12 @@ impl RainyClient {
         &self,
         request: ResponsesRequest,
     ) -> Result<(RainyEnvelope<ResponsesApiResponse>, RequestMetadata)> {
+        self.ensure_input_modalities(&request.model, &request)
+            .await?;
+        let hold = self
+            .reserve_budget(&request.model, request.user.as_deref(), &request)
+            .await?;
+
         #[cfg(feature = "rate-limiting")]
         if let Some(ref limiter) = self.rate_limiter {
             limiter.until_ready().await;
@@ -376,19 +682,43 @@ impl RainyClient {
             Ok((api_response, metadata))
         };
 
-        if self.auth_config.enable_retry {
+        let result = if self.auth_config.enable_retry {
             retry_with_backoff(&self.retry_config, operation).await
         } else {
             operation().await
-        }
+        };
+        self.finish_call(
+            "/responses",
+            &request.model,
+            request.user.as_deref(),
+            start_time,
+            hold,
+            result,
+            |response, entry| entry.with_responses_usage(response.data.usage.as_ref()),
+        )
     }
 
     /// Creates a streaming Responses API completion and returns SSE events.
     pub async fn create_response_stream(
+        &self,
+        request: ResponsesRequest,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<ResponsesStreamEvent>> + Send>>> {
+        self.response_stream_with(request, None).await
+    }
+
+    /// Streams a Responses API completion, calling `on_finish` with the final metadata once
+    /// the stream has ended.
+    pub(crate) async fn response_stream_with(
         &self,
         mut request: ResponsesRequest,
+        on_finish: Option<StreamFinish>,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<ResponsesStreamEvent>> + Send>>> {
         request.stream = Some(true);
+        self.ensure_input_modalities(&request.model, &request)
+            .await?;
+        let hold = self
+            .reserve_budget(&request.model, request.user.as_deref(), &request)
+            .await?;
 
         #[cfg(feature = "rate-limiting")]
         if let Some(ref limiter) = self.rate_limiter {
@@ -396,6 +726,7 @@ impl RainyClient {
         }
 
         let url = self.api_v1_url("/responses");
+        let start_time = Instant::now();
 
         let operation = || async {
             let response = self
@@ -410,6 +741,7 @@ impl RainyClient {
                     source_error: Some(e.to_string()),
                 })?;
 
+            let metadata = self.extract_metadata(&response, start_time);
             let status = response.status();
             if !status.is_success() {
                 return Err(self
@@ -445,17 +777,30 @@ impl RainyClient {
                     }
                 });
 
-            Ok(Box::pin(stream)
-                as Pin<
-                    Box<dyn Stream<Item = Result<ResponsesStreamEvent>> + Send>,
-                >)
+            Ok((
+                Box::pin(stream)
+                    as Pin<Box<dyn Stream<Item = Result<ResponsesStreamEvent>> + Send>>,
+                metadata,
+            ))
         };
 
-        if self.auth_config.enable_retry {
-            retry_with_backoff(&self.retry_config, operation).await
+        let (stream, metadata) = if self.auth_config.enable_retry {
+            retry_with_backoff(&self.retry_config, operation).await?
         } else {
-            operation().await
-        }
+            operation().await?
+        };
+        Ok(self
+            .meter_stream(
+                stream,
+                hold,
+                "/responses",
+                &request.model,
+                request.user.as_deref(),
+                start_time,
+                metadata,
+                on_finish,
+            )
+            .await)
     }
 
     /// Retrieves `/api/v1/models/catalog` entries including `rainy_capabilities` metadata.
@@ -483,6 +828,44 @@ impl RainyClient {
         }
     }
 
+    /// Default time a fetched catalog is reused before it is fetched again.
+    pub const DEFAULT_CATALOG_TTL: Duration = Duration::from_secs(300);
+
+    /// Sets how long the catalog fetched for budget holds, fallbacks and capability checks
+    /// is reused.
+    pub fn with_catalog_ttl(mut self, ttl: Duration) -> Self {
+        self.catalog_ttl = ttl;
+        self
+    }
+
+    /// Returns the cached model catalog, fetching it when missing or older than the TTL.
+    pub async fn cached_models_catalog(&self) -> Result<Arc<Vec<ModelCatalogItem>>> {
+        {
+            let cached = self.catalog.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some((fetched_at, catalog)) = cached.as_ref() {
+                if fetched_at.elapsed() < self.catalog_ttl {
+                    return Ok(catalog.clone());
+                }
+            }
+        }
+
+        let catalog = Arc::new(self.get_models_catalog().await?);
+        *self.catalog.lock().unwrap_or_else(|e| e.into_inner()) =
+            Some((Instant::now(), catalog.clone()));
+        Ok(catalog)
+    }
+
+    /// Looks `model` up in the cached catalog.
+    pub async fn catalog_entry(&self, model: &str) -> Result<Option<ModelCatalogItem>> {
+        let catalog = self.cached_models_catalog().await?;
+        Ok(find_catalog_entry(&catalog, model).cloned())
+    }
+
+    /// Drops the cached catalog so the next lookup fetches it again.
+    pub fn invalidate_models_catalog(&self) {
+        *self.catalog.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
     /// Retrieves catalog and filters/sorts models using SDK selector criteria.
     pub async fn select_models(
         &self,
@@ -501,6 +884,17 @@ impl RainyClient {
         crate::models::build_reasoning_config(model, preference)
     }
 
+    /// Estimates the credit range of a request against a catalog entry before sending it.
+    ///
+    /// See [`crate::cost::estimate_cost`] for how the range is computed.
+    pub fn estimate_cost<R: CostEstimable + ?Sized>(
+        &self,
+        request: &R,
+        model: &ModelCatalogItem,
+    ) -> Option<CostEstimate> {
+        crate::cost::estimate_cost(request, model)
+    }
+
     /// Creates a simple chat completion with a single user prompt.
     ///
     /// This is a convenience method for simple use cases where you only need to send a single
@@ -580,7 +974,8 @@ impl RainyClient {
     pub(crate) async fn handle_stream_response(
         &self,
         response: Response,
-    ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatCompletionChunk>> + Send>>> {
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<(ChatCompletionChunk, Option<Usage>)>> + Send>>>
+    {
         let status = response.status();
         let request_id = response
             .headers()
@@ -598,8 +993,16 @@ impl RainyClient {
                             return None;
                         }
 
-                        match serde_json::from_str::<ChatCompletionChunk>(&event.data) {
-                            Ok(chunk) => Some(Ok(chunk)),
+                        // Chunks carry no usage, so the final usage is read alongside them.
+                        #[derive(Deserialize)]
+                        struct WithUsage {
+                            #[serde(flatten)]
+                            chunk: ChatCompletionChunk,
+                            #[serde(default)]
+                            usage: Option<Usage>,
+                        }
+                        match serde_json::from_str::<WithUsage>(&event.data) {
+                            Ok(parsed) => Some(Ok((parsed.chunk, parsed.usage))),
                             Err(e) => Some(Err(RainyError::Serialization {
                                 message: format!("Failed to parse stream chunk: {}", e),
                                 source_error: Some(e.to_string()),
@@ -642,7 +1045,11 @@ impl RainyClient {
     /// Extracts request metadata from the HTTP response headers.
     ///
     /// This is an internal method.
-    fn extract_metadata(&self, response: &Response, start_time: Instant) -> RequestMetadata {
+    pub(crate) fn extract_metadata(
+        &self,
+        response: &Response,
+        start_time: Instant,
+    ) -> RequestMetadata {
         let headers = response.headers();
 
         RequestMetadata {
@@ -691,6 +1098,7 @@ impl RainyClient {
                 .get("x-rainy-daily-credits-remaining")
                 .and_then(|v| v.to_str().ok())
                 .map(String::from),
+            served_model: None,
         }
     }
 
@@ -871,6 +1279,18 @@ impl RainyClient {
         endpoint: &str,
         body: Option<serde_json::Value>,
     ) -> Result<T> {
+        self.make_request_with_metadata(method, endpoint, body)
+            .await
+            .map(|(value, _)| value)
+    }
+
+    /// Makes a generic HTTP request and returns the response metadata alongside the body.
+    pub(crate) async fn make_request_with_metadata<T: serde::de::DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<(T, RequestMetadata)> {
         #[cfg(feature = "rate-limiting")]
         if let Some(ref limiter) = self.rate_limiter {
             limiter.until_ready().await;
@@ -885,8 +1305,11 @@ impl RainyClient {
             request = request.json(&body);
         }
 
+        let start_time = Instant::now();
         let response = request.send().await?;
-        self.handle_response(response).await
+        let metadata = self.extract_metadata(&response, start_time);
+        let value = self.handle_response(response).await?;
+        Ok((value, metadata))
     }
 }
 
@@ -896,6 +1319,9 @@ impl std::fmt::Debug for RainyClient {
             .field("base_url", &self.auth_config.base_url)
             .field("timeout", &self.auth_config.timeout_seconds)
             .field("max_retries", &self.retry_config.max_retries)
+            .field("budget", &self.budget.as_ref().map(|t| t.budget()))
+            .field("ledger", &self.ledger.is_some())
+            .field("thought_signatures", &self.thought_signatures.len())
             .finish()
     }
 }
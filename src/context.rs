@@ -0,0 +1,598 @@
+//! Context-window guard and automatic history truncation.
+//!
+//! [`ContextGuard`] estimates the prompt size of a request, adds its output cap, and compares
+//! the total against the catalog `context_length` before the request is sent. Token counts
+//! come from [`TokenCounter`] for the model's tokenizer family. When the
+//! request does not fit, a [`TruncationStrategy`] shortens the message history.
+//!
+//! History is truncated in whole turns: a turn starts at a user message and runs up to the
+//! next one, so it holds the assistant reply together with any tool calls and the `tool`
+//! messages answering them. A tool call is never separated from its result, and system
+//! messages are never removed.
+
+use crate::client::RainyClient;
+use crate::cost::CostEstimable;
+use crate::error::{RainyError, Result};
+use crate::models::{
+    ChatCompletionRequest, ChatMessage, MessageRole, ModelCatalogItem, OpenAIChatCompletionRequest,
+    OpenAIChatMessage, OpenAIContentPart, OpenAIMessageContent, OpenAIMessageRole,
+};
+use crate::tokens::TokenCounter;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// Messages the context guard can measure and group.
+pub trait ContextMessage: Clone {
+    /// Returns `true` for system messages, which are never truncated.
+    fn is_system(&self) -> bool;
+
+    /// Returns `true` for user messages, which start a new turn.
+    fn is_user(&self) -> bool;
+
+    /// Estimated tokens of this message, including per-message overhead.
+    fn estimated_tokens(&self, counter: &TokenCounter) -> u32;
+
+    /// IDs of the tool calls issued by this message.
+    fn tool_call_ids(&self) -> Vec<&str>;
+
+    /// The tool call ID this message answers, for tool results.
+    fn tool_result_for(&self) -> Option<&str>;
+
+    /// Plain-text rendering used when summarizing history.
+    fn transcript_line(&self) -> String;
+
+    /// Builds the message that carries a history summary.
+    fn summary_message(summary: String) -> Self;
+}
+
+impl ContextMessage for ChatMessage {
+    fn is_system(&self) -> bool {
+        self.role == MessageRole::System
+    }
+
+    fn is_user(&self) -> bool {
+        self.role == MessageRole::User
+    }
+
+    fn estimated_tokens(&self, counter: &TokenCounter) -> u32 {
+        counter.count_chat_message(self)
+    }
+
+    fn tool_call_ids(&self) -> Vec<&str> {
+        Vec::new()
+    }
+
+    fn tool_result_for(&self) -> Option<&str> {
+        None
+    }
+
+    fn transcript_line(&self) -> String {
+        let role = match self.role {
+            MessageRole::System => "system",
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+        };
+        format!("{role}: {}", self.content)
+    }
+
+    fn summary_message(summary: String) -> Self {
+        ChatMessage::system(summary)
+    }
+}
+
+impl ContextMessage for OpenAIChatMessage {
+    fn is_system(&self) -> bool {
+        self.role == OpenAIMessageRole::System
+    }
+
+    fn is_user(&self) -> bool {
+        self.role == OpenAIMessageRole::User
+    }
+
+    fn estimated_tokens(&self, counter: &TokenCounter) -> u32 {
+        counter.count_openai_message(self)
+    }
+
+    fn tool_call_ids(&self) -> Vec<&str> {
+        self.tool_calls
+            .iter()
+            .flatten()
+            .map(|call| call.id.as_str())
+            .collect()
+    }
+
+    fn tool_result_for(&self) -> Option<&str> {
+        if self.role == OpenAIMessageRole::Tool {
+            self.tool_call_id.as_deref()
+        } else {
+            None
+        }
+    }
+
+    fn transcript_line(&self) -> String {
+        let role = match self.role {
+            OpenAIMessageRole::System => "system",
+            OpenAIMessageRole::User => "user",
+            OpenAIMessageRole::Assistant => "assistant",
+            OpenAIMessageRole::Tool => "tool",
+        };
+        let mut text = match &self.content {
+            Some(OpenAIMessageContent::Text(text)) => text.clone(),
+            Some(OpenAIMessageContent::Parts(parts)) => parts
+                .iter()
+                .map(|part| match part {
+                    OpenAIContentPart::Text { text } => text.clone(),
+                    OpenAIContentPart::ImageUrl { .. } => "[image]".to_string(),
+                    OpenAIContentPart::InputAudio { .. } => "[audio]".to_string(),
+                    OpenAIContentPart::File { file } => match &file.filename {
+                        Some(name) => format!("[file: {name}]"),
+                        None => "[file]".to_string(),
+                    },
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+            None => String::new(),
+        };
+        for call in self.tool_calls.iter().flatten() {
+            text.push_str(&format!(
+                " [called {}({})]",
+                call.function.name, call.function.arguments
+            ));
+        }
+        format!("{role}: {}", text.trim())
+    }
+
+    fn summary_message(summary: String) -> Self {
+        OpenAIChatMessage::system(summary)
+    }
+}
+
+/// Requests whose message history the context guard can truncate.
+pub trait ContextRequest: CostEstimable {
+    /// Message type of the request.
+    type Message: ContextMessage;
+
+    /// Returns the message history.
+    fn messages(&self) -> &[Self::Message];
+
+    /// Returns the message history for modification.
+    fn messages_mut(&mut self) -> &mut Vec<Self::Message>;
+}
+
+impl ContextRequest for ChatCompletionRequest {
+    type Message = ChatMessage;
+
+    fn messages(&self) -> &[ChatMessage] {
+        &self.messages
+    }
+
+    fn messages_mut(&mut self) -> &mut Vec<ChatMessage> {
+        &mut self.messages
+    }
+}
+
+impl ContextRequest for OpenAIChatCompletionRequest {
+    type Message = OpenAIChatMessage;
+
+    fn messages(&self) -> &[OpenAIChatMessage] {
+        &self.messages
+    }
+
+    fn messages_mut(&mut self) -> &mut Vec<OpenAIChatMessage> {
+        &mut self.messages
+    }
+}
+
+/// Splits a history into groups that must be kept or dropped together.
+///
+/// Each group is a single message, except an assistant message with `tool_calls`, which is
+/// grouped with the `tool` messages that immediately follow it and answer those calls.
+pub fn message_groups<M: ContextMessage>(messages: &[M]) -> Vec<Range<usize>> {
+    let mut groups = Vec::new();
+    let mut index = 0;
+    while index < messages.len() {
+        let start = index;
+        let call_ids = messages[index].tool_call_ids();
+        index += 1;
+        if !call_ids.is_empty() {
+            while index < messages.len()
+                && messages[index]
+                    .tool_result_for()
+                    .is_some_and(|id| call_ids.contains(&id))
+            {
+                index += 1;
+            }
+        }
+        groups.push(start..index);
+    }
+    groups
+}
+
+/// Splits a history into system groups and turns.
+///
+/// Each turn is the list of [`message_groups`] from a user message up to the next one;
+/// non-system messages before the first user message form a turn of their own.
+fn split_turns<M: ContextMessage>(messages: &[M]) -> (Vec<Range<usize>>, Vec<Vec<Range<usize>>>) {
+    let mut pinned = Vec::new();
+    let mut turns: Vec<Vec<Range<usize>>> = Vec::new();
+    for group in message_groups(messages) {
+        let first = &messages[group.start];
+        if first.is_system() {
+            pinned.push(group);
+            continue;
+        }
+        match turns.last_mut() {
+            Some(turn) if !first.is_user() => turn.push(group),
+            _ => turns.push(vec![group]),
+        }
+    }
+    (pinned, turns)
+}
+
+/// How history is shortened when a request does not fit the context window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TruncationStrategy {
+    /// Do not truncate; requests that do not fit fail with `CONTEXT_LENGTH_EXCEEDED`.
+    Reject,
+    /// Drop the oldest non-system turns until the request fits.
+    DropOldest,
+    /// Keep system messages and the last `turns` turns, then drop older turns if needed.
+    KeepSystemAndLastN {
+        /// Number of trailing turns (a user message and everything answering it) to keep.
+        turns: usize,
+    },
+    /// Replace the turns between the system messages and the last `keep_last` turns with a
+    /// summary produced by `model`.
+    SummarizeMiddle {
+        /// Model used to write the summary (typically a cheap one).
+        model: String,
+        /// Number of trailing turns kept verbatim.
+        keep_last: usize,
+        /// Output cap for the summary.
+        max_summary_tokens: u32,
+    },
+}
+
+/// Result of comparing a request with a model's context window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextCheck {
+    /// Estimated prompt tokens.
+    pub prompt_tokens: u32,
+    /// Tokens reserved for the completion (`max_tokens` × `n`).
+    pub completion_tokens: u32,
+    /// Extra safety margin configured on the guard.
+    pub reserve_tokens: u32,
+    /// The model's context length.
+    pub context_length: u32,
+}
+
+impl ContextCheck {
+    /// Tokens needed beyond the context length (0 when the request fits).
+    pub fn overflow_tokens(&self) -> u32 {
+        (self.prompt_tokens + self.completion_tokens + self.reserve_tokens)
+            .saturating_sub(self.context_length)
+    }
+
+    /// Returns `true` when the request fits in the context window.
+    pub fn fits(&self) -> bool {
+        self.overflow_tokens() == 0
+    }
+}
+
+/// Summary of the changes a guard made to a request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TruncationReport {
+    /// Estimated prompt tokens before truncation.
+    pub original_prompt_tokens: u32,
+    /// Estimated prompt tokens after truncation.
+    pub final_prompt_tokens: u32,
+    /// Number of messages removed.
+    pub dropped_messages: usize,
+    /// Number of messages replaced by a summary.
+    pub summarized_messages: usize,
+}
+
+impl TruncationReport {
+    /// Returns `true` when the request was modified.
+    pub fn truncated(&self) -> bool {
+        self.dropped_messages > 0 || self.summarized_messages > 0
+    }
+}
+
+/// Guards requests against exceeding a model's context window.
+///
+/// # Examples
+///
+/// ```rust
+/// use rainy_sdk::context::{ContextGuard, TruncationStrategy};
+/// use rainy_sdk::{ModelCatalogItem, OpenAIChatCompletionRequest, OpenAIChatMessage};
+///
+/// let model = ModelCatalogItem {
+///     id: "model/small".to_string(),
+///     context_length: Some(40),
+///     ..Default::default()
+/// };
+/// let mut request = OpenAIChatCompletionRequest::new(
+///     "model/small",
+///     vec![
+///         OpenAIChatMessage::system("Be brief."),
+///         OpenAIChatMessage::user("An old question that no longer matters at all."),
+///         OpenAIChatMessage::assistant("An old answer that no longer matters either."),
+///         OpenAIChatMessage::user("Hi"),
+///     ],
+/// )
+/// .with_max_tokens(16);
+///
+/// let guard = ContextGuard::new(TruncationStrategy::DropOldest);
+/// let report = guard.truncate(&mut request, &model)?;
+/// assert!(report.truncated());
+/// assert!(guard.check(&request, &model).unwrap().fits());
+/// # Ok::<(), rainy_sdk::RainyError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextGuard {
+    /// Truncation strategy applied when a request does not fit.
+    pub strategy: TruncationStrategy,
+    /// Extra tokens kept free to absorb estimation error.
+    pub reserve_tokens: u32,
+}
+
+impl ContextGuard {
+    /// Creates a guard with the given strategy and no safety margin.
+    pub fn new(strategy: TruncationStrategy) -> Self {
+        Self {
+            strategy,
+            reserve_tokens: 0,
+        }
+    }
+
+    /// Sets the number of tokens kept free to absorb estimation error.
+    pub fn with_reserve_tokens(mut self, reserve_tokens: u32) -> Self {
+        self.reserve_tokens = reserve_tokens;
+        self
+    }
+
+    /// Compares a request with the model's context window.
+    ///
+    /// Returns `None` when the catalog entry has no `context_length`.
+    pub fn check<R: CostEstimable + ?Sized>(
+        &self,
+        request: &R,
+        model: &ModelCatalogItem,
+    ) -> Option<ContextCheck> {
+        Some(ContextCheck {
+            prompt_tokens: request.estimated_prompt_tokens_with(&TokenCounter::for_model(model)),
+            completion_tokens: request.max_completion_tokens().unwrap_or(0),
+            reserve_tokens: self.reserve_tokens,
+            context_length: model.context_length?,
+        })
+    }
+
+    fn message_budget<R: ContextRequest>(
+        &self,
+        request: &R,
+        check: &ContextCheck,
+        counter: &TokenCounter,
+    ) -> u32 {
+        let messages: u32 = request
+            .messages()
+            .iter()
+            .map(|message| message.estimated_tokens(counter))
+            .sum();
+        let overhead = check.prompt_tokens.saturating_sub(messages);
+        check
+            .context_length
+            .saturating_sub(check.completion_tokens + check.reserve_tokens + overhead)
+    }
+
+    fn exceeded(check: &ContextCheck) -> RainyError {
+        RainyError::InvalidRequest {
+            code: "CONTEXT_LENGTH_EXCEEDED".to_string(),
+            message: format!(
+                "Request needs about {} tokens but the model context length is {}",
+                check.prompt_tokens + check.completion_tokens + check.reserve_tokens,
+                check.context_length
+            ),
+            details: serde_json::to_value(check).ok(),
+        }
+    }
+
+    /// Truncates the request history in place until it fits, without calling any model.
+    ///
+    /// `SummarizeMiddle` drops the middle turns here instead of summarizing them; use
+    /// [`apply`](Self::apply) to summarize.
+    pub fn truncate<R: ContextRequest>(
+        &self,
+        request: &mut R,
+        model: &ModelCatalogItem,
+    ) -> Result<TruncationReport> {
+        let Some(check) = self.check(request, model) else {
+            return Ok(TruncationReport::default());
+        };
+        let mut report = TruncationReport {
+            original_prompt_tokens: check.prompt_tokens,
+            final_prompt_tokens: check.prompt_tokens,
+            ..Default::default()
+        };
+        if check.fits() {
+            return Ok(report);
+        }
+
+        let keep_last = match &self.strategy {
+            TruncationStrategy::Reject => return Err(Self::exceeded(&check)),
+            TruncationStrategy::DropOldest => None,
+            TruncationStrategy::KeepSystemAndLastN { turns } => Some(*turns),
+            TruncationStrategy::SummarizeMiddle { keep_last, .. } => Some(*keep_last),
+        };
+
+        let counter = TokenCounter::for_model(model);
+        let budget = self.message_budget(request, &check, &counter);
+        let before = request.messages().len();
+        let kept = fit_groups(request.messages(), budget, keep_last, &counter);
+        *request.messages_mut() = kept;
+        report.dropped_messages = before - request.messages().len();
+
+        let check = self
+            .check(request, model)
+            .expect("context length checked above");
+        report.final_prompt_tokens = check.prompt_tokens;
+        if !check.fits() {
+            return Err(Self::exceeded(&check));
+        }
+        Ok(report)
+    }
+
+    /// Makes the request fit, summarizing middle turns with `client` for `SummarizeMiddle`.
+    ///
+    /// Other strategies behave like [`truncate`](Self::truncate).
+    pub async fn apply<R: ContextRequest>(
+        &self,
+        client: &RainyClient,
+        request: &mut R,
+        model: &ModelCatalogItem,
+    ) -> Result<TruncationReport> {
+        let TruncationStrategy::SummarizeMiddle {
+            model: summary_model,
+            keep_last,
+            max_summary_tokens,
+        } = &self.strategy
+        else {
+            return self.truncate(request, model);
+        };
+
+        let Some(check) = self.check(request, model) else {
+            return Ok(TruncationReport::default());
+        };
+        if check.fits() {
+            return Ok(TruncationReport {
+                original_prompt_tokens: check.prompt_tokens,
+                final_prompt_tokens: check.prompt_tokens,
+                ..Default::default()
+            });
+        }
+
+        let messages = request.messages();
+        let (_, turns) = split_turns(messages);
+        let middle_count = turns.len().saturating_sub(*keep_last);
+        let middle: Vec<usize> = turns[..middle_count]
+            .iter()
+            .flatten()
+            .flat_map(|group| group.clone())
+            .collect();
+
+        if middle.is_empty() {
+            return self.truncate(request, model);
+        }
+
+        // Summarize as many of the latest middle turns as fit the summary model's own
+        // context window; older ones are dropped.
+        let summary_entry = client.catalog_entry(summary_model).await.ok().flatten();
+        let mut summarized = &turns[..middle_count];
+        let summary_request = loop {
+            let transcript = summarized
+                .iter()
+                .flatten()
+                .flat_map(|group| messages[group.clone()].iter())
+                .map(|message| message.transcript_line())
+                .collect::<Vec<_>>()
+                .join("\n");
+            let summary_request = ChatCompletionRequest::new(
+                summary_model.clone(),
+                vec![
+                    ChatMessage::system(
+                        "Summarize the following conversation excerpt. Keep facts, decisions, \
+                         tool results and open questions. Be concise.",
+                    ),
+                    ChatMessage::user(transcript),
+                ],
+            )
+            .with_max_tokens(*max_summary_tokens);
+            let fits = summary_entry
+                .as_ref()
+                .and_then(|entry| self.check(&summary_request, entry))
+                .is_none_or(|check| check.fits());
+            if fits {
+                break Some(summary_request);
+            }
+            if summarized.len() <= 1 {
+                break None;
+            }
+            summarized = &summarized[1..];
+        };
+        let Some(summary_request) = summary_request else {
+            return self.truncate(request, model);
+        };
+        let summarized_messages = summarized
+            .iter()
+            .flatten()
+            .map(|group| group.len())
+            .sum::<usize>();
+
+        let (response, _) = client.chat_completion(summary_request).await?;
+        let summary = response
+            .choices
+            .first()
+            .map(|choice| choice.message.content.clone())
+            .unwrap_or_default();
+
+        let first_middle = middle[0];
+        let mut rebuilt = Vec::with_capacity(messages.len() - middle.len() + 1);
+        for (index, message) in messages.iter().enumerate() {
+            if index == first_middle {
+                rebuilt.push(R::Message::summary_message(format!(
+                    "Summary of earlier conversation:\n{summary}"
+                )));
+            }
+            if !middle.contains(&index) {
+                rebuilt.push(message.clone());
+            }
+        }
+        *request.messages_mut() = rebuilt;
+
+        let mut report = self.truncate(request, model)?;
+        report.original_prompt_tokens = check.prompt_tokens;
+        report.dropped_messages += middle.len() - summarized_messages;
+        report.summarized_messages = summarized_messages;
+        Ok(report)
+    }
+}
+/// Keeps system groups plus as many trailing turns as fit in `budget` tokens.
+///
+/// At least the last turn is always kept so the request still has a turn.
+fn fit_groups<M: ContextMessage>(
+    messages: &[M],
+    budget: u32,
+    keep_last: Option<usize>,
+    counter: &TokenCounter,
+) -> Vec<M> {
+    let tokens = |groups: &[Range<usize>]| -> u32 {
+        groups
+            .iter()
+            .flat_map(|group| messages[group.clone()].iter())
+            .map(|message| message.estimated_tokens(counter))
+            .sum()
+    };
+
+    let (pinned, mut turns) = split_turns(messages);
+    if let Some(keep_last) = keep_last {
+        let skip = turns.len().saturating_sub(keep_last.max(1));
+        turns.drain(..skip);
+    }
+
+    let pinned_tokens = tokens(&pinned);
+    let mut turn_tokens: u32 = turns.iter().map(|turn| tokens(turn)).sum();
+    while turns.len() > 1 && pinned_tokens + turn_tokens > budget {
+        let dropped = turns.remove(0);
+        turn_tokens -= tokens(&dropped);
+    }
+
+    let mut kept: Vec<Range<usize>> = pinned
+        .into_iter()
+        .chain(turns.into_iter().flatten())
+        .collect();
+    kept.sort_by_key(|group| group.start);
+    kept.into_iter()
+        .flat_map(|group| messages[group].iter().cloned())
+        .collect()
+}
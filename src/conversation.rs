@@ -0,0 +1,654 @@
+//! Stateful multi-turn conversations on top of [`RainyClient`].
+//!
+//! [`Conversation`] owns the system prompt, the OpenAI-compatible message history and
+//! per-turn metadata. `send`/`send_stream` append the user message, call the API, and append
+//! the finished assistant message (content, reasoning and tool calls, including any
+//! provider metadata such as thought signatures) so history can be replayed verbatim.
+//!
+//! Conversations are plain serde values and can be persisted and restored as JSON, including
+//! tool calls still waiting for approval (see [`Conversation::request_approvals`]).
+
+use crate::approval::{ApprovalPolicy, ToolApprovals};
+use crate::client::RainyClient;
+use crate::error::{RainyError, Result};
+use crate::metering::StreamFinish;
+use crate::models::{
+    ChatCompletionStreamResponse, FinishReason, OpenAIChatCompletionRequest, OpenAIChatMessage,
+    OpenAIMessageContent, OpenAIMessageRole, OpenAIToolCall, RequestMetadata, Usage,
+};
+use crate::tool_calls::{empty_tool_call, merge_tool_call_delta};
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+/// Metadata recorded for one completed assistant turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnMetadata {
+    /// Index of the assistant message in [`Conversation::messages`].
+    pub message_index: usize,
+    /// Model that produced the reply.
+    pub model: String,
+    /// Completion ID returned by the API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_id: Option<String>,
+    /// Reason generation stopped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<FinishReason>,
+    /// Token usage reported for the turn.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+    /// Rainy request ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Credits charged for the turn.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credits_charged: Option<f64>,
+    /// Wall-clock latency of the call in milliseconds.
+    pub latency_ms: u64,
+    /// When the turn completed.
+    pub completed_at: DateTime<Utc>,
+}
+
+/// Multi-turn conversation state: system prompt, history and per-turn metadata.
+///
+/// # Examples
+///
+/// ```rust
+/// use rainy_sdk::Conversation;
+///
+/// let mut conversation = Conversation::new("google/gemini-3-flash-preview")
+///     .with_system_prompt("You are terse.");
+/// conversation.push_user("Hi");
+///
+/// let request = conversation.build_request();
+/// assert_eq!(request.messages.len(), 2);
+///
+/// let saved = serde_json::to_string(&conversation)?;
+/// let restored: Conversation = serde_json::from_str(&saved)?;
+/// assert_eq!(restored.messages(), conversation.messages());
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    system_prompt: Option<String>,
+    #[serde(default)]
+    messages: Vec<OpenAIChatMessage>,
+    #[serde(default)]
+    turns: Vec<TurnMetadata>,
+    template: OpenAIChatCompletionRequest,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    approvals: Option<ToolApprovals>,
+}
+
+impl Conversation {
+    /// Creates an empty conversation for `model`.
+    pub fn new(model: impl Into<String>) -> Self {
+        Self::with_request(OpenAIChatCompletionRequest::new(model, Vec::new()))
+    }
+
+    /// Creates an empty conversation whose calls use `template` for model and parameters.
+    ///
+    /// Any messages in the template are ignored; history is owned by the conversation.
+    pub fn with_request(mut template: OpenAIChatCompletionRequest) -> Self {
+        template.messages.clear();
+        template.stream = None;
+        Self {
+            system_prompt: None,
+            messages: Vec::new(),
+            turns: Vec::new(),
+            template,
+            approvals: None,
+        }
+    }
+
+    /// Sets the system prompt sent ahead of the history.
+    pub fn with_system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Replaces or clears the system prompt.
+    pub fn set_system_prompt(&mut self, prompt: Option<String>) {
+        self.system_prompt = prompt;
+    }
+
+    /// The system prompt, if any.
+    pub fn system_prompt(&self) -> Option<&str> {
+        self.system_prompt.as_deref()
+    }
+
+    /// The model used for the next call.
+    pub fn model(&self) -> &str {
+        &self.template.model
+    }
+
+    /// Request parameters (tools, temperature, thinking config, ...) applied to every call.
+    pub fn request_template_mut(&mut self) -> &mut OpenAIChatCompletionRequest {
+        &mut self.template
+    }
+
+    /// Message history, excluding the system prompt.
+    pub fn messages(&self) -> &[OpenAIChatMessage] {
+        &self.messages
+    }
+
+    /// Metadata for each completed assistant turn, oldest first.
+    pub fn turns(&self) -> &[TurnMetadata] {
+        &self.turns
+    }
+
+    /// The most recent assistant message.
+    pub fn last_reply(&self) -> Option<&OpenAIChatMessage> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|message| message.role == OpenAIMessageRole::Assistant)
+    }
+
+    /// Appends any message to the history.
+    pub fn push(&mut self, message: OpenAIChatMessage) {
+        self.messages.push(message);
+    }
+
+    /// Appends a user message.
+    pub fn push_user(&mut self, content: impl Into<OpenAIMessageContent>) {
+        self.push(OpenAIChatMessage::user(content));
+    }
+
+    /// Appends the result of a tool call made by the last assistant message.
+    pub fn push_tool_result(
+        &mut self,
+        tool_call_id: impl Into<String>,
+        content: impl Into<OpenAIMessageContent>,
+    ) {
+        self.push(OpenAIChatMessage::tool(tool_call_id, content));
+    }
+
+    /// Starts the approval flow for the tool calls of the last assistant message.
+    ///
+    /// Calls `policy` trusts are approved at once; the rest wait for
+    /// [`ToolApprovals::resolve`] or [`ToolApprovals::review`]. The queue is saved with the
+    /// conversation. Returns `None` when the last message is not an assistant tool call.
+    pub fn request_approvals(&mut self, policy: &ApprovalPolicy) -> Option<&mut ToolApprovals> {
+        let calls = self
+            .messages
+            .last()
+            .filter(|message| message.role == OpenAIMessageRole::Assistant)?
+            .tool_calls
+            .as_deref()
+            .filter(|calls| !calls.is_empty())?;
+        self.approvals = Some(ToolApprovals::new(calls, policy));
+        self.approvals.as_mut()
+    }
+
+    /// The approval queue for the last assistant message, if one is open.
+    pub fn approvals(&self) -> Option<&ToolApprovals> {
+        self.approvals.as_ref()
+    }
+
+    /// Mutable access to the open approval queue, e.g. to record decisions.
+    pub fn approvals_mut(&mut self) -> Option<&mut ToolApprovals> {
+        self.approvals.as_mut()
+    }
+
+    /// Closes the approval queue and returns the calls to run.
+    ///
+    /// Edited arguments are written back into the assistant message so the history matches
+    /// what ran, and a tool message is appended for every denied call. Append the results
+    /// of the returned calls with [`push_tool_result`](Self::push_tool_result). Fails with
+    /// `APPROVALS_PENDING` while any call still awaits a decision.
+    pub fn take_approved_calls(&mut self) -> Result<Vec<OpenAIToolCall>> {
+        let Some(approvals) = self.approvals.take() else {
+            return Ok(Vec::new());
+        };
+        if !approvals.is_resolved() {
+            let pending = approvals.pending().count();
+            self.approvals = Some(approvals);
+            return Err(RainyError::InvalidRequest {
+                code: "APPROVALS_PENDING".to_string(),
+                message: format!("{pending} tool call(s) still await approval"),
+                details: None,
+            });
+        }
+
+        if let Some(calls) = self
+            .messages
+            .iter_mut()
+            .rev()
+            .find(|message| message.role == OpenAIMessageRole::Assistant)
+            .and_then(|message| message.tool_calls.as_mut())
+        {
+            for call in calls.iter_mut() {
+                if let Some(entry) = approvals.calls().iter().find(|e| e.id() == call.id) {
+                    call.function.arguments = entry.tool_call.function.arguments.clone();
+                }
+            }
+        }
+        self.messages.extend(approvals.denial_messages());
+        Ok(approvals.approved_calls())
+    }
+
+    /// Builds the request for the next call: template parameters, system prompt and history.
+    pub fn build_request(&self) -> OpenAIChatCompletionRequest {
+        let mut request = self.template.clone();
+        request.messages = self
+            .system_prompt
+            .iter()
+            .map(|prompt| OpenAIChatMessage::system(prompt.as_str()))
+            .chain(self.messages.iter().cloned())
+            .collect();
+        request
+    }
+
+    /// Returns an independent copy of this conversation.
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
+    /// Returns a copy holding only the first `message_count` messages.
+    pub fn fork_at(&self, message_count: usize) -> Self {
+        let mut fork = self.clone();
+        fork.truncate(message_count);
+        fork
+    }
+
+    /// Drops every message from `message_count` on, with the metadata of dropped turns.
+    pub fn truncate(&mut self, message_count: usize) {
+        if message_count < self.messages.len() {
+            self.approvals = None;
+        }
+        self.messages.truncate(message_count);
+        self.turns
+            .retain(|turn| turn.message_index < self.messages.len());
+    }
+
+    /// Removes the last user message and everything after it, returning the removed messages.
+    ///
+    /// Returns `None` when the history has no user message.
+    pub fn undo(&mut self) -> Option<Vec<OpenAIChatMessage>> {
+        let index = self
+            .messages
+            .iter()
+            .rposition(|message| message.role == OpenAIMessageRole::User)?;
+        let removed = self.messages[index..].to_vec();
+        self.truncate(index);
+        Some(removed)
+    }
+
+    /// Replaces the user message at `message_index` and drops everything after it.
+    pub fn edit(
+        &mut self,
+        message_index: usize,
+        content: impl Into<OpenAIMessageContent>,
+    ) -> Result<()> {
+        match self.messages.get(message_index) {
+            Some(message) if message.role == OpenAIMessageRole::User => {}
+            _ => {
+                return Err(RainyError::InvalidRequest {
+                    code: "INVALID_MESSAGE_INDEX".to_string(),
+                    message: format!("Message {message_index} is not a user message"),
+                    details: None,
+                })
+            }
+        }
+        self.truncate(message_index);
+        self.push_user(content);
+        Ok(())
+    }
+
+    /// Sends a user message and appends the assistant reply.
+    ///
+    /// On failure the user message is removed again so the call can be retried.
+    pub async fn send(
+        &mut self,
+        client: &RainyClient,
+        content: impl Into<OpenAIMessageContent>,
+    ) -> Result<&OpenAIChatMessage> {
+        self.push_user(content);
+        let pending = self.messages.len() - 1;
+        match self.complete(client).await {
+            Ok(_) => Ok(self.messages.last().expect("reply appended")),
+            Err(error) => {
+                self.truncate(pending);
+                Err(error)
+            }
+        }
+    }
+
+    /// Asks the model to continue from the current history (e.g. after tool results).
+    pub async fn complete(&mut self, client: &RainyClient) -> Result<&OpenAIChatMessage> {
+        let start_time = Instant::now();
+        let (response, metadata) = client
+            .openai_chat_completion_with_metadata(self.build_request())
+            .await?;
+        let choice =
+            response
+                .choices
+                .into_iter()
+                .next()
+                .ok_or_else(|| RainyError::Serialization {
+                    message: "Chat completion response contained no choices".to_string(),
+                    source_error: None,
+                })?;
+
+        let mut message = choice.message;
+        message.role = OpenAIMessageRole::Assistant;
+        self.append_reply(
+            message,
+            TurnMetadata {
+                message_index: 0,
+                model: metadata.served_model.clone().unwrap_or(response.model),
+                response_id: Some(response.id),
+                finish_reason: Some(choice.finish_reason),
+                usage: response.usage,
+                request_id: None,
+                credits_charged: None,
+                latency_ms: 0,
+                completed_at: Utc::now(),
+            },
+            &metadata,
+            start_time,
+        );
+        Ok(self.messages.last().expect("reply appended"))
+    }
+
+    /// Sends a user message and streams the reply.
+    ///
+    /// The assistant message is appended once the returned stream is exhausted. If the
+    /// stream fails, or is dropped early, nothing is appended and the user message is
+    /// removed again.
+    pub async fn send_stream<'a>(
+        &'a mut self,
+        client: &RainyClient,
+        content: impl Into<OpenAIMessageContent>,
+    ) -> Result<ConversationStream<'a>> {
+        self.push_user(content);
+        let pending = self.messages.len() - 1;
+        let metadata = MetadataSlot::default();
+        match client
+            .openai_chat_completion_stream_with(
+                self.build_request(),
+                serde_json::Map::new(),
+                Some(store_metadata(&metadata)),
+            )
+            .await
+        {
+            Ok(inner) => Ok(ConversationStream::new(
+                self,
+                inner,
+                metadata,
+                Some(pending),
+            )),
+            Err(error) => {
+                self.truncate(pending);
+                Err(error)
+            }
+        }
+    }
+
+    /// Streams a continuation of the current history (e.g. after tool results).
+    pub async fn complete_stream<'a>(
+        &'a mut self,
+        client: &RainyClient,
+    ) -> Result<ConversationStream<'a>> {
+        let metadata = MetadataSlot::default();
+        let inner = client
+            .openai_chat_completion_stream_with(
+                self.build_request(),
+                serde_json::Map::new(),
+                Some(store_metadata(&metadata)),
+            )
+            .await?;
+        Ok(ConversationStream::new(self, inner, metadata, None))
+    }
+
+    /// Edits the user message at `message_index`, drops later history and sends it again.
+    ///
+    /// On failure the history is restored as it was before the edit.
+    pub async fn edit_and_resend(
+        &mut self,
+        client: &RainyClient,
+        message_index: usize,
+        content: impl Into<OpenAIMessageContent>,
+    ) -> Result<&OpenAIChatMessage> {
+        let messages = self.messages.clone();
+        let turns = self.turns.clone();
+        let approvals = self.approvals.clone();
+        self.edit(message_index, content)?;
+        match self.complete(client).await {
+            Ok(_) => Ok(self.messages.last().expect("reply appended")),
+            Err(error) => {
+                self.messages = messages;
+                self.turns = turns;
+                self.approvals = approvals;
+                Err(error)
+            }
+        }
+    }
+
+    fn append_reply(
+        &mut self,
+        message: OpenAIChatMessage,
+        mut turn: TurnMetadata,
+        metadata: &RequestMetadata,
+        start_time: Instant,
+    ) {
+        turn.message_index = self.messages.len();
+        turn.request_id = metadata.request_id.clone();
+        turn.credits_charged = metadata.rainy_credits_charged.or(metadata.credits_used);
+        turn.latency_ms = start_time.elapsed().as_millis() as u64;
+        self.messages.push(message);
+        self.turns.push(turn);
+    }
+}
+
+/// Folds streaming chunks into a complete assistant message.
+///
+/// Content and reasoning deltas are concatenated; tool-call deltas are merged by index.
+#[derive(Debug, Clone, Default)]
+pub struct StreamAccumulator {
+    id: Option<String>,
+    model: Option<String>,
+    content: String,
+    reasoning: String,
+    tool_calls: BTreeMap<u32, OpenAIToolCall>,
+    finish_reason: Option<FinishReason>,
+    usage: Option<Usage>,
+}
+
+impl StreamAccumulator {
+    /// Creates an empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Folds one chunk (first choice only) into the accumulated message.
+    pub fn push(&mut self, chunk: &ChatCompletionStreamResponse) {
+        self.id.get_or_insert_with(|| chunk.id.clone());
+        self.model.get_or_insert_with(|| chunk.model.clone());
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage.clone();
+        }
+        let Some(choice) = chunk.choices.iter().find(|choice| choice.index == 0) else {
+            return;
+        };
+        if let Some(reason) = &choice.finish_reason {
+            self.finish_reason = Some(reason.clone());
+        }
+
+        let delta = &choice.delta;
+        if let Some(content) = &delta.content {
+            self.content.push_str(content);
+        }
+        for reasoning in [&delta.thought, &delta.reasoning].into_iter().flatten() {
+            self.reasoning.push_str(reasoning);
+        }
+        for call in delta.tool_calls.iter().flatten() {
+            let entry = self
+                .tool_calls
+                .entry(call.index)
+                .or_insert_with(empty_tool_call);
+            merge_tool_call_delta(entry, call);
+        }
+    }
+
+    /// Text content received so far.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Reason generation stopped, once the final chunk has arrived.
+    pub fn finish_reason(&self) -> Option<&FinishReason> {
+        self.finish_reason.as_ref()
+    }
+
+    /// Usage from the final chunk, when the provider sends it.
+    pub fn usage(&self) -> Option<&Usage> {
+        self.usage.as_ref()
+    }
+
+    /// The assistant message accumulated so far.
+    pub fn message(&self) -> OpenAIChatMessage {
+        let tool_calls: Vec<OpenAIToolCall> = self.tool_calls.values().cloned().collect();
+        let content = if self.content.is_empty() && !tool_calls.is_empty() {
+            None
+        } else {
+            Some(OpenAIMessageContent::Text(self.content.clone()))
+        };
+        let mut message = OpenAIChatMessage::with_parts(
+            OpenAIMessageRole::Assistant,
+            content,
+            (!tool_calls.is_empty()).then_some(tool_calls),
+            None,
+        );
+        if !self.reasoning.is_empty() {
+            message.reasoning = Some(self.reasoning.clone());
+        }
+        message
+    }
+}
+
+type ChunkStream = Pin<Box<dyn Stream<Item = Result<ChatCompletionStreamResponse>> + Send>>;
+/// Final metadata of a stream, filled in by its finish callback.
+type MetadataSlot = Arc<Mutex<Option<RequestMetadata>>>;
+
+fn store_metadata(slot: &MetadataSlot) -> StreamFinish {
+    let slot = Arc::clone(slot);
+    Box::new(move |metadata| {
+        *slot.lock().unwrap_or_else(|e| e.into_inner()) = Some(metadata.clone());
+    })
+}
+
+/// Stream of reply chunks that appends the finished message to its [`Conversation`].
+pub struct ConversationStream<'a> {
+    conversation: &'a mut Conversation,
+    inner: ChunkStream,
+    metadata: MetadataSlot,
+    accumulator: StreamAccumulator,
+    pending_user: Option<usize>,
+    start_time: Instant,
+    failed: bool,
+    finished: bool,
+}
+
+impl<'a> ConversationStream<'a> {
+    fn new(
+        conversation: &'a mut Conversation,
+        inner: ChunkStream,
+        metadata: MetadataSlot,
+        pending_user: Option<usize>,
+    ) -> Self {
+        Self {
+            conversation,
+            inner,
+            metadata,
+            accumulator: StreamAccumulator::new(),
+            pending_user,
+            start_time: Instant::now(),
+            failed: false,
+            finished: false,
+        }
+    }
+
+    /// The reply accumulated so far.
+    pub fn accumulator(&self) -> &StreamAccumulator {
+        &self.accumulator
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+        if self.failed {
+            if let Some(index) = self.pending_user.take() {
+                self.conversation.truncate(index);
+            }
+            return;
+        }
+        let metadata = self
+            .metadata
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .unwrap_or_default();
+        let accumulator = &self.accumulator;
+        let turn = TurnMetadata {
+            message_index: 0,
+            model: metadata
+                .served_model
+                .clone()
+                .or_else(|| accumulator.model.clone())
+                .unwrap_or_else(|| self.conversation.model().to_string()),
+            response_id: accumulator.id.clone(),
+            finish_reason: accumulator.finish_reason.clone(),
+            usage: accumulator.usage.clone(),
+            request_id: None,
+            credits_charged: None,
+            latency_ms: 0,
+            completed_at: Utc::now(),
+        };
+        self.conversation
+            .append_reply(accumulator.message(), turn, &metadata, self.start_time);
+        self.pending_user = None;
+    }
+}
+
+impl Stream for ConversationStream<'_> {
+    type Item = Result<ChatCompletionStreamResponse>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.finished {
+            return Poll::Ready(None);
+        }
+        match this.inner.as_mut().poll_next(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                this.accumulator.push(&chunk);
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Poll::Ready(Some(Err(error))) => {
+                this.failed = true;
+                Poll::Ready(Some(Err(error)))
+            }
+            Poll::Ready(None) => {
+                this.finish();
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl Drop for ConversationStream<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            if let Some(index) = self.pending_user.take() {
+                self.conversation.truncate(index);
+            }
+        }
+    }
+}
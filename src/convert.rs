@@ -0,0 +1,501 @@
+//! Conversions between the chat, OpenAI-compatible and Responses API request shapes.
+//!
+//! Conversions keep every field the target shape can express and return a
+//! [`ConversionReport`] naming the fields that had to be dropped, so call sites can move to
+//! the Responses API incrementally and reuse one message history across both endpoints:
+//!
+//! - [`ChatCompletionRequest`] → [`OpenAIChatCompletionRequest`] is lossless (`From`).
+//! - [`OpenAIChatCompletionRequest::to_responses`] maps messages to input items, tools to
+//!   flat function tools and `max_tokens` to `max_output_tokens`.
+//! - [`responses_items_to_openai_messages`] turns Responses input or output items back into
+//!   OpenAI-compatible messages.
+
+use crate::models::{
+    ChatCompletionRequest, ChatMessage, MessageRole, OpenAIChatCompletionRequest,
+    OpenAIChatMessage, OpenAIContentPart, OpenAIFileInput, OpenAIFunctionCall, OpenAIImageUrl,
+    OpenAIMessageContent, OpenAIMessageRole, OpenAIToolCall, ResponseFormat, ResponsesApiResponse,
+    ResponsesRequest, ToolChoice,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Fields a conversion could not carry over to the target shape.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConversionReport {
+    /// Dropped fields, by source path (e.g. `frequency_penalty`, `messages[2].name`).
+    pub dropped: Vec<String>,
+}
+
+impl ConversionReport {
+    /// Returns `true` when nothing was dropped.
+    pub fn is_lossless(&self) -> bool {
+        self.dropped.is_empty()
+    }
+
+    fn drop_field(&mut self, field: impl Into<String>) {
+        self.dropped.push(field.into());
+    }
+
+    fn drop_if<T>(&mut self, value: &Option<T>, field: &str) {
+        if value.is_some() {
+            self.drop_field(field);
+        }
+    }
+}
+
+impl From<MessageRole> for OpenAIMessageRole {
+    fn from(role: MessageRole) -> Self {
+        match role {
+            MessageRole::System => Self::System,
+            MessageRole::User => Self::User,
+            MessageRole::Assistant => Self::Assistant,
+        }
+    }
+}
+
+impl From<ChatMessage> for OpenAIChatMessage {
+    fn from(message: ChatMessage) -> Self {
+        Self::with_parts(
+            message.role.into(),
+            Some(OpenAIMessageContent::Text(message.content)),
+            None,
+            None,
+        )
+    }
+}
+
+impl From<ChatCompletionRequest> for OpenAIChatCompletionRequest {
+    fn from(request: ChatCompletionRequest) -> Self {
+        Self {
+            model: request.model,
+            messages: request.messages.into_iter().map(Into::into).collect(),
+            temperature: request.temperature,
+            max_tokens: request.max_tokens,
+            top_p: request.top_p,
+            frequency_penalty: request.frequency_penalty,
+            presence_penalty: request.presence_penalty,
+            stop: request.stop,
+            user: request.user,
+            provider: request.provider,
+            stream: request.stream,
+            logit_bias: request.logit_bias,
+            logprobs: request.logprobs,
+            top_logprobs: request.top_logprobs,
+            n: request.n,
+            response_format: request.response_format,
+            tools: request.tools,
+            tool_choice: request.tool_choice,
+            parallel_tool_calls: request.parallel_tool_calls,
+            thinking_config: request.thinking_config,
+            thinking: None,
+            modalities: None,
+        }
+    }
+}
+
+impl ChatCompletionRequest {
+    /// Converts to the OpenAI-compatible shape; the conversion is lossless.
+    pub fn to_openai(&self) -> (OpenAIChatCompletionRequest, ConversionReport) {
+        (self.clone().into(), ConversionReport::default())
+    }
+}
+
+/// Joins the text parts of a message, the way the chat endpoint concatenates them.
+fn join_text<'a>(texts: impl Iterator<Item = &'a str>) -> String {
+    texts.collect()
+}
+
+/// Text of a tool result; `function_call_output` only carries text, so other parts are
+/// reported as dropped.
+fn tool_output_text(
+    content: &OpenAIMessageContent,
+    index: usize,
+    report: &mut ConversionReport,
+) -> String {
+    match content {
+        OpenAIMessageContent::Text(text) => text.clone(),
+        OpenAIMessageContent::Parts(parts) => {
+            for (part_index, part) in parts.iter().enumerate() {
+                if !matches!(part, OpenAIContentPart::Text { .. }) {
+                    report.drop_field(format!("messages[{index}].content[{part_index}]"));
+                }
+            }
+            join_text(parts.iter().filter_map(|part| match part {
+                OpenAIContentPart::Text { text } => Some(text.as_str()),
+                _ => None,
+            }))
+        }
+    }
+}
+
+fn message_content_to_input(role: &OpenAIMessageRole, content: &OpenAIMessageContent) -> Value {
+    match content {
+        OpenAIMessageContent::Text(text) => Value::String(text.clone()),
+        OpenAIMessageContent::Parts(parts) => Value::Array(
+            parts
+                .iter()
+                .map(|part| match part {
+                    OpenAIContentPart::Text { text } if *role == OpenAIMessageRole::Assistant => {
+                        json!({ "type": "output_text", "text": text })
+                    }
+                    part => part.to_responses_input(),
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Converts OpenAI-compatible messages into Responses API input items.
+pub fn openai_messages_to_responses_input(
+    messages: &[OpenAIChatMessage],
+    report: &mut ConversionReport,
+) -> Value {
+    let mut items = Vec::new();
+    for (index, message) in messages.iter().enumerate() {
+        report.drop_if(&message.name, &format!("messages[{index}].name"));
+
+        if message.role == OpenAIMessageRole::Tool {
+            items.push(json!({
+                "type": "function_call_output",
+                "call_id": message.tool_call_id.clone().unwrap_or_default(),
+                "output": message
+                    .content
+                    .as_ref()
+                    .map(|content| tool_output_text(content, index, report))
+                    .unwrap_or_default(),
+            }));
+            continue;
+        }
+        // Replayed `reasoning` items need the `id` the API issued, which chat history lacks.
+        report.drop_if(&message.reasoning, &format!("messages[{index}].reasoning"));
+        report.drop_if(&message.images, &format!("messages[{index}].images"));
+        if let Some(content) = &message.content {
+            items.push(json!({
+                "type": "message",
+                "role": message.role.as_str(),
+                "content": message_content_to_input(&message.role, content),
+            }));
+        }
+        for (call_index, call) in message.tool_calls.iter().flatten().enumerate() {
+            // Responses `function_call` items have no slot for provider extras.
+            report.drop_if(
+                &call.extra_content,
+                &format!("messages[{index}].tool_calls[{call_index}].extra_content"),
+            );
+            items.push(json!({
+                "type": "function_call",
+                "call_id": call.id,
+                "name": call.function.name,
+                "arguments": call.function.arguments,
+            }));
+        }
+    }
+    Value::Array(items)
+}
+
+/// Converts a chat `response_format` to the Responses API `text.format` value.
+///
+/// The Responses API flattens `json_schema`: `name`, `schema`, `strict` and `description`
+/// sit next to `type` instead of under a nested `json_schema` object.
+fn response_format_to_responses(format: &ResponseFormat) -> Value {
+    match format {
+        ResponseFormat::Text => json!({ "type": "text" }),
+        ResponseFormat::JsonObject => json!({ "type": "json_object" }),
+        ResponseFormat::JsonSchema { json_schema } => {
+            let mut flat = json!({ "type": "json_schema" });
+            match json_schema {
+                Value::Object(fields) if fields.contains_key("schema") => {
+                    for (key, value) in fields {
+                        flat[key] = value.clone();
+                    }
+                }
+                schema => {
+                    flat["name"] = json!("response");
+                    flat["schema"] = schema.clone();
+                }
+            }
+            flat
+        }
+    }
+}
+
+impl OpenAIChatCompletionRequest {
+    /// Converts to a Responses API request.
+    ///
+    /// `response_format` becomes `text.format`. Provider-specific `provider`,
+    /// `thinking_config` and `thinking` are passed through as extra fields. Sampling controls
+    /// the Responses API lacks (`frequency_penalty`, `presence_penalty`, `stop`, `logit_bias`,
+    /// `logprobs`, `top_logprobs`, `n`), message `name`s, `reasoning` and generated `images`,
+    /// tool call `extra_content` and non-text parts of tool results are dropped and listed in
+    /// the report.
+    pub fn to_responses(&self) -> (ResponsesRequest, ConversionReport) {
+        let mut report = ConversionReport::default();
+        let input = openai_messages_to_responses_input(&self.messages, &mut report);
+        let mut request = ResponsesRequest::new(self.model.clone(), input);
+
+        request.stream = self.stream;
+        request.temperature = self.temperature;
+        request.top_p = self.top_p;
+        request.max_output_tokens = self.max_tokens;
+        request.user = self.user.clone();
+        request.tools = self.tools.as_ref().map(|tools| {
+            tools
+                .iter()
+                .map(|tool| {
+                    let mut function = json!({ "type": "function", "name": tool.function.name });
+                    if let Some(description) = &tool.function.description {
+                        function["description"] = json!(description);
+                    }
+                    if let Some(parameters) = &tool.function.parameters {
+                        function["parameters"] = parameters.clone();
+                    }
+                    function
+                })
+                .collect()
+        });
+        request.tool_choice = self.tool_choice.as_ref().map(ToolChoice::to_responses);
+        request.parallel_tool_calls = self.parallel_tool_calls;
+        if let Some(format) = &self.response_format {
+            request.extra.insert(
+                "text".to_string(),
+                json!({ "format": response_format_to_responses(format) }),
+            );
+        }
+
+        if let Some(provider) = &self.provider {
+            request
+                .extra
+                .insert("provider".to_string(), json!(provider));
+        }
+        if let Some(config) = &self.thinking_config {
+            if let Ok(value) = serde_json::to_value(config) {
+                request.extra.insert("thinking_config".to_string(), value);
+            }
+        }
+        if let Some(thinking) = &self.thinking {
+            request
+                .extra
+                .insert("thinking".to_string(), thinking.clone());
+        }
+
+        report.drop_if(&self.frequency_penalty, "frequency_penalty");
+        report.drop_if(&self.presence_penalty, "presence_penalty");
+        report.drop_if(&self.stop, "stop");
+        report.drop_if(&self.logit_bias, "logit_bias");
+        report.drop_if(&self.logprobs, "logprobs");
+        report.drop_if(&self.top_logprobs, "top_logprobs");
+        report.drop_if(&self.modalities, "modalities");
+        if self.n.is_some_and(|n| n != 1) {
+            report.drop_field("n");
+        }
+        (request, report)
+    }
+}
+
+fn item_text(content: &Value) -> Option<OpenAIMessageContent> {
+    match content {
+        Value::String(text) => Some(OpenAIMessageContent::Text(text.clone())),
+        Value::Array(parts) => {
+            let parts: Vec<OpenAIContentPart> = parts
+                .iter()
+                .filter_map(|part| match part.get("type")?.as_str()? {
+                    "input_text" | "output_text" | "text" | "refusal" => {
+                        let text = part.get("text").or_else(|| part.get("refusal"))?;
+                        Some(OpenAIContentPart::Text {
+                            text: text.as_str()?.to_string(),
+                        })
+                    }
+                    "input_image" => Some(OpenAIContentPart::ImageUrl {
+                        image_url: OpenAIImageUrl {
+                            url: part.get("image_url")?.as_str()?.to_string(),
+                            detail: part
+                                .get("detail")
+                                .and_then(Value::as_str)
+                                .map(str::to_string),
+                        },
+                    }),
+                    "input_audio" => Some(OpenAIContentPart::InputAudio {
+                        input_audio: serde_json::from_value(part.get("input_audio")?.clone())
+                            .ok()?,
+                    }),
+                    "input_file" => Some(OpenAIContentPart::File {
+                        file: serde_json::from_value::<OpenAIFileInput>(part.clone()).ok()?,
+                    }),
+                    _ => None,
+                })
+                .collect();
+            match parts.as_slice() {
+                [] => None,
+                [OpenAIContentPart::Text { text }] => {
+                    Some(OpenAIMessageContent::Text(text.clone()))
+                }
+                _ if parts
+                    .iter()
+                    .all(|part| matches!(part, OpenAIContentPart::Text { .. })) =>
+                {
+                    Some(OpenAIMessageContent::Text(join_text(
+                        parts.iter().filter_map(|part| match part {
+                            OpenAIContentPart::Text { text } => Some(text.as_str()),
+                            _ => None,
+                        }),
+                    )))
+                }
+                _ => Some(OpenAIMessageContent::Parts(parts)),
+            }
+        }
+        _ => None,
+    }
+}
+
+fn reasoning_text(item: &Value) -> String {
+    ["summary", "content"]
+        .iter()
+        .filter_map(|key| item.get(*key)?.as_array())
+        .flatten()
+        .filter_map(|part| part.get("text")?.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn parse_role(role: Option<&str>) -> OpenAIMessageRole {
+    match role {
+        Some("system") | Some("developer") => OpenAIMessageRole::System,
+        Some("user") => OpenAIMessageRole::User,
+        _ => OpenAIMessageRole::Assistant,
+    }
+}
+
+/// Converts Responses API input or output items into OpenAI-compatible messages.
+///
+/// Consecutive assistant items (reasoning, text and function calls) are merged into one
+/// assistant message so tool calls and their results pair up as the chat endpoint expects.
+/// Items with no chat equivalent (e.g. `web_search_call`) are reported as dropped.
+pub fn responses_items_to_openai_messages(
+    items: &[Value],
+) -> (Vec<OpenAIChatMessage>, ConversionReport) {
+    let mut report = ConversionReport::default();
+    let mut messages: Vec<OpenAIChatMessage> = Vec::new();
+    let mut assistant: Option<OpenAIChatMessage> = None;
+
+    fn flush(assistant: &mut Option<OpenAIChatMessage>, messages: &mut Vec<OpenAIChatMessage>) {
+        if let Some(message) = assistant.take() {
+            messages.push(message);
+        }
+    }
+    fn open(assistant: &mut Option<OpenAIChatMessage>) -> &mut OpenAIChatMessage {
+        assistant.get_or_insert_with(|| {
+            OpenAIChatMessage::with_parts(OpenAIMessageRole::Assistant, None, None, None)
+        })
+    }
+
+    for (index, item) in items.iter().enumerate() {
+        let kind = match item {
+            Value::String(text) => {
+                flush(&mut assistant, &mut messages);
+                messages.push(OpenAIChatMessage::user(text.as_str()));
+                continue;
+            }
+            _ => item
+                .get("type")
+                .and_then(Value::as_str)
+                .unwrap_or("message"),
+        };
+        match kind {
+            "message" => {
+                let role = parse_role(item.get("role").and_then(Value::as_str));
+                let content = item.get("content").and_then(item_text);
+                if role == OpenAIMessageRole::Assistant {
+                    let message = open(&mut assistant);
+                    if message.tool_calls.is_some() {
+                        flush(&mut assistant, &mut messages);
+                    }
+                    let message = open(&mut assistant);
+                    message.content = match (message.content.take(), content) {
+                        (
+                            Some(OpenAIMessageContent::Text(mut existing)),
+                            Some(OpenAIMessageContent::Text(text)),
+                        ) => {
+                            existing.push_str(&text);
+                            Some(OpenAIMessageContent::Text(existing))
+                        }
+                        (existing, content) => content.or(existing),
+                    };
+                } else {
+                    flush(&mut assistant, &mut messages);
+                    messages.push(OpenAIChatMessage::with_parts(role, content, None, None));
+                }
+            }
+            "reasoning" => {
+                let text = reasoning_text(item);
+                if !text.is_empty() {
+                    let message = open(&mut assistant);
+                    let reasoning = message.reasoning.get_or_insert_with(String::new);
+                    if !reasoning.is_empty() {
+                        reasoning.push('\n');
+                    }
+                    reasoning.push_str(&text);
+                }
+            }
+            "function_call" => {
+                let field = |key: &str| {
+                    item.get(key)
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string()
+                };
+                let call = OpenAIToolCall {
+                    id: item
+                        .get("call_id")
+                        .or_else(|| item.get("id"))
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    r#type: "function".to_string(),
+                    extra_content: item.get("extra_content").cloned(),
+                    function: OpenAIFunctionCall {
+                        name: field("name"),
+                        arguments: field("arguments"),
+                    },
+                };
+                open(&mut assistant)
+                    .tool_calls
+                    .get_or_insert_with(Vec::new)
+                    .push(call);
+            }
+            "function_call_output" => {
+                flush(&mut assistant, &mut messages);
+                let output = match item.get("output") {
+                    Some(Value::String(text)) => text.clone(),
+                    Some(other) => other.to_string(),
+                    None => String::new(),
+                };
+                let call_id = item
+                    .get("call_id")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                messages.push(OpenAIChatMessage::tool(call_id, output));
+            }
+            other => report.drop_field(format!("items[{index}] ({other})")),
+        }
+    }
+    flush(&mut assistant, &mut messages);
+    (messages, report)
+}
+
+impl ResponsesRequest {
+    /// Converts this request's `input` into OpenAI-compatible messages.
+    pub fn input_to_openai_messages(&self) -> (Vec<OpenAIChatMessage>, ConversionReport) {
+        match &self.input {
+            Value::Array(items) => responses_items_to_openai_messages(items),
+            other => responses_items_to_openai_messages(std::slice::from_ref(other)),
+        }
+    }
+}
+
+impl ResponsesApiResponse {
+    /// Converts the response `output` into OpenAI-compatible assistant messages, ready to
+    /// append to a chat history.
+    pub fn to_openai_messages(&self) -> (Vec<OpenAIChatMessage>, ConversionReport) {
+        responses_items_to_openai_messages(self.output.as_deref().unwrap_or_default())
+    }
+}
@@ -0,0 +1,215 @@
+//! Pre-request cost estimation and post-request cost reconciliation.
+//!
+//! Catalog pricing (`ModelPricing.prompt` / `ModelPricing.completion`) is published as
+//! per-token price strings. The helpers in this module combine those prices with a local
+//! prompt token estimate and the request's output cap to produce a credit range before a
+//! request is sent, and compare the real `Usage` of a finished call with what Rainy charged.
+
+use crate::embeddings::EmbeddingRequest;
+use crate::models::{
+    ChatCompletionRequest, ModelCatalogItem, OpenAIChatCompletionRequest, RequestMetadata,
+    ResponsesRequest, Usage,
+};
+use crate::tokens::TokenCounter;
+use serde::{Deserialize, Serialize};
+
+/// Completion tokens assumed for the upper bound when neither the request nor the catalog
+/// entry's `context_length` caps the output.
+pub const DEFAULT_MAX_COMPLETION_TOKENS: u32 = 32_768;
+
+/// Requests whose cost can be estimated locally before they are sent.
+pub trait CostEstimable {
+    /// Returns the estimated number of prompt tokens for this request using `counter`.
+    fn estimated_prompt_tokens_with(&self, counter: &TokenCounter) -> u32;
+
+    /// Returns the estimated number of prompt tokens using the generic estimator.
+    fn estimated_prompt_tokens(&self) -> u32 {
+        self.estimated_prompt_tokens_with(&TokenCounter::default())
+    }
+
+    /// Returns the caller-provided cap on generated tokens, if any.
+    fn max_completion_tokens(&self) -> Option<u32>;
+}
+
+/// Estimated credit range for a request that has not been sent yet.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CostEstimate {
+    /// Catalog model identifier the estimate was computed for.
+    pub model: String,
+    /// Locally estimated prompt tokens.
+    pub prompt_tokens: u32,
+    /// Upper bound on completion tokens used for `max_cost`.
+    pub max_completion_tokens: u32,
+    /// Cost of the prompt alone.
+    pub prompt_cost: f64,
+    /// Lowest expected cost (prompt only, no generated tokens).
+    pub min_cost: f64,
+    /// Highest expected cost (prompt plus `max_completion_tokens` generated tokens).
+    pub max_cost: f64,
+}
+
+impl CostEstimate {
+    /// Returns a copy of the estimate with a gateway markup percentage applied.
+    ///
+    /// Use the `rainy_markup_percent` reported in a previous `RequestMetadata` to
+    /// approximate the credits Rainy will actually charge.
+    pub fn with_markup(&self, markup_percent: f64) -> Self {
+        let factor = 1.0 + markup_percent / 100.0;
+        Self {
+            prompt_cost: self.prompt_cost * factor,
+            min_cost: self.min_cost * factor,
+            max_cost: self.max_cost * factor,
+            ..self.clone()
+        }
+    }
+
+    /// Checks whether the whole estimated range fits within `available` credits.
+    pub fn fits_within(&self, available: f64) -> bool {
+        self.max_cost <= available
+    }
+}
+
+/// Comparison between the cost computed from `Usage` and the credits Rainy reported.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CostReconciliation {
+    /// Cost computed from usage and catalog pricing, before markup.
+    pub computed_cost: f64,
+    /// Markup percentage reported by Rainy, if any.
+    pub markup_percent: Option<f64>,
+    /// Computed cost with the reported markup applied.
+    pub expected_charge: f64,
+    /// Credits Rainy reported as charged (`rainy_credits_charged`, falling back to `credits_used`).
+    pub charged: Option<f64>,
+    /// `charged - expected_charge`, when a charge was reported.
+    pub difference: Option<f64>,
+}
+
+impl CostReconciliation {
+    /// Returns `true` when the reported charge matches the expected charge within
+    /// `tolerance` credits, or when no charge was reported.
+    pub fn within_tolerance(&self, tolerance: f64) -> bool {
+        self.difference.is_none_or(|diff| diff.abs() <= tolerance)
+    }
+}
+
+fn price_per_token(value: Option<&str>) -> Option<f64> {
+    value
+        .and_then(|raw| raw.trim().parse::<f64>().ok())
+        .filter(|v| v.is_finite() && *v >= 0.0)
+}
+
+fn model_prices(model: &ModelCatalogItem) -> Option<(f64, f64)> {
+    let pricing = model.pricing.as_ref()?;
+    let prompt = price_per_token(pricing.prompt.as_deref())?;
+    let completion = price_per_token(pricing.completion.as_deref())?;
+    Some((prompt, completion))
+}
+
+impl CostEstimable for ChatCompletionRequest {
+    fn estimated_prompt_tokens_with(&self, counter: &TokenCounter) -> u32 {
+        counter.count_chat_request(self)
+    }
+
+    fn max_completion_tokens(&self) -> Option<u32> {
+        self.max_tokens
+            .map(|tokens| tokens.saturating_mul(self.n.unwrap_or(1)))
+    }
+}
+
+impl CostEstimable for OpenAIChatCompletionRequest {
+    fn estimated_prompt_tokens_with(&self, counter: &TokenCounter) -> u32 {
+        counter.count_openai_request(self)
+    }
+
+    fn max_completion_tokens(&self) -> Option<u32> {
+        self.max_tokens
+            .map(|tokens| tokens.saturating_mul(self.n.unwrap_or(1)))
+    }
+}
+
+impl CostEstimable for EmbeddingRequest {
+    fn estimated_prompt_tokens_with(&self, counter: &TokenCounter) -> u32 {
+        self.input
+            .as_slice()
+            .iter()
+            .map(|text| counter.count_text(text))
+            .sum()
+    }
+
+    /// Embeddings generate no tokens.
+    fn max_completion_tokens(&self) -> Option<u32> {
+        Some(0)
+    }
+}
+
+impl CostEstimable for ResponsesRequest {
+    fn estimated_prompt_tokens_with(&self, counter: &TokenCounter) -> u32 {
+        counter.count_responses_request(self)
+    }
+
+    fn max_completion_tokens(&self) -> Option<u32> {
+        self.max_output_tokens
+    }
+}
+
+/// Estimates the credit range of a request against a catalog entry before sending it.
+///
+/// Prompt tokens are estimated locally with the model's tokenizer family. The upper bound
+/// uses the request's output cap (`max_tokens` × `n`, or `max_output_tokens`); when the
+/// request sets no cap, the rest of the model's `context_length` is used instead, or
+/// [`DEFAULT_MAX_COMPLETION_TOKENS`] when the catalog lists none. Returns `None` when the
+/// catalog entry has no parseable prompt/completion pricing.
+pub fn estimate_cost<R: CostEstimable + ?Sized>(
+    request: &R,
+    model: &ModelCatalogItem,
+) -> Option<CostEstimate> {
+    let (prompt_price, completion_price) = model_prices(model)?;
+    let prompt_tokens = request.estimated_prompt_tokens_with(&TokenCounter::for_model(model));
+    let max_completion_tokens = request.max_completion_tokens().unwrap_or_else(|| {
+        model
+            .context_length
+            .map(|context| context.saturating_sub(prompt_tokens))
+            .unwrap_or(DEFAULT_MAX_COMPLETION_TOKENS)
+    });
+
+    let prompt_cost = f64::from(prompt_tokens) * prompt_price;
+    let max_cost = prompt_cost + f64::from(max_completion_tokens) * completion_price;
+
+    Some(CostEstimate {
+        model: model.id.clone(),
+        prompt_tokens,
+        max_completion_tokens,
+        prompt_cost,
+        min_cost: prompt_cost,
+        max_cost,
+    })
+}
+
+/// Computes the base (pre-markup) cost of a finished call from its `Usage`.
+pub fn actual_cost(usage: &Usage, model: &ModelCatalogItem) -> Option<f64> {
+    let (prompt_price, completion_price) = model_prices(model)?;
+    Some(
+        f64::from(usage.prompt_tokens) * prompt_price
+            + f64::from(usage.completion_tokens) * completion_price,
+    )
+}
+
+/// Compares the cost computed from `Usage` with the credits reported in `RequestMetadata`.
+pub fn reconcile_cost(
+    usage: &Usage,
+    model: &ModelCatalogItem,
+    metadata: &RequestMetadata,
+) -> Option<CostReconciliation> {
+    let computed_cost = actual_cost(usage, model)?;
+    let markup_percent = metadata.rainy_markup_percent;
+    let expected_charge = computed_cost * (1.0 + markup_percent.unwrap_or(0.0) / 100.0);
+    let charged = metadata.rainy_credits_charged.or(metadata.credits_used);
+
+    Some(CostReconciliation {
+        computed_cost,
+        markup_percent,
+        expected_charge,
+        charged,
+        difference: charged.map(|charged| charged - expected_charge),
+    })
+}
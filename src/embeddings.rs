@@ -0,0 +1,301 @@
+//! Embedding types for the OpenAI-compatible `/api/v1/embeddings` endpoint.
+//!
+//! [`EmbeddingRequest`] inputs larger than the provider limits are split into batches by
+//! `RainyClient::create_embeddings`; results are merged back in input order. Vectors requested
+//! with [`EncodingFormat::Base64`] arrive as packed little-endian `f32` values and are decoded
+//! by [`EmbeddingVector::to_vec`].
+
+use crate::error::{RainyError, Result};
+use crate::models::Usage;
+use crate::tokens::TokenCounter;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// Default maximum number of inputs sent in one embeddings request.
+pub const DEFAULT_EMBEDDING_BATCH_INPUTS: usize = 2048;
+/// Default maximum estimated tokens sent in one embeddings request.
+pub const DEFAULT_EMBEDDING_BATCH_TOKENS: u32 = 300_000;
+
+/// Text to embed: one string or a list of strings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum EmbeddingInput {
+    /// A single input.
+    Text(String),
+    /// Several inputs, embedded in order.
+    Texts(Vec<String>),
+}
+
+impl EmbeddingInput {
+    /// The inputs as a slice of strings.
+    pub fn as_slice(&self) -> &[String] {
+        match self {
+            Self::Text(text) => std::slice::from_ref(text),
+            Self::Texts(texts) => texts,
+        }
+    }
+
+    /// Number of inputs.
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    /// Returns `true` when there is nothing to embed.
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+}
+
+impl From<String> for EmbeddingInput {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<&str> for EmbeddingInput {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl From<Vec<String>> for EmbeddingInput {
+    fn from(value: Vec<String>) -> Self {
+        Self::Texts(value)
+    }
+}
+
+impl From<Vec<&str>> for EmbeddingInput {
+    fn from(value: Vec<&str>) -> Self {
+        Self::Texts(value.into_iter().map(str::to_string).collect())
+    }
+}
+
+/// Wire encoding of returned vectors.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EncodingFormat {
+    /// JSON arrays of floats.
+    Float,
+    /// Base64-encoded little-endian `f32` arrays, about a quarter of the JSON size.
+    Base64,
+}
+
+/// Request body for `POST /api/v1/embeddings`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EmbeddingRequest {
+    /// The embedding model identifier.
+    pub model: String,
+    /// Text to embed.
+    pub input: EmbeddingInput,
+    /// Output dimensions, for models that support shortened embeddings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<u32>,
+    /// Vector encoding; defaults to `float` on the server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding_format: Option<EncodingFormat>,
+    /// End-user identifier.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Maximum inputs per request; larger inputs are split client-side.
+    #[serde(skip)]
+    pub max_batch_inputs: Option<usize>,
+    /// Maximum estimated tokens per request; larger inputs are split client-side.
+    #[serde(skip)]
+    pub max_batch_tokens: Option<u32>,
+}
+
+impl EmbeddingRequest {
+    /// Creates a request for `input` with the given model.
+    pub fn new(model: impl Into<String>, input: impl Into<EmbeddingInput>) -> Self {
+        Self {
+            model: model.into(),
+            input: input.into(),
+            dimensions: None,
+            encoding_format: None,
+            user: None,
+            max_batch_inputs: None,
+            max_batch_tokens: None,
+        }
+    }
+
+    /// Sets the output dimensions.
+    pub fn with_dimensions(mut self, dimensions: u32) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    /// Sets the vector encoding.
+    pub fn with_encoding_format(mut self, encoding_format: EncodingFormat) -> Self {
+        self.encoding_format = Some(encoding_format);
+        self
+    }
+
+    /// Sets the end-user identifier.
+    pub fn with_user(mut self, user: impl Into<String>) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+
+    /// Overrides the per-request batch limits.
+    pub fn with_batch_limits(mut self, max_inputs: usize, max_tokens: u32) -> Self {
+        self.max_batch_inputs = Some(max_inputs.max(1));
+        self.max_batch_tokens = Some(max_tokens.max(1));
+        self
+    }
+
+    /// Splits the request into batches within the input-count and token limits.
+    ///
+    /// Each batch keeps the model and options; an input larger than the token limit on its
+    /// own is sent alone and left for the server to reject or truncate.
+    pub fn batches(&self) -> Vec<EmbeddingRequest> {
+        let max_inputs = self
+            .max_batch_inputs
+            .unwrap_or(DEFAULT_EMBEDDING_BATCH_INPUTS);
+        let max_tokens = self
+            .max_batch_tokens
+            .unwrap_or(DEFAULT_EMBEDDING_BATCH_TOKENS);
+        let inputs = self.input.as_slice();
+        if inputs.len() <= 1 {
+            return vec![self.clone()];
+        }
+
+        let counter = TokenCounter::for_model_id(&self.model);
+        let mut batches = Vec::new();
+        let mut current: Vec<String> = Vec::new();
+        let mut current_tokens = 0u32;
+        for text in inputs {
+            let tokens = counter.count_text(text);
+            if !current.is_empty()
+                && (current.len() >= max_inputs || current_tokens + tokens > max_tokens)
+            {
+                batches.push(self.with_input(std::mem::take(&mut current)));
+                current_tokens = 0;
+            }
+            current.push(text.clone());
+            current_tokens += tokens;
+        }
+        if !current.is_empty() {
+            batches.push(self.with_input(current));
+        }
+        batches
+    }
+
+    fn with_input(&self, texts: Vec<String>) -> EmbeddingRequest {
+        EmbeddingRequest {
+            input: EmbeddingInput::Texts(texts),
+            ..self.clone()
+        }
+    }
+}
+
+/// One embedding vector, as floats or base64-packed `f32` values.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum EmbeddingVector {
+    /// Float array (`encoding_format: "float"`).
+    Float(Vec<f32>),
+    /// Base64 little-endian `f32` array (`encoding_format: "base64"`).
+    Base64(String),
+}
+
+impl EmbeddingVector {
+    /// Returns the vector as floats, decoding base64 payloads.
+    pub fn to_vec(&self) -> Result<Vec<f32>> {
+        match self {
+            Self::Float(values) => Ok(values.clone()),
+            Self::Base64(data) => {
+                let bytes = STANDARD
+                    .decode(data)
+                    .map_err(|err| RainyError::Serialization {
+                        message: "embedding is not valid base64".to_string(),
+                        source_error: Some(err.to_string()),
+                    })?;
+                if bytes.len() % 4 != 0 {
+                    return Err(RainyError::Serialization {
+                        message: format!(
+                            "embedding payload of {} bytes is not a whole number of f32 values",
+                            bytes.len()
+                        ),
+                        source_error: None,
+                    });
+                }
+                Ok(bytes
+                    .chunks_exact(4)
+                    .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .collect())
+            }
+        }
+    }
+}
+
+/// One entry of an embeddings response.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Embedding {
+    /// Object type, always `embedding`.
+    #[serde(default)]
+    pub object: String,
+    /// Position of the corresponding input.
+    pub index: u32,
+    /// The vector.
+    pub embedding: EmbeddingVector,
+}
+
+/// Token usage of an embeddings request.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct EmbeddingUsage {
+    /// Tokens in the inputs.
+    pub prompt_tokens: u32,
+    /// Total tokens billed.
+    pub total_tokens: u32,
+}
+
+impl From<EmbeddingUsage> for Usage {
+    fn from(usage: EmbeddingUsage) -> Self {
+        Usage {
+            prompt_tokens: usage.prompt_tokens,
+            total_tokens: usage.total_tokens,
+            ..Default::default()
+        }
+    }
+}
+
+/// Response from `POST /api/v1/embeddings`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EmbeddingResponse {
+    /// Object type, always `list`.
+    #[serde(default)]
+    pub object: String,
+    /// Embeddings, one per input.
+    pub data: Vec<Embedding>,
+    /// The model that produced the embeddings.
+    pub model: String,
+    /// Token usage.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<EmbeddingUsage>,
+}
+
+impl EmbeddingResponse {
+    /// Decoded vectors in input order.
+    pub fn vectors(&self) -> Result<Vec<Vec<f32>>> {
+        let mut data: Vec<&Embedding> = self.data.iter().collect();
+        data.sort_by_key(|embedding| embedding.index);
+        data.into_iter()
+            .map(|embedding| embedding.embedding.to_vec())
+            .collect()
+    }
+
+    /// Appends a later batch, shifting its indexes by `offset` and summing usage.
+    pub(crate) fn merge(&mut self, batch: EmbeddingResponse, offset: u32) {
+        self.data
+            .extend(batch.data.into_iter().map(|mut embedding| {
+                embedding.index += offset;
+                embedding
+            }));
+        if let Some(usage) = batch.usage {
+            let total = self.usage.get_or_insert_with(EmbeddingUsage::default);
+            total.prompt_tokens += usage.prompt_tokens;
+            total.total_tokens += usage.total_tokens;
+        }
+    }
+}
@@ -1,11 +1,15 @@
 use crate::client::RainyClient;
 use crate::error::{RainyError, Result};
+use crate::metering::StreamFinish;
 use crate::models::{
     ChatCompletionRequest, ChatCompletionResponse, ChatCompletionStreamResponse,
-    OpenAIChatCompletionRequest, OpenAIChatCompletionResponse,
+    OpenAIChatCompletionRequest, OpenAIChatCompletionResponse, RequestMetadata,
 };
+use crate::thought_signatures::SignatureScope;
 use futures::Stream;
+use std::collections::HashMap;
 use std::pin::Pin;
+use std::time::Instant;
 
 impl RainyClient {
     /// Create a chat completion
@@ -47,9 +51,27 @@ impl RainyClient {
         &self,
         request: ChatCompletionRequest,
     ) -> Result<ChatCompletionResponse> {
+        let user = request.user.clone();
+        let model = request.model.clone();
+        let hold = self
+            .reserve_budget(&model, user.as_deref(), &request)
+            .await?;
+
+        let start_time = Instant::now();
         let body = serde_json::to_value(request)?;
-        self.make_request(reqwest::Method::POST, "/chat/completions", Some(body))
-            .await
+        let result = self
+            .make_request_with_metadata(reqwest::Method::POST, "/chat/completions", Some(body))
+            .await;
+        self.finish_call(
+            "/chat/completions",
+            &model,
+            user.as_deref(),
+            start_time,
+            hold,
+            result,
+            |response: &ChatCompletionResponse, entry| entry.with_usage(response.usage.as_ref()),
+        )
+        .map(|(response, _)| response)
     }
 
     /// Create an OpenAI-compatible chat completion with full tool-call replay support.
@@ -61,9 +83,58 @@ impl RainyClient {
         &self,
         request: OpenAIChatCompletionRequest,
     ) -> Result<OpenAIChatCompletionResponse> {
-        let body = serde_json::to_value(request)?;
-        self.make_request(reqwest::Method::POST, "/chat/completions", Some(body))
+        self.openai_chat_completion_with_metadata(request)
             .await
+            .map(|(response, _)| response)
+    }
+
+    pub(crate) async fn openai_chat_completion_with_metadata(
+        &self,
+        request: OpenAIChatCompletionRequest,
+    ) -> Result<(OpenAIChatCompletionResponse, RequestMetadata)> {
+        self.openai_chat_completion_with_extra(request, serde_json::Map::new())
+            .await
+    }
+
+    /// Sends an OpenAI-compatible chat completion with `extra` body fields the request type
+    /// does not model; modelled fields take precedence.
+    pub(crate) async fn openai_chat_completion_with_extra(
+        &self,
+        mut request: OpenAIChatCompletionRequest,
+        extra: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<(OpenAIChatCompletionResponse, RequestMetadata)> {
+        let user = request.user.clone();
+        let model = request.model.clone();
+        self.ensure_input_modalities(&model, &request).await?;
+        let hold = self
+            .reserve_budget(&model, user.as_deref(), &request)
+            .await?;
+        self.prepare_thought_signatures(&mut request)?;
+        // Captured signatures are scoped to the history they answer.
+        let history = std::mem::take(&mut request.messages);
+
+        let start_time = Instant::now();
+        let mut body = serde_json::to_value(request)?;
+        body["messages"] = serde_json::to_value(&history)?;
+        merge_extra(&mut body, extra);
+        let result = self
+            .make_request_with_metadata(reqwest::Method::POST, "/chat/completions", Some(body))
+            .await;
+        self.finish_call(
+            "/chat/completions",
+            &model,
+            user.as_deref(),
+            start_time,
+            hold,
+            result,
+            |response: &OpenAIChatCompletionResponse, entry| {
+                entry.with_usage(response.usage.as_ref())
+            },
+        )
+        .inspect(|(response, _)| {
+            self.thought_signatures()
+                .capture_response(&history, response);
+        })
     }
 
     /// Create a chat completion with streaming
@@ -121,17 +192,26 @@ impl RainyClient {
 
         let mut request_with_stream = request;
         request_with_stream.stream = Some(true);
+        let hold = self
+            .reserve_budget(
+                &request_with_stream.model,
+                request_with_stream.user.as_deref(),
+                &request_with_stream,
+            )
+            .await?;
 
         let url = format!("{}/api/v1/chat/completions", self.auth_config().base_url);
         let headers = self.auth_config().build_headers()?;
 
+        let start_time = Instant::now();
         let response = self
             .http_client()
             .post(&url)
             .headers(headers)
-            .json(&request_with_stream)
+            .json(&self.chat_stream_body(&request_with_stream)?)
             .send()
             .await?;
+        let metadata = self.extract_metadata(&response, start_time);
 
         if !response.status().is_success() {
             return Err(self
@@ -172,7 +252,18 @@ impl RainyClient {
                 }
             });
 
-        Ok(Box::pin(stream))
+        Ok(self
+            .meter_stream(
+                Box::pin(stream),
+                hold,
+                "/chat/completions",
+                &request_with_stream.model,
+                request_with_stream.user.as_deref(),
+                start_time,
+                metadata,
+                None,
+            )
+            .await)
     }
 
     /// Create a streaming OpenAI-compatible chat completion.
@@ -183,23 +274,50 @@ impl RainyClient {
     pub async fn create_openai_chat_completion_stream(
         &self,
         request: OpenAIChatCompletionRequest,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatCompletionStreamResponse>> + Send>>> {
+        self.openai_chat_completion_stream_with(request, serde_json::Map::new(), None)
+            .await
+    }
+
+    /// Streams an OpenAI-compatible chat completion with `extra` body fields, calling
+    /// `on_finish` with the final metadata once the stream has ended.
+    pub(crate) async fn openai_chat_completion_stream_with(
+        &self,
+        request: OpenAIChatCompletionRequest,
+        extra: serde_json::Map<String, serde_json::Value>,
+        on_finish: Option<StreamFinish>,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatCompletionStreamResponse>> + Send>>> {
         use eventsource_stream::Eventsource;
         use futures::StreamExt;
 
         let mut request_with_stream = request;
         request_with_stream.stream = Some(true);
+        self.ensure_input_modalities(&request_with_stream.model, &request_with_stream)
+            .await?;
+        let hold = self
+            .reserve_budget(
+                &request_with_stream.model,
+                request_with_stream.user.as_deref(),
+                &request_with_stream,
+            )
+            .await?;
+        self.prepare_thought_signatures(&mut request_with_stream)?;
 
         let url = format!("{}/api/v1/chat/completions", self.auth_config().base_url);
         let headers = self.auth_config().build_headers()?;
 
+        let mut body = serde_json::to_value(&request_with_stream)?;
+        merge_extra(&mut body, extra);
+
+        let start_time = Instant::now();
         let response = self
             .http_client()
             .post(&url)
             .headers(headers)
-            .json(&request_with_stream)
+            .json(&self.chat_stream_body(&body)?)
             .send()
             .await?;
+        let metadata = self.extract_metadata(&response, start_time);
 
         if !response.status().is_success() {
             return Err(self
@@ -209,6 +327,9 @@ impl RainyClient {
                 .unwrap());
         }
 
+        let signatures = self.shared_thought_signatures();
+        let scope = SignatureScope::of(&request_with_stream.messages);
+        let mut tool_call_ids = HashMap::new();
         let stream = response
             .bytes_stream()
             .eventsource()
@@ -233,8 +354,33 @@ impl RainyClient {
                         source_error: Some(e.to_string()),
                     })),
                 }
+            })
+            .inspect(move |chunk| {
+                if let Ok(chunk) = chunk {
+                    signatures.capture_chunk(scope, chunk, &mut tool_call_ids);
+                }
             });
 
-        Ok(Box::pin(stream))
+        Ok(self
+            .meter_stream(
+                Box::pin(stream),
+                hold,
+                "/chat/completions",
+                &request_with_stream.model,
+                request_with_stream.user.as_deref(),
+                start_time,
+                metadata,
+                on_finish,
+            )
+            .await)
+    }
+}
+
+/// Adds `extra` fields to a serialized request body without overriding modelled ones.
+fn merge_extra(body: &mut serde_json::Value, extra: serde_json::Map<String, serde_json::Value>) {
+    if let Some(object) = body.as_object_mut() {
+        for (key, value) in extra {
+            object.entry(key).or_insert(value);
+        }
     }
 }
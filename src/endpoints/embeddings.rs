@@ -0,0 +1,132 @@
+//! Embeddings endpoint
+//!
+//! OpenAI-compatible `POST /api/v1/embeddings` with client-side batching of large inputs.
+
+use crate::{
+    embeddings::{EmbeddingRequest, EmbeddingResponse},
+    error::{RainyError, Result},
+    models::{RequestMetadata, Usage},
+    RainyClient,
+};
+use std::time::Instant;
+
+fn sum<T: std::ops::Add<Output = T>>(total: Option<T>, value: Option<T>) -> Option<T> {
+    match (total, value) {
+        (Some(total), Some(value)) => Some(total + value),
+        (total, value) => total.or(value),
+    }
+}
+
+/// Folds the metadata of a later batch into the running total: usage and credits are summed,
+/// balances and identifiers come from the latest batch.
+fn merge_metadata(total: &mut RequestMetadata, batch: RequestMetadata) {
+    total.response_time = sum(total.response_time, batch.response_time);
+    total.tokens_used = sum(total.tokens_used, batch.tokens_used);
+    total.credits_used = sum(total.credits_used, batch.credits_used);
+    total.rainy_credits_charged = sum(total.rainy_credits_charged, batch.rainy_credits_charged);
+    total.compat_warnings = sum(total.compat_warnings, batch.compat_warnings);
+    total.provider = batch.provider.or(total.provider.take());
+    total.request_id = batch.request_id.or(total.request_id.take());
+    total.credits_remaining = batch.credits_remaining.or(total.credits_remaining);
+    total.rainy_daily_credits_remaining = batch
+        .rainy_daily_credits_remaining
+        .or(total.rainy_daily_credits_remaining.take());
+    total.rainy_markup_percent = batch.rainy_markup_percent.or(total.rainy_markup_percent);
+    total.billing_plan = batch.billing_plan.or(total.billing_plan.take());
+    total.response_mode = batch.response_mode.or(total.response_mode.take());
+}
+
+impl RainyClient {
+    /// Creates embeddings for one or more inputs.
+    ///
+    /// Inputs above the request's batch limits (by default 2048 inputs or about 300k
+    /// estimated tokens per request) are sent as several requests and merged back in input
+    /// order. Every batch is checked against the budget and recorded in the ledger.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The embeddings request.
+    ///
+    /// # Returns
+    ///
+    /// The merged response and the request metadata, with token usage and credits summed
+    /// across batches. `tokens_used` falls back to the response usage when the gateway does
+    /// not report it in headers.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use rainy_sdk::{EmbeddingRequest, EncodingFormat, RainyClient};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = RainyClient::with_api_key("your-api-key")?;
+    ///
+    /// let request = EmbeddingRequest::new("text-embedding-3-small", vec!["first", "second"])
+    ///     .with_dimensions(256)
+    ///     .with_encoding_format(EncodingFormat::Base64);
+    /// let (response, metadata) = client.create_embeddings(request).await?;
+    ///
+    /// let vectors = response.vectors()?;
+    /// println!("{} vectors, {:?} tokens", vectors.len(), metadata.tokens_used);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_embeddings(
+        &self,
+        request: EmbeddingRequest,
+    ) -> Result<(EmbeddingResponse, RequestMetadata)> {
+        if request.input.is_empty() {
+            return Err(RainyError::InvalidRequest {
+                code: "EMPTY_INPUT".to_string(),
+                message: "embeddings request has no input".to_string(),
+                details: None,
+            });
+        }
+
+        let mut merged: Option<(EmbeddingResponse, RequestMetadata)> = None;
+        let mut offset = 0u32;
+        for batch in request.batches() {
+            let count = batch.input.len() as u32;
+            let (response, mut metadata) = self.create_embeddings_batch(batch).await?;
+            if metadata.tokens_used.is_none() {
+                metadata.tokens_used = response.usage.map(|usage| usage.total_tokens);
+            }
+            match &mut merged {
+                None => merged = Some((response, metadata)),
+                Some((total, total_metadata)) => {
+                    total.merge(response, offset);
+                    merge_metadata(total_metadata, metadata);
+                }
+            }
+            offset += count;
+        }
+        Ok(merged.expect("at least one batch"))
+    }
+
+    async fn create_embeddings_batch(
+        &self,
+        request: EmbeddingRequest,
+    ) -> Result<(EmbeddingResponse, RequestMetadata)> {
+        let user = request.user.clone();
+        let model = request.model.clone();
+        let hold = self
+            .reserve_budget(&model, user.as_deref(), &request)
+            .await?;
+
+        let start_time = Instant::now();
+        let body = serde_json::to_value(request)?;
+        let result = self
+            .make_request_with_metadata(reqwest::Method::POST, "/embeddings", Some(body))
+            .await;
+        self.finish_call(
+            "/embeddings",
+            &model,
+            user.as_deref(),
+            start_time,
+            hold,
+            result,
+            |response: &EmbeddingResponse, entry| {
+                entry.with_usage(response.usage.map(Usage::from).as_ref())
+            },
+        )
+    }
+}
@@ -8,6 +8,8 @@ pub mod chat;
 /// Endpoint for Cowork capabilities and tier management.
 #[cfg(feature = "cowork")]
 pub mod cowork;
+/// Endpoint for OpenAI-compatible embeddings.
+pub mod embeddings;
 /// Endpoint for checking the health of the API.
 pub mod health;
 /// Endpoint for managing API keys.
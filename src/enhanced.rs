@@ -0,0 +1,222 @@
+//! Conversions from Gemini-shaped [`EnhancedChatMessage`] history to request payloads.
+//!
+//! Gemini function calls carry no IDs, so each conversion assigns IDs made of a random prefix
+//! and a counter (`call_<prefix>_N`), unique across conversions, and pairs each
+//! `function_response` with the oldest unanswered call of the same name. Thought parts
+//! become assistant `reasoning` on the chat surface and are left out of Responses input. On
+//! the chat surface, thought signatures on function calls travel in
+//! `extra_content.google.thought_signature`.
+//!
+//! A signature on a text part is moved to the first tool call of the same message; when the
+//! message has no tool call it is dropped, since the OpenAI-compatible shape has no slot for it.
+//! Responses API `function_call` items have no slot for signatures either, so
+//! [`to_responses_input`](crate::enhanced::to_responses_input) leaves them out.
+
+use crate::models::{
+    ContentPart, EnhancedChatMessage, OpenAIChatCompletionRequest, OpenAIChatMessage,
+    OpenAIFunctionCall, OpenAIMessageContent, OpenAIMessageRole, OpenAIToolCall, ResponsesRequest,
+};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+/// Assigns IDs to function calls and pairs responses with them by function name.
+struct CallIds {
+    prefix: String,
+    next: usize,
+    pending: HashMap<String, VecDeque<String>>,
+}
+
+impl CallIds {
+    fn new() -> Self {
+        let mut prefix = Uuid::new_v4().simple().to_string();
+        prefix.truncate(12);
+        Self {
+            prefix,
+            next: 0,
+            pending: HashMap::new(),
+        }
+    }
+
+    fn next_id(&mut self) -> String {
+        self.next += 1;
+        format!("call_{}_{}", self.prefix, self.next)
+    }
+
+    fn call(&mut self, name: &str) -> String {
+        let id = self.next_id();
+        self.pending
+            .entry(name.to_string())
+            .or_default()
+            .push_back(id.clone());
+        id
+    }
+
+    fn response(&mut self, name: &str) -> String {
+        match self.pending.get_mut(name).and_then(VecDeque::pop_front) {
+            Some(id) => id,
+            None => self.next_id(),
+        }
+    }
+}
+
+fn is_thought(part: &ContentPart) -> bool {
+    part.thought == Some(true)
+}
+
+/// Converts enhanced history into OpenAI-compatible chat messages.
+///
+/// Function responses become `tool` messages emitted ahead of any text in the same message.
+///
+/// # Examples
+///
+/// ```rust
+/// use rainy_sdk::enhanced::to_openai_messages;
+/// use rainy_sdk::{ContentPart, EnhancedChatMessage, MessageRole};
+/// use serde_json::json;
+///
+/// let history = vec![
+///     EnhancedChatMessage::user("List the files."),
+///     EnhancedChatMessage::with_parts(
+///         MessageRole::Assistant,
+///         vec![ContentPart::function_call("list_files", json!({"path": "."}))
+///             .with_thought_signature("sig")],
+///     ),
+///     EnhancedChatMessage::with_parts(
+///         MessageRole::User,
+///         vec![ContentPart::function_response("list_files", json!(["src"]))],
+///     ),
+/// ];
+///
+/// let messages = to_openai_messages(&history);
+/// let call = &messages[1].tool_calls.as_ref().unwrap()[0];
+/// assert_eq!(call.thought_signature(), Some("sig"));
+/// assert_eq!(messages[2].tool_call_id.as_deref(), Some(call.id.as_str()));
+/// ```
+pub fn to_openai_messages(messages: &[EnhancedChatMessage]) -> Vec<OpenAIChatMessage> {
+    let mut ids = CallIds::new();
+    let mut converted = Vec::with_capacity(messages.len());
+
+    for message in messages {
+        let mut text = String::new();
+        let mut reasoning = String::new();
+        let mut tool_calls = Vec::new();
+        let mut stray_signature = None;
+
+        for part in &message.parts {
+            if let Some(call) = &part.function_call {
+                let mut tool_call = OpenAIToolCall {
+                    id: ids.call(&call.name),
+                    r#type: "function".to_string(),
+                    extra_content: None,
+                    function: OpenAIFunctionCall {
+                        name: call.name.clone(),
+                        arguments: call.args.to_string(),
+                    },
+                };
+                if let Some(signature) = &part.thought_signature {
+                    tool_call.set_thought_signature(signature.clone());
+                }
+                tool_calls.push(tool_call);
+            } else if let Some(response) = &part.function_response {
+                converted.push(OpenAIChatMessage::tool(
+                    ids.response(&response.name),
+                    response.response.to_string(),
+                ));
+            } else {
+                if let Some(part_text) = &part.text {
+                    if is_thought(part) {
+                        reasoning.push_str(part_text);
+                    } else {
+                        text.push_str(part_text);
+                    }
+                }
+                if part.thought_signature.is_some() {
+                    stray_signature = part.thought_signature.clone();
+                }
+            }
+        }
+
+        if let (Some(signature), Some(first)) = (stray_signature, tool_calls.first_mut()) {
+            if first.thought_signature().is_none() {
+                first.set_thought_signature(signature);
+            }
+        }
+        if text.is_empty() && reasoning.is_empty() && tool_calls.is_empty() {
+            continue;
+        }
+        let content =
+            (!text.is_empty() || tool_calls.is_empty()).then_some(OpenAIMessageContent::Text(text));
+        let mut openai = OpenAIChatMessage::with_parts(
+            OpenAIMessageRole::from(message.role.clone()),
+            content,
+            (!tool_calls.is_empty()).then_some(tool_calls),
+            None,
+        );
+        if !reasoning.is_empty() {
+            openai.reasoning = Some(reasoning);
+        }
+        converted.push(openai);
+    }
+    converted
+}
+
+/// Converts enhanced history into a Responses API `input` array.
+///
+/// Text becomes `message` items, function calls become `function_call` items and function
+/// responses become `function_call_output` items. Thought parts are left out: the API only
+/// accepts replayed `reasoning` items with the `id` it issued, which enhanced history lacks.
+pub fn to_responses_input(messages: &[EnhancedChatMessage]) -> Value {
+    let mut ids = CallIds::new();
+    let mut items = Vec::new();
+
+    for message in messages {
+        let mut text = String::new();
+        let flush = |text: &mut String, items: &mut Vec<Value>| {
+            if !text.is_empty() {
+                items.push(json!({
+                    "type": "message",
+                    "role": OpenAIMessageRole::from(message.role.clone()).as_str(),
+                    "content": std::mem::take(text),
+                }));
+            }
+        };
+
+        for part in &message.parts {
+            if let Some(call) = &part.function_call {
+                flush(&mut text, &mut items);
+                items.push(json!({
+                    "type": "function_call",
+                    "call_id": ids.call(&call.name),
+                    "name": call.name,
+                    "arguments": call.args.to_string(),
+                }));
+            } else if let Some(response) = &part.function_response {
+                flush(&mut text, &mut items);
+                items.push(json!({
+                    "type": "function_call_output",
+                    "call_id": ids.response(&response.name),
+                    "output": response.response.to_string(),
+                }));
+            } else if let Some(part_text) = part.text.as_ref().filter(|_| !is_thought(part)) {
+                text.push_str(part_text);
+            }
+        }
+        flush(&mut text, &mut items);
+    }
+    Value::Array(items)
+}
+
+impl OpenAIChatCompletionRequest {
+    /// Creates an OpenAI-compatible request from Gemini-shaped enhanced history.
+    pub fn from_enhanced(model: impl Into<String>, messages: &[EnhancedChatMessage]) -> Self {
+        Self::new(model, to_openai_messages(messages))
+    }
+}
+
+impl ResponsesRequest {
+    /// Creates a Responses API request from Gemini-shaped enhanced history.
+    pub fn from_enhanced(model: impl Into<String>, messages: &[EnhancedChatMessage]) -> Self {
+        Self::new(model, to_responses_input(messages))
+    }
+}
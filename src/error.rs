@@ -70,6 +70,21 @@ pub enum RainyError {
         reset_date: Option<String>,
     },
 
+    /// An error raised locally when a client-side spend budget or credit reserve is reached.
+    #[error("Budget exceeded ({scope}): {message}")]
+    BudgetExceeded {
+        /// A machine-readable error code (`BUDGET_EXCEEDED` or `CREDIT_RESERVE_REACHED`).
+        code: String,
+        /// A human-readable error message.
+        message: String,
+        /// The budget scope that was reached (e.g. `client`, `user:alice`, `window:3600s`).
+        scope: String,
+        /// The configured limit or reserve for the scope.
+        limit: f64,
+        /// The spend (or remaining balance, for reserves) at the time of the check.
+        spent: f64,
+    },
+
     /// An error related to network connectivity or HTTP-level issues.
     #[error("Network error: {message}")]
     Network {
@@ -114,6 +129,15 @@ pub enum RainyError {
         source_error: Option<String>,
     },
 
+    /// An error that occurs while reading or writing local files or streams.
+    #[error("I/O error: {message}")]
+    Io {
+        /// A message describing the I/O error.
+        message: String,
+        /// The underlying error message, if available.
+        source_error: Option<String>,
+    },
+
     /// An error indicating that a feature is not available for the current plan.
     #[error("Feature not available: {feature} - {message}")]
     FeatureNotAvailable {
@@ -175,6 +199,7 @@ impl RainyError {
             | RainyError::Provider { code, .. }
             | RainyError::RateLimit { code, .. }
             | RainyError::InsufficientCredits { code, .. }
+            | RainyError::BudgetExceeded { code, .. }
             | RainyError::Api { code, .. } => Some(code),
             _ => None,
         }
@@ -261,6 +286,18 @@ impl From<serde_json::Error> for RainyError {
     }
 }
 
+/// Converts a `std::io::Error` into a `RainyError`.
+///
+/// This is used by helpers that read or write local files, such as ledger sinks.
+impl From<std::io::Error> for RainyError {
+    fn from(err: std::io::Error) -> Self {
+        RainyError::Io {
+            message: err.to_string(),
+            source_error: Some(format!("{:?}", err.kind())),
+        }
+    }
+}
+
 /// Converts a `reqwest::header::InvalidHeaderValue` into a `RainyError`.
 ///
 /// This is used when an invalid value is provided for an HTTP header.
@@ -0,0 +1,488 @@
+//! Model fallback chains for provider failures.
+//!
+//! A [`FallbackPolicy`] lists the models to try after the requested one fails, either as an
+//! explicit ordered list or as [`ModelSelectionCriteria`] resolved against `/models/catalog`.
+//! Only errors matching one of the policy's [`FallbackTrigger`]s move on to the next
+//! candidate; anything else is returned immediately. Before each attempt the request is
+//! re-targeted with [`FallbackRequest::adapt_to_model`], which rewrites or drops
+//! model-specific settings such as Gemini thinking configuration, Anthropic `thinking` and
+//! reasoning effort. The catalog is only loaded once the requested model has failed.
+//! The model that answered is reported in [`RequestMetadata::served_model`].
+
+use crate::client::RainyClient;
+use crate::error::{RainyError, Result};
+use crate::models::{
+    build_reasoning_config, find_catalog_entry, select_models, ChatCompletionRequest,
+    ChatCompletionResponse, ModelCatalogItem, ModelSelectionCriteria, OpenAIChatCompletionRequest,
+    OpenAIChatCompletionResponse, ReasoningMode, ReasoningPreference, ReasoningProvider,
+    RequestMetadata, ResponsesApiResponse, ResponsesRequest, ThinkingConfig, ThinkingLevel,
+};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
+
+/// Error conditions that move a request on to the next fallback candidate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum FallbackTrigger {
+    /// `RainyError::Provider` with code `PROVIDER_UNAVAILABLE`.
+    ProviderUnavailable,
+    /// Any `RainyError::Provider` error.
+    ProviderError,
+    /// `RainyError::RateLimit`.
+    RateLimit,
+    /// `RainyError::Timeout`.
+    Timeout,
+    /// Retryable `RainyError::Network` errors.
+    Network,
+    /// `RainyError::Api` with a 5xx status code.
+    ServerError,
+    /// `RainyError::InvalidRequest` with code `INVALID_MODEL` (model removed or unknown).
+    InvalidModel,
+    /// Any error carrying this machine-readable code.
+    Code(String),
+}
+
+impl FallbackTrigger {
+    /// Returns `true` when the error matches this trigger.
+    pub fn matches(&self, error: &RainyError) -> bool {
+        match self {
+            FallbackTrigger::ProviderUnavailable => matches!(
+                error,
+                RainyError::Provider { code, .. } if code == "PROVIDER_UNAVAILABLE"
+            ),
+            FallbackTrigger::ProviderError => matches!(error, RainyError::Provider { .. }),
+            FallbackTrigger::RateLimit => matches!(error, RainyError::RateLimit { .. }),
+            FallbackTrigger::Timeout => matches!(error, RainyError::Timeout { .. }),
+            FallbackTrigger::Network => matches!(
+                error,
+                RainyError::Network {
+                    retryable: true,
+                    ..
+                }
+            ),
+            FallbackTrigger::ServerError => matches!(
+                error,
+                RainyError::Api { status_code, .. } if *status_code >= 500
+            ),
+            FallbackTrigger::InvalidModel => matches!(
+                error,
+                RainyError::InvalidRequest { code, .. } if code == "INVALID_MODEL"
+            ),
+            FallbackTrigger::Code(expected) => error.code() == Some(expected.as_str()),
+        }
+    }
+}
+
+/// Source of fallback candidates.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum FallbackCandidates {
+    /// Explicit ordered list of model identifiers.
+    Models(Vec<String>),
+    /// Criteria resolved against `/models/catalog`, cheapest first (see `select_models`).
+    Criteria(Box<ModelSelectionCriteria>),
+}
+
+/// Policy describing which models to fall back to and when.
+///
+/// # Examples
+///
+/// ```rust
+/// use rainy_sdk::fallback::{FallbackPolicy, FallbackTrigger};
+///
+/// let policy = FallbackPolicy::models(["gemini-3-flash-preview", "gpt-4o-mini"])
+///     .with_triggers(vec![FallbackTrigger::ProviderUnavailable, FallbackTrigger::Timeout])
+///     .with_max_attempts(2);
+///
+/// let chain = policy.candidate_chain("gemini-3-pro-preview", None);
+/// assert_eq!(chain, vec!["gemini-3-pro-preview", "gemini-3-flash-preview"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FallbackPolicy {
+    /// Where fallback candidates come from.
+    pub candidates: FallbackCandidates,
+    /// Errors that cause the next candidate to be tried.
+    pub triggers: Vec<FallbackTrigger>,
+    /// Maximum number of models tried, including the requested one.
+    pub max_attempts: Option<usize>,
+}
+
+impl FallbackPolicy {
+    /// Default triggers: provider errors, rate limits, timeouts, retryable network
+    /// errors and 5xx responses.
+    pub fn default_triggers() -> Vec<FallbackTrigger> {
+        vec![
+            FallbackTrigger::ProviderUnavailable,
+            FallbackTrigger::ProviderError,
+            FallbackTrigger::RateLimit,
+            FallbackTrigger::Timeout,
+            FallbackTrigger::Network,
+            FallbackTrigger::ServerError,
+        ]
+    }
+
+    /// Creates a policy that falls back through an ordered list of models.
+    pub fn models<I, S>(models: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            candidates: FallbackCandidates::Models(models.into_iter().map(Into::into).collect()),
+            triggers: Self::default_triggers(),
+            max_attempts: None,
+        }
+    }
+
+    /// Creates a policy that resolves candidates from the model catalog.
+    pub fn criteria(criteria: ModelSelectionCriteria) -> Self {
+        Self {
+            candidates: FallbackCandidates::Criteria(Box::new(criteria)),
+            triggers: Self::default_triggers(),
+            max_attempts: None,
+        }
+    }
+
+    /// Replaces the error triggers.
+    pub fn with_triggers(mut self, triggers: Vec<FallbackTrigger>) -> Self {
+        self.triggers = triggers;
+        self
+    }
+
+    /// Limits the number of models tried, including the requested one.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Returns `true` when the error should move on to the next candidate.
+    pub fn should_fallback(&self, error: &RainyError) -> bool {
+        self.triggers.iter().any(|trigger| trigger.matches(error))
+    }
+
+    /// Builds the ordered list of models to try, starting with `primary`.
+    ///
+    /// For criteria-based policies `catalog` must contain the `/models/catalog` entries;
+    /// without it only `primary` is returned. Duplicates are removed.
+    pub fn candidate_chain(
+        &self,
+        primary: &str,
+        catalog: Option<&[ModelCatalogItem]>,
+    ) -> Vec<String> {
+        let mut chain = vec![primary.to_string()];
+        let rest: Vec<String> = match &self.candidates {
+            FallbackCandidates::Models(models) => models.clone(),
+            FallbackCandidates::Criteria(criteria) => catalog
+                .map(|catalog| {
+                    select_models(catalog, criteria)
+                        .into_iter()
+                        .map(|item| item.id)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+        for model in rest {
+            if !chain.contains(&model) {
+                chain.push(model);
+            }
+        }
+        if let Some(max) = self.max_attempts {
+            chain.truncate(max.max(1));
+        }
+        chain
+    }
+}
+
+/// Requests that can be re-targeted to a fallback model.
+pub trait FallbackRequest: Clone {
+    /// Returns the target model identifier.
+    fn model(&self) -> &str;
+
+    /// Sets the target model identifier, leaving every other parameter unchanged.
+    fn set_model(&mut self, model: &str);
+
+    /// Re-targets the request to `model`, adapting model-specific parameters.
+    ///
+    /// `catalog_item` is the catalog entry for `model` when known; it is used to check
+    /// reasoning controls against the model's advertised capabilities.
+    fn adapt_to_model(&mut self, model: &str, catalog_item: Option<&ModelCatalogItem>);
+}
+
+/// Rewrites a Gemini thinking configuration for another model.
+///
+/// Thinking levels are Gemini 3 only and budgets are Gemini 2.5 only, so each is converted
+/// to the other's closest equivalent. Gemini 3 Pro only accepts `low` and `high`. Models
+/// without thinking support get `None`.
+pub fn adapt_thinking_config(config: &ThinkingConfig, model: &str) -> Option<ThinkingConfig> {
+    let is_gemini_3 = model.contains("gemini-3");
+    let is_gemini_2_5 = model.contains("gemini-2.5");
+    if !is_gemini_3 && !is_gemini_2_5 {
+        return None;
+    }
+
+    let mut adapted = config.clone();
+    if is_gemini_3 {
+        if let Some(budget) = adapted.thinking_budget.take() {
+            if adapted.thinking_level.is_none() {
+                adapted.thinking_level = Some(if budget == -1 || budget >= 8192 {
+                    ThinkingLevel::High
+                } else {
+                    ThinkingLevel::Low
+                });
+            }
+        }
+        if model.contains("gemini-3-pro") {
+            adapted.thinking_level = adapted.thinking_level.map(|level| match level {
+                ThinkingLevel::Minimal => ThinkingLevel::Low,
+                ThinkingLevel::Medium => ThinkingLevel::High,
+                other => other,
+            });
+        }
+    } else if let Some(level) = adapted.thinking_level.take() {
+        if adapted.thinking_budget.is_none() {
+            adapted.thinking_budget = Some(match level {
+                ThinkingLevel::Minimal => 0,
+                ThinkingLevel::Low => 1024,
+                ThinkingLevel::Medium => 8192,
+                ThinkingLevel::High => -1,
+            });
+        }
+        if model.contains("2.5-pro") {
+            adapted.thinking_budget = adapted.thinking_budget.map(|budget| match budget {
+                -1 => -1,
+                b => b.clamp(128, 32768),
+            });
+        } else if model.contains("2.5-flash") {
+            adapted.thinking_budget = adapted.thinking_budget.map(|budget| match budget {
+                -1 => -1,
+                b => b.clamp(0, 24576),
+            });
+        }
+    }
+    Some(adapted)
+}
+
+/// Returns `true` for Anthropic models, which are the only ones accepting `thinking`.
+fn is_anthropic_model(model: &str, catalog_item: Option<&ModelCatalogItem>) -> bool {
+    model.starts_with("anthropic/")
+        || model.contains("claude")
+        || catalog_item
+            .and_then(|item| item.rainy_capabilities_v2.as_ref())
+            .is_some_and(|v2| {
+                v2.reasoning
+                    .profiles
+                    .iter()
+                    .any(|profile| profile.provider == ReasoningProvider::Anthropic)
+            })
+}
+
+/// Effort levels from weakest to strongest, used to clamp unsupported efforts.
+const EFFORT_LEVELS: [&str; 5] = ["minimal", "low", "medium", "high", "xhigh"];
+
+fn effort_rank(effort: &str) -> Option<usize> {
+    EFFORT_LEVELS
+        .iter()
+        .position(|level| level.eq_ignore_ascii_case(effort))
+}
+
+/// Returns the effort to send to `item` for a requested `effort`.
+///
+/// A supported effort is kept; otherwise the closest supported level is used, preferring
+/// the weaker one on ties. Returns `None` when the model takes no reasoning effort.
+fn clamp_effort(item: &ModelCatalogItem, effort: &str) -> Option<String> {
+    let accepts = |value: &str| {
+        let preference = ReasoningPreference {
+            mode: ReasoningMode::Effort,
+            value: Some(value.to_string()),
+            budget: None,
+        };
+        build_reasoning_config(item, &preference).is_some()
+    };
+    if accepts(effort) {
+        return Some(effort.to_string());
+    }
+    let wanted = effort_rank(effort)?;
+    item.rainy_capabilities_v2
+        .as_ref()?
+        .reasoning
+        .controls
+        .as_ref()?
+        .effort
+        .as_ref()?
+        .iter()
+        .filter(|level| accepts(level))
+        .filter_map(|level| effort_rank(level).map(|rank| (rank, level)))
+        .min_by_key(|(rank, _)| (rank.abs_diff(wanted), *rank))
+        .map(|(_, level)| level.clone())
+}
+
+impl FallbackRequest for ChatCompletionRequest {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn set_model(&mut self, model: &str) {
+        self.model = model.to_string();
+    }
+
+    fn adapt_to_model(&mut self, model: &str, _catalog_item: Option<&ModelCatalogItem>) {
+        self.model = model.to_string();
+        self.thinking_config = self
+            .thinking_config
+            .as_ref()
+            .and_then(|config| adapt_thinking_config(config, model));
+    }
+}
+
+impl FallbackRequest for OpenAIChatCompletionRequest {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn set_model(&mut self, model: &str) {
+        self.model = model.to_string();
+    }
+
+    fn adapt_to_model(&mut self, model: &str, catalog_item: Option<&ModelCatalogItem>) {
+        self.model = model.to_string();
+        self.thinking_config = self
+            .thinking_config
+            .as_ref()
+            .and_then(|config| adapt_thinking_config(config, model));
+        if !is_anthropic_model(model, catalog_item) {
+            self.thinking = None;
+        }
+    }
+}
+
+impl FallbackRequest for ResponsesRequest {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn set_model(&mut self, model: &str) {
+        self.model = model.to_string();
+    }
+
+    /// Without a catalog entry the model's reasoning support is unknown, so `reasoning` is
+    /// dropped; otherwise an unsupported effort is clamped to the closest supported one.
+    fn adapt_to_model(&mut self, model: &str, catalog_item: Option<&ModelCatalogItem>) {
+        self.model = model.to_string();
+        let Some(reasoning) = self.reasoning.as_mut() else {
+            return;
+        };
+        let Some(item) = catalog_item else {
+            self.reasoning = None;
+            return;
+        };
+        let Some(effort) = reasoning.get("effort").and_then(|v| v.as_str()) else {
+            return;
+        };
+        match clamp_effort(item, effort) {
+            Some(effort) => reasoning["effort"] = serde_json::Value::String(effort),
+            None => self.reasoning = None,
+        }
+    }
+}
+
+impl RainyClient {
+    async fn run_with_fallback<R, T, F, Fut>(
+        &self,
+        request: R,
+        policy: &FallbackPolicy,
+        call: F,
+    ) -> Result<(T, RequestMetadata)>
+    where
+        R: FallbackRequest,
+        F: Fn(R) -> Fut,
+        Fut: Future<Output = Result<(T, RequestMetadata)>>,
+    {
+        let primary = request.model().to_string();
+        let mut catalog: Option<Arc<Vec<ModelCatalogItem>>> = None;
+        let mut chain = vec![primary.clone()];
+        let mut index = 0;
+        loop {
+            let model = chain[index].clone();
+            let mut attempt = request.clone();
+            if index > 0 {
+                let item = catalog
+                    .as_deref()
+                    .and_then(|items| find_catalog_entry(items, &model));
+                attempt.adapt_to_model(&model, item);
+            }
+
+            let error = match call(attempt).await {
+                Ok((response, mut metadata)) => {
+                    metadata.served_model = Some(model);
+                    return Ok((response, metadata));
+                }
+                Err(error) => error,
+            };
+            if !policy.should_fallback(&error) {
+                return Err(error);
+            }
+            if index == 0 {
+                // The catalog is only needed once the requested model has failed.
+                catalog = match self.cached_models_catalog().await {
+                    Ok(items) => Some(items),
+                    Err(_) if matches!(policy.candidates, FallbackCandidates::Criteria(_)) => {
+                        return Err(error);
+                    }
+                    Err(_) => None,
+                };
+                chain = policy.candidate_chain(&primary, catalog.as_deref().map(Vec::as_slice));
+            }
+            index += 1;
+            if index >= chain.len() {
+                return Err(error);
+            }
+            #[cfg(feature = "tracing")]
+            tracing::warn!("Model {} failed ({}), falling back", model, error);
+        }
+    }
+
+    /// Creates a chat completion, falling back through `policy` on matching failures.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request for the preferred model.
+    /// * `policy` - The fallback policy to apply.
+    ///
+    /// # Returns
+    ///
+    /// The first successful response; `RequestMetadata::served_model` names the model that
+    /// answered. If every candidate fails, the last error is returned.
+    pub async fn chat_completion_with_fallback(
+        &self,
+        request: ChatCompletionRequest,
+        policy: &FallbackPolicy,
+    ) -> Result<(ChatCompletionResponse, RequestMetadata)> {
+        self.run_with_fallback(request, policy, |attempt| self.chat_completion(attempt))
+            .await
+    }
+
+    /// Creates an OpenAI-compatible chat completion, falling back through `policy` on
+    /// matching failures.
+    pub async fn openai_chat_completion_with_fallback(
+        &self,
+        request: OpenAIChatCompletionRequest,
+        policy: &FallbackPolicy,
+    ) -> Result<(OpenAIChatCompletionResponse, RequestMetadata)> {
+        self.run_with_fallback(request, policy, |attempt| {
+            self.openai_chat_completion_with_metadata(attempt)
+        })
+        .await
+    }
+
+    /// Creates a Responses API completion, falling back through `policy` on matching
+    /// failures.
+    pub async fn create_response_with_fallback(
+        &self,
+        request: ResponsesRequest,
+        policy: &FallbackPolicy,
+    ) -> Result<(ResponsesApiResponse, RequestMetadata)> {
+        self.run_with_fallback(request, policy, |attempt| self.create_response(attempt))
+            .await
+    }
+}
@@ -0,0 +1,283 @@
+//! Image outputs from image-generation models such as `gemini-3-pro-image-preview`.
+//!
+//! Image models return pictures as `data:` URIs or hosted URLs, either in the assistant
+//! message `images` array, as `image_url` content parts, or as Responses API output items
+//! (`image_generation_call`, `output_image`). The `generated_images()` accessors collect
+//! all of them as [`GeneratedImage`] values.
+
+use crate::client::RainyClient;
+use crate::error::{RainyError, Result};
+use crate::models::{
+    ChatCompletionResponse, ModelCatalogItem, OpenAIChatCompletionRequest,
+    OpenAIChatCompletionResponse, OpenAIChatMessage, OpenAIContentPart, OpenAIMessageContent,
+    ResponsesApiResponse,
+};
+use crate::multimodal::mime_from_path;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Where the bytes of a generated image live.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum ImageData {
+    /// Inline base64-encoded bytes.
+    Base64(String),
+    /// Hosted image URL.
+    Url(String),
+}
+
+/// An image produced by an image-output model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratedImage {
+    /// MIME type (e.g. `image/png`); `application/octet-stream` when it cannot be determined.
+    pub mime_type: String,
+    /// Inline data or URL.
+    pub data: ImageData,
+}
+
+impl GeneratedImage {
+    /// Creates an image from base64 data.
+    pub fn from_base64(data: impl Into<String>, mime_type: impl Into<String>) -> Self {
+        Self {
+            mime_type: mime_type.into(),
+            data: ImageData::Base64(data.into()),
+        }
+    }
+
+    /// Parses a `data:` URI or hosted URL.
+    pub fn from_url(url: &str) -> Self {
+        if let Some((header, payload)) = url
+            .strip_prefix("data:")
+            .and_then(|rest| rest.split_once(','))
+        {
+            if let Some(mime) = header.strip_suffix(";base64") {
+                return Self::from_base64(payload, mime);
+            }
+        }
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        Self {
+            mime_type: mime_from_path(path)
+                .unwrap_or("application/octet-stream")
+                .to_string(),
+            data: ImageData::Url(url.to_string()),
+        }
+    }
+
+    /// Returns `true` when the image bytes are inline.
+    pub fn is_inline(&self) -> bool {
+        matches!(self.data, ImageData::Base64(_))
+    }
+
+    /// Decodes inline image bytes.
+    ///
+    /// Fails with `IMAGE_NOT_INLINE` for hosted images, which must be downloaded instead.
+    pub fn bytes(&self) -> Result<Vec<u8>> {
+        match &self.data {
+            ImageData::Base64(data) => {
+                STANDARD
+                    .decode(data.trim())
+                    .map_err(|err| RainyError::Serialization {
+                        message: "generated image is not valid base64".to_string(),
+                        source_error: Some(err.to_string()),
+                    })
+            }
+            ImageData::Url(url) => Err(RainyError::InvalidRequest {
+                code: "IMAGE_NOT_INLINE".to_string(),
+                message: format!("generated image is hosted at {url}"),
+                details: None,
+            }),
+        }
+    }
+
+    /// Returns a URL for the image: the hosted URL, or a `data:` URI for inline bytes.
+    pub fn url(&self) -> String {
+        match &self.data {
+            ImageData::Base64(data) => format!("data:{};base64,{data}", self.mime_type),
+            ImageData::Url(url) => url.clone(),
+        }
+    }
+}
+
+fn part_image(part: &OpenAIContentPart) -> Option<GeneratedImage> {
+    match part {
+        OpenAIContentPart::ImageUrl { image_url } => Some(GeneratedImage::from_url(&image_url.url)),
+        _ => None,
+    }
+}
+
+/// Extracts `data:image/...` URIs embedded in text, e.g. markdown image links.
+fn text_images(text: &str) -> Vec<GeneratedImage> {
+    text.match_indices("data:image/")
+        .map(|(start, _)| {
+            let rest = &text[start..];
+            let end = rest
+                .find(|c: char| c == ')' || c == '"' || c == '\'' || c.is_whitespace())
+                .unwrap_or(rest.len());
+            GeneratedImage::from_url(&rest[..end])
+        })
+        .filter(GeneratedImage::is_inline)
+        .collect()
+}
+
+impl OpenAIChatMessage {
+    /// Images carried by this message's `images` array and `image_url` content parts.
+    pub fn generated_images(&self) -> Vec<GeneratedImage> {
+        let content_parts = match &self.content {
+            Some(OpenAIMessageContent::Parts(parts)) => parts.as_slice(),
+            _ => &[],
+        };
+        self.images
+            .iter()
+            .flatten()
+            .chain(content_parts)
+            .filter_map(part_image)
+            .collect()
+    }
+}
+
+impl OpenAIChatCompletionResponse {
+    /// Images generated across all choices.
+    pub fn generated_images(&self) -> Vec<GeneratedImage> {
+        self.choices
+            .iter()
+            .flat_map(|choice| choice.message.generated_images())
+            .collect()
+    }
+}
+
+impl ChatCompletionResponse {
+    /// Images embedded as `data:image/...` URIs in the text of all choices.
+    pub fn generated_images(&self) -> Vec<GeneratedImage> {
+        self.choices
+            .iter()
+            .flat_map(|choice| text_images(&choice.message.content))
+            .collect()
+    }
+}
+
+fn output_item_images(item: &Value, images: &mut Vec<GeneratedImage>) {
+    match item.get("type").and_then(Value::as_str) {
+        Some("image_generation_call") => {
+            if let Some(result) = item.get("result").and_then(Value::as_str) {
+                let format = item
+                    .get("output_format")
+                    .and_then(Value::as_str)
+                    .unwrap_or("png");
+                images.push(GeneratedImage::from_base64(
+                    result,
+                    format!("image/{format}"),
+                ));
+            }
+        }
+        Some("output_image" | "image_url" | "image") => {
+            let url = item
+                .get("image_url")
+                .and_then(|url| url.as_str().or_else(|| url.get("url")?.as_str()))
+                .or_else(|| item.get("url").and_then(Value::as_str));
+            let base64 = item
+                .get("b64_json")
+                .or_else(|| item.get("data"))
+                .and_then(Value::as_str);
+            if let Some(url) = url {
+                images.push(GeneratedImage::from_url(url));
+            } else if let Some(data) = base64 {
+                let mime = item
+                    .get("mime_type")
+                    .and_then(Value::as_str)
+                    .unwrap_or("image/png");
+                images.push(GeneratedImage::from_base64(data, mime));
+            }
+        }
+        Some("message") => {
+            for part in item
+                .get("content")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                output_item_images(part, images);
+            }
+        }
+        _ => {}
+    }
+}
+
+impl ResponsesApiResponse {
+    /// Images in `image_generation_call` items and `output_image` message parts.
+    pub fn generated_images(&self) -> Vec<GeneratedImage> {
+        let mut images = Vec::new();
+        for item in self.output.iter().flatten() {
+            output_item_images(item, &mut images);
+        }
+        images
+    }
+}
+
+fn output_modalities(model: &ModelCatalogItem) -> Option<&[String]> {
+    if let Some(v2) = &model.rainy_capabilities_v2 {
+        if !v2.multimodal.output.is_empty() {
+            return Some(&v2.multimodal.output);
+        }
+    }
+    model
+        .architecture
+        .as_ref()?
+        .output_modalities
+        .as_deref()
+        .filter(|outputs| !outputs.is_empty())
+}
+
+/// Returns `true` when the catalog entry lists `image` among its output modalities.
+pub fn supports_image_output(model: &ModelCatalogItem) -> bool {
+    output_modalities(model)
+        .is_some_and(|outputs| outputs.iter().any(|m| m.eq_ignore_ascii_case("image")))
+}
+
+impl RainyClient {
+    /// Generates images from a text prompt with an image-output model.
+    ///
+    /// The model's entry in the cached catalog must list `image` among its output modalities;
+    /// models missing from the catalog, or a catalog that cannot be loaded, are tried as-is.
+    ///
+    /// # Arguments
+    ///
+    /// * `prompt` - The image description.
+    /// * `model` - An image-output model, e.g. `model_constants::GOOGLE_GEMINI_3_PRO_IMAGE`.
+    ///
+    /// # Returns
+    ///
+    /// The generated images; fails with `NO_IMAGE_GENERATED` when the model answered with
+    /// text only.
+    pub async fn generate_image(
+        &self,
+        prompt: impl Into<String>,
+        model: impl Into<String>,
+    ) -> Result<Vec<GeneratedImage>> {
+        let model = model.into();
+        if let Ok(Some(entry)) = self.catalog_entry(&model).await {
+            if output_modalities(&entry).is_some() && !supports_image_output(&entry) {
+                return Err(RainyError::InvalidRequest {
+                    code: "UNSUPPORTED_OUTPUT_MODALITY".to_string(),
+                    message: format!("model {} does not produce image output", entry.id),
+                    details: None,
+                });
+            }
+        }
+
+        let request =
+            OpenAIChatCompletionRequest::new(model, vec![OpenAIChatMessage::user(prompt.into())])
+                .with_modalities(vec!["image".to_string(), "text".to_string()]);
+        let response = self.create_openai_chat_completion(request).await?;
+        let images = response.generated_images();
+        if images.is_empty() {
+            return Err(RainyError::InvalidRequest {
+                code: "NO_IMAGE_GENERATED".to_string(),
+                message: format!("model {} returned no image", response.model),
+                details: None,
+            });
+        }
+        Ok(images)
+    }
+}
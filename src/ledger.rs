@@ -0,0 +1,432 @@
+//! Usage ledger that records one entry per API call.
+//!
+//! Attach a [`LedgerSink`] to a [`RainyClient`](crate::RainyClient) with
+//! [`with_ledger`](crate::RainyClient::with_ledger) to keep the `RequestMetadata` and `Usage`
+//! of every billable call for chargeback. Entries can be kept in memory ([`MemoryLedger`]),
+//! appended to a JSON Lines file ([`JsonlLedger`]) or a CSV file ([`CsvLedger`]), aggregated
+//! with [`aggregate`], and compared with the dashboard totals from
+//! [`RainySessionClient::usage_stats`](crate::RainySessionClient::usage_stats) using
+//! [`reconcile_usage_stats`].
+
+use crate::error::{RainyError, Result};
+use crate::models::{RequestMetadata, ResponsesUsage, Usage};
+use crate::session::UsageStatsResponse;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// A single recorded API call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    /// When the call completed.
+    pub timestamp: DateTime<Utc>,
+    /// API path that was called (e.g. `/chat/completions`).
+    pub endpoint: String,
+    /// Requested model identifier.
+    pub model: Option<String>,
+    /// Provider that served the call (`x-provider`).
+    pub provider: Option<String>,
+    /// Rainy request ID.
+    pub request_id: Option<String>,
+    /// End-user tag (the request `user` field).
+    pub user: Option<String>,
+    /// Prompt / input tokens.
+    pub prompt_tokens: Option<u32>,
+    /// Completion / output tokens.
+    pub completion_tokens: Option<u32>,
+    /// Total tokens.
+    pub total_tokens: Option<u32>,
+    /// Credits charged (`rainy_credits_charged`, falling back to `credits_used`).
+    pub credits_charged: Option<f64>,
+    /// Markup percent applied by the gateway.
+    pub markup_percent: Option<f64>,
+    /// Round-trip latency in milliseconds.
+    pub latency_ms: Option<u64>,
+    /// Machine-readable error code when the call failed.
+    pub error_code: Option<String>,
+}
+
+impl LedgerEntry {
+    /// Creates an entry for a successful call from its response metadata.
+    pub fn from_metadata(
+        endpoint: impl Into<String>,
+        model: Option<&str>,
+        user: Option<&str>,
+        metadata: &RequestMetadata,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            endpoint: endpoint.into(),
+            model: model.map(str::to_string),
+            provider: metadata.provider.clone(),
+            request_id: metadata.request_id.clone(),
+            user: user.map(str::to_string),
+            prompt_tokens: None,
+            completion_tokens: None,
+            total_tokens: metadata.tokens_used,
+            credits_charged: metadata.rainy_credits_charged.or(metadata.credits_used),
+            markup_percent: metadata.rainy_markup_percent,
+            latency_ms: metadata.response_time,
+            error_code: None,
+        }
+    }
+
+    /// Creates an entry for a failed call.
+    ///
+    /// Errors without a machine-readable code are recorded as `UNKNOWN_ERROR`.
+    pub fn from_error(
+        endpoint: impl Into<String>,
+        model: Option<&str>,
+        user: Option<&str>,
+        error: &RainyError,
+        latency_ms: Option<u64>,
+    ) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            endpoint: endpoint.into(),
+            model: model.map(str::to_string),
+            provider: None,
+            request_id: error.request_id().map(str::to_string),
+            user: user.map(str::to_string),
+            prompt_tokens: None,
+            completion_tokens: None,
+            total_tokens: None,
+            credits_charged: None,
+            markup_percent: None,
+            latency_ms,
+            error_code: Some(error.code().unwrap_or("UNKNOWN_ERROR").to_string()),
+        }
+    }
+
+    /// Fills token counts from a chat completion `Usage`.
+    pub fn with_usage(mut self, usage: Option<&Usage>) -> Self {
+        if let Some(usage) = usage {
+            self.prompt_tokens = Some(usage.prompt_tokens);
+            self.completion_tokens = Some(usage.completion_tokens);
+            self.total_tokens = Some(usage.total_tokens);
+        }
+        self
+    }
+
+    /// Fills token counts from a Responses API usage object.
+    pub fn with_responses_usage(mut self, usage: Option<&ResponsesUsage>) -> Self {
+        if let Some(usage) = usage {
+            self.prompt_tokens = usage.input_tokens;
+            self.completion_tokens = usage.output_tokens;
+            if let (Some(input), Some(output)) = (usage.input_tokens, usage.output_tokens) {
+                self.total_tokens = Some(input + output);
+            }
+        }
+        self
+    }
+
+    /// Returns `true` when the call failed.
+    pub fn is_error(&self) -> bool {
+        self.error_code.is_some()
+    }
+}
+
+/// Destination for ledger entries.
+///
+/// Sinks are called synchronously after each call; failures are logged and never fail
+/// the API call itself.
+pub trait LedgerSink: Send + Sync {
+    /// Records a single entry.
+    fn record(&self, entry: &LedgerEntry) -> Result<()>;
+
+    /// Flushes buffered entries to their destination.
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// In-memory ledger, useful for tests and short-lived processes.
+#[derive(Debug, Default)]
+pub struct MemoryLedger {
+    entries: Mutex<Vec<LedgerEntry>>,
+}
+
+impl MemoryLedger {
+    /// Creates an empty in-memory ledger.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a copy of all recorded entries.
+    pub fn entries(&self) -> Vec<LedgerEntry> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Removes and returns all recorded entries.
+    pub fn drain(&self) -> Vec<LedgerEntry> {
+        std::mem::take(&mut *self.entries.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Aggregates the recorded entries by the given key.
+    pub fn aggregate(&self, by: LedgerGroupBy) -> BTreeMap<String, LedgerTotals> {
+        aggregate(&self.entries(), by)
+    }
+}
+
+impl LedgerSink for MemoryLedger {
+    fn record(&self, entry: &LedgerEntry) -> Result<()> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(entry.clone());
+        Ok(())
+    }
+}
+
+/// Ledger that appends one JSON object per line to a file.
+#[derive(Debug)]
+pub struct JsonlLedger {
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl JsonlLedger {
+    /// Opens (or creates) a JSON Lines file for appending.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    /// Reads all entries from a JSON Lines ledger file.
+    pub fn read(path: impl AsRef<Path>) -> Result<Vec<LedgerEntry>> {
+        let raw = std::fs::read_to_string(path)?;
+        raw.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(RainyError::from))
+            .collect()
+    }
+}
+
+impl LedgerSink for JsonlLedger {
+    fn record(&self, entry: &LedgerEntry) -> Result<()> {
+        let line = serde_json::to_string(entry)?;
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(writer, "{line}")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<()> {
+        self.writer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .flush()?;
+        Ok(())
+    }
+}
+
+/// Column order used by [`CsvLedger`].
+pub const CSV_HEADER: &str = "timestamp,endpoint,model,provider,request_id,user,prompt_tokens,completion_tokens,total_tokens,credits_charged,markup_percent,latency_ms,error_code";
+
+/// Ledger that appends rows to a CSV file, writing the header when the file is new.
+#[derive(Debug)]
+pub struct CsvLedger {
+    writer: Mutex<BufWriter<File>>,
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_opt<T: ToString>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map(|v| csv_field(&v.to_string()))
+        .unwrap_or_default()
+}
+
+impl CsvLedger {
+    /// Opens (or creates) a CSV file for appending.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let is_new = std::fs::metadata(path)
+            .map(|meta| meta.len() == 0)
+            .unwrap_or(true);
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut writer = BufWriter::new(file);
+        if is_new {
+            writeln!(writer, "{CSV_HEADER}")?;
+            writer.flush()?;
+        }
+        Ok(Self {
+            writer: Mutex::new(writer),
+        })
+    }
+
+    /// Formats an entry as a CSV row (without a trailing newline).
+    pub fn format_row(entry: &LedgerEntry) -> String {
+        [
+            csv_field(&entry.timestamp.to_rfc3339()),
+            csv_field(&entry.endpoint),
+            csv_opt(&entry.model),
+            csv_opt(&entry.provider),
+            csv_opt(&entry.request_id),
+            csv_opt(&entry.user),
+            csv_opt(&entry.prompt_tokens),
+            csv_opt(&entry.completion_tokens),
+            csv_opt(&entry.total_tokens),
+            csv_opt(&entry.credits_charged),
+            csv_opt(&entry.markup_percent),
+            csv_opt(&entry.latency_ms),
+            csv_opt(&entry.error_code),
+        ]
+        .join(",")
+    }
+}
+
+impl LedgerSink for CsvLedger {
+    fn record(&self, entry: &LedgerEntry) -> Result<()> {
+        let row = Self::format_row(entry);
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        writeln!(writer, "{row}")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<()> {
+        self.writer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .flush()?;
+        Ok(())
+    }
+}
+
+/// Key used to group ledger entries in [`aggregate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerGroupBy {
+    /// Group by requested model.
+    Model,
+    /// Group by serving provider.
+    Provider,
+    /// Group by end-user tag.
+    User,
+}
+
+/// Totals for one group of ledger entries.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LedgerTotals {
+    /// Number of calls.
+    pub requests: u64,
+    /// Number of failed calls.
+    pub errors: u64,
+    /// Sum of prompt tokens.
+    pub prompt_tokens: u64,
+    /// Sum of completion tokens.
+    pub completion_tokens: u64,
+    /// Sum of total tokens.
+    pub total_tokens: u64,
+    /// Sum of credits charged.
+    pub credits: f64,
+    /// Sum of latencies in milliseconds.
+    pub latency_ms: u64,
+}
+
+impl LedgerTotals {
+    /// Adds an entry to the totals.
+    pub fn add(&mut self, entry: &LedgerEntry) {
+        self.requests += 1;
+        if entry.is_error() {
+            self.errors += 1;
+        }
+        self.prompt_tokens += u64::from(entry.prompt_tokens.unwrap_or(0));
+        self.completion_tokens += u64::from(entry.completion_tokens.unwrap_or(0));
+        self.total_tokens += u64::from(entry.total_tokens.unwrap_or(0));
+        self.credits += entry.credits_charged.unwrap_or(0.0);
+        self.latency_ms += entry.latency_ms.unwrap_or(0);
+    }
+
+    /// Average latency per call in milliseconds.
+    pub fn average_latency_ms(&self) -> Option<f64> {
+        (self.requests > 0).then(|| self.latency_ms as f64 / self.requests as f64)
+    }
+}
+
+/// Aggregates entries by model, provider or user. Entries without a value for the key are
+/// grouped under `"unknown"`.
+pub fn aggregate(entries: &[LedgerEntry], by: LedgerGroupBy) -> BTreeMap<String, LedgerTotals> {
+    let mut groups: BTreeMap<String, LedgerTotals> = BTreeMap::new();
+    for entry in entries {
+        let key = match by {
+            LedgerGroupBy::Model => entry.model.as_deref(),
+            LedgerGroupBy::Provider => entry.provider.as_deref(),
+            LedgerGroupBy::User => entry.user.as_deref(),
+        }
+        .unwrap_or("unknown");
+        groups.entry(key.to_string()).or_default().add(entry);
+    }
+    groups
+}
+
+/// Comparison between local ledger totals and the dashboard usage statistics.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerReconciliation {
+    /// Days covered by the comparison (`UsageStatsResponse::period_days`).
+    pub period_days: u32,
+    /// Successful calls recorded locally within the period.
+    pub ledger_requests: u64,
+    /// Credits recorded locally within the period.
+    pub ledger_credits: f64,
+    /// Requests reported by Rainy.
+    pub reported_requests: u64,
+    /// Credits reported by Rainy.
+    pub reported_credits: f64,
+    /// `reported_requests - ledger_requests`.
+    pub request_difference: i64,
+    /// `reported_credits - ledger_credits`.
+    pub credit_difference: f64,
+}
+
+impl LedgerReconciliation {
+    /// Returns `true` when request counts match and credits agree within `tolerance`.
+    pub fn matches(&self, tolerance: f64) -> bool {
+        self.request_difference == 0 && self.credit_difference.abs() <= tolerance
+    }
+}
+
+/// Compares ledger entries with usage statistics from
+/// [`RainySessionClient::usage_stats`](crate::RainySessionClient::usage_stats).
+///
+/// Only successful entries recorded within the last `period_days` days are counted. Usage
+/// stats cover the whole account, so differences are expected when other clients or keys
+/// share it.
+pub fn reconcile_usage_stats(
+    entries: &[LedgerEntry],
+    stats: &UsageStatsResponse,
+) -> LedgerReconciliation {
+    let since = Utc::now() - Duration::days(i64::from(stats.period_days));
+    let mut totals = LedgerTotals::default();
+    for entry in entries
+        .iter()
+        .filter(|entry| !entry.is_error() && entry.timestamp >= since)
+    {
+        totals.add(entry);
+    }
+
+    LedgerReconciliation {
+        period_days: stats.period_days,
+        ledger_requests: totals.requests,
+        ledger_credits: totals.credits,
+        reported_requests: stats.total_requests,
+        reported_credits: stats.total_credits_deducted,
+        request_difference: stats.total_requests as i64 - totals.requests as i64,
+        credit_difference: stats.total_credits_deducted - totals.credits,
+    }
+}
@@ -48,36 +48,141 @@
 //! ```
 //!
 
+/// Human-in-the-loop approval of tool calls.
+pub mod approval;
 /// Handles authentication and API key management.
 pub mod auth;
+/// Batch execution of chat requests with bounded concurrency and JSONL input/output.
+pub mod batch;
+/// Client-side spend budgets and credit guardrails.
+pub mod budget;
 /// The main client for interacting with the Rainy API.
 pub mod client;
+/// Context-window guard and automatic history truncation.
+pub mod context;
+/// Stateful multi-turn conversations with automatic history management.
+pub mod conversation;
+/// Conversions between chat, OpenAI-compatible and Responses request shapes.
+pub mod convert;
+/// Pre-request cost estimation and cost reconciliation helpers.
+pub mod cost;
 /// Cowork integration (legacy compatibility, opt-in feature).
 #[cfg(feature = "cowork")]
 pub mod cowork;
+/// Embedding request and response types.
+pub mod embeddings;
+/// Conversions from Gemini-shaped enhanced messages to request payloads.
+pub mod enhanced;
 /// Defines error types and result aliases for the SDK.
 pub mod error;
+/// Model fallback chains for provider failures.
+pub mod fallback;
+/// Image outputs from image-generation models.
+pub mod images;
+/// Usage ledger with in-memory, JSONL and CSV sinks.
+pub mod ledger;
+/// Model Context Protocol client and tool bridge (requires the `mcp` feature).
+#[cfg(feature = "mcp")]
+pub mod mcp;
 /// Contains the data models for API requests and responses.
 pub mod models;
+/// Image, audio and file inputs from bytes or paths, with capability checks.
+pub mod multimodal;
+/// Partial JSON parsing for streamed structured outputs.
+pub mod partial_json;
+/// OpenAI-compatible local HTTP proxy (requires the `proxy` feature).
+#[cfg(feature = "proxy")]
+pub mod proxy;
 /// Implements retry logic with exponential backoff.
 pub mod retry;
+/// Request-time model routing by catalog criteria.
+pub mod router;
+/// Lightweight JSON Schema validation for tool arguments and structured outputs.
+pub mod schema;
 /// Web search types and options for Tavily-powered search.
 pub mod search;
 /// JWT/session client for Rainy API v3 dashboard endpoints.
 pub mod session;
+/// Gemini 3 thought-signature capture, replay and validation.
+pub mod thought_signatures;
+/// Local token counting keyed by the model's tokenizer family.
+pub mod tokens;
+/// Incremental assembly and validation of streamed tool calls.
+pub mod tool_calls;
+/// Concurrent execution of tool calls with timeouts and error-to-message mapping.
+pub mod tool_executor;
 
 mod endpoints;
+mod metering;
 
+pub use approval::{
+    ApprovalDecision, ApprovalHook, ApprovalPolicy, ApprovalStatus, PendingApproval, ToolApprovals,
+};
 pub use auth::AuthConfig;
+pub use batch::{
+    read_batch_lines, BatchError, BatchReport, BatchRequestLine, BatchResponse, BatchResultLine,
+    BatchRunner, BatchStats,
+};
+pub use budget::{
+    Budget, BudgetReservation, BudgetScope, BudgetSnapshot, BudgetTracker, BudgetWarning,
+};
 pub use client::RainyClient;
+pub use context::{
+    message_groups, ContextCheck, ContextGuard, ContextMessage, ContextRequest, TruncationReport,
+    TruncationStrategy,
+};
+pub use conversation::{Conversation, ConversationStream, StreamAccumulator, TurnMetadata};
+pub use convert::{responses_items_to_openai_messages, ConversionReport};
+pub use cost::{
+    actual_cost, estimate_cost, reconcile_cost, CostEstimable, CostEstimate, CostReconciliation,
+    DEFAULT_MAX_COMPLETION_TOKENS,
+};
+pub use embeddings::{
+    Embedding, EmbeddingInput, EmbeddingRequest, EmbeddingResponse, EmbeddingUsage,
+    EmbeddingVector, EncodingFormat,
+};
 pub use error::{ApiErrorDetails, ApiErrorResponse, RainyError, Result};
+pub use fallback::{
+    adapt_thinking_config, FallbackCandidates, FallbackPolicy, FallbackRequest, FallbackTrigger,
+};
+pub use images::{supports_image_output, GeneratedImage, ImageData};
+pub use ledger::{
+    aggregate, reconcile_usage_stats, CsvLedger, JsonlLedger, LedgerEntry, LedgerGroupBy,
+    LedgerReconciliation, LedgerSink, LedgerTotals, MemoryLedger,
+};
+#[cfg(feature = "mcp")]
+pub use mcp::{
+    McpBridge, McpClient, McpServerConfig, McpServerInfo, McpTool, McpToolResult,
+    DEFAULT_MCP_REQUEST_TIMEOUT, MCP_NAMESPACE_SEPARATOR, MCP_PROTOCOL_VERSION,
+};
 pub use models::*;
+pub use multimodal::{
+    check_input_modalities, sniff_mime, MediaOptions, Modality, MultimodalRequest,
+};
+pub use partial_json::{
+    parse_partial_json, Partial, PartialJsonParser, PartialJsonStream, StructuredEvent, TextDelta,
+};
+#[cfg(feature = "proxy")]
+pub use proxy::{
+    openai_error_body, ProxyLogEntry, RainyProxy, DEFAULT_PROXY_ADDR, MAX_PROXY_BODY_BYTES,
+};
 pub use retry::{retry_with_backoff, RetryConfig};
+pub use router::{ModelRouter, ModelSpec, RequestNeeds, RoutableRequest, RoutingStrategy};
+pub use schema::{validate_json_schema, SchemaViolation};
 pub use session::{
     CreatedApiKey, LoginResponse, OrgProfile, RainySessionClient, RefreshResponse,
     SessionApiKeyListItem, SessionConfig, SessionTokens, SessionUser, UsageCreditsResponse,
     UsageStatsResponse,
 };
+pub use thought_signatures::{missing_thought_signatures, SignatureScope, ThoughtSignatureCache};
+pub use tokens::{TokenCounter, TokenizerFamily};
+pub use tool_calls::{
+    MalformedReason, MalformedToolCall, ToolCallAssembler, ToolCallEvent, ToolCallStream,
+};
+pub use tool_executor::{
+    ToolError, ToolErrorKind, ToolExecution, ToolExecutor, ToolOutcome, DEFAULT_TOOL_CONCURRENCY,
+    DEFAULT_TOOL_TIMEOUT,
+};
 
 // Re-export Cowork types for convenience
 #[cfg(feature = "cowork")]
@@ -0,0 +1,720 @@
+//! Model Context Protocol (MCP) tools over stdio.
+//!
+//! [`McpClient`] speaks JSON-RPC 2.0 to one MCP server, either a child process started with
+//! [`McpClient::spawn`] or any pair of byte streams via [`McpClient::connect`]. It performs
+//! the `initialize` handshake, lists the server's tools and calls them. [`McpBridge`] gathers
+//! the tools of several servers, exposes them as [`Tool`] definitions for chat requests or
+//! `add_function_tool` entries for Responses, and routes model tool calls back to the server
+//! that owns each tool.
+//!
+//! ```rust,no_run
+//! # use rainy_sdk::{McpBridge, McpServerConfig, OpenAIToolCall};
+//! # async fn example(calls: Vec<OpenAIToolCall>) -> rainy_sdk::Result<()> {
+//! let mut bridge = McpBridge::new();
+//! bridge
+//!     .spawn_server(McpServerConfig::new("files", "mcp-server-filesystem").with_arg("/tmp"))
+//!     .await?;
+//!
+//! let tools = bridge.tools(); // pass to `ChatCompletionRequest::with_tools`
+//!
+//! // Route the model's tool calls to their servers and build the `tool` messages.
+//! let messages = bridge.executor().execute_messages(&calls).await;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{RainyError, Result};
+use crate::models::{FunctionDefinition, ResponsesRequest, Tool, ToolType};
+use crate::tool_executor::ToolExecutor;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// MCP protocol revision requested during `initialize`.
+pub const MCP_PROTOCOL_VERSION: &str = "2025-06-18";
+/// Default time an MCP request may take.
+pub const DEFAULT_MCP_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Separator between server and tool name when the bridge namespaces tools.
+pub const MCP_NAMESPACE_SEPARATOR: &str = "__";
+
+type Writer = tokio::sync::Mutex<Box<dyn AsyncWrite + Send + Unpin>>;
+type PendingRequests = Mutex<HashMap<u64, oneshot::Sender<std::result::Result<Value, Value>>>>;
+
+/// How to start an MCP server process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McpServerConfig {
+    /// Name used to identify the server (and to namespace its tools).
+    pub name: String,
+    /// Program to run.
+    pub command: String,
+    /// Program arguments.
+    pub args: Vec<String>,
+    /// Extra environment variables.
+    pub env: HashMap<String, String>,
+    /// Working directory, if not inherited.
+    pub current_dir: Option<PathBuf>,
+    /// Time each request may take.
+    pub request_timeout: Duration,
+}
+
+impl McpServerConfig {
+    /// Creates a config that runs `command` without arguments.
+    pub fn new(name: impl Into<String>, command: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            command: command.into(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            current_dir: None,
+            request_timeout: DEFAULT_MCP_REQUEST_TIMEOUT,
+        }
+    }
+
+    /// Appends one argument.
+    pub fn with_arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Appends several arguments.
+    pub fn with_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets an environment variable for the server process.
+    pub fn with_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets the server's working directory.
+    pub fn with_current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Sets the time each request may take.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+}
+
+/// Name and version reported by the server during `initialize`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct McpServerInfo {
+    /// Server name.
+    #[serde(default)]
+    pub name: String,
+    /// Server version.
+    #[serde(default)]
+    pub version: String,
+    /// Additional fields sent by the server.
+    #[serde(flatten, skip_serializing_if = "HashMap::is_empty", default)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// A tool advertised by an MCP server.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpTool {
+    /// Tool name, unique within its server.
+    pub name: String,
+    /// Human-readable title.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// What the tool does.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// JSON Schema of the arguments.
+    #[serde(rename = "inputSchema", default = "empty_object_schema")]
+    pub input_schema: Value,
+    /// Additional fields, such as `outputSchema` and `annotations`.
+    #[serde(flatten, skip_serializing_if = "HashMap::is_empty", default)]
+    pub extra: HashMap<String, Value>,
+}
+
+fn empty_object_schema() -> Value {
+    json!({ "type": "object" })
+}
+
+impl McpTool {
+    /// The function definition for this tool, exposed under `name`.
+    pub fn to_function_definition(&self, name: impl Into<String>) -> FunctionDefinition {
+        FunctionDefinition {
+            name: name.into(),
+            description: self.description.clone().or_else(|| self.title.clone()),
+            parameters: Some(self.input_schema.clone()),
+        }
+    }
+
+    /// The chat tool definition for this tool, under its own name.
+    pub fn to_tool(&self) -> Tool {
+        Tool {
+            r#type: ToolType::Function,
+            function: self.to_function_definition(self.name.clone()),
+        }
+    }
+}
+
+/// Result of a `tools/call` request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct McpToolResult {
+    /// Content blocks (`text`, `image`, `resource`, ...).
+    #[serde(default)]
+    pub content: Vec<Value>,
+    /// Structured output, when the tool declares an output schema.
+    #[serde(rename = "structuredContent", skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
+    /// `true` when the tool reports a failure.
+    #[serde(rename = "isError", default)]
+    pub is_error: bool,
+}
+
+impl McpToolResult {
+    /// The text blocks joined with newlines.
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter(|block| block.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|block| block.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The value to hand back to the model, or the error text when the tool failed.
+    ///
+    /// Prefers structured content, then text-only content as a string, then the raw blocks.
+    pub fn into_output(self) -> std::result::Result<Value, String> {
+        if self.is_error {
+            let text = self.text();
+            return Err(if text.is_empty() {
+                Value::from(self.content).to_string()
+            } else {
+                text
+            });
+        }
+        if let Some(structured) = self.structured_content {
+            return Ok(structured);
+        }
+        let text_only = self
+            .content
+            .iter()
+            .all(|block| block.get("type").and_then(Value::as_str) == Some("text"));
+        if text_only {
+            Ok(Value::String(self.text()))
+        } else {
+            Ok(Value::from(self.content))
+        }
+    }
+}
+
+/// A JSON-RPC connection to one MCP server.
+pub struct McpClient {
+    name: String,
+    writer: Arc<Writer>,
+    pending: Arc<PendingRequests>,
+    next_id: AtomicU64,
+    request_timeout: Duration,
+    server_info: McpServerInfo,
+    instructions: Option<String>,
+    child: Option<Child>,
+    reader: JoinHandle<()>,
+}
+
+impl std::fmt::Debug for McpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpClient")
+            .field("name", &self.name)
+            .field("server_info", &self.server_info)
+            .field("request_timeout", &self.request_timeout)
+            .field("spawned", &self.child.is_some())
+            .finish()
+    }
+}
+
+impl Drop for McpClient {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+impl McpClient {
+    /// Starts the server process described by `config` and initializes the session.
+    ///
+    /// The process is killed when the client is dropped.
+    pub async fn spawn(config: McpServerConfig) -> Result<Self> {
+        let mut command = Command::new(&config.command);
+        command
+            .args(&config.args)
+            .envs(&config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true);
+        if let Some(dir) = &config.current_dir {
+            command.current_dir(dir);
+        }
+        let mut child = command.spawn().map_err(|err| RainyError::Io {
+            message: format!(
+                "failed to start MCP server '{}' ({}): {err}",
+                config.name, config.command
+            ),
+            source_error: Some(format!("{:?}", err.kind())),
+        })?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        Self::start(
+            config.name,
+            stdout,
+            stdin,
+            Some(child),
+            config.request_timeout,
+        )
+        .await
+    }
+
+    /// Initializes a session over an existing connection, e.g. a socket or an in-process stub.
+    ///
+    /// `request_timeout` applies to the `initialize` handshake and every later request;
+    /// [`DEFAULT_MCP_REQUEST_TIMEOUT`] matches [`McpClient::spawn`]'s default.
+    pub async fn connect<R, W>(
+        name: impl Into<String>,
+        reader: R,
+        writer: W,
+        request_timeout: Duration,
+    ) -> Result<Self>
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        Self::start(name.into(), reader, writer, None, request_timeout).await
+    }
+
+    async fn start<R, W>(
+        name: String,
+        reader: R,
+        writer: W,
+        child: Option<Child>,
+        request_timeout: Duration,
+    ) -> Result<Self>
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let writer: Arc<Writer> = Arc::new(tokio::sync::Mutex::new(Box::new(writer)));
+        let pending: Arc<PendingRequests> = Arc::default();
+        let reader = tokio::spawn(read_loop(reader, writer.clone(), pending.clone()));
+        let mut client = Self {
+            name,
+            writer,
+            pending,
+            next_id: AtomicU64::new(1),
+            request_timeout,
+            server_info: McpServerInfo::default(),
+            instructions: None,
+            child,
+            reader,
+        };
+
+        let result = client
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": MCP_PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": { "name": "rainy-sdk", "version": crate::VERSION },
+                }),
+            )
+            .await?;
+        if let Some(info) = result.get("serverInfo") {
+            client.server_info = serde_json::from_value(info.clone()).unwrap_or_default();
+        }
+        client.instructions = result
+            .get("instructions")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        client
+            .send(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+            .await?;
+        Ok(client)
+    }
+
+    /// Sets the time each later request may take.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// The name this client was created with.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Name and version reported by the server.
+    pub fn server_info(&self) -> &McpServerInfo {
+        &self.server_info
+    }
+
+    /// Usage instructions sent by the server, if any.
+    pub fn instructions(&self) -> Option<&str> {
+        self.instructions.as_deref()
+    }
+
+    /// Lists every tool the server offers, following pagination.
+    pub async fn list_tools(&self) -> Result<Vec<McpTool>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let mut page = self.request("tools/list", params).await?;
+            let batch: Vec<McpTool> =
+                serde_json::from_value(page.get_mut("tools").map(Value::take).unwrap_or_default())
+                    .map_err(|err| self.invalid_response("tools/list", err))?;
+            tools.extend(batch);
+            cursor = page
+                .get("nextCursor")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            if cursor.is_none() {
+                return Ok(tools);
+            }
+        }
+    }
+
+    /// Calls tool `name` with `arguments`.
+    ///
+    /// A tool that reports a failure returns `Ok` with [`McpToolResult::is_error`] set;
+    /// protocol errors, such as an unknown tool, return `Err`.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<McpToolResult> {
+        let result = self
+            .request(
+                "tools/call",
+                json!({ "name": name, "arguments": arguments }),
+            )
+            .await?;
+        serde_json::from_value(result).map_err(|err| self.invalid_response("tools/call", err))
+    }
+
+    /// Sends a JSON-RPC request and waits for its result.
+    ///
+    /// JSON-RPC errors are returned as [`RainyError::Provider`] with the provider set to
+    /// `mcp:<name>`.
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id, sender);
+
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if let Err(err) = self.send(&message).await {
+            self.pending
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&id);
+            return Err(err);
+        }
+
+        match tokio::time::timeout(self.request_timeout, receiver).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(error))) => Err(RainyError::Provider {
+                code: error
+                    .get("code")
+                    .map(Value::to_string)
+                    .unwrap_or_else(|| "MCP_ERROR".to_string()),
+                message: error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error")
+                    .to_string(),
+                provider: format!("mcp:{}", self.name),
+                retryable: false,
+            }),
+            Ok(Err(_)) => Err(self.closed()),
+            Err(_) => {
+                self.pending
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .remove(&id);
+                Err(RainyError::Timeout {
+                    message: format!(
+                        "MCP server '{}' did not answer '{method}' in time",
+                        self.name
+                    ),
+                    duration_ms: self.request_timeout.as_millis() as u64,
+                })
+            }
+        }
+    }
+
+    /// Closes the connection and waits briefly for a spawned server to exit.
+    pub async fn shutdown(mut self) -> Result<()> {
+        // Closing stdin is the stdio transport's shutdown signal.
+        let _ = self.writer.lock().await.shutdown().await;
+        if let Some(mut child) = self.child.take() {
+            if tokio::time::timeout(Duration::from_secs(5), child.wait())
+                .await
+                .is_err()
+            {
+                child.kill().await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn send(&self, message: &Value) -> Result<()> {
+        let mut line = message.to_string();
+        line.push('\n');
+        let mut writer = self.writer.lock().await;
+        let written = async {
+            writer.write_all(line.as_bytes()).await?;
+            writer.flush().await
+        };
+        written.await.map_err(|_| self.closed())
+    }
+
+    fn closed(&self) -> RainyError {
+        RainyError::Network {
+            message: format!("MCP server '{}' closed the connection", self.name),
+            retryable: false,
+            source_error: None,
+        }
+    }
+
+    fn invalid_response(&self, method: &str, err: serde_json::Error) -> RainyError {
+        RainyError::Serialization {
+            message: format!(
+                "MCP server '{}' sent an invalid '{method}' result",
+                self.name
+            ),
+            source_error: Some(err.to_string()),
+        }
+    }
+}
+
+/// Dispatches responses to waiting requests and answers server-initiated requests.
+async fn read_loop<R>(reader: R, writer: Arc<Writer>, pending: Arc<PendingRequests>)
+where
+    R: AsyncRead + Send + Unpin,
+{
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        let id = message.get("id").cloned().unwrap_or(Value::Null);
+        if let Some(method) = message.get("method").and_then(Value::as_str) {
+            if id.is_null() {
+                continue; // notification
+            }
+            let reply = if method == "ping" {
+                json!({ "jsonrpc": "2.0", "id": id, "result": {} })
+            } else {
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32601, "message": format!("method '{method}' is not supported") },
+                })
+            };
+            let mut writer = writer.lock().await;
+            let _ = writer.write_all(format!("{reply}\n").as_bytes()).await;
+            let _ = writer.flush().await;
+            continue;
+        }
+        let Some(id) = id.as_u64() else {
+            continue;
+        };
+        let Some(sender) = pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&id)
+        else {
+            continue;
+        };
+        let outcome = match message.get("error") {
+            Some(error) => Err(error.clone()),
+            None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+        };
+        let _ = sender.send(outcome);
+    }
+    // Dropping the senders fails every request still waiting.
+    pending.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+#[derive(Debug, Clone)]
+struct McpRoute {
+    server: usize,
+    tool: McpTool,
+}
+
+/// Tools from several MCP servers behind one set of function names.
+#[derive(Debug, Default)]
+pub struct McpBridge {
+    servers: Vec<Arc<McpClient>>,
+    routes: Vec<(String, McpRoute)>,
+    namespaced: bool,
+}
+
+impl McpBridge {
+    /// Creates an empty bridge that exposes tools under their own names.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Exposes tools as `<server>__<tool>` so that servers may share tool names.
+    ///
+    /// Applies to servers added afterwards.
+    pub fn with_namespacing(mut self, namespaced: bool) -> Self {
+        self.namespaced = namespaced;
+        self
+    }
+
+    /// Lists the tools of `client` and routes them to it.
+    ///
+    /// Fails with `MCP_TOOL_CONFLICT` when a function name is already taken.
+    pub async fn add_server(&mut self, client: McpClient) -> Result<()> {
+        let tools = client.list_tools().await?;
+        let names: Vec<String> = tools
+            .iter()
+            .map(|tool| {
+                if self.namespaced {
+                    format!("{}{MCP_NAMESPACE_SEPARATOR}{}", client.name(), tool.name)
+                } else {
+                    tool.name.clone()
+                }
+            })
+            .collect();
+        for name in &names {
+            if let Some(owner) = self.route(name) {
+                return Err(RainyError::InvalidRequest {
+                    code: "MCP_TOOL_CONFLICT".to_string(),
+                    message: format!(
+                        "tool '{name}' of MCP server '{}' is already provided by '{}'",
+                        client.name(),
+                        self.servers[owner.server].name()
+                    ),
+                    details: None,
+                });
+            }
+        }
+
+        let server = self.servers.len();
+        self.servers.push(Arc::new(client));
+        self.routes.extend(
+            names
+                .into_iter()
+                .zip(tools)
+                .map(|(name, tool)| (name, McpRoute { server, tool })),
+        );
+        Ok(())
+    }
+
+    /// Spawns the server described by `config` and adds it.
+    pub async fn spawn_server(&mut self, config: McpServerConfig) -> Result<()> {
+        let client = McpClient::spawn(config).await?;
+        self.add_server(client).await
+    }
+
+    /// The connected servers, in the order they were added.
+    pub fn servers(&self) -> impl Iterator<Item = &McpClient> {
+        self.servers.iter().map(|server| server.as_ref())
+    }
+
+    /// Exposed function names, in the order the servers listed them.
+    pub fn tool_names(&self) -> impl Iterator<Item = &str> {
+        self.routes.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Name of the server that owns function `name`.
+    pub fn server_for(&self, name: &str) -> Option<&str> {
+        self.route(name)
+            .map(|route| self.servers[route.server].name())
+    }
+
+    /// Chat tool definitions for every exposed tool.
+    pub fn tools(&self) -> Vec<Tool> {
+        self.routes
+            .iter()
+            .map(|(name, route)| Tool {
+                r#type: ToolType::Function,
+                function: route.tool.to_function_definition(name.clone()),
+            })
+            .collect()
+    }
+
+    /// Adds every exposed tool to `request` with `add_function_tool`.
+    pub fn apply_to_responses(&self, request: ResponsesRequest) -> ResponsesRequest {
+        self.tools().into_iter().fold(request, |request, tool| {
+            request.add_function_tool(
+                tool.function.name,
+                tool.function.description.unwrap_or_default(),
+                tool.function.parameters.unwrap_or_else(empty_object_schema),
+            )
+        })
+    }
+
+    /// Calls function `name` on the server that owns it.
+    ///
+    /// Fails with `UNKNOWN_TOOL` when no server provides `name`.
+    pub async fn call(&self, name: &str, arguments: Value) -> Result<McpToolResult> {
+        let route = self.route(name).ok_or_else(|| RainyError::InvalidRequest {
+            code: "UNKNOWN_TOOL".to_string(),
+            message: format!("no MCP server provides tool '{name}'"),
+            details: None,
+        })?;
+        self.servers[route.server]
+            .call_tool(&route.tool.name, arguments)
+            .await
+    }
+
+    /// Registers a handler for every exposed tool on `executor`.
+    ///
+    /// Tool failures reported by a server become [`ToolErrorKind::Failed`](crate::ToolErrorKind::Failed)
+    /// results carrying the server's error text.
+    pub fn register_tools(&self, executor: ToolExecutor) -> ToolExecutor {
+        self.routes
+            .iter()
+            .fold(executor, |executor, (name, route)| {
+                let server = self.servers[route.server].clone();
+                let tool = route.tool.name.clone();
+                executor.with_tool(name.clone(), move |arguments| {
+                    let server = server.clone();
+                    let tool = tool.clone();
+                    async move {
+                        let result = server.call_tool(&tool, arguments).await?;
+                        Ok(result.into_output()?)
+                    }
+                })
+            })
+    }
+
+    /// A [`ToolExecutor`] with every exposed tool registered.
+    pub fn executor(&self) -> ToolExecutor {
+        self.register_tools(ToolExecutor::new())
+    }
+
+    fn route(&self, name: &str) -> Option<&McpRoute> {
+        self.routes
+            .iter()
+            .find(|(exposed, _)| exposed == name)
+            .map(|(_, route)| route)
+    }
+}
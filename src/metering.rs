@@ -0,0 +1,206 @@
+//! Budget holds and spend accounting for billable calls.
+//!
+//! Every billable call first takes a [`SpendHold`]: the budget check plus the credits held
+//! back for the call's worst-case cost. Non-streaming calls settle it in
+//! `RainyClient::finish_call`. Streaming calls wrap their event stream in a [`MeteredStream`],
+//! which picks up the final usage chunk and settles the hold, writes the ledger entry and
+//! reports the final [`RequestMetadata`] when the stream ends or is dropped.
+
+use crate::budget::BudgetReservation;
+use crate::cost::actual_cost;
+use crate::error::Result;
+use crate::ledger::{LedgerEntry, LedgerSink};
+use crate::models::{
+    ChatCompletionChunk, ChatCompletionStreamResponse, ModelCatalogItem, RequestMetadata,
+    ResponsesStreamEvent, ResponsesUsage, Usage,
+};
+use futures::Stream;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+/// Callback receiving the final metadata of a streamed call.
+pub(crate) type StreamFinish = Box<dyn FnOnce(&RequestMetadata) + Send>;
+
+/// Budget reservation and catalog pricing for one billable call.
+#[derive(Debug, Default)]
+pub(crate) struct SpendHold {
+    pub(crate) reservation: Option<BudgetReservation>,
+    pub(crate) entry: Option<ModelCatalogItem>,
+}
+
+impl SpendHold {
+    /// Settles the reservation, if any, with the call's reported spend.
+    pub(crate) fn settle(self, metadata: &RequestMetadata) {
+        if let Some(reservation) = self.reservation {
+            reservation.settle(metadata);
+        }
+    }
+}
+
+/// Stream items that may carry the call's final token usage.
+pub(crate) trait StreamUsage {
+    /// Returns the usage reported by this item, if any.
+    fn stream_usage(&self) -> Option<Usage>;
+}
+
+impl StreamUsage for ChatCompletionStreamResponse {
+    fn stream_usage(&self) -> Option<Usage> {
+        self.usage.clone()
+    }
+}
+
+impl StreamUsage for (ChatCompletionChunk, Option<Usage>) {
+    fn stream_usage(&self) -> Option<Usage> {
+        self.1.clone()
+    }
+}
+
+impl StreamUsage for ResponsesStreamEvent {
+    fn stream_usage(&self) -> Option<Usage> {
+        if self.get("type").and_then(|kind| kind.as_str()) != Some("response.completed") {
+            return None;
+        }
+        let usage: ResponsesUsage =
+            serde_json::from_value(self.get("response")?.get("usage")?.clone()).ok()?;
+        let prompt_tokens = usage.input_tokens.unwrap_or(0);
+        let completion_tokens = usage.output_tokens.unwrap_or(0);
+        Some(Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            ..Default::default()
+        })
+    }
+}
+
+/// Accounting state of one streamed call.
+pub(crate) struct StreamMeter {
+    pub(crate) hold: SpendHold,
+    pub(crate) ledger: Option<Arc<dyn LedgerSink>>,
+    pub(crate) endpoint: &'static str,
+    pub(crate) model: String,
+    pub(crate) user: Option<String>,
+    pub(crate) start_time: Instant,
+    pub(crate) metadata: RequestMetadata,
+    pub(crate) on_finish: Option<StreamFinish>,
+    usage: Option<Usage>,
+    finished: bool,
+}
+
+impl StreamMeter {
+    pub(crate) fn new(
+        hold: SpendHold,
+        endpoint: &'static str,
+        model: impl Into<String>,
+        user: Option<&str>,
+        start_time: Instant,
+        metadata: RequestMetadata,
+    ) -> Self {
+        Self {
+            hold,
+            ledger: None,
+            endpoint,
+            model: model.into(),
+            user: user.map(str::to_string),
+            start_time,
+            metadata,
+            on_finish: None,
+            usage: None,
+            finished: false,
+        }
+    }
+
+    /// Settles the budget hold, writes the ledger entry and runs the finish callback.
+    ///
+    /// Credits come from the response headers when the gateway reported them, otherwise
+    /// from the final usage chunk priced with the catalog entry (plus the reported markup).
+    /// A stream that ends without usage or pricing is charged the credits held for it.
+    fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+
+        let mut metadata = std::mem::take(&mut self.metadata);
+        metadata.response_time = Some(self.start_time.elapsed().as_millis() as u64);
+        if let Some(usage) = &self.usage {
+            metadata.tokens_used.get_or_insert(usage.total_tokens);
+        }
+        if metadata.rainy_credits_charged.is_none() && metadata.credits_used.is_none() {
+            let markup = 1.0 + metadata.rainy_markup_percent.unwrap_or(0.0) / 100.0;
+            metadata.credits_used = self
+                .usage
+                .as_ref()
+                .zip(self.hold.entry.as_ref())
+                .and_then(|(usage, entry)| actual_cost(usage, entry))
+                .map(|cost| cost * markup)
+                .or_else(|| {
+                    self.hold
+                        .reservation
+                        .as_ref()
+                        .map(BudgetReservation::amount)
+                        .filter(|amount| *amount > 0.0)
+                });
+        }
+
+        std::mem::take(&mut self.hold).settle(&metadata);
+        if let Some(ledger) = &self.ledger {
+            let entry = LedgerEntry::from_metadata(
+                self.endpoint,
+                Some(&self.model),
+                self.user.as_deref(),
+                &metadata,
+            )
+            .with_usage(self.usage.as_ref());
+            if let Err(_e) = ledger.record(&entry) {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("Failed to record ledger entry: {}", _e);
+            }
+        }
+        if let Some(on_finish) = self.on_finish.take() {
+            on_finish(&metadata);
+        }
+    }
+}
+
+impl Drop for StreamMeter {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Stream wrapper that feeds a [`StreamMeter`] and finishes it at the end of the stream.
+pub(crate) struct MeteredStream<T> {
+    inner: Pin<Box<dyn Stream<Item = Result<T>> + Send>>,
+    meter: StreamMeter,
+}
+
+impl<T> MeteredStream<T> {
+    pub(crate) fn new(
+        inner: Pin<Box<dyn Stream<Item = Result<T>> + Send>>,
+        meter: StreamMeter,
+    ) -> Self {
+        Self { inner, meter }
+    }
+}
+
+impl<T: StreamUsage> Stream for MeteredStream<T> {
+    type Item = Result<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let polled = this.inner.as_mut().poll_next(cx);
+        match &polled {
+            Poll::Ready(Some(Ok(item))) => {
+                if let Some(usage) = item.stream_usage() {
+                    this.meter.usage = Some(usage);
+                }
+            }
+            Poll::Ready(None) => this.meter.finish(),
+            _ => {}
+        }
+        polled
+    }
+}
@@ -42,6 +42,18 @@ pub enum OpenAIMessageRole {
     Tool,
 }
 
+impl OpenAIMessageRole {
+    /// The role's wire name, e.g. `"assistant"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::User => "user",
+            Self::Assistant => "assistant",
+            Self::Tool => "tool",
+        }
+    }
+}
+
 /// OpenAI-compatible chat message content.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(untagged)]
@@ -66,6 +78,16 @@ pub enum OpenAIContentPart {
         /// Image URL payload.
         image_url: OpenAIImageUrl,
     },
+    /// Base64-encoded audio content part.
+    InputAudio {
+        /// Audio payload.
+        input_audio: OpenAIInputAudio,
+    },
+    /// File content part (inline data or an uploaded file id).
+    File {
+        /// File payload.
+        file: OpenAIFileInput,
+    },
 }
 
 /// OpenAI-compatible image URL payload.
@@ -78,6 +100,29 @@ pub struct OpenAIImageUrl {
     pub detail: Option<String>,
 }
 
+/// OpenAI-compatible audio input payload.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OpenAIInputAudio {
+    /// Base64-encoded audio bytes.
+    pub data: String,
+    /// Audio format (`wav`, `mp3`, `flac`, ...).
+    pub format: String,
+}
+
+/// OpenAI-compatible file input payload.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct OpenAIFileInput {
+    /// File contents as a `data:` URI.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_data: Option<String>,
+    /// ID of a previously uploaded file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
+    /// Original file name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+}
+
 /// OpenAI-compatible function call payload.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct OpenAIFunctionCall {
@@ -118,6 +163,12 @@ pub struct OpenAIChatMessage {
     /// Tool call ID associated with a `tool` role message.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+    /// Reasoning text returned with an assistant message, replayed on later turns.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+    /// Images generated by image-output models, as `image_url` parts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub images: Option<Vec<OpenAIContentPart>>,
 }
 
 /// The search provider to use for web research.
@@ -222,6 +273,10 @@ pub struct ChatCompletionRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_choice: Option<ToolChoice>,
 
+    /// Whether the model may call several tools in one turn.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+
     /// Configuration for thinking capabilities (Gemini 3 and 2.5 series).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thinking_config: Option<ThinkingConfig>,
@@ -300,6 +355,10 @@ pub struct OpenAIChatCompletionRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_choice: Option<ToolChoice>,
 
+    /// Whether the model may call several tools in one turn.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+
     /// Gemini thinking configuration.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thinking_config: Option<ThinkingConfig>,
@@ -309,6 +368,10 @@ pub struct OpenAIChatCompletionRequest {
     /// OpenRouter/Anthropic as `{"type":"enabled","budget_tokens":N}`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thinking: Option<serde_json::Value>,
+
+    /// Output modalities to generate (e.g. `["image", "text"]` for image models).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modalities: Option<Vec<String>>,
 }
 
 /// Represents the response from a chat completion request.
@@ -332,6 +395,10 @@ pub struct ChatCompletionResponse {
     /// Information about the token usage for this completion.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub usage: Option<Usage>,
+
+    /// Response fields not modelled above (e.g. `system_fingerprint`), preserved as-is.
+    #[serde(flatten, skip_serializing_if = "map_is_empty", default)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// OpenAI-compatible chat completion response with tool-call aware messages.
@@ -355,6 +422,10 @@ pub struct OpenAIChatCompletionResponse {
     /// Token usage information for this completion.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub usage: Option<Usage>,
+
+    /// Response fields not modelled above (e.g. `system_fingerprint`), preserved as-is.
+    #[serde(flatten, skip_serializing_if = "map_is_empty", default)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// Represents a chunk of a streaming chat completion response.
@@ -387,7 +458,11 @@ pub struct ChatCompletionChunkChoice {
 
     /// The reason the model stopped generating tokens.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub finish_reason: Option<String>,
+    pub finish_reason: Option<FinishReason>,
+
+    /// Log probabilities of the tokens in this chunk, when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<ChoiceLogprobs>,
 }
 
 /// Represents the delta payload of a streaming chat completion chunk.
@@ -416,7 +491,15 @@ pub struct ChatChoice {
     pub message: ChatMessage,
 
     /// The reason the model stopped generating tokens.
-    pub finish_reason: String,
+    pub finish_reason: FinishReason,
+
+    /// Log probabilities of the generated tokens, when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<ChoiceLogprobs>,
+
+    /// Choice fields not modelled above, preserved as-is.
+    #[serde(flatten, skip_serializing_if = "map_is_empty", default)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// Represents a single choice in an OpenAI-compatible chat completion response.
@@ -429,11 +512,158 @@ pub struct OpenAIChatChoice {
     pub message: OpenAIChatMessage,
 
     /// The reason the model stopped generating tokens.
-    pub finish_reason: String,
+    pub finish_reason: FinishReason,
+
+    /// Log probabilities of the generated tokens, when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<ChoiceLogprobs>,
+
+    /// Choice fields not modelled above, preserved as-is.
+    #[serde(flatten, skip_serializing_if = "map_is_empty", default)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Why the model stopped generating tokens.
+///
+/// Serialized as the plain OpenAI string; values this SDK does not know are kept in
+/// [`FinishReason::Other`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FinishReason {
+    /// The model reached a natural stop point or a stop sequence.
+    Stop,
+    /// Generation hit `max_tokens` or the context limit.
+    Length,
+    /// The model called one or more tools.
+    ToolCalls,
+    /// Content was omitted by a content filter.
+    ContentFilter,
+    /// The model called a function (legacy `functions` API).
+    FunctionCall,
+    /// Any other provider-specific reason.
+    Other(String),
+}
+
+impl FinishReason {
+    /// The wire value, e.g. `"tool_calls"`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Stop => "stop",
+            Self::Length => "length",
+            Self::ToolCalls => "tool_calls",
+            Self::ContentFilter => "content_filter",
+            Self::FunctionCall => "function_call",
+            Self::Other(reason) => reason,
+        }
+    }
+
+    /// Returns `true` when the output was cut off by the token limit.
+    pub fn is_truncated(&self) -> bool {
+        matches!(self, Self::Length)
+    }
+}
+
+impl From<&str> for FinishReason {
+    fn from(value: &str) -> Self {
+        match value {
+            "stop" => Self::Stop,
+            "length" => Self::Length,
+            "tool_calls" => Self::ToolCalls,
+            "content_filter" => Self::ContentFilter,
+            "function_call" => Self::FunctionCall,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for FinishReason {
+    fn from(value: String) -> Self {
+        Self::from(value.as_str())
+    }
+}
+
+impl std::fmt::Display for FinishReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for FinishReason {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for FinishReason {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// Log probability information for a choice (`choices[].logprobs`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct ChoiceLogprobs {
+    /// Per-token log probabilities of the message content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<Vec<TokenLogprob>>,
+
+    /// Per-token log probabilities of a refusal message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refusal: Option<Vec<TokenLogprob>>,
+}
+
+impl ChoiceLogprobs {
+    /// Content tokens, or an empty slice when none were returned.
+    pub fn tokens(&self) -> &[TokenLogprob] {
+        self.content.as_deref().unwrap_or_default()
+    }
+
+    /// Sum of the content token log probabilities (log probability of the whole output).
+    pub fn total_logprob(&self) -> f64 {
+        self.tokens().iter().map(|token| token.logprob).sum()
+    }
+}
+
+/// Log probability of one generated token.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TokenLogprob {
+    /// The token text.
+    pub token: String,
+
+    /// Log probability of the token.
+    pub logprob: f64,
+
+    /// UTF-8 bytes of the token, for tokens that split multi-byte characters.
+    #[serde(default)]
+    pub bytes: Option<Vec<u8>>,
+
+    /// Most likely alternatives at this position (`top_logprobs` in the request).
+    #[serde(default)]
+    pub top_logprobs: Vec<TopLogprob>,
+}
+
+impl TokenLogprob {
+    /// Linear probability of the token (`exp(logprob)`).
+    pub fn probability(&self) -> f64 {
+        self.logprob.exp()
+    }
+}
+
+/// An alternative token and its log probability.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TopLogprob {
+    /// The token text.
+    pub token: String,
+
+    /// Log probability of the token.
+    pub logprob: f64,
+
+    /// UTF-8 bytes of the token.
+    #[serde(default)]
+    pub bytes: Option<Vec<u8>>,
 }
 
 /// Represents the token usage statistics for a chat completion.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Usage {
     /// The number of tokens in the prompt.
     pub prompt_tokens: u32,
@@ -443,6 +673,70 @@ pub struct Usage {
 
     /// The total number of tokens used in the request (prompt + completion).
     pub total_tokens: u32,
+
+    /// Breakdown of prompt tokens, including cached tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+
+    /// Breakdown of completion tokens, including reasoning/thinking tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completion_tokens_details: Option<CompletionTokensDetails>,
+
+    /// Usage fields not modelled above (e.g. provider cost), preserved as-is.
+    #[serde(flatten, skip_serializing_if = "map_is_empty", default)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Breakdown of prompt tokens reported by OpenAI-compatible providers.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct PromptTokensDetails {
+    /// Prompt tokens served from the provider's prompt cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_tokens: Option<u32>,
+
+    /// Audio input tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_tokens: Option<u32>,
+
+    /// Additional provider-specific breakdown fields.
+    #[serde(flatten, default)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// Breakdown of completion tokens reported by OpenAI-compatible providers.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CompletionTokensDetails {
+    /// Tokens spent on reasoning (Gemini `thoughtsTokenCount`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_tokens: Option<u32>,
+
+    /// Audio output tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_tokens: Option<u32>,
+
+    /// Predicted-output tokens that appeared in the completion.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accepted_prediction_tokens: Option<u32>,
+
+    /// Predicted-output tokens that did not appear in the completion (still billed).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rejected_prediction_tokens: Option<u32>,
+
+    /// Additional provider-specific breakdown fields.
+    #[serde(flatten, default)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+impl Usage {
+    /// Reasoning/thinking tokens included in `completion_tokens`, when reported.
+    pub fn thoughts_token_count(&self) -> Option<u32> {
+        self.completion_tokens_details.as_ref()?.reasoning_tokens
+    }
+
+    /// Prompt tokens served from the prompt cache, when reported.
+    pub fn cached_tokens(&self) -> Option<u32> {
+        self.prompt_tokens_details.as_ref()?.cached_tokens
+    }
 }
 
 /// Represents the health status of the Rainy API.
@@ -508,7 +802,7 @@ pub struct CreditInfo {
 }
 
 /// Represents metadata extracted from the response headers of an API request.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct RequestMetadata {
     /// The time taken for the request to complete, in milliseconds.
     pub response_time: Option<u64>,
@@ -545,6 +839,9 @@ pub struct RequestMetadata {
 
     /// Remaining daily credits reported by Rainy.
     pub rainy_daily_credits_remaining: Option<String>,
+
+    /// Model that answered the request when a fallback policy was applied.
+    pub served_model: Option<String>,
 }
 
 /// OpenRouter/Rainy Responses API request payload.
@@ -564,10 +861,16 @@ pub struct ResponsesRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<serde_json::Value>>,
 
-    /// Tool selection strategy.
+    /// Tool selection strategy in the Responses shape (`"auto"`, `{"type": "function",
+    /// "name": ...}`, or a hosted tool choice); set it with [`ResponsesRequest::with_tool_choice`]
+    /// and read it back with [`ResponsesRequest::typed_tool_choice`].
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_choice: Option<serde_json::Value>,
 
+    /// Whether the model may call several tools in one turn.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+
     /// Structured output format.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response_format: Option<serde_json::Value>,
@@ -610,6 +913,7 @@ impl ResponsesRequest {
             stream: None,
             tools: None,
             tool_choice: None,
+            parallel_tool_calls: None,
             response_format: None,
             temperature: None,
             top_p: None,
@@ -668,6 +972,24 @@ impl ResponsesRequest {
         self
     }
 
+    /// Sets the tool choice, converted to the Responses shape.
+    pub fn with_tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = Some(tool_choice.to_responses());
+        self
+    }
+
+    /// The tool choice as a [`ToolChoice`], or `None` when unset or when it names a hosted
+    /// tool that `ToolChoice` cannot represent.
+    pub fn typed_tool_choice(&self) -> Option<ToolChoice> {
+        serde_json::from_value(self.tool_choice.clone()?).ok()
+    }
+
+    /// Allows or forbids several tool calls in one turn.
+    pub fn with_parallel_tool_calls(mut self, parallel_tool_calls: bool) -> Self {
+        self.parallel_tool_calls = Some(parallel_tool_calls);
+        self
+    }
+
     /// Adds a function tool using Responses-style shape.
     pub fn add_function_tool(
         mut self,
@@ -719,6 +1041,18 @@ pub struct ResponsesUsage {
     pub extra: HashMap<String, serde_json::Value>,
 }
 
+impl ResponsesUsage {
+    /// Reasoning/thinking tokens from `output_tokens_details` (or
+    /// `completion_tokens_details`), when reported.
+    pub fn reasoning_tokens(&self) -> Option<u32> {
+        [&self.output_tokens_details, &self.completion_tokens_details]
+            .into_iter()
+            .flatten()
+            .find_map(|details| details.get("reasoning_tokens")?.as_u64())
+            .map(|tokens| tokens as u32)
+    }
+}
+
 /// Responses API raw response payload.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ResponsesApiResponse {
@@ -1095,6 +1429,74 @@ pub struct ModelSelectionCriteria {
     /// Reasoning value to match.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reasoning_value: Option<String>,
+    /// Maximum prompt price per token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_prompt_price: Option<f64>,
+    /// Maximum completion price per token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_price: Option<f64>,
+    /// Minimum context length in tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_context_length: Option<u32>,
+    /// Providers to allow (id prefix before `/`, e.g. `google`). Empty allows all.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_providers: Vec<String>,
+    /// Providers to exclude (id prefix before `/`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied_providers: Vec<String>,
+    /// Required tokenizer family (`architecture.tokenizer`, e.g. `Gemini`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<String>,
+    /// API parameters the model must accept (e.g. `seed`, `logprobs`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_parameters: Vec<String>,
+    /// Weighted scoring used to rank matches instead of the default price comparator.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weights: Option<SelectionWeights>,
+}
+
+/// Weights for ranking models in `select_models`.
+///
+/// Each component is normalized to `0.0..=1.0` across the matching models and the
+/// weighted sum decides the order (highest first):
+/// - `cost`: cheapest combined prompt + completion price scores 1.0.
+/// - `context`: largest `context_length` scores 1.0.
+/// - `capability`: most accepted API parameters scores 1.0.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct SelectionWeights {
+    /// Weight of the price component.
+    #[serde(default)]
+    pub cost: f64,
+    /// Weight of the context length component.
+    #[serde(default)]
+    pub context: f64,
+    /// Weight of the capability breadth component.
+    #[serde(default)]
+    pub capability: f64,
+}
+
+impl Default for SelectionWeights {
+    fn default() -> Self {
+        Self {
+            cost: 1.0,
+            context: 0.0,
+            capability: 0.0,
+        }
+    }
+}
+
+impl ModelCatalogItem {
+    /// Returns the provider prefix of the model id (`google` for `google/gemini-3-flash`).
+    pub fn provider(&self) -> Option<&str> {
+        self.id.split_once('/').map(|(provider, _)| provider)
+    }
+
+    fn accepted_parameter_count(&self) -> usize {
+        if let Some(v2) = &self.rainy_capabilities_v2 {
+            return v2.parameters.accepted.len();
+        }
+        self.supported_parameters.as_ref().map_or(0, Vec::len)
+    }
 }
 
 /// Builder preference for reasoning payload generation.
@@ -1174,7 +1576,7 @@ fn supports_reasoning_preference(
     }
 }
 
-fn catalog_item_supports(item: &ModelCatalogItem, parameter: &str) -> bool {
+pub(crate) fn catalog_item_supports(item: &ModelCatalogItem, parameter: &str) -> bool {
     if let Some(v2) = &item.rainy_capabilities_v2 {
         return v2
             .parameters
@@ -1189,7 +1591,24 @@ fn catalog_item_supports(item: &ModelCatalogItem, parameter: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Finds `model` in `catalog` by full id, or by the name after the provider prefix
+/// (`gemini-3-flash-preview` matches `google/gemini-3-flash-preview`).
+pub fn find_catalog_entry<'a>(
+    catalog: &'a [ModelCatalogItem],
+    model: &str,
+) -> Option<&'a ModelCatalogItem> {
+    catalog.iter().find(|item| item.id == model).or_else(|| {
+        catalog.iter().find(|item| {
+            item.id
+                .rsplit_once('/')
+                .is_some_and(|(_, name)| name == model)
+        })
+    })
+}
+
 /// Select models from catalog and rank by prompt price, completion price, then context length desc.
+///
+/// When `criteria.weights` is set, matches are ranked by their weighted score instead.
 pub fn select_models(
     models: &[ModelCatalogItem],
     criteria: &ModelSelectionCriteria,
@@ -1250,34 +1669,163 @@ pub fn select_models(
                 }
             }
 
-            true
+            matches_extended_criteria(item, criteria)
         })
         .cloned()
         .collect();
 
-    filtered.sort_by(|a, b| {
-        let a_prompt = parse_price(a.pricing.as_ref().and_then(|p| p.prompt.as_deref()));
-        let b_prompt = parse_price(b.pricing.as_ref().and_then(|p| p.prompt.as_deref()));
-        let prompt_cmp = a_prompt.partial_cmp(&b_prompt).unwrap_or(Ordering::Equal);
-        if prompt_cmp != Ordering::Equal {
-            return prompt_cmp;
+    if let Some(weights) = &criteria.weights {
+        return rank_by_weights(filtered, weights);
+    }
+
+    filtered.sort_by(compare_by_price);
+    filtered
+}
+
+fn matches_extended_criteria(item: &ModelCatalogItem, criteria: &ModelSelectionCriteria) -> bool {
+    let pricing = item.pricing.as_ref();
+    if let Some(max) = criteria.max_prompt_price {
+        if parse_price(pricing.and_then(|p| p.prompt.as_deref())) > max {
+            return false;
+        }
+    }
+    if let Some(max) = criteria.max_completion_price {
+        if parse_price(pricing.and_then(|p| p.completion.as_deref())) > max {
+            return false;
         }
+    }
 
-        let a_completion = parse_price(a.pricing.as_ref().and_then(|p| p.completion.as_deref()));
-        let b_completion = parse_price(b.pricing.as_ref().and_then(|p| p.completion.as_deref()));
-        let completion_cmp = a_completion
-            .partial_cmp(&b_completion)
-            .unwrap_or(Ordering::Equal);
-        if completion_cmp != Ordering::Equal {
-            return completion_cmp;
+    if let Some(min) = criteria.min_context_length {
+        if item.context_length.unwrap_or_default() < min {
+            return false;
         }
+    }
 
-        let a_context = a.context_length.unwrap_or_default();
-        let b_context = b.context_length.unwrap_or_default();
-        b_context.cmp(&a_context)
+    let provider = item.provider();
+    if !criteria.allowed_providers.is_empty()
+        && !provider.is_some_and(|provider| {
+            criteria
+                .allowed_providers
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(provider))
+        })
+    {
+        return false;
+    }
+    if provider.is_some_and(|provider| {
+        criteria
+            .denied_providers
+            .iter()
+            .any(|denied| denied.eq_ignore_ascii_case(provider))
+    }) {
+        return false;
+    }
+
+    if let Some(tokenizer) = &criteria.tokenizer {
+        let matches = item
+            .architecture
+            .as_ref()
+            .and_then(|a| a.tokenizer.as_deref())
+            .is_some_and(|candidate| candidate.eq_ignore_ascii_case(tokenizer));
+        if !matches {
+            return false;
+        }
+    }
+
+    criteria
+        .required_parameters
+        .iter()
+        .all(|parameter| catalog_item_supports(item, parameter))
+}
+
+fn normalized(value: f64, min: f64, max: f64) -> f64 {
+    if max > min {
+        (value - min) / (max - min)
+    } else {
+        1.0
+    }
+}
+
+fn rank_by_weights(
+    models: Vec<ModelCatalogItem>,
+    weights: &SelectionWeights,
+) -> Vec<ModelCatalogItem> {
+    let total_price = |item: &ModelCatalogItem| {
+        let pricing = item.pricing.as_ref();
+        let prompt = parse_price(pricing.and_then(|p| p.prompt.as_deref()));
+        let completion = parse_price(pricing.and_then(|p| p.completion.as_deref()));
+        if prompt == f64::MAX || completion == f64::MAX {
+            None
+        } else {
+            Some(prompt + completion)
+        }
+    };
+
+    let prices: Vec<f64> = models.iter().filter_map(total_price).collect();
+    let min_price = prices.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_price = prices.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let max_context = models
+        .iter()
+        .map(|m| m.context_length.unwrap_or_default())
+        .max()
+        .unwrap_or_default();
+    let max_params = models
+        .iter()
+        .map(ModelCatalogItem::accepted_parameter_count)
+        .max()
+        .unwrap_or_default();
+
+    let mut scored: Vec<(f64, ModelCatalogItem)> = models
+        .into_iter()
+        .map(|item| {
+            let cost = total_price(&item)
+                .map(|price| 1.0 - normalized(price, min_price, max_price))
+                .unwrap_or(0.0);
+            let context = if max_context > 0 {
+                f64::from(item.context_length.unwrap_or_default()) / f64::from(max_context)
+            } else {
+                0.0
+            };
+            let capability = if max_params > 0 {
+                item.accepted_parameter_count() as f64 / max_params as f64
+            } else {
+                0.0
+            };
+            let score =
+                weights.cost * cost + weights.context * context + weights.capability * capability;
+            (score, item)
+        })
+        .collect();
+
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .partial_cmp(a_score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| compare_by_price(a, b))
     });
+    scored.into_iter().map(|(_, item)| item).collect()
+}
 
-    filtered
+fn compare_by_price(a: &ModelCatalogItem, b: &ModelCatalogItem) -> Ordering {
+    let a_prompt = parse_price(a.pricing.as_ref().and_then(|p| p.prompt.as_deref()));
+    let b_prompt = parse_price(b.pricing.as_ref().and_then(|p| p.prompt.as_deref()));
+    let prompt_cmp = a_prompt.partial_cmp(&b_prompt).unwrap_or(Ordering::Equal);
+    if prompt_cmp != Ordering::Equal {
+        return prompt_cmp;
+    }
+
+    let a_completion = parse_price(a.pricing.as_ref().and_then(|p| p.completion.as_deref()));
+    let b_completion = parse_price(b.pricing.as_ref().and_then(|p| p.completion.as_deref()));
+    let completion_cmp = a_completion
+        .partial_cmp(&b_completion)
+        .unwrap_or(Ordering::Equal);
+    if completion_cmp != Ordering::Equal {
+        return completion_cmp;
+    }
+
+    let a_context = a.context_length.unwrap_or_default();
+    let b_context = b.context_length.unwrap_or_default();
+    b_context.cmp(&a_context)
 }
 
 /// Build provider-aware reasoning payload from `rainy_capabilities_v2`.
@@ -1489,6 +2037,7 @@ impl ChatCompletionRequest {
             response_format: None,
             tools: None,
             tool_choice: None,
+            parallel_tool_calls: None,
             thinking_config: None,
         }
     }
@@ -1615,6 +2164,16 @@ impl ChatCompletionRequest {
         self
     }
 
+    /// Allows or forbids several tool calls in one turn.
+    ///
+    /// # Arguments
+    ///
+    /// * `parallel_tool_calls` - Whether parallel tool calls are allowed.
+    pub fn with_parallel_tool_calls(mut self, parallel_tool_calls: bool) -> Self {
+        self.parallel_tool_calls = Some(parallel_tool_calls);
+        self
+    }
+
     /// Sets the thinking configuration for Gemini 3 and 2.5 series models.
     ///
     /// # Arguments
@@ -1746,6 +2305,25 @@ impl ChatCompletionRequest {
             }
         }
 
+        // Validate tool choice against the declared tools
+        if let Some(tool_choice) = &self.tool_choice {
+            let tools = self.tools.as_deref().unwrap_or_default();
+            match tool_choice {
+                ToolChoice::Required if tools.is_empty() => {
+                    return Err("tool_choice 'required' needs at least one tool".to_string());
+                }
+                ToolChoice::Tool { function, .. }
+                    if !tools.iter().any(|tool| tool.function.name == function.name) =>
+                {
+                    return Err(format!(
+                        "tool_choice names undeclared function '{}'",
+                        function.name
+                    ));
+                }
+                _ => {}
+            }
+        }
+
         // Validate thinking configuration for Gemini models
         if let Some(thinking_config) = &self.thinking_config {
             self.validate_thinking_config(thinking_config)?;
@@ -1767,13 +2345,10 @@ impl ChatCompletionRequest {
             }
 
             match level {
-                ThinkingLevel::Minimal | ThinkingLevel::Medium => {
-                    if is_gemini_3_pro {
-                        return Err(
-                            "Gemini 3 Pro only supports 'low' and 'high' thinking levels"
-                                .to_string(),
-                        );
-                    }
+                ThinkingLevel::Minimal | ThinkingLevel::Medium if is_gemini_3_pro => {
+                    return Err(
+                        "Gemini 3 Pro only supports 'low' and 'high' thinking levels".to_string(),
+                    );
                 }
                 _ => {}
             }
@@ -1845,8 +2420,10 @@ impl OpenAIChatCompletionRequest {
             response_format: None,
             tools: None,
             tool_choice: None,
+            parallel_tool_calls: None,
             thinking_config: None,
             thinking: None,
+            modalities: None,
         }
     }
 
@@ -1946,6 +2523,18 @@ impl OpenAIChatCompletionRequest {
         self
     }
 
+    /// Allows or forbids several tool calls in one turn.
+    pub fn with_parallel_tool_calls(mut self, parallel_tool_calls: bool) -> Self {
+        self.parallel_tool_calls = Some(parallel_tool_calls);
+        self
+    }
+
+    /// Sets the output modalities to generate, e.g. `["image", "text"]`.
+    pub fn with_modalities(mut self, modalities: Vec<String>) -> Self {
+        self.modalities = Some(modalities);
+        self
+    }
+
     /// Sets the Gemini thinking configuration.
     pub fn with_thinking_config(mut self, thinking_config: ThinkingConfig) -> Self {
         self.thinking_config = Some(thinking_config);
@@ -1986,8 +2575,10 @@ impl OpenAIChatCompletionRequest {
         self
     }
 
-    /// Validates compatibility using the same parameter rules as the simple chat request.
+    /// Validates compatibility using the same parameter rules as the simple chat request,
+    /// plus [`validate_thought_signatures`](Self::validate_thought_signatures).
     pub fn validate_openai_compatibility(&self) -> Result<(), String> {
+        self.validate_thought_signatures()?;
         ChatCompletionRequest {
             model: self.model.clone(),
             messages: vec![],
@@ -2007,6 +2598,7 @@ impl OpenAIChatCompletionRequest {
             response_format: self.response_format.clone(),
             tools: self.tools.clone(),
             tool_choice: self.tool_choice.clone(),
+            parallel_tool_calls: self.parallel_tool_calls,
             thinking_config: self.thinking_config.clone(),
         }
         .validate_openai_compatibility()
@@ -2021,6 +2613,28 @@ impl OpenAIChatCompletionRequest {
     pub fn requires_thought_signatures(&self) -> bool {
         self.model.contains("gemini-3")
     }
+
+    /// Checks that every assistant tool call of the current turn carries the thought
+    /// signature Gemini 3 requires.
+    ///
+    /// Only the first call of each assistant message is checked, since Gemini attaches a
+    /// single signature to a batch of parallel calls. Models that do not require signatures
+    /// always pass.
+    pub fn validate_thought_signatures(&self) -> Result<(), String> {
+        if !self.requires_thought_signatures() {
+            return Ok(());
+        }
+        let missing = crate::thought_signatures::missing_thought_signatures(&self.messages);
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "Model {} requires thought signatures, but tool calls {} have none",
+                self.model,
+                missing.join(", ")
+            ))
+        }
+    }
 }
 
 impl ChatMessage {
@@ -2102,6 +2716,42 @@ impl OpenAIContentPart {
     }
 }
 
+impl OpenAIToolCall {
+    /// Returns the Gemini thought signature stored in `extra_content.google.thought_signature`.
+    pub fn thought_signature(&self) -> Option<&str> {
+        self.extra_content
+            .as_ref()?
+            .get("google")?
+            .get("thought_signature")?
+            .as_str()
+    }
+
+    /// Stores a Gemini thought signature, keeping any other `extra_content` fields.
+    pub fn set_thought_signature(&mut self, signature: impl Into<String>) {
+        let extra = self
+            .extra_content
+            .get_or_insert_with(|| serde_json::json!({}));
+        if !extra.is_object() {
+            *extra = serde_json::json!({});
+        }
+        let google = extra
+            .as_object_mut()
+            .expect("object")
+            .entry("google")
+            .or_insert_with(|| serde_json::json!({}));
+        if !google.is_object() {
+            *google = serde_json::json!({});
+        }
+        google["thought_signature"] = serde_json::Value::String(signature.into());
+    }
+
+    /// Returns this tool call with a Gemini thought signature attached.
+    pub fn with_thought_signature(mut self, signature: impl Into<String>) -> Self {
+        self.set_thought_signature(signature);
+        self
+    }
+}
+
 impl OpenAIChatMessage {
     /// Creates a new system message.
     pub fn system(content: impl Into<OpenAIMessageContent>) -> Self {
@@ -2111,6 +2761,8 @@ impl OpenAIChatMessage {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            reasoning: None,
+            images: None,
         }
     }
 
@@ -2122,6 +2774,8 @@ impl OpenAIChatMessage {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            reasoning: None,
+            images: None,
         }
     }
 
@@ -2133,6 +2787,8 @@ impl OpenAIChatMessage {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            reasoning: None,
+            images: None,
         }
     }
 
@@ -2144,6 +2800,8 @@ impl OpenAIChatMessage {
             name: None,
             tool_calls: Some(tool_calls),
             tool_call_id: None,
+            reasoning: None,
+            images: None,
         }
     }
 
@@ -2155,9 +2813,17 @@ impl OpenAIChatMessage {
             name: None,
             tool_calls: None,
             tool_call_id: Some(tool_call_id.into()),
+            reasoning: None,
+            images: None,
         }
     }
 
+    /// Attaches reasoning text to this message.
+    pub fn with_reasoning(mut self, reasoning: impl Into<String>) -> Self {
+        self.reasoning = Some(reasoning.into());
+        self
+    }
+
     /// Creates a message with full control over optional OpenAI-compatible fields.
     pub fn with_parts(
         role: OpenAIMessageRole,
@@ -2171,6 +2837,8 @@ impl OpenAIChatMessage {
             name: None,
             tool_calls,
             tool_call_id,
+            reasoning: None,
+            images: None,
         }
     }
 }
@@ -2353,7 +3021,7 @@ pub struct Tool {
 }
 
 /// The type of tool.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ToolType {
     /// A function tool.
@@ -2374,13 +3042,18 @@ pub struct FunctionDefinition {
 }
 
 /// Controls which tool is called by the model.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
+///
+/// Serialized in the OpenAI chat wire format: the strings `"none"`, `"auto"` and
+/// `"required"`, or `{"type": "function", "function": {"name": ...}}`. The Responses API
+/// shape (`{"type": "function", "name": ...}`) is also accepted when deserializing.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ToolChoice {
     /// No tool is called.
     None,
     /// The model chooses which tool to call.
     Auto,
+    /// The model must call at least one tool.
+    Required,
     /// A specific tool is called.
     Tool {
         /// The type of the tool being called.
@@ -2390,8 +3063,81 @@ pub enum ToolChoice {
     },
 }
 
+impl ToolChoice {
+    /// Forces a call to the named function.
+    pub fn function(name: impl Into<String>) -> Self {
+        Self::Tool {
+            r#type: ToolType::Function,
+            function: ToolFunction { name: name.into() },
+        }
+    }
+
+    /// The Responses API shape of this tool choice.
+    pub fn to_responses(&self) -> serde_json::Value {
+        match self {
+            Self::None => serde_json::json!("none"),
+            Self::Auto => serde_json::json!("auto"),
+            Self::Required => serde_json::json!("required"),
+            Self::Tool { function, .. } => {
+                serde_json::json!({ "type": "function", "name": function.name })
+            }
+        }
+    }
+}
+
+impl Serialize for ToolChoice {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::None => serializer.serialize_str("none"),
+            Self::Auto => serializer.serialize_str("auto"),
+            Self::Required => serializer.serialize_str("required"),
+            Self::Tool { r#type, function } => {
+                use serde::ser::SerializeStruct;
+                let mut state = serializer.serialize_struct("ToolChoice", 2)?;
+                state.serialize_field("type", r#type)?;
+                state.serialize_field("function", function)?;
+                state.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ToolChoice {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Wire {
+            Mode(String),
+            Chat {
+                r#type: ToolType,
+                function: ToolFunction,
+            },
+            Responses {
+                r#type: ToolType,
+                name: String,
+            },
+        }
+
+        match Wire::deserialize(deserializer)? {
+            Wire::Mode(mode) => match mode.as_str() {
+                "none" => Ok(Self::None),
+                "auto" => Ok(Self::Auto),
+                "required" => Ok(Self::Required),
+                other => Err(D::Error::custom(format!("unknown tool_choice '{other}'"))),
+            },
+            Wire::Chat { r#type, function } => Ok(Self::Tool { r#type, function }),
+            Wire::Responses { r#type, name } => Ok(Self::Tool {
+                r#type,
+                function: ToolFunction { name },
+            }),
+        }
+    }
+}
+
 /// Represents a tool function call.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ToolFunction {
     /// The name of the function to call.
     pub name: String,
@@ -2492,6 +3238,30 @@ pub struct EnhancedUsage {
     pub thoughts_token_count: Option<u32>,
 }
 
+impl From<&Usage> for EnhancedUsage {
+    fn from(usage: &Usage) -> Self {
+        Self {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            total_tokens: usage.total_tokens,
+            thoughts_token_count: usage.thoughts_token_count(),
+        }
+    }
+}
+
+impl From<&ResponsesUsage> for EnhancedUsage {
+    fn from(usage: &ResponsesUsage) -> Self {
+        let prompt_tokens = usage.input_tokens.unwrap_or(0);
+        let completion_tokens = usage.output_tokens.unwrap_or(0);
+        Self {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            thoughts_token_count: usage.reasoning_tokens(),
+        }
+    }
+}
+
 impl ThinkingConfig {
     /// Creates a new thinking configuration with default values.
     pub fn new() -> Self {
@@ -2656,7 +3426,10 @@ pub struct ChatCompletionStreamChoice {
     pub delta: ChatCompletionStreamDelta,
     /// The reason the model stopped generating tokens (only present in the final chunk).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub finish_reason: Option<String>,
+    pub finish_reason: Option<FinishReason>,
+    /// Log probabilities of the tokens in this chunk, when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<ChoiceLogprobs>,
 }
 
 /// Represents the delta (change) in a streaming chat completion response.
@@ -2671,6 +3444,9 @@ pub struct ChatCompletionStreamDelta {
     /// The thinking/reasoning content for this chunk (if any).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thought: Option<String>,
+    /// OpenRouter-style reasoning text for this chunk (if any).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
     /// Tool calls for this chunk (if any).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<ToolCall>>,
@@ -2690,6 +3466,9 @@ pub struct ToolCall {
     /// The function being called.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub function: Option<ToolCallFunction>,
+    /// Provider-specific metadata, e.g. `{"google": {"thought_signature": "..."}}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_content: Option<serde_json::Value>,
 }
 
 /// Represents a function call in a tool call.
@@ -0,0 +1,606 @@
+//! Image, audio and file inputs from bytes or paths, with model capability checks.
+//!
+//! The constructors on [`OpenAIContentPart`] base64-encode media for the caller, detect the
+//! MIME type from magic bytes (falling back to the file extension), and reject payloads above
+//! the per-kind size limits. Images over the limit can be passed through a caller-supplied
+//! [`MediaOptions::downscaler`] first; the SDK does not decode images itself.
+//!
+//! [`OpenAIContentPart::to_responses_input`] maps each part to its Responses API equivalent.
+//! [`check_input_modalities`] compares what a request contains against the catalog entry's
+//! `rainy_capabilities_v2.multimodal.input`; the OpenAI chat and Responses send paths run it
+//! against the cached catalog for every request that carries media.
+
+use crate::client::RainyClient;
+use crate::error::{RainyError, Result};
+use crate::models::{
+    ChatCompletionRequest, ModelCatalogItem, OpenAIChatCompletionRequest, OpenAIContentPart,
+    OpenAIFileInput, OpenAIImageUrl, OpenAIInputAudio, OpenAIMessageContent, ResponsesRequest,
+};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Largest image accepted inline, in bytes.
+pub const MAX_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+/// Largest audio clip accepted inline, in bytes.
+pub const MAX_AUDIO_BYTES: usize = 25 * 1024 * 1024;
+/// Largest file accepted inline, in bytes.
+pub const MAX_FILE_BYTES: usize = 50 * 1024 * 1024;
+
+/// Input modality of a content part, named as in `rainy_capabilities_v2.multimodal.input`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Modality {
+    /// Text input.
+    Text,
+    /// Image input.
+    Image,
+    /// Audio input.
+    Audio,
+    /// File (document) input.
+    File,
+}
+
+impl Modality {
+    /// Catalog name of the modality.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Image => "image",
+            Self::Audio => "audio",
+            Self::File => "file",
+        }
+    }
+}
+
+impl fmt::Display for Modality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Hook that shrinks an oversized image; receives the bytes and MIME type and returns the
+/// re-encoded bytes and their MIME type.
+pub type Downscaler = Arc<dyn Fn(&[u8], &str) -> Result<(Vec<u8>, String)> + Send + Sync>;
+
+/// Options for building media content parts.
+#[derive(Clone, Default)]
+pub struct MediaOptions {
+    /// Size limit in bytes; defaults to the per-kind `MAX_*_BYTES` constant.
+    pub max_bytes: Option<usize>,
+    /// Image detail hint (`low`, `high`, `auto`).
+    pub detail: Option<String>,
+    /// Hook applied to images above `max_bytes` before the size check.
+    pub downscaler: Option<Downscaler>,
+}
+
+impl fmt::Debug for MediaOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MediaOptions")
+            .field("max_bytes", &self.max_bytes)
+            .field("detail", &self.detail)
+            .field("downscaler", &self.downscaler.is_some())
+            .finish()
+    }
+}
+
+impl MediaOptions {
+    /// Creates options with the default limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the size limit in bytes.
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Sets the image detail hint.
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Sets the hook used to shrink oversized images.
+    pub fn with_downscaler<F>(mut self, downscaler: F) -> Self
+    where
+        F: Fn(&[u8], &str) -> Result<(Vec<u8>, String)> + Send + Sync + 'static,
+    {
+        self.downscaler = Some(Arc::new(downscaler));
+        self
+    }
+}
+
+/// Detects a MIME type from the leading bytes of common image, audio and document formats.
+pub fn sniff_mime(bytes: &[u8]) -> Option<&'static str> {
+    let riff = |kind: &[u8]| bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == kind;
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if riff(b"WEBP") {
+        Some("image/webp")
+    } else if riff(b"WAVE") {
+        Some("audio/wav")
+    } else if bytes.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else if bytes.starts_with(b"fLaC") {
+        Some("audio/flac")
+    } else if bytes.starts_with(b"OggS") {
+        Some("audio/ogg")
+    } else if bytes.starts_with(b"ID3")
+        || (bytes.len() >= 2 && bytes[0] == 0xFF && bytes[1] & 0xE0 == 0xE0)
+    {
+        Some("audio/mpeg")
+    } else if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" && &bytes[8..11] == b"M4A" {
+        Some("audio/mp4")
+    } else {
+        None
+    }
+}
+
+/// Guesses a MIME type from a path's extension.
+pub fn mime_from_path(path: impl AsRef<Path>) -> Option<&'static str> {
+    let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "wav" => "audio/wav",
+        "mp3" => "audio/mpeg",
+        "flac" => "audio/flac",
+        "ogg" | "oga" => "audio/ogg",
+        "m4a" => "audio/mp4",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "html" | "htm" => "text/html",
+        _ => return None,
+    })
+}
+
+/// Audio `format` value for an audio MIME type.
+fn audio_format(mime: &str) -> Option<&'static str> {
+    Some(match mime {
+        "audio/wav" | "audio/x-wav" | "audio/wave" => "wav",
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        "audio/flac" => "flac",
+        "audio/ogg" => "ogg",
+        "audio/mp4" | "audio/m4a" | "audio/x-m4a" => "m4a",
+        _ => return None,
+    })
+}
+
+fn unsupported(message: String) -> RainyError {
+    RainyError::InvalidRequest {
+        code: "UNSUPPORTED_MEDIA_TYPE".to_string(),
+        message,
+        details: None,
+    }
+}
+
+fn check_size(kind: &str, len: usize, limit: usize) -> Result<()> {
+    if len > limit {
+        return Err(RainyError::InvalidRequest {
+            code: "MEDIA_TOO_LARGE".to_string(),
+            message: format!("{kind} is {len} bytes, above the {limit}-byte limit"),
+            details: None,
+        });
+    }
+    Ok(())
+}
+
+fn resolve_mime(bytes: &[u8], declared: Option<&str>, path: Option<&Path>) -> Option<String> {
+    declared
+        .map(str::to_string)
+        .or_else(|| sniff_mime(bytes).map(str::to_string))
+        .or_else(|| path.and_then(mime_from_path).map(str::to_string))
+}
+
+fn data_url(mime: &str, bytes: &[u8]) -> String {
+    format!("data:{mime};base64,{}", STANDARD.encode(bytes))
+}
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name()?.to_str().map(str::to_string)
+}
+
+fn build_image(
+    bytes: &[u8],
+    mime: Option<&str>,
+    path: Option<&Path>,
+    options: &MediaOptions,
+) -> Result<OpenAIContentPart> {
+    let mime = resolve_mime(bytes, mime, path)
+        .filter(|mime| mime.starts_with("image/"))
+        .ok_or_else(|| unsupported("image type could not be determined".to_string()))?;
+    let limit = options.max_bytes.unwrap_or(MAX_IMAGE_BYTES);
+
+    let (bytes, mime) = match &options.downscaler {
+        Some(downscale) if bytes.len() > limit => downscale(bytes, &mime)?,
+        _ => (bytes.to_vec(), mime),
+    };
+    check_size("image", bytes.len(), limit)?;
+
+    Ok(OpenAIContentPart::ImageUrl {
+        image_url: OpenAIImageUrl {
+            url: data_url(&mime, &bytes),
+            detail: options.detail.clone(),
+        },
+    })
+}
+
+fn build_audio(
+    bytes: &[u8],
+    format: Option<&str>,
+    path: Option<&Path>,
+    options: &MediaOptions,
+) -> Result<OpenAIContentPart> {
+    let format = match format {
+        Some(format) => format.to_string(),
+        None => resolve_mime(bytes, None, path)
+            .as_deref()
+            .and_then(audio_format)
+            .ok_or_else(|| unsupported("audio format could not be determined".to_string()))?
+            .to_string(),
+    };
+    check_size(
+        "audio",
+        bytes.len(),
+        options.max_bytes.unwrap_or(MAX_AUDIO_BYTES),
+    )?;
+    Ok(OpenAIContentPart::InputAudio {
+        input_audio: OpenAIInputAudio {
+            data: STANDARD.encode(bytes),
+            format,
+        },
+    })
+}
+
+fn build_file(
+    bytes: &[u8],
+    filename: Option<String>,
+    mime: Option<&str>,
+    path: Option<&Path>,
+    options: &MediaOptions,
+) -> Result<OpenAIContentPart> {
+    check_size(
+        "file",
+        bytes.len(),
+        options.max_bytes.unwrap_or(MAX_FILE_BYTES),
+    )?;
+    let mime = resolve_mime(bytes, mime, path)
+        .or_else(|| {
+            filename
+                .as_deref()
+                .and_then(mime_from_path)
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    Ok(OpenAIContentPart::File {
+        file: OpenAIFileInput {
+            file_data: Some(data_url(&mime, bytes)),
+            file_id: None,
+            filename,
+        },
+    })
+}
+
+impl OpenAIContentPart {
+    /// Creates an inline image part; `mime` is sniffed from the bytes when `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use rainy_sdk::OpenAIContentPart;
+    ///
+    /// let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    /// let part = OpenAIContentPart::image_from_bytes(png, None).unwrap();
+    /// match part {
+    ///     OpenAIContentPart::ImageUrl { image_url } => {
+    ///         assert!(image_url.url.starts_with("data:image/png;base64,"))
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn image_from_bytes(bytes: &[u8], mime: Option<&str>) -> Result<Self> {
+        Self::image_from_bytes_with(bytes, mime, &MediaOptions::default())
+    }
+
+    /// Creates an inline image part with explicit limits, detail and downscaling hook.
+    pub fn image_from_bytes_with(
+        bytes: &[u8],
+        mime: Option<&str>,
+        options: &MediaOptions,
+    ) -> Result<Self> {
+        build_image(bytes, mime, None, options)
+    }
+
+    /// Reads an image file into an inline image part.
+    pub fn image_from_path(path: impl AsRef<Path>) -> Result<Self> {
+        Self::image_from_path_with(path, &MediaOptions::default())
+    }
+
+    /// Reads an image file into an inline image part with explicit options.
+    pub fn image_from_path_with(path: impl AsRef<Path>, options: &MediaOptions) -> Result<Self> {
+        let path = path.as_ref();
+        build_image(&std::fs::read(path)?, None, Some(path), options)
+    }
+
+    /// Creates an audio part from base64 data that is already encoded.
+    pub fn input_audio(data: impl Into<String>, format: impl Into<String>) -> Self {
+        Self::InputAudio {
+            input_audio: OpenAIInputAudio {
+                data: data.into(),
+                format: format.into(),
+            },
+        }
+    }
+
+    /// Creates an audio part; `format` (`wav`, `mp3`, ...) is sniffed when `None`.
+    pub fn audio_from_bytes(bytes: &[u8], format: Option<&str>) -> Result<Self> {
+        build_audio(bytes, format, None, &MediaOptions::default())
+    }
+
+    /// Reads an audio file into an audio part.
+    pub fn audio_from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        build_audio(
+            &std::fs::read(path)?,
+            None,
+            Some(path),
+            &MediaOptions::default(),
+        )
+    }
+
+    /// Creates a part referencing a previously uploaded file.
+    pub fn file_id(file_id: impl Into<String>) -> Self {
+        Self::File {
+            file: OpenAIFileInput {
+                file_id: Some(file_id.into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Creates an inline file part; `mime` is sniffed from the bytes or file name when `None`.
+    pub fn file_from_bytes(
+        bytes: &[u8],
+        filename: impl Into<String>,
+        mime: Option<&str>,
+    ) -> Result<Self> {
+        build_file(
+            bytes,
+            Some(filename.into()),
+            mime,
+            None,
+            &MediaOptions::default(),
+        )
+    }
+
+    /// Reads a file into an inline file part named after the path.
+    pub fn file_from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        build_file(
+            &std::fs::read(path)?,
+            file_name(path),
+            None,
+            Some(path),
+            &MediaOptions::default(),
+        )
+    }
+
+    /// Input modality of this part.
+    pub fn modality(&self) -> Modality {
+        match self {
+            Self::Text { .. } => Modality::Text,
+            Self::ImageUrl { .. } => Modality::Image,
+            Self::InputAudio { .. } => Modality::Audio,
+            Self::File { .. } => Modality::File,
+        }
+    }
+
+    /// Converts this part to a Responses API input content item.
+    pub fn to_responses_input(&self) -> Value {
+        match self {
+            Self::Text { text } => json!({ "type": "input_text", "text": text }),
+            Self::ImageUrl { image_url } => {
+                let mut item = json!({ "type": "input_image", "image_url": image_url.url });
+                if let Some(detail) = &image_url.detail {
+                    item["detail"] = json!(detail);
+                }
+                item
+            }
+            Self::InputAudio { input_audio } => {
+                json!({ "type": "input_audio", "input_audio": input_audio })
+            }
+            Self::File { file } => {
+                let mut item = json!({ "type": "input_file" });
+                if let Value::Object(fields) = json!(file) {
+                    item.as_object_mut().expect("object").extend(fields);
+                }
+                item
+            }
+        }
+    }
+}
+
+impl ResponsesRequest {
+    /// Creates a Responses API request with one user message built from content parts.
+    pub fn from_parts(model: impl Into<String>, parts: &[OpenAIContentPart]) -> Self {
+        let content: Vec<Value> = parts
+            .iter()
+            .map(OpenAIContentPart::to_responses_input)
+            .collect();
+        Self::new(
+            model,
+            json!([{ "type": "message", "role": "user", "content": content }]),
+        )
+    }
+}
+
+/// Requests whose input modalities can be checked against a model's capabilities.
+pub trait MultimodalRequest {
+    /// Distinct input modalities used by the request, always including text.
+    fn input_modalities(&self) -> Vec<Modality>;
+}
+
+fn with_text(mut modalities: Vec<Modality>) -> Vec<Modality> {
+    modalities.push(Modality::Text);
+    modalities.sort();
+    modalities.dedup();
+    modalities
+}
+
+impl MultimodalRequest for ChatCompletionRequest {
+    fn input_modalities(&self) -> Vec<Modality> {
+        vec![Modality::Text]
+    }
+}
+
+impl MultimodalRequest for OpenAIChatCompletionRequest {
+    fn input_modalities(&self) -> Vec<Modality> {
+        with_text(
+            self.messages
+                .iter()
+                .filter_map(|message| match &message.content {
+                    Some(OpenAIMessageContent::Parts(parts)) => Some(parts),
+                    _ => None,
+                })
+                .flatten()
+                .map(OpenAIContentPart::modality)
+                .collect(),
+        )
+    }
+}
+
+fn collect_json_modalities(value: &Value, modalities: &mut Vec<Modality>) {
+    match value {
+        Value::Object(map) => {
+            match map.get("type").and_then(Value::as_str) {
+                Some("input_image" | "image_url") => modalities.push(Modality::Image),
+                Some("input_audio") => modalities.push(Modality::Audio),
+                Some("input_file" | "file") => modalities.push(Modality::File),
+                _ => {}
+            }
+            map.values()
+                .for_each(|value| collect_json_modalities(value, modalities));
+        }
+        Value::Array(items) => items
+            .iter()
+            .for_each(|item| collect_json_modalities(item, modalities)),
+        _ => {}
+    }
+}
+
+impl MultimodalRequest for ResponsesRequest {
+    fn input_modalities(&self) -> Vec<Modality> {
+        let mut modalities = Vec::new();
+        collect_json_modalities(&self.input, &mut modalities);
+        with_text(modalities)
+    }
+}
+
+/// Input modalities a catalog entry accepts, or `None` when the catalog does not say.
+pub(crate) fn accepted_modalities(model: &ModelCatalogItem) -> Option<&[String]> {
+    if let Some(v2) = &model.rainy_capabilities_v2 {
+        if !v2.multimodal.input.is_empty() {
+            return Some(&v2.multimodal.input);
+        }
+    }
+    model
+        .architecture
+        .as_ref()?
+        .input_modalities
+        .as_deref()
+        .filter(|inputs| !inputs.is_empty())
+}
+
+/// Checks the request's input modalities against `rainy_capabilities_v2.multimodal.input`.
+///
+/// Models whose catalog entry lists no input modalities are not checked. Fails with
+/// `UNSUPPORTED_INPUT_MODALITY` naming the modalities the model does not accept.
+pub fn check_input_modalities<R: MultimodalRequest + ?Sized>(
+    request: &R,
+    model: &ModelCatalogItem,
+) -> Result<()> {
+    let Some(accepted) = accepted_modalities(model) else {
+        return Ok(());
+    };
+    let missing: Vec<&str> = request
+        .input_modalities()
+        .into_iter()
+        .filter(|modality| *modality != Modality::Text)
+        .map(|modality| modality.as_str())
+        .filter(|name| !accepted.iter().any(|a| a.eq_ignore_ascii_case(name)))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(RainyError::InvalidRequest {
+        code: "UNSUPPORTED_INPUT_MODALITY".to_string(),
+        message: format!(
+            "model {} does not accept {} input",
+            model.id,
+            missing.join(", ")
+        ),
+        details: None,
+    })
+}
+
+impl RainyClient {
+    /// Looks up `model` in the cached catalog and checks the request's input modalities
+    /// against it.
+    ///
+    /// The OpenAI chat and Responses methods already run this check before sending; call it
+    /// directly to validate a request up front.
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - The model the request will be sent to.
+    /// * `request` - The request to check.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` when the model accepts every modality or is not in the catalog.
+    pub async fn check_input_modalities<R: MultimodalRequest + ?Sized>(
+        &self,
+        model: &str,
+        request: &R,
+    ) -> Result<()> {
+        match self.catalog_entry(model).await? {
+            Some(item) => check_input_modalities(request, &item),
+            None => Ok(()),
+        }
+    }
+
+    /// Send-path modality check: text-only requests skip the catalog, and a catalog that
+    /// cannot be loaded is treated like a missing entry.
+    pub(crate) async fn ensure_input_modalities<R: MultimodalRequest + ?Sized>(
+        &self,
+        model: &str,
+        request: &R,
+    ) -> Result<()> {
+        if request
+            .input_modalities()
+            .iter()
+            .all(|modality| *modality == Modality::Text)
+        {
+            return Ok(());
+        }
+        match self.catalog_entry(model).await {
+            Ok(Some(item)) => check_input_modalities(request, &item),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,558 @@
+//! Partial JSON parsing for streamed structured outputs.
+//!
+//! With [`ResponseFormat::JsonSchema`] and streaming, the model's JSON document arrives a few
+//! characters at a time. [`PartialJsonParser`] feeds the text deltas to an incremental parser
+//! and, after each one, snapshots the longest valid prefix as a `serde_json::Value`: open
+//! strings are kept as far as they go, open objects and arrays are closed, and keys without a
+//! value yet are left out. Once the stream ends, [`PartialJsonParser::finish`] parses the whole document
+//! strictly and validates it against the response schema.
+//!
+//! ```rust,no_run
+//! # use futures::StreamExt;
+//! # use rainy_sdk::{OpenAIChatCompletionRequest, PartialJsonParser, RainyClient, StructuredEvent};
+//! # async fn example(request: OpenAIChatCompletionRequest) -> Result<(), Box<dyn std::error::Error>> {
+//! let client = RainyClient::with_api_key("your-api-key")?;
+//! let parser = request
+//!     .response_format
+//!     .as_ref()
+//!     .map(PartialJsonParser::for_response_format)
+//!     .unwrap_or_default();
+//! let stream = client.create_openai_chat_completion_stream(request).await?;
+//!
+//! let mut events = parser.stream::<serde_json::Value, _>(stream);
+//! while let Some(event) = events.next().await {
+//!     match event? {
+//!         StructuredEvent::Partial(partial) => println!("so far: {}", partial.snapshot),
+//!         StructuredEvent::Complete(value) => println!("done: {value}"),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{RainyError, Result};
+use crate::models::{ChatCompletionStreamResponse, ResponseFormat, ResponsesStreamEvent};
+use crate::schema::validate_json_schema;
+use futures::Stream;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Parses a possibly truncated JSON document into its longest valid prefix.
+///
+/// Returns `None` when nothing usable has arrived yet or the text is not JSON.
+pub fn parse_partial_json(text: &str) -> Option<Value> {
+    let mut state = PartialState::default();
+    state.feed(text);
+    state.snapshot()
+}
+
+/// Incremental parser: open containers, the token in progress and what may come next.
+///
+/// Each character is consumed once, so feeding deltas never re-parses earlier text. Building a
+/// snapshot still copies the open containers, so it costs time proportional to the document
+/// so far. Once the text stops being JSON the state stays failed.
+#[derive(Debug, Clone, Default)]
+struct PartialState {
+    stack: Vec<Frame>,
+    token: Option<Token>,
+    expect: Expect,
+    root: Option<Value>,
+    failed: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Frame {
+    Object {
+        map: Map<String, Value>,
+        key: Option<String>,
+    },
+    Array(Vec<Value>),
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    String(JsonString),
+    Number(String),
+    Literal { word: &'static str, matched: usize },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Expect {
+    /// A value: at the root or after `:`.
+    #[default]
+    Value,
+    /// A value or `]`: after `[` or `,` in an array.
+    ValueOrEnd,
+    /// A key or `}`: after `{` or `,` in an object.
+    KeyOrEnd,
+    /// The `:` after an object key.
+    Colon,
+    /// `,` or the closing bracket after a value in a container.
+    Separator,
+    /// The root value is complete; trailing text is ignored.
+    Done,
+}
+
+type Step = std::result::Result<(), ()>;
+
+impl PartialState {
+    fn feed(&mut self, text: &str) {
+        for ch in text.chars() {
+            if self.failed || self.expect == Expect::Done {
+                return;
+            }
+            if self.step(ch).is_err() {
+                self.failed = true;
+            }
+        }
+    }
+
+    fn step(&mut self, ch: char) -> Step {
+        match self.token.take() {
+            Some(Token::String(mut string)) => {
+                if string.push(ch) {
+                    if string.key {
+                        if let Some(Frame::Object { key, .. }) = self.stack.last_mut() {
+                            *key = Some(string.text);
+                        }
+                        self.expect = Expect::Colon;
+                    } else {
+                        self.complete_value(Value::String(string.text));
+                    }
+                } else {
+                    self.token = Some(Token::String(string));
+                }
+                Ok(())
+            }
+            Some(Token::Number(mut text)) => {
+                if ch.is_ascii_digit() || matches!(ch, '-' | '+' | '.' | 'e' | 'E') {
+                    text.push(ch);
+                    self.token = Some(Token::Number(text));
+                    return Ok(());
+                }
+                let number = serde_json::from_str(&text).map_err(|_| ())?;
+                self.complete_value(number);
+                self.step(ch)
+            }
+            Some(Token::Literal { word, matched }) => {
+                if !word[matched..].starts_with(ch) {
+                    return Err(());
+                }
+                if matched + 1 < word.len() {
+                    self.token = Some(Token::Literal {
+                        word,
+                        matched: matched + 1,
+                    });
+                } else {
+                    self.complete_value(match word {
+                        "true" => Value::Bool(true),
+                        "false" => Value::Bool(false),
+                        _ => Value::Null,
+                    });
+                }
+                Ok(())
+            }
+            None => self.structural(ch),
+        }
+    }
+
+    fn structural(&mut self, ch: char) -> Step {
+        if ch.is_whitespace() {
+            return Ok(());
+        }
+        let in_object = matches!(self.stack.last(), Some(Frame::Object { .. }));
+        match (self.expect, ch) {
+            (Expect::KeyOrEnd, '"') => {
+                self.token = Some(Token::String(JsonString::new(true)));
+            }
+            (Expect::KeyOrEnd | Expect::Separator, '}') if in_object => self.close(),
+            (Expect::ValueOrEnd | Expect::Separator, ']') if !in_object => self.close(),
+            (Expect::Colon, ':') => self.expect = Expect::Value,
+            (Expect::Separator, ',') => {
+                self.expect = if in_object {
+                    Expect::KeyOrEnd
+                } else {
+                    Expect::ValueOrEnd
+                };
+            }
+            (Expect::Value | Expect::ValueOrEnd, _) => return self.start_value(ch),
+            _ => return Err(()),
+        }
+        Ok(())
+    }
+
+    fn start_value(&mut self, ch: char) -> Step {
+        match ch {
+            '{' => {
+                self.stack.push(Frame::Object {
+                    map: Map::new(),
+                    key: None,
+                });
+                self.expect = Expect::KeyOrEnd;
+            }
+            '[' => {
+                self.stack.push(Frame::Array(Vec::new()));
+                self.expect = Expect::ValueOrEnd;
+            }
+            '"' => self.token = Some(Token::String(JsonString::new(false))),
+            't' | 'f' | 'n' => {
+                let word = match ch {
+                    't' => "true",
+                    'f' => "false",
+                    _ => "null",
+                };
+                self.token = Some(Token::Literal { word, matched: 1 });
+            }
+            '-' | '0'..='9' => self.token = Some(Token::Number(ch.to_string())),
+            _ => return Err(()),
+        }
+        Ok(())
+    }
+
+    fn complete_value(&mut self, value: Value) {
+        match self.stack.last_mut() {
+            None => {
+                self.root = Some(value);
+                self.expect = Expect::Done;
+            }
+            Some(Frame::Array(items)) => {
+                items.push(value);
+                self.expect = Expect::Separator;
+            }
+            Some(Frame::Object { map, key }) => {
+                if let Some(key) = key.take() {
+                    map.insert(key, value);
+                }
+                self.expect = Expect::Separator;
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        let value = match self.stack.pop() {
+            Some(Frame::Object { map, .. }) => Value::Object(map),
+            Some(Frame::Array(items)) => Value::Array(items),
+            None => return,
+        };
+        self.complete_value(value);
+    }
+
+    /// The document so far: open strings are kept, open containers closed, and keys
+    /// without a value left out.
+    fn snapshot(&self) -> Option<Value> {
+        if self.failed {
+            return None;
+        }
+        if let Some(root) = &self.root {
+            return Some(root.clone());
+        }
+        let mut value = match &self.token {
+            Some(Token::String(string)) if !string.key => Some(Value::String(string.text.clone())),
+            Some(Token::Number(text)) => serde_json::from_str(text).ok(),
+            _ => None,
+        };
+        for frame in self.stack.iter().rev() {
+            value = Some(match frame {
+                Frame::Array(items) => {
+                    let mut items = items.clone();
+                    items.extend(value);
+                    Value::Array(items)
+                }
+                Frame::Object { map, key } => {
+                    let mut map = map.clone();
+                    if let (Some(key), Some(value)) = (key, value) {
+                        map.insert(key.clone(), value);
+                    }
+                    Value::Object(map)
+                }
+            });
+        }
+        value
+    }
+}
+
+/// A string token in progress; a truncated escape is left out until it completes.
+#[derive(Debug, Clone)]
+struct JsonString {
+    text: String,
+    key: bool,
+    escape: Escape,
+    high_surrogate: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+enum Escape {
+    None,
+    Backslash,
+    Unicode(String),
+}
+
+impl JsonString {
+    fn new(key: bool) -> Self {
+        Self {
+            text: String::new(),
+            key,
+            escape: Escape::None,
+            high_surrogate: None,
+        }
+    }
+
+    /// Consumes one character; returns `true` at the closing quote.
+    fn push(&mut self, ch: char) -> bool {
+        match std::mem::replace(&mut self.escape, Escape::None) {
+            Escape::None => match ch {
+                '"' => return true,
+                '\\' => self.escape = Escape::Backslash,
+                other => self.push_char(other),
+            },
+            Escape::Backslash => match ch {
+                'u' => self.escape = Escape::Unicode(String::new()),
+                'n' => self.push_char('\n'),
+                't' => self.push_char('\t'),
+                'r' => self.push_char('\r'),
+                'b' => self.push_char('\u{8}'),
+                'f' => self.push_char('\u{c}'),
+                other => self.push_char(other),
+            },
+            Escape::Unicode(mut hex) => {
+                hex.push(ch);
+                if hex.chars().count() < 4 {
+                    self.escape = Escape::Unicode(hex);
+                } else {
+                    self.push_code(u32::from_str_radix(&hex, 16).ok());
+                }
+            }
+        }
+        false
+    }
+
+    fn push_char(&mut self, ch: char) {
+        self.high_surrogate = None;
+        self.text.push(ch);
+    }
+
+    /// Appends a `\u` code unit, combining a UTF-16 surrogate pair into one character.
+    /// A lone surrogate is dropped.
+    fn push_code(&mut self, code: Option<u32>) {
+        match (self.high_surrogate.take(), code) {
+            (_, Some(high @ 0xD800..=0xDBFF)) => self.high_surrogate = Some(high),
+            (Some(high), Some(low @ 0xDC00..=0xDFFF)) => self.text.extend(char::from_u32(
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00),
+            )),
+            (_, code) => self.text.extend(code.and_then(char::from_u32)),
+        }
+    }
+}
+
+/// A partial document snapshot, with a typed view once it deserializes as `T`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partial<T = Value> {
+    /// The document parsed so far.
+    pub snapshot: Value,
+    /// `snapshot` as `T`, when it already deserializes (e.g. `T` with optional fields).
+    pub value: Option<T>,
+}
+
+impl<T: DeserializeOwned> Partial<T> {
+    /// Wraps a snapshot, attempting the typed view.
+    pub fn new(snapshot: Value) -> Self {
+        let value = serde_json::from_value(snapshot.clone()).ok();
+        Self { snapshot, value }
+    }
+}
+
+/// Event of a [`PartialJsonStream`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum StructuredEvent<T = Value> {
+    /// A new, larger snapshot of the document.
+    Partial(Partial<T>),
+    /// The complete document, parsed strictly and validated against the schema.
+    Complete(T),
+}
+
+/// Streamed items that carry output text deltas.
+pub trait TextDelta {
+    /// The output text added by this item, if any.
+    fn text_delta(&self) -> Option<&str>;
+}
+
+impl TextDelta for ChatCompletionStreamResponse {
+    /// Content of the first choice.
+    fn text_delta(&self) -> Option<&str> {
+        self.choices
+            .iter()
+            .find(|choice| choice.index == 0)?
+            .delta
+            .content
+            .as_deref()
+    }
+}
+
+impl TextDelta for ResponsesStreamEvent {
+    /// `delta` of `response.output_text.delta` events.
+    fn text_delta(&self) -> Option<&str> {
+        if self.get("type")?.as_str()? == "response.output_text.delta" {
+            self.get("delta")?.as_str()
+        } else {
+            None
+        }
+    }
+}
+
+/// Buffers structured-output deltas and produces progressively completed snapshots.
+#[derive(Debug, Clone, Default)]
+pub struct PartialJsonParser {
+    buffer: String,
+    state: PartialState,
+    snapshot: Option<Value>,
+    schema: Option<Value>,
+}
+
+impl PartialJsonParser {
+    /// Creates a parser without schema validation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a parser that validates against a `json_schema` response format.
+    ///
+    /// Other formats are accepted without validation.
+    pub fn for_response_format(format: &ResponseFormat) -> Self {
+        match format {
+            ResponseFormat::JsonSchema { json_schema } => {
+                Self::new().with_schema(json_schema.get("schema").unwrap_or(json_schema).clone())
+            }
+            _ => Self::new(),
+        }
+    }
+
+    /// Validates the final document against `schema`.
+    pub fn with_schema(mut self, schema: Value) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Appends a delta and returns the new snapshot when it changed.
+    ///
+    /// Parsing is incremental, but each call builds a fresh snapshot of the whole document so
+    /// far. A stream of many small deltas therefore costs time quadratic in the document size.
+    pub fn push(&mut self, delta: &str) -> Option<Value> {
+        self.buffer.push_str(delta);
+        self.state.feed(delta);
+        let snapshot = self.state.snapshot()?;
+        if self.snapshot.as_ref() == Some(&snapshot) {
+            return None;
+        }
+        self.snapshot = Some(snapshot.clone());
+        Some(snapshot)
+    }
+
+    /// The text received so far.
+    pub fn buffer(&self) -> &str {
+        &self.buffer
+    }
+
+    /// The latest snapshot.
+    pub fn snapshot(&self) -> Option<&Value> {
+        self.snapshot.as_ref()
+    }
+
+    /// Parses the complete document strictly and validates it against the schema.
+    pub fn finish(&self) -> Result<Value> {
+        let value: Value =
+            serde_json::from_str(self.buffer.trim()).map_err(|err| RainyError::Serialization {
+                message: "structured output is not valid JSON".to_string(),
+                source_error: Some(err.to_string()),
+            })?;
+        if let Some(schema) = &self.schema {
+            let violations = validate_json_schema(schema, &value);
+            if !violations.is_empty() {
+                let violations: Vec<String> = violations.iter().map(ToString::to_string).collect();
+                return Err(RainyError::Serialization {
+                    message: format!(
+                        "structured output does not match schema: {}",
+                        violations.join("; ")
+                    ),
+                    source_error: None,
+                });
+            }
+        }
+        Ok(value)
+    }
+
+    /// Parses and validates the complete document as `T`.
+    pub fn finish_as<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_value(self.finish()?)?)
+    }
+
+    /// Wraps a chat or Responses stream, yielding snapshots and the final value as `T`.
+    pub fn stream<T, S>(self, inner: S) -> PartialJsonStream<S, T>
+    where
+        S: Stream + Unpin,
+    {
+        PartialJsonStream {
+            inner,
+            parser: self,
+            pending: VecDeque::new(),
+            finished: false,
+            completed: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Stream of [`StructuredEvent`]s built by [`PartialJsonParser::stream`].
+pub struct PartialJsonStream<S, T = Value> {
+    inner: S,
+    parser: PartialJsonParser,
+    pending: VecDeque<Value>,
+    finished: bool,
+    completed: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<S, T> PartialJsonStream<S, T> {
+    /// The parser state, e.g. to read the raw [`buffer`](PartialJsonParser::buffer).
+    pub fn parser(&self) -> &PartialJsonParser {
+        &self.parser
+    }
+}
+
+impl<S, I, T> Stream for PartialJsonStream<S, T>
+where
+    S: Stream<Item = Result<I>> + Unpin,
+    I: TextDelta,
+    T: DeserializeOwned,
+{
+    type Item = Result<StructuredEvent<T>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(snapshot) = this.pending.pop_front() {
+                return Poll::Ready(Some(Ok(StructuredEvent::Partial(Partial::new(snapshot)))));
+            }
+            if this.completed {
+                return Poll::Ready(None);
+            }
+            if this.finished {
+                this.completed = true;
+                return Poll::Ready(Some(this.parser.finish_as().map(StructuredEvent::Complete)));
+            }
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(item))) => {
+                    if let Some(delta) = item.text_delta() {
+                        this.pending.extend(this.parser.push(delta));
+                    }
+                }
+                Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error))),
+                Poll::Ready(None) => this.finished = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
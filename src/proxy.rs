@@ -0,0 +1,552 @@
+//! OpenAI-compatible local HTTP proxy backed by [`RainyClient`].
+//!
+//! [`RainyProxy`] serves `POST /v1/chat/completions`, `POST /v1/responses` and
+//! `GET /v1/models` so tools that only speak the OpenAI HTTP API can use Rainy unchanged.
+//! Requests go through the proxy's own client, so its API key, [`Budget`](crate::Budget),
+//! ledger and retry settings apply; the caller's `Authorization` header is only checked
+//! against an optional local token and never forwarded. Streaming requests are relayed as
+//! server-sent events; chat streams always ask upstream for usage so their spend reaches the
+//! budget and ledger. Every request is reported as a [`ProxyLogEntry`] with its
+//! [`RequestMetadata`]; streamed requests are reported when the stream ends.
+//!
+//! ```rust,no_run
+//! # use rainy_sdk::{Budget, RainyClient, RainyProxy};
+//! # async fn example() -> rainy_sdk::Result<()> {
+//! let client = RainyClient::with_api_key("ra-...")?
+//!     .with_budget(Budget::new().with_per_user_limit(50.0));
+//!
+//! RainyProxy::new(client)
+//!     .with_access_token("local-secret")
+//!     .on_request(|entry| eprintln!("{} {} -> {}", entry.method, entry.path, entry.status))
+//!     .listen("127.0.0.1:8787")
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::RainyClient;
+use crate::error::{RainyError, Result};
+use crate::metering::StreamFinish;
+use crate::models::{OpenAIChatCompletionRequest, RequestMetadata, ResponsesRequest};
+use futures::StreamExt;
+use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited, StreamBody};
+use hyper::body::{Bytes, Frame, Incoming};
+use hyper::header::{HeaderValue, AUTHORIZATION, CACHE_CONTROL, CONTENT_TYPE, RETRY_AFTER};
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use secrecy::{ExposeSecret, SecretString};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use std::convert::Infallible;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::net::{TcpListener, ToSocketAddrs};
+
+/// Address the proxy listens on by default.
+pub const DEFAULT_PROXY_ADDR: &str = "127.0.0.1:8787";
+/// Largest request body the proxy accepts.
+pub const MAX_PROXY_BODY_BYTES: usize = 32 * 1024 * 1024;
+
+type ProxyBody = UnsyncBoxBody<Bytes, Infallible>;
+type ProxyLogger = Arc<dyn Fn(&ProxyLogEntry) + Send + Sync>;
+/// Entry of a streamed request, logged by the stream's finish callback.
+type PendingLog = Arc<Mutex<Option<ProxyLogEntry>>>;
+
+/// One request handled by the proxy.
+#[derive(Debug, Clone)]
+pub struct ProxyLogEntry {
+    /// HTTP method.
+    pub method: String,
+    /// Request path.
+    pub path: String,
+    /// Requested model, when the body named one.
+    pub model: Option<String>,
+    /// Whether the response was streamed.
+    pub stream: bool,
+    /// HTTP status returned to the caller.
+    pub status: u16,
+    /// Time until the response headers were sent, in milliseconds.
+    pub duration_ms: u64,
+    /// Upstream metadata; `None` for failed calls.
+    pub metadata: Option<RequestMetadata>,
+    /// Error code, for failed requests.
+    pub error_code: Option<String>,
+}
+
+/// Serves the OpenAI HTTP API on a local address using a [`RainyClient`].
+#[derive(Clone)]
+pub struct RainyProxy {
+    client: Arc<RainyClient>,
+    access_token: Option<Arc<SecretString>>,
+    logger: Option<ProxyLogger>,
+}
+
+impl std::fmt::Debug for RainyProxy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RainyProxy")
+            .field("base_url", &self.client.base_url())
+            .field("access_token", &self.access_token.as_ref().map(|_| "***"))
+            .field("logger", &self.logger.is_some())
+            .finish()
+    }
+}
+
+impl RainyProxy {
+    /// Creates a proxy that forwards requests with `client`.
+    pub fn new(client: impl Into<Arc<RainyClient>>) -> Self {
+        Self {
+            client: client.into(),
+            access_token: None,
+            logger: None,
+        }
+    }
+
+    /// Requires callers to send `Authorization: Bearer <token>`.
+    ///
+    /// Without a token any local process may use the proxy's API key.
+    pub fn with_access_token(mut self, token: impl Into<String>) -> Self {
+        self.access_token = Some(Arc::new(SecretString::from(token.into())));
+        self
+    }
+
+    /// Calls `callback` after every request.
+    ///
+    /// Streamed requests are reported once the stream has ended, with its final metadata.
+    pub fn on_request<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&ProxyLogEntry) + Send + Sync + 'static,
+    {
+        self.logger = Some(Arc::new(callback));
+        self
+    }
+
+    /// The client requests are forwarded with.
+    pub fn client(&self) -> &RainyClient {
+        &self.client
+    }
+
+    /// Binds `addr` and serves until the process exits.
+    pub async fn listen(self, addr: impl ToSocketAddrs) -> Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        self.serve(listener).await
+    }
+
+    /// Serves connections accepted on `listener` until the process exits.
+    pub async fn serve(self, listener: TcpListener) -> Result<()> {
+        self.serve_with_shutdown(listener, std::future::pending())
+            .await
+    }
+
+    /// Serves connections accepted on `listener` until `shutdown` completes.
+    ///
+    /// Connections already accepted are allowed to finish.
+    pub async fn serve_with_shutdown(
+        self,
+        listener: TcpListener,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<()> {
+        tokio::pin!(shutdown);
+        loop {
+            let (stream, _) = tokio::select! {
+                accepted = listener.accept() => accepted?,
+                () = &mut shutdown => return Ok(()),
+            };
+            let proxy = self.clone();
+            tokio::spawn(async move {
+                let service = hyper::service::service_fn(move |request| {
+                    let proxy = proxy.clone();
+                    async move { Ok::<_, Infallible>(proxy.handle(request).await) }
+                });
+                let _ = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    }
+
+    async fn handle(&self, request: Request<Incoming>) -> Response<ProxyBody> {
+        let start = Instant::now();
+        let mut entry = ProxyLogEntry {
+            method: request.method().to_string(),
+            path: request.uri().path().to_string(),
+            model: None,
+            stream: false,
+            status: 0,
+            duration_ms: 0,
+            metadata: None,
+            error_code: None,
+        };
+        let pending = PendingLog::default();
+        let response = match self.route(request, &mut entry, &pending).await {
+            Ok(response) => response,
+            Err(err) => {
+                entry.error_code = err.code().map(str::to_string);
+                error_response(&err)
+            }
+        };
+        entry.status = response.status().as_u16();
+        entry.duration_ms = start.elapsed().as_millis() as u64;
+        if entry.stream && entry.error_code.is_none() {
+            *pending.lock().unwrap_or_else(|e| e.into_inner()) = Some(entry);
+        } else {
+            self.log(&entry);
+        }
+        response
+    }
+
+    async fn route(
+        &self,
+        request: Request<Incoming>,
+        entry: &mut ProxyLogEntry,
+        pending: &PendingLog,
+    ) -> Result<Response<ProxyBody>> {
+        self.authorize(&request)?;
+        let path = request.uri().path().trim_end_matches('/').to_string();
+        match (request.method(), path.as_str()) {
+            (&Method::GET, "/v1/models") => self.models().await,
+            (&Method::POST, "/v1/chat/completions") => {
+                let (request, extra) = split_chat_request(read_json(request).await?)?;
+                self.chat_completions(request, extra, entry, pending).await
+            }
+            (&Method::POST, "/v1/responses") => {
+                let request = read_json(request).await?;
+                self.responses(request, entry, pending).await
+            }
+            (_, "/v1/models" | "/v1/chat/completions" | "/v1/responses") => Err(http_error(
+                StatusCode::METHOD_NOT_ALLOWED,
+                format!("{} is not supported on {path}", request.method()),
+            )),
+            _ => Err(http_error(
+                StatusCode::NOT_FOUND,
+                format!("unknown endpoint {path}"),
+            )),
+        }
+    }
+
+    fn authorize(&self, request: &Request<Incoming>) -> Result<()> {
+        let Some(expected) = &self.access_token else {
+            return Ok(());
+        };
+        let presented = request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if presented == Some(expected.expose_secret()) {
+            Ok(())
+        } else {
+            Err(RainyError::Authentication {
+                code: "INVALID_PROXY_TOKEN".to_string(),
+                message: "missing or invalid proxy access token".to_string(),
+                retryable: false,
+            })
+        }
+    }
+
+    async fn models(&self) -> Result<Response<ProxyBody>> {
+        let models = self.client.get_available_models().await?;
+        let mut data: Vec<Value> = models
+            .providers
+            .iter()
+            .flat_map(|(provider, ids)| {
+                ids.iter().map(move |id| {
+                    json!({ "id": id, "object": "model", "created": 0, "owned_by": provider })
+                })
+            })
+            .collect();
+        data.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+        Ok(json_response(
+            StatusCode::OK,
+            &json!({ "object": "list", "data": data }),
+        ))
+    }
+
+    async fn chat_completions(
+        &self,
+        request: OpenAIChatCompletionRequest,
+        mut extra: Map<String, Value>,
+        entry: &mut ProxyLogEntry,
+        pending: &PendingLog,
+    ) -> Result<Response<ProxyBody>> {
+        entry.model = Some(request.model.clone());
+        if request.stream == Some(true) {
+            entry.stream = true;
+            let mut options = match extra.remove("stream_options") {
+                Some(Value::Object(options)) => options,
+                _ => Map::new(),
+            };
+            let wants_usage = options.get("include_usage") == Some(&Value::Bool(true));
+            options.insert("include_usage".to_string(), Value::Bool(true));
+            extra.insert("stream_options".to_string(), Value::Object(options));
+            let stream = self
+                .client
+                .openai_chat_completion_stream_with(
+                    request,
+                    extra,
+                    Some(self.finish_stream(pending)),
+                )
+                .await?;
+            // Usage was only requested for metering; hide it from callers that did not ask.
+            let events = stream.filter_map(move |chunk| {
+                futures::future::ready(match chunk {
+                    Ok(chunk)
+                        if !wants_usage && chunk.choices.is_empty() && chunk.usage.is_some() =>
+                    {
+                        None
+                    }
+                    Ok(mut chunk) => {
+                        if !wants_usage {
+                            chunk.usage = None;
+                        }
+                        Some(Ok(sse_event(None, &json!(chunk))))
+                    }
+                    Err(err) => Some(Err(sse_event(None, &openai_error_body(&err).1))),
+                })
+            });
+            return Ok(sse_response(events, Some("data: [DONE]\n\n")));
+        }
+
+        let (response, metadata) = self
+            .client
+            .openai_chat_completion_with_extra(request, extra)
+            .await?;
+        let mut http = json_response(StatusCode::OK, &json!(response));
+        add_metadata_headers(&mut http, &metadata);
+        entry.metadata = Some(metadata);
+        Ok(http)
+    }
+
+    async fn responses(
+        &self,
+        request: ResponsesRequest,
+        entry: &mut ProxyLogEntry,
+        pending: &PendingLog,
+    ) -> Result<Response<ProxyBody>> {
+        entry.model = Some(request.model.clone());
+        if request.stream == Some(true) {
+            entry.stream = true;
+            let stream = self
+                .client
+                .response_stream_with(request, Some(self.finish_stream(pending)))
+                .await?;
+            let events = stream.map(|event| match event {
+                Ok(event) => Ok(sse_event(event.get("type").and_then(Value::as_str), &event)),
+                Err(err) => Err(sse_event(Some("error"), &openai_error_body(&err).1)),
+            });
+            return Ok(sse_response(events, None));
+        }
+
+        let (response, metadata) = self.client.create_response(request).await?;
+        let mut http = json_response(StatusCode::OK, &json!(response));
+        add_metadata_headers(&mut http, &metadata);
+        entry.metadata = Some(metadata);
+        Ok(http)
+    }
+
+    /// Logs the pending entry of a stream with the stream's final metadata.
+    fn finish_stream(&self, pending: &PendingLog) -> StreamFinish {
+        let proxy = self.clone();
+        let pending = Arc::clone(pending);
+        Box::new(move |metadata| {
+            let entry = pending.lock().unwrap_or_else(|e| e.into_inner()).take();
+            if let Some(mut entry) = entry {
+                entry.metadata = Some(metadata.clone());
+                proxy.log(&entry);
+            }
+        })
+    }
+
+    fn log(&self, entry: &ProxyLogEntry) {
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            method = %entry.method,
+            path = %entry.path,
+            model = entry.model.as_deref().unwrap_or("-"),
+            stream = entry.stream,
+            status = entry.status,
+            duration_ms = entry.duration_ms,
+            provider = entry.metadata.as_ref().and_then(|m| m.provider.as_deref()).unwrap_or("-"),
+            request_id = entry.metadata.as_ref().and_then(|m| m.request_id.as_deref()).unwrap_or("-"),
+            credits_used = entry.metadata.as_ref().and_then(|m| m.credits_used),
+            "proxied request"
+        );
+        if let Some(logger) = &self.logger {
+            logger(entry);
+        }
+    }
+}
+
+/// The HTTP status and OpenAI-style error body (`{"error": {"message", "type", "code"}}`)
+/// the proxy returns for `err`.
+///
+/// Budget and credit errors map to `429 insufficient_quota`, as OpenAI reports exhausted
+/// quota; upstream HTTP errors keep their status.
+pub fn openai_error_body(err: &RainyError) -> (u16, Value) {
+    let (status, kind) = match err {
+        RainyError::Authentication { .. } => (401, "authentication_error"),
+        RainyError::InvalidRequest { .. } | RainyError::ValidationError(_) => {
+            (400, "invalid_request_error")
+        }
+        RainyError::RateLimit { .. } => (429, "rate_limit_error"),
+        RainyError::InsufficientCredits { .. } | RainyError::BudgetExceeded { .. } => {
+            (429, "insufficient_quota")
+        }
+        RainyError::FeatureNotAvailable { .. } => (403, "permission_error"),
+        RainyError::Api { status_code, .. } => (
+            *status_code,
+            match status_code {
+                401 => "authentication_error",
+                403 => "permission_error",
+                429 => "rate_limit_error",
+                400..=499 => "invalid_request_error",
+                _ => "api_error",
+            },
+        ),
+        RainyError::Timeout { .. } => (504, "timeout_error"),
+        RainyError::Provider { .. }
+        | RainyError::Network { .. }
+        | RainyError::NetworkError(_)
+        | RainyError::Serialization { .. } => (502, "api_error"),
+        RainyError::Io { .. } => (500, "api_error"),
+    };
+    let body = json!({
+        "error": {
+            "message": err.to_string(),
+            "type": kind,
+            "param": null,
+            "code": err.code(),
+        }
+    });
+    (status, body)
+}
+
+fn http_error(status: StatusCode, message: String) -> RainyError {
+    RainyError::Api {
+        code: status
+            .canonical_reason()
+            .unwrap_or("ERROR")
+            .to_uppercase()
+            .replace(' ', "_"),
+        message,
+        status_code: status.as_u16(),
+        retryable: false,
+        request_id: None,
+    }
+}
+
+async fn read_json<T: DeserializeOwned>(request: Request<Incoming>) -> Result<T> {
+    let body = Limited::new(request.into_body(), MAX_PROXY_BODY_BYTES)
+        .collect()
+        .await
+        .map_err(|err| {
+            if err.downcast_ref::<LengthLimitError>().is_some() {
+                http_error(
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    format!("request body exceeds {MAX_PROXY_BODY_BYTES} bytes"),
+                )
+            } else {
+                RainyError::Network {
+                    message: format!("failed to read request body: {err}"),
+                    retryable: false,
+                    source_error: None,
+                }
+            }
+        })?
+        .to_bytes();
+    serde_json::from_slice(&body).map_err(|err| RainyError::InvalidRequest {
+        code: "INVALID_REQUEST_BODY".to_string(),
+        message: format!("request body is not a valid request: {err}"),
+        details: None,
+    })
+}
+
+/// Splits a chat body into the typed request and the fields it does not model, which are
+/// forwarded unchanged.
+fn split_chat_request(body: Value) -> Result<(OpenAIChatCompletionRequest, Map<String, Value>)> {
+    let request: OpenAIChatCompletionRequest =
+        serde_json::from_value(body.clone()).map_err(|err| RainyError::InvalidRequest {
+            code: "INVALID_REQUEST_BODY".to_string(),
+            message: format!("request body is not a valid request: {err}"),
+            details: None,
+        })?;
+    let modelled = serde_json::to_value(&request)?;
+    let extra = match body {
+        Value::Object(fields) => fields
+            .into_iter()
+            .filter(|(key, _)| modelled.get(key).is_none())
+            .collect(),
+        _ => Map::new(),
+    };
+    Ok((request, extra))
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response<ProxyBody> {
+    let mut response = Response::new(Full::new(Bytes::from(body.to_string())).boxed_unsync());
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+fn error_response(err: &RainyError) -> Response<ProxyBody> {
+    let (status, body) = openai_error_body(err);
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    let mut response = json_response(status, &body);
+    if let Some(seconds) = err.retry_after() {
+        response.headers_mut().insert(RETRY_AFTER, seconds.into());
+    }
+    response
+}
+
+fn add_metadata_headers(response: &mut Response<ProxyBody>, metadata: &RequestMetadata) {
+    let headers = response.headers_mut();
+    let values = [
+        ("x-request-id", metadata.request_id.clone()),
+        ("x-rainy-provider", metadata.provider.clone()),
+        (
+            "x-rainy-credits-used",
+            metadata.credits_used.map(|credits| credits.to_string()),
+        ),
+    ];
+    for (name, value) in values {
+        if let Some(value) = value.and_then(|value| HeaderValue::from_str(&value).ok()) {
+            headers.insert(name, value);
+        }
+    }
+}
+
+fn sse_event(event: Option<&str>, data: &Value) -> String {
+    match event {
+        Some(event) => format!("event: {event}\ndata: {data}\n\n"),
+        None => format!("data: {data}\n\n"),
+    }
+}
+
+/// Relays `events` as SSE; an `Err` frame is sent and ends the stream.
+fn sse_response<S>(events: S, done: Option<&'static str>) -> Response<ProxyBody>
+where
+    S: futures::Stream<Item = std::result::Result<String, String>> + Send + 'static,
+{
+    let frames = events
+        .scan(false, |failed, event| {
+            let frame = match event {
+                _ if *failed => None,
+                Ok(frame) => Some(frame),
+                Err(frame) => {
+                    *failed = true;
+                    Some(frame)
+                }
+            };
+            futures::future::ready(frame)
+        })
+        .chain(futures::stream::iter(done.map(str::to_string)))
+        .map(|frame| Ok::<_, Infallible>(Frame::data(Bytes::from(frame))));
+    let mut response = Response::new(StreamBody::new(frames).boxed_unsync());
+    let headers = response.headers_mut();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    response
+}
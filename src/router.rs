@@ -0,0 +1,362 @@
+//! Request-time model routing by criteria.
+//!
+//! A [`ModelRouter`] replaces the fixed model id of a request with one resolved from the
+//! model catalog. The router reads the client's cached `/models/catalog`, filters it with a
+//! [`ModelSpec`] and with what the request actually needs ([`RequestNeeds`]: image, audio
+//! and file inputs, tools, `response_format`), and picks a candidate with a
+//! [`RoutingStrategy`].
+
+use crate::client::RainyClient;
+use crate::error::{RainyError, Result};
+use crate::fallback::FallbackRequest;
+use crate::models::{
+    catalog_item_supports, select_models, ChatCompletionRequest, ChatCompletionResponse,
+    ModelCatalogItem, ModelSelectionCriteria, OpenAIChatCompletionRequest,
+    OpenAIChatCompletionResponse, RequestMetadata, ResponsesApiResponse, ResponsesRequest,
+};
+use crate::multimodal::{accepted_modalities, Modality, MultimodalRequest};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// How the model for a routed request is chosen.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ModelSpec {
+    /// Always use this model id.
+    Fixed(String),
+    /// Resolve a model from the catalog using these criteria.
+    Criteria(Box<ModelSelectionCriteria>),
+}
+
+impl ModelSpec {
+    /// Creates a spec that resolves the model from the catalog using `criteria`.
+    pub fn criteria(criteria: ModelSelectionCriteria) -> Self {
+        ModelSpec::Criteria(Box::new(criteria))
+    }
+}
+
+/// Strategy used to pick one model among the catalog entries that match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingStrategy {
+    /// Lowest prompt price, then completion price, then largest context.
+    Cheapest,
+    /// Largest `context_length`.
+    LargestContext,
+    /// Lowest average latency observed by this router. Models without observations are
+    /// tried first (cheapest first) so every candidate gets measured.
+    LowestLatency,
+    /// Rotate through the matching models on each call.
+    RoundRobin,
+}
+
+/// Capabilities a request needs from the model that serves it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestNeeds {
+    /// The request contains image inputs.
+    pub images: bool,
+    /// The request contains audio inputs.
+    pub audio: bool,
+    /// The request contains file inputs.
+    pub files: bool,
+    /// The request declares tools.
+    pub tools: bool,
+    /// The request sets `response_format`.
+    pub response_format: bool,
+}
+
+impl RequestNeeds {
+    /// Needs of a request whose input uses `modalities`; tools and `response_format` are
+    /// left unset.
+    pub fn from_input_modalities(modalities: &[Modality]) -> Self {
+        Self {
+            images: modalities.contains(&Modality::Image),
+            audio: modalities.contains(&Modality::Audio),
+            files: modalities.contains(&Modality::File),
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` when the catalog entry advertises every needed capability.
+    pub fn satisfied_by(&self, item: &ModelCatalogItem) -> bool {
+        let inputs = [
+            (self.images, Modality::Image),
+            (self.audio, Modality::Audio),
+            (self.files, Modality::File),
+        ];
+        if inputs
+            .into_iter()
+            .any(|(needed, modality)| needed && !accepts_input_modality(item, modality))
+        {
+            return false;
+        }
+        if self.tools && !catalog_item_supports(item, "tools") {
+            return false;
+        }
+        if self.response_format
+            && !catalog_item_supports(item, "response_format")
+            && !catalog_item_supports(item, "structured_outputs")
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// Unlike the send-path check, a model that lists no input modalities is not routed media.
+fn accepts_input_modality(item: &ModelCatalogItem, modality: Modality) -> bool {
+    accepted_modalities(item).is_some_and(|inputs| {
+        inputs
+            .iter()
+            .any(|m| m.eq_ignore_ascii_case(modality.as_str()))
+    })
+}
+
+/// Requests that can be routed to a model chosen at request time.
+pub trait RoutableRequest: FallbackRequest + MultimodalRequest {
+    /// Returns the capabilities this request needs from the serving model.
+    fn needs(&self) -> RequestNeeds;
+}
+
+impl RoutableRequest for ChatCompletionRequest {
+    fn needs(&self) -> RequestNeeds {
+        RequestNeeds {
+            tools: self.tools.as_ref().is_some_and(|tools| !tools.is_empty()),
+            response_format: self.response_format.is_some(),
+            ..RequestNeeds::from_input_modalities(&self.input_modalities())
+        }
+    }
+}
+
+impl RoutableRequest for OpenAIChatCompletionRequest {
+    fn needs(&self) -> RequestNeeds {
+        RequestNeeds {
+            tools: self.tools.as_ref().is_some_and(|tools| !tools.is_empty()),
+            response_format: self.response_format.is_some(),
+            ..RequestNeeds::from_input_modalities(&self.input_modalities())
+        }
+    }
+}
+
+impl RoutableRequest for ResponsesRequest {
+    fn needs(&self) -> RequestNeeds {
+        RequestNeeds {
+            tools: self.tools.as_ref().is_some_and(|tools| !tools.is_empty()),
+            response_format: self.response_format.is_some(),
+            ..RequestNeeds::from_input_modalities(&self.input_modalities())
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct LatencyStats {
+    total_ms: u64,
+    samples: u64,
+}
+
+/// Routes requests to a model resolved from the catalog at request time.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use rainy_sdk::{RainyClient, ModelSelectionCriteria, OpenAIChatCompletionRequest, OpenAIChatMessage};
+/// # use rainy_sdk::router::{ModelRouter, ModelSpec, RoutingStrategy};
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = RainyClient::with_api_key("user-api-key")?;
+/// let router = ModelRouter::new(
+///     ModelSpec::criteria(ModelSelectionCriteria {
+///         require_tools: Some(true),
+///         ..Default::default()
+///     }),
+///     RoutingStrategy::Cheapest,
+/// );
+///
+/// // The model id is replaced by the routed one.
+/// let request = OpenAIChatCompletionRequest::new("auto", vec![OpenAIChatMessage::user("Hi")]);
+/// let (response, metadata) = router.openai_chat_completion(&client, request).await?;
+/// println!("{:?} answered: {:?}", metadata.served_model, response.choices.first());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ModelRouter {
+    spec: ModelSpec,
+    strategy: RoutingStrategy,
+    catalog: Option<Arc<Vec<ModelCatalogItem>>>,
+    latencies: Mutex<HashMap<String, LatencyStats>>,
+    cursor: AtomicUsize,
+}
+
+impl ModelRouter {
+    /// Creates a router for the given spec and strategy.
+    pub fn new(spec: ModelSpec, strategy: RoutingStrategy) -> Self {
+        Self {
+            spec,
+            strategy,
+            catalog: None,
+            latencies: Mutex::new(HashMap::new()),
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    /// Routes against `catalog` instead of the client's cached catalog.
+    pub fn with_catalog(mut self, catalog: Vec<ModelCatalogItem>) -> Self {
+        self.catalog = Some(Arc::new(catalog));
+        self
+    }
+
+    /// Returns the model spec.
+    pub fn spec(&self) -> &ModelSpec {
+        &self.spec
+    }
+
+    /// Returns the routing strategy.
+    pub fn strategy(&self) -> RoutingStrategy {
+        self.strategy
+    }
+
+    /// Records an observed latency for a model (used by `LowestLatency`).
+    pub fn record_latency(&self, model: &str, latency_ms: u64) {
+        let mut latencies = self.latencies.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = latencies.entry(model.to_string()).or_default();
+        stats.total_ms += latency_ms;
+        stats.samples += 1;
+    }
+
+    /// Returns the average observed latency for a model, if any.
+    pub fn average_latency_ms(&self, model: &str) -> Option<f64> {
+        self.latencies
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(model)
+            .filter(|stats| stats.samples > 0)
+            .map(|stats| stats.total_ms as f64 / stats.samples as f64)
+    }
+
+    async fn catalog(&self, client: &RainyClient) -> Result<Arc<Vec<ModelCatalogItem>>> {
+        match &self.catalog {
+            Some(catalog) => Ok(catalog.clone()),
+            None => client.cached_models_catalog().await,
+        }
+    }
+
+    /// Picks a model from `catalog` for a request with the given needs.
+    ///
+    /// For `ModelSpec::Fixed` the fixed id is returned without consulting the catalog.
+    pub fn select(&self, catalog: &[ModelCatalogItem], needs: RequestNeeds) -> Result<String> {
+        let criteria = match &self.spec {
+            ModelSpec::Fixed(model) => return Ok(model.clone()),
+            ModelSpec::Criteria(criteria) => criteria,
+        };
+
+        let candidates: Vec<ModelCatalogItem> = select_models(catalog, criteria)
+            .into_iter()
+            .filter(|item| needs.satisfied_by(item))
+            .collect();
+        if candidates.is_empty() {
+            return Err(RainyError::InvalidRequest {
+                code: "NO_MATCHING_MODEL".to_string(),
+                message: "No catalog model matches the routing criteria and request needs"
+                    .to_string(),
+                details: serde_json::to_value(needs).ok(),
+            });
+        }
+
+        let chosen = match self.strategy {
+            RoutingStrategy::Cheapest => &candidates[0],
+            RoutingStrategy::LargestContext => candidates
+                .iter()
+                .max_by_key(|item| item.context_length.unwrap_or_default())
+                .unwrap_or(&candidates[0]),
+            RoutingStrategy::LowestLatency => {
+                let latencies = self.latencies.lock().unwrap_or_else(|e| e.into_inner());
+                let average = |item: &ModelCatalogItem| {
+                    latencies
+                        .get(&item.id)
+                        .filter(|stats| stats.samples > 0)
+                        .map(|stats| stats.total_ms as f64 / stats.samples as f64)
+                        .unwrap_or(0.0)
+                };
+                candidates
+                    .iter()
+                    .min_by(|a, b| {
+                        average(a)
+                            .partial_cmp(&average(b))
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    })
+                    .unwrap_or(&candidates[0])
+            }
+            RoutingStrategy::RoundRobin => {
+                let index = self.cursor.fetch_add(1, Ordering::Relaxed) % candidates.len();
+                &candidates[index]
+            }
+        };
+        Ok(chosen.id.clone())
+    }
+
+    /// Resolves the model for `request` against the cached catalog and re-targets it.
+    ///
+    /// A `ModelSpec::Fixed` model is set as is; a model chosen by criteria also gets the
+    /// request's reasoning and thinking settings adapted to it. Returns the chosen model id.
+    pub async fn route<R: RoutableRequest>(
+        &self,
+        client: &RainyClient,
+        request: &mut R,
+    ) -> Result<String> {
+        if let ModelSpec::Fixed(model) = &self.spec {
+            // A pinned model is the caller's choice; keep its other parameters as they are.
+            request.set_model(model);
+            return Ok(model.clone());
+        }
+        let catalog = self.catalog(client).await?;
+        let model = self.select(&catalog, request.needs())?;
+        let item = catalog.iter().find(|item| item.id == model);
+        request.adapt_to_model(&model, item);
+        Ok(model)
+    }
+
+    fn observe(&self, model: String, metadata: &mut RequestMetadata) {
+        if let Some(latency) = metadata.response_time {
+            self.record_latency(&model, latency);
+        }
+        metadata.served_model = Some(model);
+    }
+
+    /// Routes and sends a chat completion.
+    pub async fn chat_completion(
+        &self,
+        client: &RainyClient,
+        mut request: ChatCompletionRequest,
+    ) -> Result<(ChatCompletionResponse, RequestMetadata)> {
+        let model = self.route(client, &mut request).await?;
+        let (response, mut metadata) = client.chat_completion(request).await?;
+        self.observe(model, &mut metadata);
+        Ok((response, metadata))
+    }
+
+    /// Routes and sends an OpenAI-compatible chat completion.
+    pub async fn openai_chat_completion(
+        &self,
+        client: &RainyClient,
+        mut request: OpenAIChatCompletionRequest,
+    ) -> Result<(OpenAIChatCompletionResponse, RequestMetadata)> {
+        let model = self.route(client, &mut request).await?;
+        let (response, mut metadata) = client.openai_chat_completion_with_metadata(request).await?;
+        self.observe(model, &mut metadata);
+        Ok((response, metadata))
+    }
+
+    /// Routes and sends a Responses API request.
+    pub async fn create_response(
+        &self,
+        client: &RainyClient,
+        mut request: ResponsesRequest,
+    ) -> Result<(ResponsesApiResponse, RequestMetadata)> {
+        let model = self.route(client, &mut request).await?;
+        let (response, mut metadata) = client.create_response(request).await?;
+        self.observe(model, &mut metadata);
+        Ok((response, metadata))
+    }
+}
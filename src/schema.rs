@@ -0,0 +1,261 @@
+//! Lightweight JSON Schema validation for tool arguments and structured outputs.
+//!
+//! Covers the subset of JSON Schema that function-calling schemas use in practice: `type`,
+//! `enum`, `const`, `properties`, `required`, `additionalProperties`, `items`,
+//! `minItems`/`maxItems`, `minLength`/`maxLength`, numeric bounds, `allOf`/`anyOf`/`oneOf`,
+//! `not` and local `$ref`s (`#/$defs/...`). Other keywords, such as `pattern` and `format`,
+//! are ignored.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Maximum `$ref` / combinator nesting followed before validation gives up.
+const MAX_DEPTH: usize = 64;
+
+/// One way a value fails its schema.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// JSON Pointer to the offending value (`""` for the root, e.g. `/items/0/city`).
+    pub path: String,
+    /// What is wrong with the value.
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Validates `value` against `schema`, returning every violation found.
+///
+/// An empty result means the value conforms (within the supported keyword subset).
+pub fn validate_json_schema(schema: &Value, value: &Value) -> Vec<SchemaViolation> {
+    let mut validator = Validator {
+        root: schema,
+        violations: Vec::new(),
+    };
+    validator.check(schema, value, String::new(), 0);
+    validator.violations
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    violations: Vec<SchemaViolation>,
+}
+
+impl<'a> Validator<'a> {
+    fn fail(&mut self, path: &str, message: impl Into<String>) {
+        self.violations.push(SchemaViolation {
+            path: path.to_string(),
+            message: message.into(),
+        });
+    }
+
+    fn conforms(&self, schema: &'a Value, value: &Value, depth: usize) -> bool {
+        let mut nested = Validator {
+            root: self.root,
+            violations: Vec::new(),
+        };
+        nested.check(schema, value, String::new(), depth);
+        nested.violations.is_empty()
+    }
+
+    fn check(&mut self, schema: &'a Value, value: &Value, path: String, depth: usize) {
+        if depth > MAX_DEPTH {
+            self.fail(&path, "schema nesting too deep");
+            return;
+        }
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => return self.fail(&path, "no value is allowed here"),
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.root.pointer(pointer))
+            {
+                Some(target) => self.check(target, value, path.clone(), depth + 1),
+                None => self.fail(&path, format!("unresolvable $ref '{reference}'")),
+            }
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let allowed: Vec<&str> = match expected {
+                Value::String(kind) => vec![kind.as_str()],
+                Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !allowed.is_empty() && !allowed.iter().any(|kind| is_type(value, kind)) {
+                return self.fail(
+                    &path,
+                    format!(
+                        "expected {}, got {}",
+                        allowed.join(" or "),
+                        type_name(value)
+                    ),
+                );
+            }
+        }
+
+        if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+            if !options.contains(value) {
+                self.fail(
+                    &path,
+                    format!("{value} is not one of {}", Value::from(options.clone())),
+                );
+            }
+        }
+        if let Some(constant) = schema.get("const") {
+            if constant != value {
+                self.fail(&path, format!("expected {constant}"));
+            }
+        }
+
+        match value {
+            Value::Object(object) => {
+                let properties = schema.get("properties").and_then(Value::as_object);
+                for name in schema
+                    .get("required")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                {
+                    if !object.contains_key(name) {
+                        self.fail(&path, format!("missing required property '{name}'"));
+                    }
+                }
+                for (name, item) in object {
+                    let item_path = format!("{path}/{}", escape_pointer(name));
+                    match properties.and_then(|properties| properties.get(name)) {
+                        Some(property) => self.check(property, item, item_path, depth + 1),
+                        None => match schema.get("additionalProperties") {
+                            Some(Value::Bool(false)) => {
+                                self.fail(&path, format!("unexpected property '{name}'"))
+                            }
+                            Some(additional @ Value::Object(_)) => {
+                                self.check(additional, item, item_path, depth + 1)
+                            }
+                            _ => {}
+                        },
+                    }
+                }
+            }
+            Value::Array(items) => {
+                if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                    if (items.len() as u64) < min {
+                        self.fail(&path, format!("expected at least {min} items"));
+                    }
+                }
+                if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                    if items.len() as u64 > max {
+                        self.fail(&path, format!("expected at most {max} items"));
+                    }
+                }
+                if let Some(item_schema) = schema.get("items") {
+                    for (index, item) in items.iter().enumerate() {
+                        self.check(item_schema, item, format!("{path}/{index}"), depth + 1);
+                    }
+                }
+            }
+            Value::String(text) => {
+                let length = text.chars().count() as u64;
+                if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                    if length < min {
+                        self.fail(&path, format!("expected at least {min} characters"));
+                    }
+                }
+                if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                    if length > max {
+                        self.fail(&path, format!("expected at most {max} characters"));
+                    }
+                }
+            }
+            Value::Number(number) => {
+                let number = number.as_f64().unwrap_or_default();
+                let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+                if let Some(min) = bound("minimum").filter(|min| number < *min) {
+                    self.fail(&path, format!("must be >= {min}"));
+                }
+                if let Some(max) = bound("maximum").filter(|max| number > *max) {
+                    self.fail(&path, format!("must be <= {max}"));
+                }
+                if let Some(min) = bound("exclusiveMinimum").filter(|min| number <= *min) {
+                    self.fail(&path, format!("must be > {min}"));
+                }
+                if let Some(max) = bound("exclusiveMaximum").filter(|max| number >= *max) {
+                    self.fail(&path, format!("must be < {max}"));
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            for sub in all {
+                self.check(sub, value, path.clone(), depth + 1);
+            }
+        }
+        if let Some(any) = schema.get("anyOf").and_then(Value::as_array) {
+            if !any.iter().any(|sub| self.conforms(sub, value, depth + 1)) {
+                self.fail(&path, "does not match any allowed schema (anyOf)");
+            }
+        }
+        if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+            let matches = one
+                .iter()
+                .filter(|sub| self.conforms(sub, value, depth + 1))
+                .count();
+            if matches != 1 {
+                self.fail(
+                    &path,
+                    format!("must match exactly one schema (oneOf), matched {matches}"),
+                );
+            }
+        }
+        if let Some(not) = schema.get("not") {
+            if self.conforms(not, value, depth + 1) {
+                self.fail(&path, "matches a disallowed schema (not)");
+            }
+        }
+    }
+}
+
+fn is_type(value: &Value, kind: &str) -> bool {
+    match kind {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64()
+                || value.is_u64()
+                || value.as_f64().is_some_and(|number| number.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn escape_pointer(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
@@ -0,0 +1,263 @@
+//! Gemini 3 thought-signature capture, replay and validation.
+//!
+//! Gemini 3 returns an encrypted thought signature with function calls and rejects the next
+//! request if the signature is not sent back with the same call. On the OpenAI-compatible
+//! surface the signature travels in `tool_calls[].extra_content.google.thought_signature`.
+//!
+//! [`RainyClient`] keeps a [`ThoughtSignatureCache`] keyed by tool call ID within a
+//! [`SignatureScope`], a fingerprint of the conversation up to the assistant message that
+//! made the call. Providers reuse short IDs such as `call_1` across conversations, so the
+//! scope keeps one conversation's signature from being replayed into another. Signatures are
+//! captured from `create_openai_chat_completion` responses and stream chunks, re-attached to
+//! replayed tool calls that lost them, and validated before a Gemini 3 request is sent.
+
+use crate::client::RainyClient;
+use crate::error::{RainyError, Result};
+use crate::models::{
+    ChatCompletionStreamResponse, OpenAIChatCompletionRequest, OpenAIChatCompletionResponse,
+    OpenAIChatMessage, OpenAIMessageRole,
+};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+/// Default number of signatures kept by a [`ThoughtSignatureCache`].
+pub const DEFAULT_SIGNATURE_CAPACITY: usize = 4096;
+
+/// Conversation a tool call belongs to: a fingerprint of the messages before the assistant
+/// message that made the call.
+///
+/// Thought signatures and other `extra_content` are left out of the fingerprint, so a
+/// history hashes the same whether or not its signatures were stripped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SignatureScope(u64);
+
+impl SignatureScope {
+    /// Scope of an assistant message that follows `history`.
+    pub fn of(history: &[OpenAIChatMessage]) -> Self {
+        let mut hasher = DefaultHasher::new();
+        for message in history {
+            hash_message(message, &mut hasher);
+        }
+        Self(hasher.finish())
+    }
+}
+
+fn hash_message(message: &OpenAIChatMessage, hasher: &mut DefaultHasher) {
+    let mut value = serde_json::to_value(message).unwrap_or_default();
+    if let Some(calls) = value
+        .get_mut("tool_calls")
+        .and_then(|calls| calls.as_array_mut())
+    {
+        for call in calls.iter_mut().filter_map(|call| call.as_object_mut()) {
+            call.remove("extra_content");
+        }
+    }
+    value.to_string().hash(hasher);
+}
+
+type EntryKey = (SignatureScope, String);
+
+#[derive(Debug, Default)]
+struct Entries {
+    signatures: HashMap<EntryKey, String>,
+    order: VecDeque<EntryKey>,
+}
+
+/// Bounded map from scoped tool call ID to thought signature; the oldest entries are evicted
+/// first.
+#[derive(Debug)]
+pub struct ThoughtSignatureCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+impl Default for ThoughtSignatureCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThoughtSignatureCache {
+    /// Creates a cache holding up to [`DEFAULT_SIGNATURE_CAPACITY`] signatures.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_SIGNATURE_CAPACITY)
+    }
+
+    /// Creates a cache holding up to `capacity` signatures.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Stores the signature of a tool call.
+    pub fn insert(
+        &self,
+        scope: SignatureScope,
+        tool_call_id: impl Into<String>,
+        signature: impl Into<String>,
+    ) {
+        let key = (scope, tool_call_id.into());
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries
+            .signatures
+            .insert(key.clone(), signature.into())
+            .is_none()
+        {
+            entries.order.push_back(key);
+        }
+        while entries.order.len() > self.capacity {
+            if let Some(oldest) = entries.order.pop_front() {
+                entries.signatures.remove(&oldest);
+            }
+        }
+    }
+
+    /// Returns the signature stored for a tool call.
+    pub fn get(&self, scope: SignatureScope, tool_call_id: &str) -> Option<String> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .signatures
+            .get(&(scope, tool_call_id.to_string()))
+            .cloned()
+    }
+
+    /// Number of stored signatures.
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .signatures
+            .len()
+    }
+
+    /// Returns `true` when no signature is stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every stored signature.
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.signatures.clear();
+        entries.order.clear();
+    }
+
+    /// Stores the signatures carried by a message's tool calls; returns how many were found.
+    pub fn capture_message(&self, scope: SignatureScope, message: &OpenAIChatMessage) -> usize {
+        let mut captured = 0;
+        for call in message.tool_calls.iter().flatten() {
+            if let Some(signature) = call.thought_signature() {
+                self.insert(scope, call.id.clone(), signature);
+                captured += 1;
+            }
+        }
+        captured
+    }
+
+    /// Stores the signatures carried by every choice of a response to a request whose
+    /// messages were `history`.
+    pub fn capture_response(
+        &self,
+        history: &[OpenAIChatMessage],
+        response: &OpenAIChatCompletionResponse,
+    ) -> usize {
+        let scope = SignatureScope::of(history);
+        response
+            .choices
+            .iter()
+            .map(|choice| self.capture_message(scope, &choice.message))
+            .sum()
+    }
+
+    /// Stores signatures from a stream chunk.
+    ///
+    /// `ids` maps tool call indexes to IDs for one stream, since later deltas of a call
+    /// carry only its index.
+    pub(crate) fn capture_chunk(
+        &self,
+        scope: SignatureScope,
+        chunk: &ChatCompletionStreamResponse,
+        ids: &mut HashMap<(u32, u32), String>,
+    ) {
+        for choice in &chunk.choices {
+            for call in choice.delta.tool_calls.iter().flatten() {
+                let key = (choice.index, call.index);
+                if let Some(id) = &call.id {
+                    ids.insert(key, id.clone());
+                }
+                let signature = call
+                    .extra_content
+                    .as_ref()
+                    .and_then(|extra| extra.get("google")?.get("thought_signature")?.as_str());
+                if let (Some(signature), Some(id)) = (signature, ids.get(&key)) {
+                    self.insert(scope, id.clone(), signature);
+                }
+            }
+        }
+    }
+
+    /// Re-attaches stored signatures to tool calls that lost them; returns how many were
+    /// restored.
+    pub fn reattach(&self, messages: &mut [OpenAIChatMessage]) -> usize {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let mut hasher = DefaultHasher::new();
+        let mut restored = 0;
+        for message in messages.iter_mut() {
+            let scope = SignatureScope(hasher.finish());
+            hash_message(message, &mut hasher);
+            for call in message.tool_calls.iter_mut().flatten() {
+                if call.thought_signature().is_some() {
+                    continue;
+                }
+                if let Some(signature) = entries.signatures.get(&(scope, call.id.clone())) {
+                    call.set_thought_signature(signature.clone());
+                    restored += 1;
+                }
+            }
+        }
+        restored
+    }
+}
+
+/// IDs of current-turn tool calls that lack a required thought signature.
+///
+/// The current turn starts after the last `user` message. Within it, the first tool call of
+/// each assistant message must carry a signature; parallel calls after the first do not.
+pub fn missing_thought_signatures(messages: &[OpenAIChatMessage]) -> Vec<&str> {
+    let turn_start = messages
+        .iter()
+        .rposition(|message| message.role == OpenAIMessageRole::User)
+        .map_or(0, |index| index + 1);
+    messages[turn_start..]
+        .iter()
+        .filter(|message| message.role == OpenAIMessageRole::Assistant)
+        .filter_map(|message| message.tool_calls.as_ref()?.first())
+        .filter(|call| call.thought_signature().is_none())
+        .map(|call| call.id.as_str())
+        .collect()
+}
+
+impl RainyClient {
+    /// Re-attaches cached signatures and validates them for models that require them.
+    pub(crate) fn prepare_thought_signatures(
+        &self,
+        request: &mut OpenAIChatCompletionRequest,
+    ) -> Result<()> {
+        if !request.requires_thought_signatures() {
+            return Ok(());
+        }
+        self.thought_signatures().reattach(&mut request.messages);
+        request
+            .validate_thought_signatures()
+            .map_err(|message| RainyError::InvalidRequest {
+                code: "MISSING_THOUGHT_SIGNATURE".to_string(),
+                message,
+                details: None,
+            })
+    }
+}
@@ -0,0 +1,506 @@
+//! Local token counting keyed by the catalog tokenizer family.
+//!
+//! Budgeting, context checks and cost estimation need prompt sizes before a request is sent.
+//! [`TokenCounter`] picks an estimator from `ModelArchitecture.tokenizer` (falling back to the
+//! model id) and counts chat messages, OpenAI-compatible messages and Responses API input,
+//! including per-message and per-request overhead.
+//!
+//! With the `tiktoken` feature enabled, the OpenAI `cl100k_base` and `o200k_base` families are
+//! counted exactly with their BPE tables. Every other family uses a heuristic calibrated per
+//! tokenizer (characters per token for Latin text, CJK density, digit grouping); treat its
+//! counts as estimates and keep a safety margin when budgeting.
+
+use crate::models::{
+    ChatCompletionRequest, ChatMessage, ModelCatalogItem, OpenAIChatCompletionRequest,
+    OpenAIChatMessage, OpenAIContentPart, OpenAIFileInput, OpenAIMessageContent, ResponsesRequest,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Extra tokens charged for each tool call in an assistant message (id, type and framing).
+const TOOL_CALL_OVERHEAD_TOKENS: u32 = 3;
+/// Decoded audio bytes per token: about 25 tokens per second of 128 kbps audio.
+const AUDIO_BYTES_PER_TOKEN: usize = 640;
+/// Decoded file bytes per token, treating inline documents as extracted text.
+const FILE_BYTES_PER_TOKEN: usize = 4;
+
+/// Tokenizer family used to pick a token estimator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenizerFamily {
+    /// OpenAI `o200k_base` (GPT-4o, GPT-4.1, GPT-5, o-series).
+    O200k,
+    /// OpenAI `cl100k_base` (GPT-4, GPT-3.5).
+    Cl100k,
+    /// Anthropic Claude.
+    Claude,
+    /// Google Gemini and Gemma.
+    Gemini,
+    /// Meta Llama 3 / Llama 4.
+    Llama,
+    /// Mistral and Mixtral.
+    Mistral,
+    /// Alibaba Qwen.
+    Qwen,
+    /// DeepSeek.
+    DeepSeek,
+    /// Unknown tokenizer; roughly four characters per token.
+    #[default]
+    Generic,
+}
+
+/// Per-family calibration used by the heuristic estimator.
+struct Profile {
+    /// Average ASCII characters per token inside long words.
+    chars_per_token: f32,
+    /// Average non-ASCII (accented, Cyrillic, Greek, ...) characters per token inside words.
+    non_ascii_chars_per_token: f32,
+    /// Tokens per CJK character.
+    cjk_tokens_per_char: f32,
+    /// Digits merged into one token.
+    digits_per_token: u32,
+    /// Role markers and separators added per message.
+    message_overhead: u32,
+    /// Reply priming added once per request.
+    request_overhead: u32,
+    /// Tokens charged for one image part.
+    image_tokens: u32,
+}
+
+impl TokenizerFamily {
+    /// Maps a catalog `architecture.tokenizer` value (e.g. `GPT`, `Claude`, `Llama3`).
+    ///
+    /// `GPT` alone is ambiguous; it maps to [`TokenizerFamily::O200k`]. Use
+    /// [`TokenizerFamily::for_model`] to disambiguate by model id.
+    pub fn from_name(name: &str) -> Self {
+        let name = name.to_ascii_lowercase();
+        match name.as_str() {
+            "o200k" | "o200k_base" | "gpt" => Self::O200k,
+            "cl100k" | "cl100k_base" => Self::Cl100k,
+            "claude" => Self::Claude,
+            "gemini" | "gemma" => Self::Gemini,
+            "mistral" | "mixtral" => Self::Mistral,
+            "deepseek" => Self::DeepSeek,
+            _ if name.starts_with("llama") => Self::Llama,
+            _ if name.starts_with("qwen") => Self::Qwen,
+            _ => Self::Generic,
+        }
+    }
+
+    /// Guesses the tokenizer family from a model id such as `openai/gpt-4o`.
+    pub fn from_model_id(model_id: &str) -> Self {
+        let name = model_id
+            .rsplit('/')
+            .next()
+            .unwrap_or(model_id)
+            .to_ascii_lowercase();
+        if name.starts_with("gpt-4o")
+            || name.starts_with("gpt-4.1")
+            || name.starts_with("gpt-5")
+            || name.starts_with("chatgpt")
+            || name.starts_with("o1")
+            || name.starts_with("o3")
+            || name.starts_with("o4")
+        {
+            Self::O200k
+        } else if name.starts_with("gpt-") {
+            Self::Cl100k
+        } else if name.contains("claude") {
+            Self::Claude
+        } else if name.contains("gemini") || name.contains("gemma") {
+            Self::Gemini
+        } else if name.contains("llama") {
+            Self::Llama
+        } else if name.contains("mistral")
+            || name.contains("mixtral")
+            || name.contains("codestral")
+            || name.contains("ministral")
+        {
+            Self::Mistral
+        } else if name.contains("qwen") || name.contains("qwq") {
+            Self::Qwen
+        } else if name.contains("deepseek") {
+            Self::DeepSeek
+        } else {
+            Self::Generic
+        }
+    }
+
+    /// Picks the family for a catalog entry, preferring `architecture.tokenizer`.
+    pub fn for_model(model: &ModelCatalogItem) -> Self {
+        let from_id = Self::from_model_id(&model.id);
+        match model
+            .architecture
+            .as_ref()
+            .and_then(|architecture| architecture.tokenizer.as_deref())
+            .map(Self::from_name)
+        {
+            Some(Self::O200k) if from_id == Self::Cl100k => Self::Cl100k,
+            Some(Self::Generic) | None => from_id,
+            Some(family) => family,
+        }
+    }
+
+    /// Whether counts for this family come from the real BPE tables.
+    pub fn is_exact(&self) -> bool {
+        cfg!(feature = "tiktoken") && matches!(self, Self::O200k | Self::Cl100k)
+    }
+
+    fn profile(&self) -> Profile {
+        let (chars_per_token, non_ascii_chars_per_token, cjk_tokens_per_char, digits_per_token) =
+            match self {
+                Self::O200k => (4.3, 4.0, 0.6, 3),
+                Self::Cl100k => (4.0, 2.0, 1.0, 3),
+                Self::Claude => (3.5, 2.0, 1.2, 3),
+                Self::Gemini => (4.0, 2.6, 0.8, 1),
+                Self::Llama => (4.0, 2.2, 1.0, 3),
+                Self::Mistral => (3.6, 2.0, 1.2, 1),
+                Self::Qwen => (4.0, 2.2, 0.7, 1),
+                Self::DeepSeek => (3.8, 2.2, 0.7, 3),
+                Self::Generic => (4.0, 2.0, 1.0, 3),
+            };
+        let (message_overhead, image_tokens) = match self {
+            Self::O200k | Self::Cl100k => (3, 765),
+            Self::Claude => (4, 1600),
+            Self::Gemini => (4, 258),
+            Self::Llama => (5, 1600),
+            Self::Qwen => (5, 1000),
+            Self::Mistral => (4, 1000),
+            Self::DeepSeek | Self::Generic => (4, 85),
+        };
+        Profile {
+            chars_per_token,
+            non_ascii_chars_per_token,
+            cjk_tokens_per_char,
+            digits_per_token,
+            message_overhead,
+            request_overhead: 3,
+            image_tokens,
+        }
+    }
+}
+
+/// Counts tokens locally for one tokenizer family.
+///
+/// # Examples
+///
+/// ```rust
+/// use rainy_sdk::tokens::{TokenCounter, TokenizerFamily};
+/// use rainy_sdk::ChatMessage;
+///
+/// let counter = TokenCounter::for_model_id("anthropic/claude-sonnet-4");
+/// assert_eq!(counter.family(), TokenizerFamily::Claude);
+/// assert!(counter.count_chat_message(&ChatMessage::user("Hello there")) > 4);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TokenCounter {
+    family: TokenizerFamily,
+}
+
+impl TokenCounter {
+    /// Creates a counter for a tokenizer family.
+    pub fn new(family: TokenizerFamily) -> Self {
+        Self { family }
+    }
+
+    /// Creates a counter for a catalog entry.
+    pub fn for_model(model: &ModelCatalogItem) -> Self {
+        Self::new(TokenizerFamily::for_model(model))
+    }
+
+    /// Creates a counter from a model id alone.
+    pub fn for_model_id(model_id: &str) -> Self {
+        Self::new(TokenizerFamily::from_model_id(model_id))
+    }
+
+    /// The tokenizer family this counter estimates for.
+    pub fn family(&self) -> TokenizerFamily {
+        self.family
+    }
+
+    /// Tokens added per message for role markers and separators.
+    pub fn message_overhead(&self) -> u32 {
+        self.family.profile().message_overhead
+    }
+
+    /// Tokens added once per request.
+    pub fn request_overhead(&self) -> u32 {
+        self.family.profile().request_overhead
+    }
+
+    /// Counts the tokens of plain text.
+    pub fn count_text(&self, text: &str) -> u32 {
+        if text.is_empty() {
+            return 0;
+        }
+        #[cfg(feature = "tiktoken")]
+        match self.family {
+            TokenizerFamily::O200k => {
+                return tiktoken_rs::o200k_base_singleton()
+                    .encode_ordinary(text)
+                    .len() as u32;
+            }
+            TokenizerFamily::Cl100k => {
+                return tiktoken_rs::cl100k_base_singleton()
+                    .encode_ordinary(text)
+                    .len() as u32;
+            }
+            _ => {}
+        }
+        heuristic_count(&self.family.profile(), text)
+    }
+
+    /// Counts a value serialized as compact JSON (tool definitions, schemas, arguments).
+    pub fn count_json<T: Serialize + ?Sized>(&self, value: &T) -> u32 {
+        serde_json::to_string(value)
+            .map(|raw| self.count_text(&raw))
+            .unwrap_or_default()
+    }
+
+    /// Counts one image part; `detail: "low"` is charged the fixed low-detail rate.
+    pub fn count_image(&self, detail: Option<&str>) -> u32 {
+        match detail {
+            Some("low") => 85,
+            _ => self.family.profile().image_tokens,
+        }
+    }
+
+    /// Counts one audio part from its base64 payload.
+    pub fn count_audio(&self, data: &str) -> u32 {
+        (base64_decoded_len(data) / AUDIO_BYTES_PER_TOKEN).max(1) as u32
+    }
+
+    /// Counts one file part; inline data is charged by size, uploaded file ids by name only.
+    pub fn count_file(&self, file: &OpenAIFileInput) -> u32 {
+        let name = file
+            .filename
+            .as_deref()
+            .map_or(0, |name| self.count_text(name));
+        let data = file.file_data.as_deref().map_or(0, |data| {
+            let payload = data.split_once(',').map_or(data, |(_, payload)| payload);
+            (base64_decoded_len(payload) / FILE_BYTES_PER_TOKEN) as u32
+        });
+        name + data
+    }
+
+    /// Counts a simple chat message, including per-message overhead.
+    pub fn count_chat_message(&self, message: &ChatMessage) -> u32 {
+        self.message_overhead() + self.count_text(&message.content)
+    }
+
+    /// Counts an OpenAI-compatible message, including content parts, images and tool calls.
+    pub fn count_openai_message(&self, message: &OpenAIChatMessage) -> u32 {
+        let content = match &message.content {
+            Some(OpenAIMessageContent::Text(text)) => self.count_text(text),
+            Some(OpenAIMessageContent::Parts(parts)) => parts
+                .iter()
+                .map(|part| match part {
+                    OpenAIContentPart::Text { text } => self.count_text(text),
+                    OpenAIContentPart::ImageUrl { image_url } => {
+                        self.count_image(image_url.detail.as_deref())
+                    }
+                    OpenAIContentPart::InputAudio { input_audio } => {
+                        self.count_audio(&input_audio.data)
+                    }
+                    OpenAIContentPart::File { file } => self.count_file(file),
+                })
+                .sum(),
+            None => 0,
+        };
+        let name = message
+            .name
+            .as_deref()
+            .map(|name| self.count_text(name) + 1)
+            .unwrap_or(0);
+        let tool_calls: u32 = message
+            .tool_calls
+            .iter()
+            .flatten()
+            .map(|call| {
+                TOOL_CALL_OVERHEAD_TOKENS
+                    + self.count_text(&call.function.name)
+                    + self.count_text(&call.function.arguments)
+            })
+            .sum();
+        let tool_call_id = message
+            .tool_call_id
+            .as_deref()
+            .map(|id| self.count_text(id))
+            .unwrap_or(0);
+        self.message_overhead() + content + name + tool_calls + tool_call_id
+    }
+
+    /// Counts the prompt of a chat completion request.
+    pub fn count_chat_request(&self, request: &ChatCompletionRequest) -> u32 {
+        let messages: u32 = request
+            .messages
+            .iter()
+            .map(|message| self.count_chat_message(message))
+            .sum();
+        let tools = request
+            .tools
+            .as_ref()
+            .map(|tools| self.count_json(tools))
+            .unwrap_or(0);
+        messages + tools + self.request_overhead()
+    }
+
+    /// Counts the prompt of an OpenAI-compatible chat completion request.
+    pub fn count_openai_request(&self, request: &OpenAIChatCompletionRequest) -> u32 {
+        let messages: u32 = request
+            .messages
+            .iter()
+            .map(|message| self.count_openai_message(message))
+            .sum();
+        let tools = request
+            .tools
+            .as_ref()
+            .map(|tools| self.count_json(tools))
+            .unwrap_or(0);
+        messages + tools + self.request_overhead()
+    }
+
+    /// Counts the input of a Responses API request, including `instructions` and tools.
+    pub fn count_responses_request(&self, request: &ResponsesRequest) -> u32 {
+        let instructions = request
+            .extra
+            .get("instructions")
+            .and_then(Value::as_str)
+            .map(|text| self.message_overhead() + self.count_text(text))
+            .unwrap_or(0);
+        let tools = request
+            .tools
+            .as_ref()
+            .map(|tools| self.count_json(tools))
+            .unwrap_or(0);
+        self.count_responses_input(&request.input) + instructions + tools + self.request_overhead()
+    }
+
+    /// Counts Responses API `input`: a string, a single item, or an array of items.
+    pub fn count_responses_input(&self, input: &Value) -> u32 {
+        match input {
+            Value::String(text) => self.message_overhead() + self.count_text(text),
+            Value::Array(items) => items.iter().map(|item| self.count_input_item(item)).sum(),
+            Value::Object(_) => self.count_input_item(input),
+            Value::Null => 0,
+            other => self.count_json(other),
+        }
+    }
+
+    fn count_input_item(&self, item: &Value) -> u32 {
+        let kind = item
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("message");
+        let body = match kind {
+            "function_call" => {
+                TOOL_CALL_OVERHEAD_TOKENS
+                    + self.count_value_text(item.get("name"))
+                    + self.count_value_text(item.get("arguments"))
+            }
+            "function_call_output" => self.count_value_text(item.get("output")),
+            "message" => match item.get("content") {
+                Some(Value::Array(parts)) => parts.iter().map(|part| self.count_part(part)).sum(),
+                content => self.count_value_text(content),
+            },
+            _ => self.count_json(item),
+        };
+        self.message_overhead() + body
+    }
+
+    fn count_part(&self, part: &Value) -> u32 {
+        match part.get("type").and_then(Value::as_str) {
+            Some("input_text" | "output_text" | "text") => self.count_value_text(part.get("text")),
+            Some("input_image" | "image_url") => {
+                self.count_image(part.get("detail").and_then(Value::as_str))
+            }
+            Some("input_audio") => {
+                let data = part
+                    .get("input_audio")
+                    .and_then(|audio| audio.get("data"))
+                    .and_then(Value::as_str);
+                self.count_audio(data.unwrap_or_default())
+            }
+            Some("input_file") => serde_json::from_value::<OpenAIFileInput>(part.clone())
+                .map_or_else(|_| self.count_json(part), |file| self.count_file(&file)),
+            _ => self.count_json(part),
+        }
+    }
+
+    fn count_value_text(&self, value: Option<&Value>) -> u32 {
+        match value {
+            Some(Value::String(text)) => self.count_text(text),
+            Some(Value::Null) | None => 0,
+            Some(other) => self.count_json(other),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CharClass {
+    Word,
+    Digit,
+    Cjk,
+    Space,
+    Newline,
+    Punct,
+    Symbol,
+}
+
+fn classify(c: char) -> CharClass {
+    match c {
+        '\n' | '\r' => CharClass::Newline,
+        c if c.is_whitespace() => CharClass::Space,
+        '0'..='9' => CharClass::Digit,
+        c if is_cjk(c) => CharClass::Cjk,
+        c if c.is_alphabetic() || c == '_' => CharClass::Word,
+        c if c.is_ascii() => CharClass::Punct,
+        _ => CharClass::Symbol,
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF
+    )
+}
+
+/// Estimates tokens by splitting text into runs of the same character class.
+fn heuristic_count(profile: &Profile, text: &str) -> u32 {
+    let mut total = 0u32;
+    let mut chars = text.chars().peekable();
+    while let Some(first) = chars.next() {
+        let class = classify(first);
+        let (mut len, mut non_ascii) = (1u32, u32::from(!first.is_ascii()));
+        while let Some(&next) = chars.peek() {
+            if classify(next) != class {
+                break;
+            }
+            len += 1;
+            non_ascii += u32::from(!next.is_ascii());
+            chars.next();
+        }
+        total += match class {
+            // Common words are a single token; only long words split further.
+            CharClass::Word => {
+                let ascii = (len - non_ascii) as f32;
+                let long = (ascii - 1.5 * profile.chars_per_token).max(0.0);
+                let other = non_ascii as f32 / profile.non_ascii_chars_per_token;
+                (1.0 + long / profile.chars_per_token + other).floor() as u32
+            }
+            CharClass::Digit => len.div_ceil(profile.digits_per_token),
+            CharClass::Cjk => ((len as f32 * profile.cjk_tokens_per_char).ceil() as u32).max(1),
+            // A single space is merged into the following word.
+            CharClass::Space => (len - 1).div_ceil(4),
+            CharClass::Newline => len.div_ceil(2),
+            CharClass::Punct => len.div_ceil(3),
+            CharClass::Symbol => len,
+        };
+    }
+    total
+}
+
+fn base64_decoded_len(data: &str) -> usize {
+    data.trim_end_matches('=').len() * 3 / 4
+}
@@ -0,0 +1,475 @@
+//! Incremental assembly of streamed tool calls.
+//!
+//! In a streamed chat completion, `tool_calls[].function.arguments` arrives in fragments.
+//! [`ToolCallAssembler`] folds the fragments per call index, tracks the JSON structure as it
+//! grows so broken arguments are noticed as soon as they go wrong, and, once a call's
+//! arguments close, parses them and validates them against the matching
+//! [`FunctionDefinition::parameters`](crate::FunctionDefinition) schema.
+//!
+//! ```rust,no_run
+//! # use futures::StreamExt;
+//! # use rainy_sdk::{OpenAIChatCompletionRequest, RainyClient, ToolCallAssembler, ToolCallEvent};
+//! # async fn example(request: OpenAIChatCompletionRequest) -> Result<(), Box<dyn std::error::Error>> {
+//! let client = RainyClient::with_api_key("your-api-key")?;
+//! let tools = request.tools.clone().unwrap_or_default();
+//! let stream = client.create_openai_chat_completion_stream(request).await?;
+//!
+//! let mut events = ToolCallAssembler::new(&tools).stream(stream);
+//! while let Some(event) = events.next().await {
+//!     match event? {
+//!         ToolCallEvent::ToolCallReady { call, arguments, .. } => {
+//!             println!("{} ready: {arguments}", call.function.name)
+//!         }
+//!         ToolCallEvent::ToolCallMalformed(error) => eprintln!("{error}"),
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{RainyError, Result};
+use crate::models::{
+    ChatCompletionStreamResponse, OpenAIFunctionCall, OpenAIToolCall, Tool, ToolCall,
+};
+use crate::schema::{validate_json_schema, SchemaViolation};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Progress of the streamed tool calls.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolCallEvent {
+    /// The first argument fragment of a call arrived; `id` and `name` are known.
+    ToolCallStarted {
+        /// Position of the call in `tool_calls`.
+        index: u32,
+        /// Tool call ID.
+        id: String,
+        /// Function name.
+        name: String,
+    },
+    /// A fragment of a call's JSON arguments.
+    ArgumentsDelta {
+        /// Position of the call in `tool_calls`.
+        index: u32,
+        /// The new argument text.
+        delta: String,
+    },
+    /// A call's arguments are complete, valid JSON and conform to the tool's schema.
+    ToolCallReady {
+        /// Position of the call in `tool_calls`.
+        index: u32,
+        /// The assembled call, ready to append to the conversation.
+        call: OpenAIToolCall,
+        /// The parsed arguments.
+        arguments: Value,
+    },
+    /// A call's arguments could not be used.
+    ToolCallMalformed(MalformedToolCall),
+}
+
+/// Why a streamed tool call was rejected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "detail", rename_all = "snake_case")]
+pub enum MalformedReason {
+    /// The arguments are not valid JSON (or not a JSON object).
+    InvalidJson(String),
+    /// The stream ended before the arguments were complete.
+    Incomplete,
+    /// The model called a function that is not among the request's tools.
+    UnknownTool,
+    /// The arguments parsed but do not match the tool's `parameters` schema.
+    SchemaViolations(Vec<SchemaViolation>),
+}
+
+/// A streamed tool call whose arguments could not be used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MalformedToolCall {
+    /// Position of the call in `tool_calls`.
+    pub index: u32,
+    /// Tool call ID.
+    pub id: String,
+    /// Function name.
+    pub name: String,
+    /// The raw argument text received.
+    pub arguments: String,
+    /// What is wrong with the call.
+    pub reason: MalformedReason,
+}
+
+impl std::fmt::Display for MalformedToolCall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "tool call #{} ({}): ", self.index, self.name)?;
+        match &self.reason {
+            MalformedReason::InvalidJson(message) => write!(f, "invalid JSON arguments: {message}"),
+            MalformedReason::Incomplete => {
+                write!(f, "arguments ended before the JSON was complete")
+            }
+            MalformedReason::UnknownTool => write!(f, "unknown tool"),
+            MalformedReason::SchemaViolations(violations) => {
+                let violations: Vec<String> = violations.iter().map(ToString::to_string).collect();
+                write!(
+                    f,
+                    "arguments do not match schema: {}",
+                    violations.join("; ")
+                )
+            }
+        }
+    }
+}
+
+impl From<MalformedToolCall> for RainyError {
+    fn from(call: MalformedToolCall) -> Self {
+        RainyError::InvalidRequest {
+            code: "MALFORMED_TOOL_CALL".to_string(),
+            message: call.to_string(),
+            details: serde_json::to_value(&call).ok(),
+        }
+    }
+}
+
+/// Tracks bracket nesting and string state of a growing JSON document.
+#[derive(Debug, Clone, Default)]
+struct JsonScanner {
+    closers: Vec<char>,
+    started: bool,
+    in_string: bool,
+    escaped: bool,
+    complete: bool,
+    error: Option<String>,
+}
+
+impl JsonScanner {
+    fn feed(&mut self, text: &str) {
+        for ch in text.chars() {
+            if self.error.is_some() {
+                return;
+            }
+            if self.in_string {
+                match ch {
+                    _ if self.escaped => self.escaped = false,
+                    '\\' => self.escaped = true,
+                    '"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            if ch.is_whitespace() {
+                continue;
+            }
+            if self.complete {
+                self.error = Some(format!("unexpected '{ch}' after the arguments object"));
+                return;
+            }
+            if !self.started {
+                if ch != '{' {
+                    self.error = Some(format!("arguments must be a JSON object, found '{ch}'"));
+                    return;
+                }
+                self.started = true;
+            }
+            match ch {
+                '"' => self.in_string = true,
+                '{' => self.closers.push('}'),
+                '[' => self.closers.push(']'),
+                '}' | ']' => {
+                    if self.closers.pop() != Some(ch) {
+                        self.error = Some(format!("unbalanced '{ch}'"));
+                        return;
+                    }
+                    self.complete = self.closers.is_empty();
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PendingCall {
+    call: OpenAIToolCall,
+    scanner: JsonScanner,
+    started: bool,
+    done: bool,
+}
+
+/// Folds streamed `tool_calls` deltas into complete, validated calls.
+///
+/// Only the first choice (`index == 0`) is tracked, as in
+/// [`StreamAccumulator`](crate::StreamAccumulator).
+#[derive(Debug, Clone, Default)]
+pub struct ToolCallAssembler {
+    schemas: Option<HashMap<String, Option<Value>>>,
+    calls: BTreeMap<u32, PendingCall>,
+}
+
+impl ToolCallAssembler {
+    /// Creates an assembler that validates calls against `tools`.
+    ///
+    /// Calls to functions missing from `tools` are reported as [`MalformedReason::UnknownTool`].
+    /// Use [`ToolCallAssembler::default`] to skip validation altogether.
+    pub fn new(tools: &[Tool]) -> Self {
+        Self {
+            schemas: Some(
+                tools
+                    .iter()
+                    .map(|tool| (tool.function.name.clone(), tool.function.parameters.clone()))
+                    .collect(),
+            ),
+            calls: BTreeMap::new(),
+        }
+    }
+
+    /// Folds one chunk and returns the events it produced.
+    ///
+    /// A `finish_reason` on the chunk finalizes every open call, as [`finish`](Self::finish)
+    /// does.
+    pub fn push(&mut self, chunk: &ChatCompletionStreamResponse) -> Vec<ToolCallEvent> {
+        let mut events = Vec::new();
+        let Some(choice) = chunk.choices.iter().find(|choice| choice.index == 0) else {
+            return events;
+        };
+        for delta in choice.delta.tool_calls.iter().flatten() {
+            self.push_delta(delta, &mut events);
+        }
+        if choice.finish_reason.is_some() {
+            events.extend(self.finish());
+        }
+        events
+    }
+
+    /// Finalizes calls whose arguments never closed, e.g. when the stream ends.
+    ///
+    /// Calls with no argument text are treated as `{}`.
+    pub fn finish(&mut self) -> Vec<ToolCallEvent> {
+        let mut events = Vec::new();
+        let indexes: Vec<u32> = self
+            .calls
+            .iter()
+            .filter(|(_, pending)| !pending.done)
+            .map(|(index, _)| *index)
+            .collect();
+        for index in indexes {
+            let schemas = self.schemas.as_ref();
+            let pending = self.calls.get_mut(&index).expect("pending call");
+            start(index, pending, &mut events);
+            if pending.call.function.arguments.trim().is_empty() {
+                pending.call.function.arguments = "{}".to_string();
+                complete(index, pending, schemas, &mut events);
+            } else {
+                pending.done = true;
+                events.push(malformed(index, pending, MalformedReason::Incomplete));
+            }
+        }
+        events
+    }
+
+    /// The calls assembled so far, in index order.
+    pub fn tool_calls(&self) -> Vec<OpenAIToolCall> {
+        self.calls
+            .values()
+            .map(|pending| pending.call.clone())
+            .collect()
+    }
+
+    /// Wraps a chat completion stream, yielding tool-call events instead of chunks.
+    pub fn stream<S>(self, inner: S) -> ToolCallStream<S>
+    where
+        S: Stream<Item = Result<ChatCompletionStreamResponse>> + Unpin,
+    {
+        ToolCallStream {
+            inner,
+            assembler: self,
+            pending: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    fn push_delta(&mut self, delta: &ToolCall, events: &mut Vec<ToolCallEvent>) {
+        let schemas = self.schemas.as_ref();
+        let pending = self
+            .calls
+            .entry(delta.index)
+            .or_insert_with(|| PendingCall {
+                call: empty_tool_call(),
+                scanner: JsonScanner::default(),
+                started: false,
+                done: false,
+            });
+        merge_tool_call_delta(&mut pending.call, delta);
+        let Some(fragment) = delta
+            .function
+            .as_ref()
+            .and_then(|function| function.arguments.as_deref())
+            .filter(|text| !text.is_empty())
+        else {
+            return;
+        };
+        if pending.done && fragment.trim().is_empty() {
+            return;
+        }
+
+        start(delta.index, pending, events);
+        events.push(ToolCallEvent::ArgumentsDelta {
+            index: delta.index,
+            delta: fragment.to_string(),
+        });
+        if pending.done {
+            return;
+        }
+        pending.scanner.feed(fragment);
+        if let Some(message) = pending.scanner.error.clone() {
+            pending.done = true;
+            events.push(malformed(
+                delta.index,
+                pending,
+                MalformedReason::InvalidJson(message),
+            ));
+        } else if pending.scanner.complete {
+            complete(delta.index, pending, schemas, events);
+        }
+    }
+}
+
+/// A call with no fields filled in yet, to merge deltas into.
+pub(crate) fn empty_tool_call() -> OpenAIToolCall {
+    OpenAIToolCall {
+        id: String::new(),
+        r#type: "function".to_string(),
+        extra_content: None,
+        function: OpenAIFunctionCall {
+            name: String::new(),
+            arguments: String::new(),
+        },
+    }
+}
+
+/// Merges one streamed delta into `call`: argument fragments are appended and the other
+/// fields are set from the delta.
+pub(crate) fn merge_tool_call_delta(call: &mut OpenAIToolCall, delta: &ToolCall) {
+    if let Some(id) = &delta.id {
+        call.id = id.clone();
+    }
+    if let Some(kind) = &delta.r#type {
+        call.r#type = kind.clone();
+    }
+    if let Some(extra) = &delta.extra_content {
+        call.extra_content = Some(extra.clone());
+    }
+    let Some(function) = &delta.function else {
+        return;
+    };
+    // Providers repeat the full name on later deltas; keep the first one.
+    if let Some(name) = function.name.as_ref().filter(|name| !name.is_empty()) {
+        if call.function.name.is_empty() {
+            call.function.name = name.clone();
+        }
+    }
+    if let Some(arguments) = &function.arguments {
+        call.function.arguments.push_str(arguments);
+    }
+}
+
+fn start(index: u32, pending: &mut PendingCall, events: &mut Vec<ToolCallEvent>) {
+    if !pending.started {
+        pending.started = true;
+        events.push(ToolCallEvent::ToolCallStarted {
+            index,
+            id: pending.call.id.clone(),
+            name: pending.call.function.name.clone(),
+        });
+    }
+}
+
+fn malformed(index: u32, pending: &PendingCall, reason: MalformedReason) -> ToolCallEvent {
+    ToolCallEvent::ToolCallMalformed(MalformedToolCall {
+        index,
+        id: pending.call.id.clone(),
+        name: pending.call.function.name.clone(),
+        arguments: pending.call.function.arguments.clone(),
+        reason,
+    })
+}
+
+fn complete(
+    index: u32,
+    pending: &mut PendingCall,
+    schemas: Option<&HashMap<String, Option<Value>>>,
+    events: &mut Vec<ToolCallEvent>,
+) {
+    pending.done = true;
+    let arguments: Value = match serde_json::from_str(&pending.call.function.arguments) {
+        Ok(arguments) => arguments,
+        Err(err) => {
+            let reason = MalformedReason::InvalidJson(err.to_string());
+            return events.push(malformed(index, pending, reason));
+        }
+    };
+    if let Some(schemas) = schemas {
+        match schemas.get(&pending.call.function.name) {
+            None => return events.push(malformed(index, pending, MalformedReason::UnknownTool)),
+            Some(Some(schema)) => {
+                let violations = validate_json_schema(schema, &arguments);
+                if !violations.is_empty() {
+                    let reason = MalformedReason::SchemaViolations(violations);
+                    return events.push(malformed(index, pending, reason));
+                }
+            }
+            Some(None) => {}
+        }
+    }
+    events.push(ToolCallEvent::ToolCallReady {
+        index,
+        call: pending.call.clone(),
+        arguments,
+    });
+}
+
+/// Stream of [`ToolCallEvent`]s built by [`ToolCallAssembler::stream`].
+///
+/// Open calls are finalized when the inner stream ends.
+pub struct ToolCallStream<S> {
+    inner: S,
+    assembler: ToolCallAssembler,
+    pending: VecDeque<ToolCallEvent>,
+    finished: bool,
+}
+
+impl<S> ToolCallStream<S> {
+    /// The assembler state, e.g. to read [`ToolCallAssembler::tool_calls`] afterwards.
+    pub fn assembler(&self) -> &ToolCallAssembler {
+        &self.assembler
+    }
+}
+
+impl<S> Stream for ToolCallStream<S>
+where
+    S: Stream<Item = Result<ChatCompletionStreamResponse>> + Unpin,
+{
+    type Item = Result<ToolCallEvent>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(event) = this.pending.pop_front() {
+                return Poll::Ready(Some(Ok(event)));
+            }
+            if this.finished {
+                return Poll::Ready(None);
+            }
+            match Pin::new(&mut this.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => this.pending.extend(this.assembler.push(&chunk)),
+                Poll::Ready(Some(Err(error))) => return Poll::Ready(Some(Err(error))),
+                Poll::Ready(None) => {
+                    this.finished = true;
+                    this.pending.extend(this.assembler.finish());
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
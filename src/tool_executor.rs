@@ -0,0 +1,300 @@
+//! Concurrent execution of model tool calls.
+//!
+//! [`ToolExecutor`] maps function names to async handlers and runs a list of
+//! [`OpenAIToolCall`]s concurrently, up to a concurrency cap and with a timeout per tool.
+//! Every call produces a [`ToolExecution`] in the original call order; unknown tools, invalid
+//! arguments, handler errors, timeouts and panics become structured error payloads the model
+//! can read and react to.
+//!
+//! ```rust,no_run
+//! # use rainy_sdk::{OpenAIToolCall, ToolExecutor};
+//! # use serde_json::{json, Value};
+//! # use std::time::Duration;
+//! # async fn example(calls: Vec<OpenAIToolCall>) {
+//! let executor = ToolExecutor::new()
+//!     .with_timeout(Duration::from_secs(10))
+//!     .with_tool("get_weather", |args: Value| async move {
+//!         let city = args["city"].as_str().ok_or("city is required")?;
+//!         Ok(json!({ "city": city, "temp_c": 21 }))
+//!     });
+//!
+//! // One tool message per call, in `tool_calls` order.
+//! let messages = executor.execute_messages(&calls).await;
+//! # }
+//! ```
+
+use crate::models::{OpenAIChatMessage, OpenAIToolCall};
+use futures::future::BoxFuture;
+use futures::{FutureExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// Default maximum number of tool calls run at once.
+pub const DEFAULT_TOOL_CONCURRENCY: usize = 8;
+/// Default time a single tool call may take.
+pub const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Error returned by a tool handler.
+pub type ToolError = Box<dyn std::error::Error + Send + Sync>;
+
+type ToolHandler = Arc<dyn Fn(Value) -> BoxFuture<'static, Result<Value, ToolError>> + Send + Sync>;
+
+/// Why a tool call produced no result.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolErrorKind {
+    /// No handler is registered for the function name.
+    UnknownTool,
+    /// The arguments are not valid JSON.
+    InvalidArguments,
+    /// The handler returned an error.
+    Failed,
+    /// The handler did not finish within its timeout.
+    Timeout,
+    /// The handler panicked.
+    Panicked,
+}
+
+/// Result of one tool call.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ToolOutcome {
+    /// The handler's return value.
+    Success {
+        /// The value returned by the handler.
+        output: Value,
+    },
+    /// The call failed.
+    Error {
+        /// The kind of failure.
+        kind: ToolErrorKind,
+        /// A description for the model.
+        message: String,
+    },
+}
+
+/// One executed tool call.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ToolExecution {
+    /// ID of the tool call this answers.
+    pub tool_call_id: String,
+    /// Function name.
+    pub name: String,
+    /// What happened.
+    pub outcome: ToolOutcome,
+    /// Wall-clock time spent, in milliseconds.
+    pub duration_ms: u64,
+}
+
+impl ToolExecution {
+    /// Returns `true` when the handler returned a value.
+    pub fn is_success(&self) -> bool {
+        matches!(self.outcome, ToolOutcome::Success { .. })
+    }
+
+    /// The tool result content sent back to the model.
+    ///
+    /// String outputs are sent as-is and other values as JSON. Failures are sent as
+    /// `{"error": {"type": "<kind>", "message": "..."}}`.
+    pub fn content(&self) -> String {
+        match &self.outcome {
+            ToolOutcome::Success {
+                output: Value::String(text),
+            } => text.clone(),
+            ToolOutcome::Success { output } => output.to_string(),
+            ToolOutcome::Error { kind, message } => {
+                json!({ "error": { "type": kind, "message": message } }).to_string()
+            }
+        }
+    }
+
+    /// The `tool` message answering the call.
+    pub fn to_message(&self) -> OpenAIChatMessage {
+        OpenAIChatMessage::tool(self.tool_call_id.clone(), self.content())
+    }
+}
+
+/// Runs tool calls concurrently against registered handlers.
+#[derive(Clone)]
+pub struct ToolExecutor {
+    handlers: HashMap<String, ToolHandler>,
+    timeouts: HashMap<String, Duration>,
+    default_timeout: Duration,
+    concurrency: usize,
+}
+
+impl Default for ToolExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ToolExecutor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut tools: Vec<&String> = self.handlers.keys().collect();
+        tools.sort();
+        f.debug_struct("ToolExecutor")
+            .field("tools", &tools)
+            .field("timeouts", &self.timeouts)
+            .field("default_timeout", &self.default_timeout)
+            .field("concurrency", &self.concurrency)
+            .finish()
+    }
+}
+
+impl ToolExecutor {
+    /// Creates an executor with [`DEFAULT_TOOL_CONCURRENCY`] and [`DEFAULT_TOOL_TIMEOUT`].
+    pub fn new() -> Self {
+        Self {
+            handlers: HashMap::new(),
+            timeouts: HashMap::new(),
+            default_timeout: DEFAULT_TOOL_TIMEOUT,
+            concurrency: DEFAULT_TOOL_CONCURRENCY,
+        }
+    }
+
+    /// Registers the handler for function `name`, receiving the parsed arguments.
+    pub fn with_tool<F, Fut>(mut self, name: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value, ToolError>> + Send + 'static,
+    {
+        self.handlers
+            .insert(name.into(), Arc::new(move |args| handler(args).boxed()));
+        self
+    }
+
+    /// Sets the maximum number of calls run at once (at least 1).
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets the timeout for tools without their own.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = timeout;
+        self
+    }
+
+    /// Sets the timeout for function `name`.
+    pub fn with_tool_timeout(mut self, name: impl Into<String>, timeout: Duration) -> Self {
+        self.timeouts.insert(name.into(), timeout);
+        self
+    }
+
+    /// Returns `true` when a handler is registered for `name`.
+    pub fn has_tool(&self, name: &str) -> bool {
+        self.handlers.contains_key(name)
+    }
+
+    /// Runs `calls` concurrently and returns one execution per call, in call order.
+    ///
+    /// Dropping the returned future aborts the handlers that are still running.
+    pub async fn execute(&self, calls: &[OpenAIToolCall]) -> Vec<ToolExecution> {
+        futures::stream::iter(calls.iter().map(|call| self.execute_one(call)))
+            .buffered(self.concurrency)
+            .collect()
+            .await
+    }
+
+    /// Runs `calls` and returns the `tool` messages to append, in call order.
+    pub async fn execute_messages(&self, calls: &[OpenAIToolCall]) -> Vec<OpenAIChatMessage> {
+        self.execute(calls)
+            .await
+            .iter()
+            .map(ToolExecution::to_message)
+            .collect()
+    }
+
+    async fn execute_one(&self, call: &OpenAIToolCall) -> ToolExecution {
+        let start = Instant::now();
+        let name = call.function.name.clone();
+        let outcome = self.run(call).await;
+        ToolExecution {
+            tool_call_id: call.id.clone(),
+            name,
+            outcome,
+            duration_ms: start.elapsed().as_millis() as u64,
+        }
+    }
+
+    async fn run(&self, call: &OpenAIToolCall) -> ToolOutcome {
+        let name = &call.function.name;
+        let error = |kind, message: String| ToolOutcome::Error { kind, message };
+        let Some(handler) = self.handlers.get(name).cloned() else {
+            return error(
+                ToolErrorKind::UnknownTool,
+                format!("no tool named '{name}' is available"),
+            );
+        };
+        let raw = call.function.arguments.trim();
+        let arguments = if raw.is_empty() {
+            json!({})
+        } else {
+            match serde_json::from_str(raw) {
+                Ok(arguments) => arguments,
+                Err(err) => {
+                    return error(
+                        ToolErrorKind::InvalidArguments,
+                        format!("arguments are not valid JSON: {err}"),
+                    )
+                }
+            }
+        };
+
+        let timeout = self
+            .timeouts
+            .get(name)
+            .copied()
+            .unwrap_or(self.default_timeout);
+        let task = AbortOnDrop(tokio::spawn(async move { handler(arguments).await }));
+        match tokio::time::timeout(timeout, task).await {
+            Ok(Ok(Ok(output))) => ToolOutcome::Success { output },
+            Ok(Ok(Err(err))) => error(ToolErrorKind::Failed, err.to_string()),
+            Ok(Err(join)) if join.is_panic() => {
+                let payload = join.into_panic();
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|text| text.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                error(
+                    ToolErrorKind::Panicked,
+                    format!("tool '{name}' panicked: {message}"),
+                )
+            }
+            Ok(Err(join)) => error(ToolErrorKind::Failed, join.to_string()),
+            Err(_) => error(
+                ToolErrorKind::Timeout,
+                format!(
+                    "tool '{name}' did not finish within {} ms",
+                    timeout.as_millis()
+                ),
+            ),
+        }
+    }
+}
+
+/// Spawned handler task that is aborted when dropped, so a timed-out or cancelled
+/// [`ToolExecutor::execute`] does not leave tools running in the background.
+struct AbortOnDrop<T>(tokio::task::JoinHandle<T>);
+
+impl<T> Future for AbortOnDrop<T> {
+    type Output = std::result::Result<T, tokio::task::JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
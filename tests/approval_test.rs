@@ -0,0 +1,172 @@
+use rainy_sdk::{
+    ApprovalDecision, ApprovalPolicy, ApprovalStatus, Conversation, OpenAIChatMessage,
+    OpenAIMessageContent, OpenAIToolCall, PendingApproval, RainyError, ToolApprovals,
+};
+use serde_json::json;
+
+fn call(id: &str, name: &str, arguments: &str) -> OpenAIToolCall {
+    serde_json::from_value(json!({
+        "id": id,
+        "type": "function",
+        "function": {"name": name, "arguments": arguments}
+    }))
+    .unwrap()
+}
+
+fn calls() -> Vec<OpenAIToolCall> {
+    vec![
+        call("call_1", "read_file", r#"{"path":"a.txt"}"#),
+        call("call_2", "delete_file", r#"{"path":"/"}"#),
+        call("call_3", "write_file", r#"{"path":"b.txt","text":"hi"}"#),
+    ]
+}
+
+fn code(error: RainyError) -> String {
+    error.code().unwrap_or_default().to_string()
+}
+
+#[test]
+fn test_policy_auto_approves_by_tool_name() {
+    let policy = ApprovalPolicy::new().with_auto_approve("read_file");
+    assert!(!policy.requires_approval("read_file"));
+    assert!(policy.requires_approval("write_file"));
+
+    let relaxed = ApprovalPolicy::auto_approve_all().with_required("delete_file");
+    assert!(!relaxed.requires_approval("write_file"));
+    assert!(relaxed.requires_approval("delete_file"));
+
+    let approvals = ToolApprovals::new(&calls(), &policy);
+    let statuses: Vec<&ApprovalStatus> = approvals.calls().iter().map(|c| &c.status).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            &ApprovalStatus::AutoApproved,
+            &ApprovalStatus::Pending,
+            &ApprovalStatus::Pending
+        ]
+    );
+    assert_eq!(approvals.calls()[1].arguments, json!({"path": "/"}));
+}
+
+#[test]
+fn test_decisions_apply_edits_and_denials() {
+    let mut approvals = ToolApprovals::new(&calls(), &ApprovalPolicy::new());
+    approvals
+        .resolve(
+            "call_2",
+            ApprovalDecision::Deny {
+                reason: "refusing to delete the root".to_string(),
+            },
+        )
+        .unwrap();
+    approvals
+        .resolve(
+            "call_3",
+            ApprovalDecision::Edit {
+                arguments: json!({"path": "b.txt", "text": "hello"}),
+            },
+        )
+        .unwrap();
+    assert!(!approvals.is_resolved());
+    approvals
+        .resolve("call_1", ApprovalDecision::Approve)
+        .unwrap();
+    assert!(approvals.is_resolved());
+
+    let err = approvals
+        .resolve("call_1", ApprovalDecision::Approve)
+        .unwrap_err();
+    assert_eq!(code(err), "APPROVAL_ALREADY_RESOLVED");
+    let err = approvals
+        .resolve("call_9", ApprovalDecision::Approve)
+        .unwrap_err();
+    assert_eq!(code(err), "UNKNOWN_TOOL_CALL");
+
+    let approved = approvals.approved_calls();
+    assert_eq!(approved.len(), 2);
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&approved[1].function.arguments).unwrap(),
+        json!({"path": "b.txt", "text": "hello"})
+    );
+    let denials = approvals.denial_messages();
+    assert_eq!(denials[0].tool_call_id.as_deref(), Some("call_2"));
+    let Some(OpenAIMessageContent::Text(text)) = &denials[0].content else {
+        panic!("expected text content");
+    };
+    assert!(text.contains("refusing to delete the root"));
+}
+
+#[test]
+fn test_hook_reviews_every_pending_call() {
+    let mut approvals = ToolApprovals::new(
+        &calls(),
+        &ApprovalPolicy::new().with_auto_approve("read_file"),
+    );
+    let hook = |pending: &PendingApproval| {
+        if pending.name() == "delete_file" {
+            ApprovalDecision::Deny {
+                reason: "destructive".to_string(),
+            }
+        } else {
+            ApprovalDecision::Approve
+        }
+    };
+    approvals.review(&hook);
+
+    assert!(approvals.is_resolved());
+    assert_eq!(approvals.calls()[0].status, ApprovalStatus::AutoApproved);
+    assert_eq!(
+        approvals.calls()[1].status,
+        ApprovalStatus::Denied {
+            reason: "destructive".to_string()
+        }
+    );
+    assert_eq!(approvals.calls()[2].status, ApprovalStatus::Approved);
+}
+
+#[test]
+fn test_conversation_approvals_survive_serialization() {
+    let mut conversation = Conversation::new("gpt-5");
+    conversation.push_user("Clean up");
+    assert!(conversation
+        .request_approvals(&ApprovalPolicy::new())
+        .is_none());
+    conversation.push(OpenAIChatMessage::assistant_with_tool_calls(calls()));
+    conversation.request_approvals(&ApprovalPolicy::new().with_auto_approve("read_file"));
+
+    let err = conversation.take_approved_calls().unwrap_err();
+    assert_eq!(code(err), "APPROVALS_PENDING");
+
+    // The operator answers from another process.
+    let saved = serde_json::to_string(&conversation).unwrap();
+    let mut restored: Conversation = serde_json::from_str(&saved).unwrap();
+    let approvals = restored.approvals_mut().unwrap();
+    assert_eq!(approvals.pending().count(), 2);
+    approvals
+        .resolve(
+            "call_2",
+            ApprovalDecision::Deny {
+                reason: "no".to_string(),
+            },
+        )
+        .unwrap();
+    approvals
+        .resolve(
+            "call_3",
+            ApprovalDecision::Edit {
+                arguments: json!({"path": "c.txt"}),
+            },
+        )
+        .unwrap();
+
+    let approved = restored.take_approved_calls().unwrap();
+    let ids: Vec<&str> = approved.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, vec!["call_1", "call_3"]);
+    assert!(restored.approvals().is_none());
+
+    let messages = restored.messages();
+    assert_eq!(messages.len(), 3);
+    let replayed = messages[1].tool_calls.as_ref().unwrap();
+    assert_eq!(replayed[2].function.arguments, r#"{"path":"c.txt"}"#);
+    assert_eq!(messages[2].tool_call_id.as_deref(), Some("call_2"));
+}
@@ -0,0 +1,256 @@
+mod common;
+
+use common::{chat_body, mock_client, mock_server};
+use mockito::Matcher;
+use rainy_sdk::{
+    read_batch_lines, BatchRequestLine, BatchResultLine, BatchStats, OpenAIChatCompletionRequest,
+    OpenAIChatMessage, RainyClient, RainyError,
+};
+use serde_json::json;
+use std::path::PathBuf;
+
+fn client() -> RainyClient {
+    RainyClient::with_api_key("ra-0123456789abcdef0123456789abcdef0123456789abcdef").unwrap()
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rainy-batch-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Lines targeting an unsupported endpoint fail locally, without a network call.
+fn embeddings_line(id: &str) -> String {
+    format!(
+        r#"{{"custom_id":"{id}","method":"POST","url":"/v1/embeddings","body":{{"model":"gpt-5","messages":[]}}}}"#
+    )
+}
+
+#[test]
+fn test_reads_openai_batch_lines_with_defaults() {
+    let dir = temp_dir("read");
+    let path = dir.join("input.jsonl");
+    std::fs::write(
+        &path,
+        r#"{"custom_id":"a","body":{"model":"gpt-5","messages":[{"role":"user","content":"Hi"}]}}
+
+{"custom_id":"b","method":"POST","url":"/v1/chat/completions","body":{"model":"gpt-5","messages":[]}}
+"#,
+    )
+    .unwrap();
+    let lines = read_batch_lines(&path).unwrap();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].method, "POST");
+    assert_eq!(lines[0].url, "/v1/chat/completions");
+    assert_eq!(lines[0].body.messages.len(), 1);
+
+    std::fs::write(&path, "{\"custom_id\":\"a\"}\nnot json\n").unwrap();
+    let err = read_batch_lines(&path).unwrap_err();
+    assert!(err.to_string().contains("line 1"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_run_returns_results_in_input_order_with_errors() {
+    let mut lines: Vec<BatchRequestLine> = (0..5)
+        .map(|i| serde_json::from_str(&embeddings_line(&format!("line-{i}"))).unwrap())
+        .collect();
+    lines.push(BatchRequestLine::new(
+        "gemini",
+        OpenAIChatCompletionRequest::new(
+            "gemini-3-pro-preview",
+            vec![
+                OpenAIChatMessage::user("Hi"),
+                OpenAIChatMessage::assistant_with_tool_calls(vec![serde_json::from_value(
+                    serde_json::json!({"id": "call_1", "type": "function",
+                        "function": {"name": "f", "arguments": "{}"}}),
+                )
+                .unwrap()]),
+                OpenAIChatMessage::tool("call_1", "ok"),
+            ],
+        ),
+    ));
+
+    let client = client();
+    let report = client
+        .batch_runner()
+        .with_concurrency(3)
+        .run(lines)
+        .await
+        .unwrap();
+
+    let ids: Vec<&str> = report
+        .results
+        .iter()
+        .map(|r| r.custom_id.as_str())
+        .collect();
+    assert_eq!(
+        ids,
+        vec!["line-0", "line-1", "line-2", "line-3", "line-4", "gemini"]
+    );
+    assert_eq!(
+        report.results[0].error.as_ref().unwrap().code,
+        "UNSUPPORTED_BATCH_ENDPOINT"
+    );
+    assert_eq!(
+        report.results[5].error.as_ref().unwrap().code,
+        "MISSING_THOUGHT_SIGNATURE"
+    );
+    assert_eq!(report.stats.completed, 6);
+    assert_eq!(report.stats.failed, 6);
+    assert_eq!(report.stats.succeeded, 0);
+}
+
+#[tokio::test]
+async fn test_jsonl_run_checkpoints_and_resumes() {
+    let dir = temp_dir("resume");
+    let input = dir.join("input.jsonl");
+    let output = dir.join("output.jsonl");
+    let errors = dir.join("errors.jsonl");
+    let checkpoint = dir.join("batch.checkpoint");
+    let first: Vec<String> = (0..3).map(|i| embeddings_line(&format!("r{i}"))).collect();
+    std::fs::write(&input, first.join("\n")).unwrap();
+
+    let client = client();
+    let runner = client
+        .batch_runner()
+        .with_checkpoint(&checkpoint)
+        .with_error_output(&errors);
+    let stats = runner.run_jsonl(&input, &output).await.unwrap();
+    assert_eq!((stats.completed, stats.skipped, stats.failed), (3, 0, 3));
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "");
+    assert_eq!(std::fs::read_to_string(&errors).unwrap().lines().count(), 3);
+    assert_eq!(
+        std::fs::read_to_string(&checkpoint)
+            .unwrap()
+            .lines()
+            .count(),
+        3
+    );
+
+    let mut extended = first.clone();
+    extended.push(embeddings_line("r3"));
+    std::fs::write(&input, extended.join("\n")).unwrap();
+    let stats = runner.run_jsonl(&input, &output).await.unwrap();
+    assert_eq!((stats.completed, stats.skipped), (1, 3));
+    let error_lines = std::fs::read_to_string(&errors).unwrap();
+    assert_eq!(error_lines.lines().count(), 4);
+    assert!(error_lines
+        .lines()
+        .last()
+        .unwrap()
+        .contains("\"custom_id\":\"r3\""));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_rejects_duplicate_custom_ids_before_sending() {
+    let lines: Vec<BatchRequestLine> = ["a", "b", "a"]
+        .iter()
+        .map(|id| serde_json::from_str(&embeddings_line(id)).unwrap())
+        .collect();
+    let err = client().batch_runner().run(lines).await.unwrap_err();
+    match err {
+        RainyError::InvalidRequest { code, message, .. } => {
+            assert_eq!(code, "DUPLICATE_CUSTOM_ID");
+            assert!(message.contains("custom_id a "));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_mock_run_aggregates_metadata_and_retries_transient_failures() {
+    let mut server = mock_server().await;
+    let ok = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({"model": "ok/model"})))
+        .with_header("x-request-id", "req-1")
+        .with_header("x-rainy-credits-charged", "0.25")
+        .with_body(chat_body("ok/model", "done", 10, 5))
+        .expect(1)
+        .create_async()
+        .await;
+    let busy = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({"model": "busy/model"})))
+        .with_status(503)
+        .with_body("overloaded")
+        .expect(2)
+        .create_async()
+        .await;
+
+    let dir = temp_dir("mock");
+    let input = dir.join("input.jsonl");
+    let output = dir.join("output.jsonl");
+    let checkpoint = dir.join("batch.checkpoint");
+    let lines: Vec<String> = [("ok", "ok/model"), ("busy", "busy/model")]
+        .iter()
+        .map(|(id, model)| {
+            json!({
+                "custom_id": id,
+                "body": {"model": model, "messages": [{"role": "user", "content": "Hi"}]}
+            })
+            .to_string()
+        })
+        .collect();
+    std::fs::write(&input, lines.join("\n")).unwrap();
+
+    let client = mock_client(&server);
+    let runner = client
+        .batch_runner()
+        .with_concurrency(1)
+        .with_checkpoint(&checkpoint);
+    let stats = runner.run_jsonl(&input, &output).await.unwrap();
+    assert_eq!((stats.succeeded, stats.failed), (1, 1));
+    assert_eq!(stats.credits_charged, 0.25);
+    assert_eq!((stats.prompt_tokens, stats.completion_tokens), (10, 5));
+
+    let results = std::fs::read_to_string(&output).unwrap();
+    let ok_line = results
+        .lines()
+        .find(|line| line.contains("\"custom_id\":\"ok\""))
+        .unwrap();
+    assert!(ok_line.contains("\"request_id\":\"req-1\""));
+    // Only the success is checkpointed; the 503 is retryable.
+    assert_eq!(std::fs::read_to_string(&checkpoint).unwrap(), "ok\n");
+
+    let stats = runner.run_jsonl(&input, &output).await.unwrap();
+    assert_eq!((stats.completed, stats.skipped, stats.failed), (1, 1, 1));
+    ok.assert_async().await;
+    busy.assert_async().await;
+    // Retryable failures stay out of the results file until the line finally completes.
+    assert_eq!(std::fs::read_to_string(&output).unwrap().lines().count(), 1);
+
+    busy.remove_async().await;
+    let _recovered = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({"model": "busy/model"})))
+        .with_body(chat_body("busy/model", "done", 1, 1))
+        .create_async()
+        .await;
+    let stats = runner.run_jsonl(&input, &output).await.unwrap();
+    assert_eq!((stats.succeeded, stats.skipped), (1, 1));
+    let results = std::fs::read_to_string(&output).unwrap();
+    let lines: Vec<BatchResultLine> = results
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let ids: Vec<&str> = lines.iter().map(|line| line.custom_id.as_str()).collect();
+    assert_eq!(ids, vec!["ok", "busy"]);
+    assert!(lines.iter().all(BatchResultLine::is_success));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_stats_summarize_latency() {
+    let stats = BatchStats {
+        latencies_ms: vec![40, 10, 30, 20, 100],
+        ..Default::default()
+    };
+    assert_eq!(stats.mean_latency_ms(), Some(40.0));
+    assert_eq!(stats.latency_percentile_ms(0.5), Some(30));
+    assert_eq!(stats.latency_percentile_ms(1.0), Some(100));
+    assert_eq!(BatchStats::default().latency_percentile_ms(0.95), None);
+}
@@ -0,0 +1,291 @@
+mod common;
+
+use common::{catalog_body, catalog_item, chat_body, mock_client, mock_server, sse_body};
+use futures::StreamExt;
+use rainy_sdk::{
+    Budget, BudgetScope, BudgetTracker, OpenAIChatCompletionRequest, OpenAIChatMessage,
+    RainyClient, RainyError, RequestMetadata,
+};
+use serde_json::json;
+use std::sync::{Arc, Barrier, Mutex};
+use std::time::Duration;
+
+fn charged(credits: f64) -> RequestMetadata {
+    RequestMetadata {
+        rainy_credits_charged: Some(credits),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_client_limit_blocks_once_reached() {
+    let tracker = BudgetTracker::new(Budget::new().with_client_limit(1.0));
+
+    assert!(tracker.check(None, 0.0).is_ok());
+    tracker.record(None, &charged(0.6));
+    assert!(tracker.check(None, 0.3).is_ok());
+    assert!(tracker.check(None, 0.5).is_err());
+
+    tracker.record(None, &charged(0.4));
+    match tracker.check(None, 0.0) {
+        Err(RainyError::BudgetExceeded {
+            code, scope, limit, ..
+        }) => {
+            assert_eq!(code, "BUDGET_EXCEEDED");
+            assert_eq!(scope, "client");
+            assert_eq!(limit, 1.0);
+        }
+        other => panic!("expected BudgetExceeded, got {other:?}"),
+    }
+
+    tracker.reset();
+    assert!(tracker.check(None, 0.0).is_ok());
+}
+
+#[test]
+fn test_per_user_limits_and_overrides() {
+    let tracker = BudgetTracker::new(
+        Budget::new()
+            .with_per_user_limit(0.5)
+            .with_user_limit("vip", 5.0),
+    );
+
+    tracker.record(Some("alice"), &charged(0.5));
+    tracker.record(Some("vip"), &charged(0.5));
+
+    let err = tracker.check(Some("alice"), 0.0).unwrap_err();
+    assert_eq!(err.code(), Some("BUDGET_EXCEEDED"));
+    assert!(tracker.check(Some("vip"), 0.0).is_ok());
+    assert!(tracker.check(Some("bob"), 0.0).is_ok());
+    assert!(!err.is_retryable());
+
+    let snapshot = tracker.snapshot();
+    assert!((snapshot.total_spent - 1.0).abs() < 1e-12);
+    assert_eq!(snapshot.spent_by_user.get("alice"), Some(&0.5));
+}
+
+#[test]
+fn test_window_limit_and_credit_reserves() {
+    let tracker = BudgetTracker::new(
+        Budget::new()
+            .with_window_limit(Duration::from_secs(3600), 1.0)
+            .with_credit_reserve(10.0)
+            .with_daily_credit_reserve(1.0),
+    );
+
+    tracker.record(None, &charged(1.0));
+    assert!(tracker.check(None, 0.0).is_err());
+    assert_eq!(tracker.snapshot().spent_by_window, vec![(3600, 1.0)]);
+
+    let reserve = BudgetTracker::new(Budget::new().with_credit_reserve(10.0));
+    reserve.record(
+        None,
+        &RequestMetadata {
+            credits_remaining: Some(9.5),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        reserve.check(None, 0.0).unwrap_err().code(),
+        Some("CREDIT_RESERVE_REACHED")
+    );
+
+    let daily = BudgetTracker::new(Budget::new().with_daily_credit_reserve(1.0));
+    daily.record(
+        None,
+        &RequestMetadata {
+            rainy_daily_credits_remaining: Some("0.75".to_string()),
+            ..Default::default()
+        },
+    );
+    assert!(daily.check(None, 0.0).is_err());
+    assert_eq!(daily.snapshot().daily_credits_remaining, Some(0.75));
+}
+
+#[test]
+fn test_warning_thresholds_fire_once_when_crossed() {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let sink = warnings.clone();
+    let tracker = BudgetTracker::new(
+        Budget::new()
+            .with_client_limit(1.0)
+            .with_warning_threshold(0.5)
+            .with_warning_threshold(0.8)
+            .on_warning(move |warning| sink.lock().unwrap().push(warning.clone())),
+    );
+
+    tracker.record(None, &charged(0.4));
+    tracker.record(None, &charged(0.2));
+    tracker.record(None, &charged(0.1));
+    tracker.record(None, &charged(0.15));
+
+    let warnings = warnings.lock().unwrap();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].scope, BudgetScope::Client);
+    assert_eq!(warnings[0].threshold, 0.5);
+    assert_eq!(warnings[1].threshold, 0.8);
+}
+
+#[test]
+fn test_client_check_budget() {
+    let client = RainyClient::with_api_key("ra-0123456789abcdef0123456789abcdef0123456789abcdef")
+        .unwrap()
+        .with_budget(Budget::new().with_client_limit(0.01));
+
+    assert!(client.check_budget(None, 0.005).is_ok());
+    assert!(client.check_budget(None, 0.02).is_err());
+    assert_eq!(client.budget_snapshot().unwrap().total_spent, 0.0);
+
+    let unbudgeted =
+        RainyClient::with_api_key("ra-0123456789abcdef0123456789abcdef0123456789abcdef").unwrap();
+    assert!(unbudgeted.check_budget(None, 1_000.0).is_ok());
+    assert!(unbudgeted.budget_snapshot().is_none());
+}
+
+#[test]
+fn test_reservations_hold_worst_case_until_settled() {
+    let tracker = Arc::new(BudgetTracker::new(Budget::new().with_client_limit(1.0)));
+
+    let first = tracker.reserve(None, 0.6).expect("first hold");
+    assert_eq!(tracker.snapshot().reserved, 0.6);
+    assert!(tracker.reserve(None, 0.5).is_err());
+    assert!(tracker.check(None, 0.5).is_err());
+
+    first.settle(&charged(0.2));
+    let snapshot = tracker.snapshot();
+    assert_eq!(snapshot.reserved, 0.0);
+    assert_eq!(snapshot.total_spent, 0.2);
+
+    // A hold dropped without settling (e.g. a failed call) is released.
+    drop(tracker.reserve(None, 0.7).expect("second hold"));
+    assert_eq!(tracker.snapshot().reserved, 0.0);
+    assert_eq!(tracker.snapshot().total_spent, 0.2);
+}
+
+#[test]
+fn test_concurrent_reservations_never_overrun_the_limit() {
+    let tracker = Arc::new(BudgetTracker::new(Budget::new().with_client_limit(1.0)));
+    let barrier = Arc::new(Barrier::new(10));
+
+    let handles: Vec<_> = (0..10)
+        .map(|_| {
+            let tracker = tracker.clone();
+            let barrier = barrier.clone();
+            std::thread::spawn(move || {
+                barrier.wait();
+                tracker.reserve(None, 0.3).ok()
+            })
+        })
+        .collect();
+    let held: Vec<_> = handles
+        .into_iter()
+        .filter_map(|handle| handle.join().unwrap())
+        .collect();
+
+    assert_eq!(held.len(), 3);
+    assert!((tracker.snapshot().reserved - 0.9).abs() < 1e-9);
+}
+
+fn priced_catalog() -> String {
+    catalog_body(&[catalog_item("openai/gpt-5")
+        .context(100_000)
+        .pricing("0.001", "0.002")
+        .build()])
+}
+
+#[tokio::test]
+async fn test_client_refuses_requests_whose_worst_case_exceeds_the_budget() {
+    let mut server = mock_server().await;
+    let _catalog = server
+        .mock("GET", "/api/v1/models/catalog")
+        .with_header("content-type", "application/json")
+        .with_body(priced_catalog())
+        .create_async()
+        .await;
+    let chat = server
+        .mock("POST", "/api/v1/chat/completions")
+        .with_header("content-type", "application/json")
+        .with_header("x-rainy-credits-charged", "0.25")
+        .with_body(chat_body("openai/gpt-5", "Hi", 10, 5))
+        .expect(1)
+        .create_async()
+        .await;
+    let client = mock_client(&server).with_budget(Budget::new().with_client_limit(1.0));
+    let request = |max_tokens| {
+        OpenAIChatCompletionRequest::new("openai/gpt-5", vec![OpenAIChatMessage::user("hi")])
+            .with_max_tokens(max_tokens)
+    };
+
+    // 1000 completion tokens at 0.002 would cost 2 credits.
+    match client.create_openai_chat_completion(request(1_000)).await {
+        Err(RainyError::BudgetExceeded { .. }) => {}
+        other => panic!("expected BudgetExceeded, got {other:?}"),
+    }
+
+    client
+        .create_openai_chat_completion(request(100))
+        .await
+        .expect("completion");
+    let snapshot = client.budget_snapshot().unwrap();
+    assert_eq!(snapshot.total_spent, 0.25);
+    assert_eq!(snapshot.reserved, 0.0);
+    chat.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_stream_spend_is_recorded_from_the_usage_chunk() {
+    let mut server = mock_server().await;
+    let _catalog = server
+        .mock("GET", "/api/v1/models/catalog")
+        .with_header("content-type", "application/json")
+        .with_body(priced_catalog())
+        .create_async()
+        .await;
+    let chunk = |choices: serde_json::Value, usage: serde_json::Value| {
+        json!({
+            "id": "chatcmpl-mock",
+            "object": "chat.completion.chunk",
+            "created": 1,
+            "model": "openai/gpt-5",
+            "choices": choices,
+            "usage": usage,
+        })
+    };
+    let chat = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_body(mockito::Matcher::PartialJson(
+            json!({"stream": true, "stream_options": {"include_usage": true}}),
+        ))
+        .with_header("content-type", "text/event-stream")
+        .with_body(sse_body(&[
+            chunk(
+                json!([{"index": 0, "delta": {"role": "assistant", "content": "Hi"}}]),
+                json!(null),
+            ),
+            chunk(
+                json!([]),
+                json!({"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}),
+            ),
+        ]))
+        .create_async()
+        .await;
+    let client = mock_client(&server).with_budget(Budget::new().with_client_limit(1.0));
+
+    let request =
+        OpenAIChatCompletionRequest::new("openai/gpt-5", vec![OpenAIChatMessage::user("hi")])
+            .with_max_tokens(100);
+    let mut stream = client
+        .create_openai_chat_completion_stream(request)
+        .await
+        .expect("stream");
+    assert!(client.budget_snapshot().unwrap().reserved > 0.0);
+    while let Some(chunk) = stream.next().await {
+        chunk.expect("chunk");
+    }
+
+    // 10 prompt tokens at 0.001 plus 5 completion tokens at 0.002.
+    let snapshot = client.budget_snapshot().unwrap();
+    assert!((snapshot.total_spent - 0.02).abs() < 1e-9);
+    assert_eq!(snapshot.reserved, 0.0);
+    chat.assert_async().await;
+}
@@ -0,0 +1,119 @@
+use rainy_sdk::{ChatCompletionStreamResponse, FinishReason, OpenAIChatCompletionResponse, Usage};
+use serde_json::json;
+
+fn response() -> serde_json::Value {
+    json!({
+        "id": "chatcmpl_1",
+        "object": "chat.completion",
+        "created": 1741171200u64,
+        "model": "gpt-5",
+        "system_fingerprint": "fp_123",
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": "Hi"},
+            "finish_reason": "length",
+            "logprobs": {
+                "content": [{
+                    "token": "Hi",
+                    "logprob": -0.25,
+                    "bytes": [72, 105],
+                    "top_logprobs": [
+                        {"token": "Hi", "logprob": -0.25, "bytes": [72, 105]},
+                        {"token": "Hello", "logprob": -1.5, "bytes": null}
+                    ]
+                }],
+                "refusal": null
+            },
+            "native_finish_reason": "MAX_TOKENS"
+        }],
+        "usage": {
+            "prompt_tokens": 120,
+            "completion_tokens": 40,
+            "total_tokens": 160,
+            "prompt_tokens_details": {"cached_tokens": 100, "audio_tokens": 0},
+            "completion_tokens_details": {
+                "reasoning_tokens": 12,
+                "accepted_prediction_tokens": 3,
+                "rejected_prediction_tokens": 1
+            },
+            "cost": 0.0012
+        }
+    })
+}
+
+#[test]
+fn test_parses_logprobs_and_finish_reason() {
+    let response: OpenAIChatCompletionResponse = serde_json::from_value(response()).unwrap();
+    let choice = &response.choices[0];
+    assert_eq!(choice.finish_reason, FinishReason::Length);
+    assert!(choice.finish_reason.is_truncated());
+
+    let logprobs = choice.logprobs.as_ref().unwrap();
+    let token = &logprobs.tokens()[0];
+    assert_eq!(token.token, "Hi");
+    assert_eq!(token.bytes.as_deref(), Some(&b"Hi"[..]));
+    assert_eq!(token.top_logprobs.len(), 2);
+    assert_eq!(token.top_logprobs[1].bytes, None);
+    assert!((token.probability() - (-0.25f64).exp()).abs() < 1e-12);
+    assert_eq!(logprobs.total_logprob(), -0.25);
+}
+
+#[test]
+fn test_usage_details_and_unknown_fields_are_preserved() {
+    let response: OpenAIChatCompletionResponse = serde_json::from_value(response()).unwrap();
+    let usage = response.usage.as_ref().unwrap();
+    assert_eq!(usage.cached_tokens(), Some(100));
+    assert_eq!(usage.thoughts_token_count(), Some(12));
+    let details = usage.completion_tokens_details.as_ref().unwrap();
+    assert_eq!(details.accepted_prediction_tokens, Some(3));
+    assert_eq!(details.rejected_prediction_tokens, Some(1));
+    assert!(details.extra.is_empty());
+    assert_eq!(usage.extra["cost"], json!(0.0012));
+    assert_eq!(response.extra["system_fingerprint"], "fp_123");
+    assert_eq!(
+        response.choices[0].extra["native_finish_reason"],
+        "MAX_TOKENS"
+    );
+
+    let round_trip = serde_json::to_value(&response).unwrap();
+    assert_eq!(round_trip["system_fingerprint"], "fp_123");
+    assert_eq!(round_trip["choices"][0]["finish_reason"], "length");
+    assert_eq!(round_trip["usage"]["cost"], json!(0.0012));
+    assert!(serde_json::to_value(Usage::default())
+        .unwrap()
+        .get("prompt_tokens_details")
+        .is_none());
+}
+
+#[test]
+fn test_unknown_finish_reasons_round_trip() {
+    let reason: FinishReason = serde_json::from_value(json!("safety")).unwrap();
+    assert_eq!(reason, FinishReason::Other("safety".to_string()));
+    assert_eq!(reason.to_string(), "safety");
+    assert_eq!(serde_json::to_value(&reason).unwrap(), json!("safety"));
+    assert_eq!(FinishReason::from("tool_calls"), FinishReason::ToolCalls);
+    assert_eq!(FinishReason::ContentFilter.as_str(), "content_filter");
+}
+
+#[test]
+fn test_stream_chunks_carry_optional_finish_reason_and_logprobs() {
+    let chunk: ChatCompletionStreamResponse = serde_json::from_value(json!({
+        "id": "chatcmpl_1",
+        "object": "chat.completion.chunk",
+        "created": 1741171200u64,
+        "model": "gpt-5",
+        "choices": [
+            {"index": 0, "delta": {"content": "Hi"}, "finish_reason": null,
+             "logprobs": {"content": [{"token": "Hi", "logprob": -0.1, "top_logprobs": []}]}},
+            {"index": 1, "delta": {}, "finish_reason": "stop"}
+        ]
+    }))
+    .unwrap();
+    assert_eq!(chunk.choices[0].finish_reason, None);
+    assert_eq!(
+        chunk.choices[0].logprobs.as_ref().unwrap().tokens()[0].bytes,
+        None
+    );
+    assert_eq!(chunk.choices[1].finish_reason, Some(FinishReason::Stop));
+    assert!(chunk.choices[1].logprobs.is_none());
+}
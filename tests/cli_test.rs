@@ -0,0 +1,279 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{chat_body, API_KEY};
+use mockito::Matcher;
+use serde_json::{json, Value};
+use std::process::{Command, Output};
+
+fn rainy(args: &[&str], env: &[(&str, &str)]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_rainy"));
+    command
+        .args(args)
+        .env_remove("RAINY_API_KEY")
+        .env_remove("RAINY_BASE_URL")
+        .env_remove("RAINY_MODEL")
+        .env_remove("RAINY_ACCESS_TOKEN")
+        .env_remove("RAINY_PASSWORD");
+    for (key, value) in env {
+        command.env(key, value);
+    }
+    command.output().unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn test_help_and_version() {
+    let output = rainy(&["--help"], &[]);
+    assert!(output.status.success());
+    let usage = String::from_utf8(output.stdout).unwrap();
+    for command in [
+        "chat",
+        "responses",
+        "models select",
+        "search",
+        "keys create",
+        "usage",
+    ] {
+        assert!(usage.contains(command), "usage is missing {command}");
+    }
+
+    let output = rainy(&["--version"], &[]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().trim(),
+        format!("rainy {}", rainy_sdk::VERSION)
+    );
+}
+
+#[test]
+fn test_usage_errors_exit_with_2() {
+    let output = rainy(&["frobnicate"], &[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("unknown command 'frobnicate'"));
+
+    let output = rainy(&["chat", "--colour", "hi"], &[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("unknown option '--colour'"));
+
+    let output = rainy(&["models", "delete"], &[("RAINY_API_KEY", API_KEY)]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("unknown models subcommand 'delete'"));
+
+    let output = rainy(&["chat", "hi", "--max-tokens"], &[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("--max-tokens needs a value"));
+}
+
+#[test]
+fn test_api_commands_need_key_and_model() {
+    let output = rainy(&["health"], &[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("RAINY_API_KEY is not set"));
+
+    let output = rainy(&["chat", "hello"], &[("RAINY_API_KEY", API_KEY)]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("--model is required"));
+
+    let output = rainy(
+        &["chat", "hello", "--model", "gpt-5", "--temperature", "warm"],
+        &[("RAINY_API_KEY", API_KEY)],
+    );
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("invalid value 'warm' for --temperature"));
+
+    let output = rainy(
+        &["chat", "hello", "--model", "gpt-5"],
+        &[("RAINY_API_KEY", "not-a-key")],
+    );
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_session_commands_need_login() {
+    let dir = std::env::temp_dir().join(format!("rainy-cli-test-{}", std::process::id()));
+    let config_dir = dir.to_str().unwrap();
+
+    let output = rainy(&["keys", "list"], &[("RAINY_CONFIG_DIR", config_dir)]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("not logged in; run `rainy login`"));
+
+    let output = rainy(&["keys", "create"], &[("RAINY_CONFIG_DIR", config_dir)]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("keys create needs a NAME"));
+
+    let output = rainy(
+        &["usage", "--days", "seven"],
+        &[("RAINY_CONFIG_DIR", config_dir)],
+    );
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("invalid value 'seven' for --days"));
+    assert!(!dir.exists());
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stdout_json(output: &Output) -> Value {
+    serde_json::from_slice(&output.stdout).expect("json output")
+}
+
+#[test]
+fn test_chat_against_mock_server() {
+    let mut server = mockito::Server::new();
+    let chat = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_header("authorization", format!("Bearer {API_KEY}").as_str())
+        .match_body(Matcher::PartialJson(json!({"model": "openai/gpt-5"})))
+        .with_header("x-request-id", "req-cli")
+        .with_header("x-credits-used", "0.5")
+        .with_body(chat_body("openai/gpt-5", "Hello from the mock", 3, 4))
+        .expect(2)
+        .create();
+    let env = [
+        ("RAINY_API_KEY", API_KEY),
+        ("RAINY_BASE_URL", &server.url()),
+    ];
+
+    let output = rainy(&["chat", "hi", "--model", "openai/gpt-5"], &env);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output).trim(), "Hello from the mock");
+    assert!(stderr(&output).contains("0.5 credits"));
+
+    let output = rainy(&["chat", "hi", "--model", "openai/gpt-5", "--json"], &env);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let value = stdout_json(&output);
+    assert_eq!(
+        value["response"]["choices"][0]["message"]["content"],
+        "Hello from the mock"
+    );
+    assert_eq!(value["metadata"]["request_id"], "req-cli");
+    assert_eq!(value["metadata"]["credits_used"], 0.5);
+    chat.assert();
+}
+
+#[test]
+fn test_models_list_against_mock_server() {
+    let mut server = mockito::Server::new();
+    server
+        .mock("GET", "/api/v1/models")
+        .with_body(
+            json!({"data": {"data": [
+                {"id": "openai/gpt-5"}, {"id": "openai/gpt-5-mini"}, {"id": "google/gemini-3-pro"}
+            ]}})
+            .to_string(),
+        )
+        .create();
+    let env = [
+        ("RAINY_API_KEY", API_KEY),
+        ("RAINY_BASE_URL", &server.url()),
+    ];
+
+    let output = rainy(&["models", "list"], &env);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(text.contains("openai (2)\n  openai/gpt-5\n  openai/gpt-5-mini\n"));
+    assert!(text.contains("google (1)"));
+
+    let output = rainy(&["models", "list", "--json"], &env);
+    let value = stdout_json(&output);
+    assert_eq!(value["total_models"], 3);
+    assert_eq!(value["active_providers"], json!(["google", "openai"]));
+}
+
+#[test]
+fn test_keys_and_usage_against_mock_server() {
+    let mut server = mockito::Server::new();
+    server
+        .mock("GET", "/api/v1/keys")
+        .match_header("authorization", "Bearer acc_cli")
+        .with_body(
+            json!({"success": true, "keys": [{
+                "id": "key_1", "name": "ci", "type": "standard", "isActive": true,
+                "createdAt": "2026-01-01T00:00:00Z"
+            }]})
+            .to_string(),
+        )
+        .create();
+    server
+        .mock("GET", "/api/v1/usage/credits")
+        .match_header("authorization", "Bearer acc_cli")
+        .with_body(json!({"success": true, "balance": 12.5, "currency": "credits"}).to_string())
+        .create();
+    server
+        .mock("GET", "/api/v1/usage/stats")
+        .match_query(Matcher::UrlEncoded("days".into(), "7".into()))
+        .match_header("authorization", "Bearer acc_cli")
+        .with_body(
+            json!({"success": true, "periodDays": 7, "totalRequests": 12,
+                   "totalCreditsDeducted": 0.42})
+            .to_string(),
+        )
+        .create();
+    let env = [
+        ("RAINY_ACCESS_TOKEN", "acc_cli"),
+        ("RAINY_BASE_URL", &server.url()),
+    ];
+
+    let output = rainy(&["keys", "list"], &env);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(text.starts_with("key_1  ci"));
+    assert!(text.contains("active"));
+    let value = stdout_json(&rainy(&["keys", "list", "--json"], &env));
+    assert_eq!(value[0]["isActive"], true);
+
+    let output = rainy(&["usage", "--days", "7"], &env);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let text = stdout(&output);
+    assert!(text.contains("balance:  12.5 credits"));
+    assert!(text.contains("last 7 days: 12 requests, 0.42 credits"));
+    let value = stdout_json(&rainy(&["usage", "--days", "7", "--json"], &env));
+    assert_eq!(value["credits"]["balance"], 12.5);
+    assert_eq!(value["stats"]["totalRequests"], 12);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_login_saves_a_private_session_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut server = mockito::Server::new();
+    server
+        .mock("POST", "/api/v1/auth/login")
+        .match_body(Matcher::PartialJson(
+            json!({"email": "dev@example.com", "password": "secret"}),
+        ))
+        .with_body(
+            json!({"success": true, "accessToken": "acc_1", "refreshToken": "ref_1",
+                   "user": {"id": "u1", "email": "dev@example.com", "role": "owner"}})
+            .to_string(),
+        )
+        .create();
+    let dir = std::env::temp_dir().join(format!("rainy-cli-login-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let session = dir.join("session.json");
+    // An existing, world-readable file is tightened on save.
+    std::fs::write(&session, "{}").unwrap();
+    std::fs::set_permissions(&session, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+    let output = rainy(
+        &["login", "--email", "dev@example.com"],
+        &[
+            ("RAINY_PASSWORD", "secret"),
+            ("RAINY_BASE_URL", &server.url()),
+            ("RAINY_CONFIG_DIR", dir.to_str().unwrap()),
+        ],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Logged in as dev@example.com (owner)"));
+    let mode = std::fs::metadata(&session).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    assert!(std::fs::read_to_string(&session).unwrap().contains("acc_1"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
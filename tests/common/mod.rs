@@ -0,0 +1,133 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+use rainy_sdk::{AuthConfig, ModelCatalogItem, RainyClient};
+use serde_json::{json, Value};
+
+/// A syntactically valid standard API key.
+pub const API_KEY: &str = "ra-0123456789abcdef0123456789abcdef0123456789abcdef";
+
+/// Builder for `/models/catalog` entries.
+pub struct CatalogItem(Value);
+
+/// Starts a catalog entry for `id` with no pricing, context length or capabilities.
+pub fn catalog_item(id: &str) -> CatalogItem {
+    CatalogItem(json!({ "id": id }))
+}
+
+impl CatalogItem {
+    /// Sets `context_length`.
+    pub fn context(mut self, tokens: impl Into<Option<u32>>) -> Self {
+        let tokens: Option<u32> = tokens.into();
+        self.0["context_length"] = json!(tokens);
+        self
+    }
+
+    /// Sets the prompt and completion price per token.
+    pub fn pricing(mut self, prompt: &str, completion: &str) -> Self {
+        self.0["pricing"] = json!({ "prompt": prompt, "completion": completion });
+        self
+    }
+
+    /// Sets `architecture.tokenizer`.
+    pub fn tokenizer(mut self, tokenizer: &str) -> Self {
+        self.0["architecture"] = json!({ "tokenizer": tokenizer });
+        self
+    }
+
+    /// Sets the v2 input modalities.
+    pub fn inputs(mut self, modalities: &[&str]) -> Self {
+        self.capabilities()["multimodal"]["input"] = json!(modalities);
+        self
+    }
+
+    /// Sets the v2 output modalities.
+    pub fn outputs(mut self, modalities: &[&str]) -> Self {
+        self.capabilities()["multimodal"]["output"] = json!(modalities);
+        self
+    }
+
+    /// Sets the v2 accepted parameters.
+    pub fn accepted(mut self, parameters: &[&str]) -> Self {
+        self.capabilities()["parameters"]["accepted"] = json!(parameters);
+        self
+    }
+
+    /// Declares OpenAI-style `reasoning.effort` support with the given effort levels.
+    pub fn efforts(mut self, efforts: &[&str]) -> Self {
+        self.capabilities()["reasoning"] = json!({
+            "supported": !efforts.is_empty(),
+            "controls": { "reasoning_effort": !efforts.is_empty(), "effort": efforts },
+            "profiles": [{ "provider": "openai", "parameter_path": "reasoning.effort" }]
+        });
+        self
+    }
+
+    /// Finishes the entry.
+    pub fn build(self) -> ModelCatalogItem {
+        serde_json::from_value(self.0).expect("catalog item")
+    }
+
+    fn capabilities(&mut self) -> &mut Value {
+        let capabilities = &mut self.0["rainy_capabilities_v2"];
+        if capabilities.is_null() {
+            *capabilities = json!({
+                "multimodal": { "input": ["text"], "output": ["text"] },
+                "reasoning": { "supported": false },
+                "parameters": { "accepted": [] }
+            });
+        }
+        capabilities
+    }
+}
+
+/// Starts a mock Rainy API server.
+pub async fn mock_server() -> mockito::ServerGuard {
+    mockito::Server::new_async().await
+}
+
+/// A client pointed at `server` with retries disabled.
+pub fn mock_client(server: &mockito::ServerGuard) -> RainyClient {
+    RainyClient::with_config(
+        AuthConfig::new(API_KEY)
+            .with_base_url(server.url())
+            .with_retry(false),
+    )
+    .expect("client")
+}
+
+/// The `/api/v1/models/catalog` response body for `items`.
+pub fn catalog_body(items: &[ModelCatalogItem]) -> String {
+    json!({ "success": true, "data": { "object": "list", "data": items } }).to_string()
+}
+
+/// An OpenAI chat completion response body with a single assistant message.
+pub fn chat_body(model: &str, content: &str, prompt_tokens: u32, completion_tokens: u32) -> String {
+    json!({
+        "id": "chatcmpl-mock",
+        "object": "chat.completion",
+        "created": 1,
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "message": { "role": "assistant", "content": content },
+            "finish_reason": "stop"
+        }],
+        "usage": {
+            "prompt_tokens": prompt_tokens,
+            "completion_tokens": completion_tokens,
+            "total_tokens": prompt_tokens + completion_tokens
+        }
+    })
+    .to_string()
+}
+
+/// An SSE body carrying `events` as `data:` frames followed by `data: [DONE]`.
+pub fn sse_body(events: &[Value]) -> String {
+    let mut body = String::new();
+    for event in events {
+        body.push_str(&format!("data: {event}\n\n"));
+    }
+    body.push_str("data: [DONE]\n\n");
+    body
+}
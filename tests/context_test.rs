@@ -0,0 +1,264 @@
+mod common;
+
+use common::{catalog_body, catalog_item, chat_body, mock_client, mock_server};
+use rainy_sdk::{
+    message_groups, ChatCompletionRequest, ChatMessage, ContextGuard, OpenAIChatCompletionRequest,
+    OpenAIChatMessage, OpenAIFunctionCall, OpenAIMessageRole, OpenAIToolCall, TruncationStrategy,
+};
+
+fn tool_call(id: &str) -> OpenAIToolCall {
+    OpenAIToolCall {
+        id: id.to_string(),
+        r#type: "function".to_string(),
+        extra_content: None,
+        function: OpenAIFunctionCall {
+            name: "lookup".to_string(),
+            arguments: r#"{"query":"weather in a faraway city"}"#.to_string(),
+        },
+    }
+}
+
+fn long(text: &str) -> String {
+    text.repeat(20)
+}
+
+fn tool_history() -> Vec<OpenAIChatMessage> {
+    vec![
+        OpenAIChatMessage::system("You are a helpful assistant."),
+        OpenAIChatMessage::user(long("first question ")),
+        OpenAIChatMessage::assistant_with_tool_calls(vec![
+            tool_call("call_1"),
+            tool_call("call_2"),
+        ]),
+        OpenAIChatMessage::tool("call_1", long("result one ")),
+        OpenAIChatMessage::tool("call_2", long("result two ")),
+        OpenAIChatMessage::assistant(long("first answer ")),
+        OpenAIChatMessage::user("And now?"),
+    ]
+}
+
+#[test]
+fn test_message_groups_keep_tool_calls_with_results() {
+    let groups = message_groups(&tool_history());
+    assert_eq!(groups, vec![0..1, 1..2, 2..5, 5..6, 6..7]);
+}
+
+#[test]
+fn test_check_and_noop_when_request_fits() {
+    let mut request =
+        OpenAIChatCompletionRequest::new("model/test", tool_history()).with_max_tokens(100);
+    let guard = ContextGuard::new(TruncationStrategy::DropOldest);
+
+    let check = guard
+        .check(
+            &request,
+            &catalog_item("model/test").context(Some(100_000)).build(),
+        )
+        .unwrap();
+    assert!(check.fits());
+    assert_eq!(check.completion_tokens, 100);
+
+    let report = guard
+        .truncate(
+            &mut request,
+            &catalog_item("model/test").context(Some(100_000)).build(),
+        )
+        .unwrap();
+    assert!(!report.truncated());
+    assert_eq!(request.messages.len(), 7);
+
+    let report = guard
+        .truncate(
+            &mut request,
+            &catalog_item("model/test").context(None).build(),
+        )
+        .unwrap();
+    assert!(!report.truncated());
+    assert!(guard
+        .check(&request, &catalog_item("model/test").context(None).build())
+        .is_none());
+}
+
+#[test]
+fn test_drop_oldest_never_splits_tool_groups() {
+    let mut request =
+        OpenAIChatCompletionRequest::new("model/test", tool_history()).with_max_tokens(50);
+    let full = ContextGuard::new(TruncationStrategy::Reject)
+        .check(
+            &request,
+            &catalog_item("model/test").context(Some(1_000_000)).build(),
+        )
+        .unwrap()
+        .prompt_tokens;
+
+    // Only room for the system prompt and the last couple of turns.
+    let context = catalog_item("model/test")
+        .context(Some(full + 50 - 150))
+        .build();
+    let guard = ContextGuard::new(TruncationStrategy::DropOldest);
+    let report = guard.truncate(&mut request, &context).unwrap();
+
+    assert!(report.truncated());
+    assert!(report.final_prompt_tokens < report.original_prompt_tokens);
+    assert_eq!(request.messages[0].role, OpenAIMessageRole::System);
+    assert_eq!(
+        request.messages.last().unwrap().role,
+        OpenAIMessageRole::User
+    );
+
+    let has_call = request.messages.iter().any(|m| m.tool_calls.is_some());
+    let results = request
+        .messages
+        .iter()
+        .filter(|m| m.role == OpenAIMessageRole::Tool)
+        .count();
+    assert!(
+        (has_call && results == 2) || (!has_call && results == 0),
+        "tool calls and results must be kept or dropped together"
+    );
+    assert!(guard.check(&request, &context).unwrap().fits());
+}
+
+#[test]
+fn test_keep_system_and_last_n_turns() {
+    let mut history = tool_history();
+    history.push(OpenAIChatMessage::assistant("Now it is sunny."));
+    history.push(OpenAIChatMessage::user("Thanks!"));
+    let mut request = OpenAIChatCompletionRequest::new("model/test", history);
+    let full = ContextGuard::new(TruncationStrategy::Reject)
+        .check(
+            &request,
+            &catalog_item("model/test").context(Some(1_000_000)).build(),
+        )
+        .unwrap()
+        .prompt_tokens;
+
+    // Turns are counted from user messages, so the tool turn is kept or dropped whole.
+    let guard = ContextGuard::new(TruncationStrategy::KeepSystemAndLastN { turns: 2 });
+    let report = guard
+        .truncate(
+            &mut request,
+            &catalog_item("model/test").context(Some(full - 1)).build(),
+        )
+        .unwrap();
+
+    assert_eq!(report.dropped_messages, 5);
+    let roles: Vec<_> = request.messages.iter().map(|m| m.role.clone()).collect();
+    assert_eq!(
+        roles,
+        vec![
+            OpenAIMessageRole::System,
+            OpenAIMessageRole::User,
+            OpenAIMessageRole::Assistant,
+            OpenAIMessageRole::User,
+        ]
+    );
+}
+
+#[test]
+fn test_leading_tool_results_stay_with_their_turn() {
+    let mut history = tool_history();
+    // Start the history mid-turn, at the assistant tool call.
+    history.remove(1);
+    let mut request = OpenAIChatCompletionRequest::new("model/test", history);
+    let full = ContextGuard::new(TruncationStrategy::Reject)
+        .check(
+            &request,
+            &catalog_item("model/test").context(Some(1_000_000)).build(),
+        )
+        .unwrap()
+        .prompt_tokens;
+
+    let guard = ContextGuard::new(TruncationStrategy::DropOldest);
+    let report = guard
+        .truncate(
+            &mut request,
+            &catalog_item("model/test").context(Some(full - 1)).build(),
+        )
+        .unwrap();
+
+    assert_eq!(report.dropped_messages, 4);
+    assert!(request
+        .messages
+        .iter()
+        .all(|m| m.role != OpenAIMessageRole::Tool));
+}
+
+#[tokio::test]
+async fn test_summarize_middle_fits_the_summary_model_window() {
+    let mut server = mock_server().await;
+    let _catalog = server
+        .mock("GET", "/api/v1/models/catalog")
+        .with_header("content-type", "application/json")
+        .with_body(catalog_body(&[catalog_item("model/summary")
+            .context(400)
+            .build()]))
+        .create_async()
+        .await;
+    let summary = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_body(mockito::Matcher::PartialJson(
+            serde_json::json!({"model": "model/summary"}),
+        ))
+        .with_header("content-type", "application/json")
+        .with_body(chat_body("model/summary", "They said hello.", 40, 5))
+        .expect(1)
+        .create_async()
+        .await;
+    let client = mock_client(&server);
+
+    let mut request = OpenAIChatCompletionRequest::new(
+        "model/test",
+        vec![
+            OpenAIChatMessage::system("Be brief."),
+            OpenAIChatMessage::user("old question ".repeat(300)),
+            OpenAIChatMessage::assistant("old answer"),
+            OpenAIChatMessage::user("Hello?"),
+            OpenAIChatMessage::assistant("Hello!"),
+            OpenAIChatMessage::user("What did we say?"),
+        ],
+    );
+    let guard = ContextGuard::new(TruncationStrategy::SummarizeMiddle {
+        model: "model/summary".to_string(),
+        keep_last: 1,
+        max_summary_tokens: 100,
+    });
+    let report = guard
+        .apply(
+            &client,
+            &mut request,
+            &catalog_item("model/test").context(300).build(),
+        )
+        .await
+        .unwrap();
+
+    // The oversized first turn cannot be summarized by the small model, so it is dropped.
+    assert_eq!(report.summarized_messages, 2);
+    assert_eq!(report.dropped_messages, 2);
+    assert_eq!(request.messages.len(), 3);
+    summary.assert_async().await;
+}
+
+#[test]
+fn test_reject_and_unfixable_requests_fail() {
+    let mut request =
+        ChatCompletionRequest::new("model/test", vec![ChatMessage::user(long("too long "))])
+            .with_max_tokens(10);
+
+    let err = ContextGuard::new(TruncationStrategy::Reject)
+        .truncate(
+            &mut request,
+            &catalog_item("model/test").context(Some(20)).build(),
+        )
+        .unwrap_err();
+    assert_eq!(err.code(), Some("CONTEXT_LENGTH_EXCEEDED"));
+
+    let err = ContextGuard::new(TruncationStrategy::DropOldest)
+        .truncate(
+            &mut request,
+            &catalog_item("model/test").context(Some(20)).build(),
+        )
+        .unwrap_err();
+    assert_eq!(err.code(), Some("CONTEXT_LENGTH_EXCEEDED"));
+    assert_eq!(request.messages.len(), 1);
+}
@@ -0,0 +1,234 @@
+mod common;
+
+use common::{chat_body, mock_client, mock_server, sse_body};
+use futures::StreamExt;
+use rainy_sdk::{
+    ChatCompletionStreamResponse, Conversation, FinishReason, OpenAIChatCompletionRequest,
+    OpenAIChatMessage, OpenAIMessageContent, OpenAIMessageRole, StreamAccumulator,
+};
+use serde_json::json;
+
+fn chunk(value: serde_json::Value) -> ChatCompletionStreamResponse {
+    serde_json::from_value(value).expect("valid chunk")
+}
+
+fn history() -> Conversation {
+    let mut conversation = Conversation::new("google/gemini-3-flash-preview");
+    conversation.push_user("What's the weather in Lima?");
+    conversation.push(OpenAIChatMessage::assistant("Sunny.").with_reasoning("Checked memory."));
+    conversation.push_user("And tomorrow?");
+    conversation.push(OpenAIChatMessage::assistant("Cloudy."));
+    conversation
+}
+
+#[test]
+fn test_build_request_uses_template_and_system_prompt() {
+    let template =
+        OpenAIChatCompletionRequest::new("openai/gpt-5", vec![OpenAIChatMessage::user("ignored")])
+            .with_temperature(0.2)
+            .with_max_tokens(64);
+    let mut conversation = Conversation::with_request(template).with_system_prompt("Be terse.");
+    conversation.push_user("Hi");
+
+    let request = conversation.build_request();
+    assert_eq!(conversation.model(), "openai/gpt-5");
+    assert_eq!(request.temperature, Some(0.2));
+    assert_eq!(request.max_tokens, Some(64));
+    assert_eq!(request.messages.len(), 2);
+    assert_eq!(request.messages[0].role, OpenAIMessageRole::System);
+    assert_eq!(request.messages[1], OpenAIChatMessage::user("Hi"));
+}
+
+#[test]
+fn test_undo_fork_and_edit() {
+    let mut conversation = history();
+
+    let fork = conversation.fork_at(2);
+    assert_eq!(fork.messages().len(), 2);
+    assert_eq!(
+        fork.last_reply().and_then(|m| m.reasoning.as_deref()),
+        Some("Checked memory.")
+    );
+
+    let removed = conversation.undo().expect("user message");
+    assert_eq!(removed.len(), 2);
+    assert_eq!(conversation.messages().len(), 2);
+
+    conversation
+        .edit(0, "What's the weather in Cusco?")
+        .unwrap();
+    assert_eq!(conversation.messages().len(), 1);
+    assert_eq!(
+        conversation.messages()[0].content,
+        Some(OpenAIMessageContent::Text(
+            "What's the weather in Cusco?".to_string()
+        ))
+    );
+
+    conversation.push(OpenAIChatMessage::assistant("Rainy."));
+    let err = conversation.edit(1, "nope").unwrap_err();
+    assert_eq!(err.code(), Some("INVALID_MESSAGE_INDEX"));
+
+    assert!(Conversation::new("m").undo().is_none());
+}
+
+#[test]
+fn test_serde_round_trip_keeps_turns_in_sync() {
+    let mut conversation: Conversation = serde_json::from_value(json!({
+        "system_prompt": "Be terse.",
+        "messages": [
+            {"role": "user", "content": "Hi"},
+            {"role": "assistant", "content": "Hello", "reasoning": "greeting"},
+            {"role": "user", "content": "Bye"},
+            {"role": "assistant", "content": "Bye"}
+        ],
+        "turns": [
+            {"message_index": 1, "model": "m", "latency_ms": 10, "completed_at": "2026-01-01T00:00:00Z"},
+            {"message_index": 3, "model": "m", "latency_ms": 12, "completed_at": "2026-01-01T00:00:05Z"}
+        ],
+        "template": {"model": "m", "messages": []}
+    }))
+    .expect("conversation");
+
+    let saved = serde_json::to_value(&conversation).unwrap();
+    let restored: Conversation = serde_json::from_value(saved).unwrap();
+    assert_eq!(restored.messages(), conversation.messages());
+    assert_eq!(restored.system_prompt(), Some("Be terse."));
+    assert_eq!(restored.turns().len(), 2);
+
+    conversation.undo();
+    assert_eq!(conversation.turns().len(), 1);
+    assert_eq!(conversation.turns()[0].message_index, 1);
+}
+
+#[test]
+fn test_stream_accumulator_merges_content_reasoning_and_tool_calls() {
+    let mut accumulator = StreamAccumulator::new();
+    for value in [
+        json!({"id": "c1", "object": "chat.completion.chunk", "created": 1, "model": "m",
+               "choices": [{"index": 0, "delta": {"role": "assistant", "reasoning": "Need "}}]}),
+        json!({"id": "c1", "object": "chat.completion.chunk", "created": 1, "model": "m",
+               "choices": [{"index": 0, "delta": {"thought": "weather.", "content": "Let me check."}}]}),
+        json!({"id": "c1", "object": "chat.completion.chunk", "created": 1, "model": "m",
+               "choices": [{"index": 0, "delta": {"tool_calls": [
+                   {"index": 0, "id": "call_1", "type": "function",
+                    "function": {"name": "get_weather", "arguments": "{\"ci"}}]}}]}),
+        json!({"id": "c1", "object": "chat.completion.chunk", "created": 1, "model": "m",
+               "choices": [{"index": 0, "delta": {"tool_calls": [
+                   {"index": 0, "function": {"name": "get_weather", "arguments": "ty\":\"Lima\"}"}}]},
+                   "finish_reason": "tool_calls"}],
+               "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}}),
+    ] {
+        accumulator.push(&chunk(value));
+    }
+
+    assert_eq!(accumulator.finish_reason(), Some(&FinishReason::ToolCalls));
+    assert_eq!(accumulator.usage().map(|u| u.total_tokens), Some(15));
+
+    let message = accumulator.message();
+    assert_eq!(message.role, OpenAIMessageRole::Assistant);
+    assert_eq!(message.reasoning.as_deref(), Some("Need weather."));
+    assert_eq!(
+        message.content,
+        Some(OpenAIMessageContent::Text("Let me check.".to_string()))
+    );
+    let calls = message.tool_calls.expect("tool calls");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].id, "call_1");
+    assert_eq!(calls[0].function.name, "get_weather");
+    assert_eq!(calls[0].function.arguments, r#"{"city":"Lima"}"#);
+}
+
+#[tokio::test]
+async fn test_send_appends_the_reply_with_request_metadata() {
+    let mut server = mock_server().await;
+    let chat = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_body(mockito::Matcher::PartialJson(json!({
+            "messages": [{"role": "user", "content": "Hi"}]
+        })))
+        .with_header("content-type", "application/json")
+        .with_header("x-request-id", "req-send")
+        .with_header("x-rainy-credits-charged", "0.5")
+        .with_body(chat_body("openai/gpt-5", "Hello!", 3, 2))
+        .create_async()
+        .await;
+    let client = mock_client(&server);
+    let mut conversation = Conversation::new("openai/gpt-5");
+
+    let reply = conversation.send(&client, "Hi").await.expect("reply");
+    assert_eq!(reply, &OpenAIChatMessage::assistant("Hello!"));
+    let turn = &conversation.turns()[0];
+    assert_eq!(turn.message_index, 1);
+    assert_eq!(turn.request_id.as_deref(), Some("req-send"));
+    assert_eq!(turn.credits_charged, Some(0.5));
+    chat.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_send_stream_records_request_metadata_on_the_turn() {
+    let mut server = mock_server().await;
+    let _chat = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_body(mockito::Matcher::PartialJson(json!({"stream": true})))
+        .with_header("content-type", "text/event-stream")
+        .with_header("x-request-id", "req-stream")
+        .with_header("x-rainy-credits-charged", "0.25")
+        .with_body(sse_body(&[
+            json!({
+                "id": "chatcmpl-1", "object": "chat.completion.chunk", "created": 1,
+                "model": "openai/gpt-5",
+                "choices": [{"index": 0, "delta": {"role": "assistant", "content": "Hel"}}]
+            }),
+            json!({
+                "id": "chatcmpl-1", "object": "chat.completion.chunk", "created": 1,
+                "model": "openai/gpt-5",
+                "choices": [{"index": 0, "delta": {"content": "lo"}, "finish_reason": "stop"}]
+            }),
+        ]))
+        .create_async()
+        .await;
+    let client = mock_client(&server);
+    let mut conversation = Conversation::new("openai/gpt-5");
+
+    let mut stream = conversation
+        .send_stream(&client, "Hi")
+        .await
+        .expect("stream");
+    while let Some(chunk) = stream.next().await {
+        chunk.expect("chunk");
+    }
+    drop(stream);
+
+    assert_eq!(
+        conversation.last_reply(),
+        Some(&OpenAIChatMessage::assistant("Hello"))
+    );
+    let turn = &conversation.turns()[0];
+    assert_eq!(turn.response_id.as_deref(), Some("chatcmpl-1"));
+    assert_eq!(turn.finish_reason, Some(FinishReason::Stop));
+    assert_eq!(turn.request_id.as_deref(), Some("req-stream"));
+    assert_eq!(turn.credits_charged, Some(0.25));
+}
+
+#[tokio::test]
+async fn test_edit_and_resend_restores_history_when_the_call_fails() {
+    let mut server = mock_server().await;
+    let _chat = server
+        .mock("POST", "/api/v1/chat/completions")
+        .with_status(500)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"error": {"code": "INTERNAL", "message": "down"}}"#)
+        .create_async()
+        .await;
+    let client = mock_client(&server);
+    let mut conversation = history();
+    let before = conversation.clone();
+
+    assert!(conversation
+        .edit_and_resend(&client, 0, "What's the weather in Quito?")
+        .await
+        .is_err());
+    assert_eq!(conversation.messages(), before.messages());
+    assert_eq!(conversation.turns().len(), before.turns().len());
+}
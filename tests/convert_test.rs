@@ -0,0 +1,207 @@
+use rainy_sdk::{
+    responses_items_to_openai_messages, ChatCompletionRequest, ChatMessage, FunctionDefinition,
+    OpenAIChatCompletionRequest, OpenAIChatMessage, OpenAIContentPart, OpenAIFunctionCall,
+    OpenAIImageUrl, OpenAIMessageContent, OpenAIMessageRole, OpenAIToolCall, ResponseFormat,
+    ResponsesApiResponse, Tool, ToolChoice, ToolFunction, ToolType,
+};
+use serde_json::json;
+
+fn weather_tool() -> Tool {
+    Tool {
+        r#type: ToolType::Function,
+        function: FunctionDefinition {
+            name: "get_weather".to_string(),
+            description: Some("Current weather".to_string()),
+            parameters: Some(json!({"type": "object", "properties": {"city": {"type": "string"}}})),
+        },
+    }
+}
+
+#[test]
+fn test_chat_request_converts_losslessly() {
+    let request = ChatCompletionRequest::new(
+        "gpt-5",
+        vec![ChatMessage::system("Be brief."), ChatMessage::user("Hi")],
+    )
+    .with_max_tokens(64);
+
+    let (openai, report) = request.to_openai();
+    assert!(report.is_lossless());
+    assert_eq!(openai.max_tokens, Some(64));
+    assert_eq!(openai.messages[0].role, OpenAIMessageRole::System);
+    assert!(matches!(
+        openai.messages[1].content,
+        Some(OpenAIMessageContent::Text(ref text)) if text == "Hi"
+    ));
+}
+
+#[test]
+fn test_openai_request_maps_to_responses_and_reports_dropped_fields() {
+    let call = OpenAIToolCall {
+        id: "call_1".to_string(),
+        r#type: "function".to_string(),
+        extra_content: None,
+        function: OpenAIFunctionCall {
+            name: "get_weather".to_string(),
+            arguments: r#"{"city":"Lima"}"#.to_string(),
+        },
+    }
+    .with_thought_signature("sig");
+    let request = OpenAIChatCompletionRequest::new(
+        "gemini-3-pro-preview",
+        vec![
+            OpenAIChatMessage::user("Weather in Lima?"),
+            OpenAIChatMessage::assistant_with_tool_calls(vec![call]).with_reasoning("Look it up."),
+            OpenAIChatMessage::tool("call_1", r#"{"temp":22}"#),
+        ],
+    )
+    .with_max_tokens(128)
+    .with_frequency_penalty(0.5)
+    .with_stop(vec!["END".to_string()])
+    .with_tools(vec![weather_tool()])
+    .with_tool_choice(ToolChoice::Tool {
+        r#type: ToolType::Function,
+        function: ToolFunction {
+            name: "get_weather".to_string(),
+        },
+    });
+
+    let (responses, report) = request.to_responses();
+    assert_eq!(responses.max_output_tokens, Some(128));
+    assert_eq!(
+        report.dropped,
+        vec![
+            "messages[1].reasoning",
+            "messages[1].tool_calls[0].extra_content",
+            "frequency_penalty",
+            "stop"
+        ]
+    );
+
+    let tools = responses.tools.unwrap();
+    assert_eq!(tools[0]["name"], "get_weather");
+    assert_eq!(tools[0]["parameters"]["type"], "object");
+    assert_eq!(
+        responses.tool_choice,
+        Some(json!({"type": "function", "name": "get_weather"}))
+    );
+
+    let items = responses.input.as_array().unwrap();
+    let kinds: Vec<&str> = items.iter().map(|i| i["type"].as_str().unwrap()).collect();
+    assert_eq!(
+        kinds,
+        vec!["message", "function_call", "function_call_output"]
+    );
+    assert!(items[1].get("extra_content").is_none());
+    assert_eq!(items[2]["call_id"], "call_1");
+}
+
+#[test]
+fn test_response_format_uses_the_flat_text_format_shape() {
+    let schema = json!({
+        "name": "weather",
+        "strict": true,
+        "schema": {"type": "object", "properties": {"temp": {"type": "number"}}}
+    });
+    let request = OpenAIChatCompletionRequest::new("gpt-5", vec![OpenAIChatMessage::user("Hi")])
+        .with_response_format(ResponseFormat::JsonSchema {
+            json_schema: schema,
+        });
+
+    let (responses, _) = request.to_responses();
+    assert!(responses.response_format.is_none());
+    assert_eq!(
+        responses.extra["text"],
+        json!({"format": {
+            "type": "json_schema",
+            "name": "weather",
+            "strict": true,
+            "schema": {"type": "object", "properties": {"temp": {"type": "number"}}}
+        }})
+    );
+}
+
+#[test]
+fn test_tool_results_report_non_text_parts_and_join_text_like_the_chat_endpoint() {
+    let mut result = OpenAIChatMessage::tool("call_1", "");
+    result.content = Some(OpenAIMessageContent::Parts(vec![
+        OpenAIContentPart::Text {
+            text: "Sunny, ".to_string(),
+        },
+        OpenAIContentPart::ImageUrl {
+            image_url: OpenAIImageUrl {
+                url: "https://example.com/map.png".to_string(),
+                detail: None,
+            },
+        },
+        OpenAIContentPart::Text {
+            text: "22°C".to_string(),
+        },
+    ]));
+    let request = OpenAIChatCompletionRequest::new("gpt-5", vec![result]);
+
+    let (responses, report) = request.to_responses();
+    assert_eq!(responses.input[0]["output"], "Sunny, 22°C");
+    assert_eq!(report.dropped, vec!["messages[0].content[1]"]);
+}
+
+#[test]
+fn test_responses_output_round_trips_to_openai_messages() {
+    let response: ResponsesApiResponse = serde_json::from_value(json!({
+        "id": "resp_1",
+        "output": [
+            {"type": "reasoning", "summary": [{"type": "summary_text", "text": "Need weather."}]},
+            {"type": "function_call", "call_id": "call_9", "name": "get_weather",
+             "arguments": "{\"city\":\"Lima\"}"},
+            {"type": "web_search_call", "id": "ws_1"}
+        ]
+    }))
+    .unwrap();
+
+    let (messages, report) = response.to_openai_messages();
+    assert_eq!(report.dropped, vec!["items[2] (web_search_call)"]);
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].role, OpenAIMessageRole::Assistant);
+    assert_eq!(messages[0].reasoning.as_deref(), Some("Need weather."));
+    assert_eq!(messages[0].tool_calls.as_ref().unwrap()[0].id, "call_9");
+}
+
+#[test]
+fn test_responses_input_items_convert_back_to_the_same_history() {
+    let request = OpenAIChatCompletionRequest::new(
+        "gpt-5",
+        vec![
+            OpenAIChatMessage::system("Be brief."),
+            OpenAIChatMessage::user("Hi"),
+            OpenAIChatMessage::assistant("Hello!"),
+        ],
+    );
+    let (responses, _) = request.to_responses();
+    let (messages, report) =
+        responses_items_to_openai_messages(responses.input.as_array().unwrap());
+
+    assert!(report.is_lossless());
+    assert_eq!(
+        serde_json::to_value(&messages).unwrap(),
+        serde_json::to_value(&request.messages).unwrap()
+    );
+}
+
+#[test]
+fn test_generated_images_are_reported_as_dropped() {
+    let mut reply = OpenAIChatMessage::assistant("Here is your cat.");
+    reply.images = Some(vec![OpenAIContentPart::ImageUrl {
+        image_url: OpenAIImageUrl {
+            url: "data:image/png;base64,iVBORw0KGgo=".to_string(),
+            detail: None,
+        },
+    }]);
+    let request = OpenAIChatCompletionRequest::new(
+        "gpt-5",
+        vec![OpenAIChatMessage::user("Draw a cat"), reply],
+    );
+
+    let (responses, report) = request.to_responses();
+    assert_eq!(report.dropped, vec!["messages[1].images"]);
+    assert!(!responses.input.to_string().contains("data:image/png"));
+}
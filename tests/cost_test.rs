@@ -0,0 +1,141 @@
+mod common;
+
+use common::{catalog_body, catalog_item, chat_body, mock_client, mock_server};
+use rainy_sdk::{
+    actual_cost, estimate_cost, reconcile_cost, ChatCompletionRequest, ChatMessage, CostEstimable,
+    ModelCatalogItem, OpenAIChatCompletionRequest, OpenAIChatMessage, RequestMetadata,
+    ResponsesRequest, TokenCounter, Usage, DEFAULT_MAX_COMPLETION_TOKENS,
+};
+
+fn priced_model(context_length: Option<u32>) -> ModelCatalogItem {
+    catalog_item("google/gemini-3-flash-preview")
+        .pricing("0.000001", "0.000004")
+        .context(context_length)
+        .build()
+}
+
+#[test]
+fn test_estimate_cost_uses_max_tokens_for_upper_bound() {
+    let request = OpenAIChatCompletionRequest::new(
+        "google/gemini-3-flash-preview",
+        vec![
+            OpenAIChatMessage::system("You are terse."),
+            OpenAIChatMessage::user("Summarize the plot of Hamlet in one sentence."),
+        ],
+    )
+    .with_max_tokens(200);
+
+    let model = priced_model(Some(1_000_000));
+    let estimate = estimate_cost(&request, &model).expect("estimate");
+
+    assert_eq!(
+        estimate.prompt_tokens,
+        request.estimated_prompt_tokens_with(&TokenCounter::for_model(&model))
+    );
+    assert!(estimate.prompt_tokens > 0);
+    assert_eq!(estimate.max_completion_tokens, 200);
+    assert!((estimate.min_cost - estimate.prompt_cost).abs() < f64::EPSILON);
+    let expected_max = estimate.prompt_cost + 200.0 * 0.000004;
+    assert!((estimate.max_cost - expected_max).abs() < 1e-12);
+
+    let marked_up = estimate.with_markup(10.0);
+    assert!((marked_up.max_cost - estimate.max_cost * 1.1).abs() < 1e-12);
+}
+
+#[test]
+fn test_estimate_cost_falls_back_to_remaining_context() {
+    let request = ResponsesRequest::text("google/gemini-3-flash-preview", "ping");
+    let estimate = estimate_cost(&request, &priced_model(Some(1_000))).expect("estimate");
+
+    assert_eq!(
+        estimate.max_completion_tokens,
+        1_000 - estimate.prompt_tokens
+    );
+}
+
+#[test]
+fn test_estimate_cost_assumes_a_default_cap_without_context_length() {
+    let request = ResponsesRequest::text("google/gemini-3-flash-preview", "ping");
+    let estimate = estimate_cost(&request, &priced_model(None)).expect("estimate");
+
+    assert_eq!(
+        estimate.max_completion_tokens,
+        DEFAULT_MAX_COMPLETION_TOKENS
+    );
+    let expected_max = estimate.prompt_cost + f64::from(DEFAULT_MAX_COMPLETION_TOKENS) * 0.000004;
+    assert!((estimate.max_cost - expected_max).abs() < 1e-12);
+    assert!(estimate.max_cost > estimate.min_cost);
+}
+
+#[test]
+fn test_estimate_cost_requires_pricing() {
+    let request = ResponsesRequest::text("model/unpriced", "ping");
+    let model = catalog_item("model/unpriced").build();
+
+    assert!(estimate_cost(&request, &model).is_none());
+}
+
+#[test]
+fn test_reconcile_cost_against_request_metadata() {
+    let usage = Usage {
+        prompt_tokens: 1_000,
+        completion_tokens: 500,
+        total_tokens: 1_500,
+        ..Default::default()
+    };
+    let model = priced_model(None);
+    let base = actual_cost(&usage, &model).expect("actual cost");
+    assert!((base - 0.003).abs() < 1e-12);
+
+    let metadata = RequestMetadata {
+        rainy_credits_charged: Some(0.0033),
+        rainy_markup_percent: Some(10.0),
+        ..Default::default()
+    };
+
+    let reconciliation = reconcile_cost(&usage, &model, &metadata).expect("reconciliation");
+    assert!((reconciliation.expected_charge - 0.0033).abs() < 1e-12);
+    assert!(reconciliation.within_tolerance(1e-9));
+
+    let overcharged = RequestMetadata {
+        rainy_credits_charged: Some(0.01),
+        ..metadata
+    };
+    let reconciliation = reconcile_cost(&usage, &model, &overcharged).expect("reconciliation");
+    assert!(!reconciliation.within_tolerance(1e-6));
+}
+
+#[tokio::test]
+async fn test_estimate_and_reconcile_against_mock_server() {
+    let mut server = mock_server().await;
+    let model = priced_model(Some(1_000_000));
+    let _catalog = server
+        .mock("GET", "/api/v1/models/catalog")
+        .with_header("content-type", "application/json")
+        .with_body(catalog_body(std::slice::from_ref(&model)))
+        .create_async()
+        .await;
+    let _chat = server
+        .mock("POST", "/api/v1/chat/completions")
+        .with_header("content-type", "application/json")
+        .with_header("x-rainy-credits-charged", "0.0033")
+        .with_header("x-rainy-markup-percent", "10")
+        .with_body(chat_body(&model.id, "Hamlet hesitates.", 1_000, 500))
+        .create_async()
+        .await;
+    let client = mock_client(&server);
+
+    let catalog = client.get_models_catalog().await.expect("catalog");
+    let request = ChatCompletionRequest::new(&model.id, vec![ChatMessage::user("Hamlet?")])
+        .with_max_tokens(500);
+    let estimate = client
+        .estimate_cost(&request, &catalog[0])
+        .expect("estimate");
+    assert_eq!(estimate.max_completion_tokens, 500);
+
+    let (response, metadata) = client.chat_completion(request).await.expect("completion");
+    let usage = response.usage.expect("usage");
+    let reconciliation = reconcile_cost(&usage, &catalog[0], &metadata).expect("reconciliation");
+    assert_eq!(reconciliation.charged, Some(0.0033));
+    assert!(reconciliation.within_tolerance(1e-9));
+}
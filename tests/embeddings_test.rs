@@ -0,0 +1,181 @@
+mod common;
+
+use common::{catalog_body, catalog_item, mock_client, mock_server};
+use rainy_sdk::{
+    Budget, EmbeddingInput, EmbeddingRequest, EmbeddingResponse, EmbeddingVector, EncodingFormat,
+    MemoryLedger, RainyClient, RainyError,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+#[test]
+fn test_request_serializes_openai_shape_without_batch_limits() {
+    let request = EmbeddingRequest::new("text-embedding-3-small", vec!["a", "b"])
+        .with_dimensions(256)
+        .with_encoding_format(EncodingFormat::Base64)
+        .with_batch_limits(1, 10);
+
+    assert_eq!(
+        serde_json::to_value(&request).unwrap(),
+        json!({
+            "model": "text-embedding-3-small",
+            "input": ["a", "b"],
+            "dimensions": 256,
+            "encoding_format": "base64"
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(EmbeddingRequest::new("m", "one")).unwrap()["input"],
+        "one"
+    );
+}
+
+#[test]
+fn test_batches_respect_input_and_token_limits() {
+    let inputs: Vec<String> = (0..5).map(|i| format!("input {i}")).collect();
+    let by_count = EmbeddingRequest::new("text-embedding-3-small", inputs.clone())
+        .with_dimensions(64)
+        .with_batch_limits(2, 1_000_000)
+        .batches();
+    let sizes: Vec<usize> = by_count.iter().map(|b| b.input.len()).collect();
+    assert_eq!(sizes, vec![2, 2, 1]);
+    assert!(by_count.iter().all(|b| b.dimensions == Some(64)));
+    assert_eq!(
+        by_count[2].input,
+        EmbeddingInput::Texts(vec!["input 4".to_string()])
+    );
+
+    let long = "word ".repeat(200);
+    let by_tokens = EmbeddingRequest::new(
+        "text-embedding-3-small",
+        vec![long.clone(), long.clone(), long],
+    )
+    .with_batch_limits(100, 250)
+    .batches();
+    assert_eq!(by_tokens.len(), 3);
+}
+
+#[test]
+fn test_base64_vectors_decode_to_floats_in_index_order() {
+    let packed: Vec<u8> = [0.5f32, -1.25]
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect();
+    let encoded = {
+        use base64::Engine;
+        base64::engine::general_purpose::STANDARD.encode(packed)
+    };
+    let response: EmbeddingResponse = serde_json::from_value(json!({
+        "object": "list",
+        "model": "text-embedding-3-small",
+        "data": [
+            {"object": "embedding", "index": 1, "embedding": [1.0, 2.0]},
+            {"object": "embedding", "index": 0, "embedding": encoded}
+        ],
+        "usage": {"prompt_tokens": 4, "total_tokens": 4}
+    }))
+    .unwrap();
+
+    assert_eq!(
+        response.vectors().unwrap(),
+        vec![vec![0.5, -1.25], vec![1.0, 2.0]]
+    );
+    assert!(EmbeddingVector::Base64("AAA=".to_string())
+        .to_vec()
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_empty_input_is_rejected_locally() {
+    let client =
+        RainyClient::with_api_key("ra-0123456789abcdef0123456789abcdef0123456789abcdef").unwrap();
+    let err = client
+        .create_embeddings(EmbeddingRequest::new("m", Vec::<String>::new()))
+        .await
+        .unwrap_err();
+    match err {
+        RainyError::InvalidRequest { code, .. } => assert_eq!(code, "EMPTY_INPUT"),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_mock_batches_merge_in_input_order_with_summed_usage() {
+    let mut server = mock_server().await;
+    let _catalog = server
+        .mock("GET", "/api/v1/models/catalog")
+        .with_header("content-type", "application/json")
+        .with_body(catalog_body(&[catalog_item("text-embedding-3-small")
+            .pricing("0.0001", "0")
+            .build()]))
+        .create_async()
+        .await;
+    // Each batch answers out of order; indexes are local to the batch.
+    let first = server
+        .mock("POST", "/api/v1/embeddings")
+        .match_body(mockito::Matcher::PartialJson(json!({"input": ["a", "b"]})))
+        .with_header("content-type", "application/json")
+        .with_header("x-request-id", "req-1")
+        .with_header("x-rainy-credits-charged", "0.25")
+        .with_body(
+            json!({
+                "object": "list",
+                "model": "text-embedding-3-small",
+                "data": [
+                    {"object": "embedding", "index": 1, "embedding": [1.0]},
+                    {"object": "embedding", "index": 0, "embedding": [0.0]}
+                ],
+                "usage": {"prompt_tokens": 2, "total_tokens": 2}
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+    let second = server
+        .mock("POST", "/api/v1/embeddings")
+        .match_body(mockito::Matcher::PartialJson(json!({"input": ["c"]})))
+        .with_header("content-type", "application/json")
+        .with_header("x-request-id", "req-2")
+        .with_header("x-rainy-credits-charged", "0.5")
+        .with_body(
+            json!({
+                "object": "list",
+                "model": "text-embedding-3-small",
+                "data": [{"object": "embedding", "index": 0, "embedding": [2.0]}],
+                "usage": {"prompt_tokens": 3, "total_tokens": 3}
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+    let ledger = Arc::new(MemoryLedger::new());
+    let client = mock_client(&server)
+        .with_budget(Budget::new().with_client_limit(10.0))
+        .with_ledger(ledger.clone());
+
+    let request = EmbeddingRequest::new("text-embedding-3-small", vec!["a", "b", "c"])
+        .with_batch_limits(2, 1_000_000);
+    let (response, metadata) = client.create_embeddings(request).await.expect("embeddings");
+
+    assert_eq!(
+        response.vectors().unwrap(),
+        vec![vec![0.0], vec![1.0], vec![2.0]]
+    );
+    let usage = response.usage.expect("usage");
+    assert_eq!((usage.prompt_tokens, usage.total_tokens), (5, 5));
+    // No token header, so each batch falls back to its usage before summing.
+    assert_eq!(metadata.tokens_used, Some(5));
+    assert_eq!(metadata.rainy_credits_charged, Some(0.75));
+    assert_eq!(metadata.request_id.as_deref(), Some("req-2"));
+    first.assert_async().await;
+    second.assert_async().await;
+
+    let snapshot = client.budget_snapshot().unwrap();
+    assert!((snapshot.total_spent - 0.75).abs() < 1e-9);
+    assert_eq!(snapshot.reserved, 0.0);
+    let entries = ledger.entries();
+    let charged: Vec<Option<f64>> = entries.iter().map(|e| e.credits_charged).collect();
+    assert_eq!(charged, vec![Some(0.25), Some(0.5)]);
+    assert!(entries.iter().all(|e| e.endpoint == "/embeddings"));
+    assert_eq!(entries[1].total_tokens, Some(3));
+}
@@ -0,0 +1,138 @@
+use rainy_sdk::enhanced::{to_openai_messages, to_responses_input};
+use rainy_sdk::{
+    ContentPart, EnhancedChatMessage, EnhancedUsage, MessageRole, OpenAIChatCompletionRequest,
+    OpenAIMessageContent, OpenAIMessageRole, ResponsesApiResponse, ResponsesRequest, Usage,
+};
+use serde_json::json;
+
+fn history() -> Vec<EnhancedChatMessage> {
+    vec![
+        EnhancedChatMessage::system("Be brief."),
+        EnhancedChatMessage::user("Weather in Lima and Cusco?"),
+        EnhancedChatMessage::with_parts(
+            MessageRole::Assistant,
+            vec![
+                ContentPart::text("Two lookups needed.").as_thought(),
+                ContentPart::function_call("get_weather", json!({"city": "Lima"}))
+                    .with_thought_signature("sig_1"),
+                ContentPart::function_call("get_weather", json!({"city": "Cusco"})),
+            ],
+        ),
+        EnhancedChatMessage::with_parts(
+            MessageRole::User,
+            vec![
+                ContentPart::function_response("get_weather", json!({"temp": 22})),
+                ContentPart::function_response("get_weather", json!({"temp": 12})),
+            ],
+        ),
+        EnhancedChatMessage::with_parts(
+            MessageRole::Assistant,
+            vec![ContentPart::text("Lima 22°C, Cusco 12°C.").with_thought_signature("sig_2")],
+        ),
+    ]
+}
+
+#[test]
+fn test_to_openai_messages_pairs_calls_and_keeps_thoughts() {
+    let messages = to_openai_messages(&history());
+    assert_eq!(messages.len(), 6);
+
+    let assistant = &messages[2];
+    assert_eq!(assistant.role, OpenAIMessageRole::Assistant);
+    assert_eq!(assistant.content, None);
+    assert_eq!(assistant.reasoning.as_deref(), Some("Two lookups needed."));
+    let calls = assistant.tool_calls.as_ref().unwrap();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].thought_signature(), Some("sig_1"));
+    assert_eq!(calls[1].thought_signature(), None);
+    assert_eq!(calls[1].function.arguments, r#"{"city":"Cusco"}"#);
+
+    assert_eq!(
+        messages[3].tool_call_id.as_deref(),
+        Some(calls[0].id.as_str())
+    );
+    assert_eq!(
+        messages[4].tool_call_id.as_deref(),
+        Some(calls[1].id.as_str())
+    );
+    assert_eq!(
+        messages[4].content,
+        Some(OpenAIMessageContent::Text(r#"{"temp":12}"#.to_string()))
+    );
+    assert_eq!(
+        messages[5].content,
+        Some(OpenAIMessageContent::Text(
+            "Lima 22°C, Cusco 12°C.".to_string()
+        ))
+    );
+
+    let request = OpenAIChatCompletionRequest::from_enhanced("gemini-3-pro-preview", &history());
+    assert!(request.validate_thought_signatures().is_ok());
+}
+
+#[test]
+fn test_to_responses_input_items() {
+    let input = to_responses_input(&history());
+    let types: Vec<&str> = input
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            "message",
+            "message",
+            "function_call",
+            "function_call",
+            "function_call_output",
+            "function_call_output",
+            "message"
+        ]
+    );
+    // Thoughts are not replayed as id-less `reasoning` items.
+    assert!(!input.to_string().contains("reasoning"));
+    // Responses `function_call` items have no slot for thought signatures.
+    assert!(input[2].get("extra_content").is_none());
+    assert_eq!(input[4]["call_id"], input[2]["call_id"]);
+    assert_eq!(input[5]["call_id"], input[3]["call_id"]);
+
+    let request = ResponsesRequest::from_enhanced("gemini-3-pro-preview", &history());
+    assert_eq!(request.input[2]["name"], input[2]["name"]);
+}
+
+#[test]
+fn test_call_ids_are_unique_per_conversion() {
+    let first = to_openai_messages(&history());
+    let second = to_openai_messages(&history());
+    let id = |messages: &[rainy_sdk::OpenAIChatMessage]| {
+        messages[2].tool_calls.as_ref().unwrap()[0].id.clone()
+    };
+    assert_ne!(id(&first), id(&second));
+    assert_ne!(
+        to_responses_input(&history())[3]["call_id"],
+        to_responses_input(&history())[3]["call_id"]
+    );
+}
+
+#[test]
+fn test_thoughts_token_count_is_surfaced() {
+    let usage: Usage = serde_json::from_value(json!({
+        "prompt_tokens": 10,
+        "completion_tokens": 40,
+        "total_tokens": 50,
+        "completion_tokens_details": {"reasoning_tokens": 32, "audio_tokens": 0}
+    }))
+    .unwrap();
+    assert_eq!(usage.thoughts_token_count(), Some(32));
+    assert_eq!(EnhancedUsage::from(&usage).thoughts_token_count, Some(32));
+
+    let response: ResponsesApiResponse = serde_json::from_value(json!({
+        "usage": {"input_tokens": 5, "output_tokens": 9, "output_tokens_details": {"reasoning_tokens": 4}}
+    }))
+    .unwrap();
+    let enhanced = EnhancedUsage::from(response.usage.as_ref().unwrap());
+    assert_eq!(enhanced.total_tokens, 14);
+    assert_eq!(enhanced.thoughts_token_count, Some(4));
+}
@@ -0,0 +1,269 @@
+mod common;
+
+use common::{catalog_body, catalog_item, chat_body, mock_client, mock_server};
+use mockito::Matcher;
+use rainy_sdk::{
+    adapt_thinking_config, ChatCompletionRequest, ChatMessage, FallbackPolicy, FallbackRequest,
+    FallbackTrigger, ModelSelectionCriteria, OpenAIChatCompletionRequest, OpenAIChatMessage,
+    RainyError, ResponsesRequest, ThinkingConfig, ThinkingLevel,
+};
+use serde_json::json;
+
+fn provider_unavailable() -> RainyError {
+    RainyError::Provider {
+        code: "PROVIDER_UNAVAILABLE".to_string(),
+        message: "upstream down".to_string(),
+        provider: "google".to_string(),
+        retryable: true,
+    }
+}
+
+#[test]
+fn test_fallback_triggers() {
+    let policy = FallbackPolicy::models(["gemini-3-flash-preview"]);
+    assert!(policy.should_fallback(&provider_unavailable()));
+    assert!(policy.should_fallback(&RainyError::Api {
+        code: "INTERNAL".to_string(),
+        message: "boom".to_string(),
+        status_code: 502,
+        retryable: true,
+        request_id: None,
+    }));
+    assert!(!policy.should_fallback(&RainyError::Authentication {
+        code: "INVALID_API_KEY".to_string(),
+        message: "bad key".to_string(),
+        retryable: false,
+    }));
+
+    let narrow = policy.with_triggers(vec![FallbackTrigger::Code("RATE_LIMIT_EXCEEDED".into())]);
+    assert!(!narrow.should_fallback(&provider_unavailable()));
+    assert!(narrow.should_fallback(&RainyError::RateLimit {
+        code: "RATE_LIMIT_EXCEEDED".to_string(),
+        message: "slow down".to_string(),
+        retry_after: None,
+        current_usage: None,
+    }));
+}
+
+#[test]
+fn test_candidate_chain_from_models_and_criteria() {
+    let policy = FallbackPolicy::models(["b", "a", "c"]).with_max_attempts(3);
+    assert_eq!(policy.candidate_chain("a", None), vec!["a", "b", "c"]);
+
+    let catalog = vec![
+        catalog_item("model/expensive")
+            .context(128_000)
+            .pricing("0.00001", "0.000002")
+            .accepted(&["tools"])
+            .build(),
+        catalog_item("model/cheap")
+            .context(128_000)
+            .pricing("0.000001", "0.000002")
+            .accepted(&["tools"])
+            .build(),
+    ];
+    let policy = FallbackPolicy::criteria(ModelSelectionCriteria {
+        required_input_modalities: vec!["text".to_string()],
+        ..Default::default()
+    });
+    assert_eq!(
+        policy.candidate_chain("model/primary", Some(&catalog)),
+        vec!["model/primary", "model/cheap", "model/expensive"]
+    );
+    assert_eq!(
+        policy.candidate_chain("model/primary", None),
+        vec!["model/primary"]
+    );
+}
+
+#[test]
+fn test_adapt_thinking_config_between_gemini_generations() {
+    let level = ThinkingConfig {
+        include_thoughts: Some(true),
+        thinking_level: Some(ThinkingLevel::Medium),
+        thinking_budget: None,
+    };
+
+    let pro = adapt_thinking_config(&level, "gemini-3-pro-preview").unwrap();
+    assert!(matches!(pro.thinking_level, Some(ThinkingLevel::High)));
+
+    let flash_25 = adapt_thinking_config(&level, "gemini-2.5-flash").unwrap();
+    assert!(flash_25.thinking_level.is_none());
+    assert_eq!(flash_25.thinking_budget, Some(8192));
+    assert_eq!(flash_25.include_thoughts, Some(true));
+
+    assert!(adapt_thinking_config(&level, "gpt-4o").is_none());
+
+    let mut request =
+        ChatCompletionRequest::new("gemini-3-pro-preview", vec![ChatMessage::user("Prove it.")])
+            .with_thinking_budget(-1);
+    request.adapt_to_model("gemini-3-flash-preview", None);
+    assert_eq!(request.model(), "gemini-3-flash-preview");
+    let config = request.thinking_config.as_ref().unwrap();
+    assert!(matches!(config.thinking_level, Some(ThinkingLevel::High)));
+    assert!(config.thinking_budget.is_none());
+    assert!(request.validate_openai_compatibility().is_ok());
+}
+
+#[test]
+fn test_responses_reasoning_dropped_when_unsupported() {
+    let supported = catalog_item("model/reasoner")
+        .context(128_000)
+        .pricing("0.000001", "0.000002")
+        .accepted(&["tools"])
+        .efforts(&["low", "high"])
+        .build();
+    let plain = catalog_item("model/plain")
+        .context(128_000)
+        .pricing("0.000001", "0.000002")
+        .accepted(&["tools"])
+        .build();
+
+    let request = ResponsesRequest::text("model/primary", "hi").with_reasoning_effort("high");
+
+    let mut attempt = request.clone();
+    attempt.adapt_to_model("model/reasoner", Some(&supported));
+    assert!(attempt.reasoning.is_some());
+
+    let mut attempt = request.clone();
+    attempt.adapt_to_model("model/plain", Some(&plain));
+    assert_eq!(attempt.model, "model/plain");
+    assert!(attempt.reasoning.is_none());
+}
+
+#[test]
+fn test_responses_reasoning_clamped_or_dropped_without_catalog_entry() {
+    let reasoner = catalog_item("model/reasoner")
+        .efforts(&["low", "medium"])
+        .build();
+    let request = ResponsesRequest::text("model/primary", "hi").with_reasoning_effort("high");
+
+    let mut attempt = request.clone();
+    attempt.adapt_to_model("model/reasoner", Some(&reasoner));
+    assert_eq!(attempt.reasoning.unwrap()["effort"], "medium");
+
+    let mut attempt = request.clone();
+    attempt.adapt_to_model("model/unknown", None);
+    assert!(attempt.reasoning.is_none());
+}
+
+#[test]
+fn test_anthropic_thinking_stripped_for_other_models() {
+    let mut request = OpenAIChatCompletionRequest::new(
+        "anthropic/claude-sonnet-4",
+        vec![OpenAIChatMessage::user("hi")],
+    );
+    request.thinking = Some(json!({"type": "enabled", "budget_tokens": 2048}));
+
+    let mut attempt = request.clone();
+    attempt.adapt_to_model("anthropic/claude-haiku-4", None);
+    assert!(attempt.thinking.is_some());
+
+    let mut attempt = request.clone();
+    attempt.adapt_to_model("openai/gpt-5", None);
+    assert!(attempt.thinking.is_none());
+}
+
+fn criteria_policy() -> FallbackPolicy {
+    FallbackPolicy::criteria(ModelSelectionCriteria::default())
+}
+
+#[tokio::test]
+async fn test_fallback_skips_catalog_when_primary_succeeds() {
+    let mut server = mock_server().await;
+    let catalog = server
+        .mock("GET", "/api/v1/models/catalog")
+        .expect(0)
+        .create_async()
+        .await;
+    let _chat = server
+        .mock("POST", "/api/v1/chat/completions")
+        .with_header("content-type", "application/json")
+        .with_body(chat_body("model/primary", "hi", 3, 1))
+        .create_async()
+        .await;
+    let client = mock_client(&server);
+
+    let request = ChatCompletionRequest::new("model/primary", vec![ChatMessage::user("hi")]);
+    let (_, metadata) = client
+        .chat_completion_with_fallback(request, &criteria_policy())
+        .await
+        .expect("completion");
+    assert_eq!(metadata.served_model.as_deref(), Some("model/primary"));
+    catalog.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_fallback_moves_to_catalog_candidate_after_server_error() {
+    let mut server = mock_server().await;
+    let catalog = server
+        .mock("GET", "/api/v1/models/catalog")
+        .with_header("content-type", "application/json")
+        .with_body(catalog_body(&[catalog_item("model/backup")
+            .context(128_000)
+            .pricing("0.000001", "0.000002")
+            .inputs(&["text"])
+            .build()]))
+        .expect(1)
+        .create_async()
+        .await;
+    let _primary = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({"model": "model/primary"})))
+        .with_status(503)
+        .with_body("upstream down")
+        .create_async()
+        .await;
+    let _backup = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_body(Matcher::PartialJson(json!({"model": "model/backup"})))
+        .with_header("content-type", "application/json")
+        .with_body(chat_body("model/backup", "hi", 3, 1))
+        .create_async()
+        .await;
+    let client = mock_client(&server);
+
+    let request = ChatCompletionRequest::new("model/primary", vec![ChatMessage::user("hi")]);
+    let (response, metadata) = client
+        .chat_completion_with_fallback(request, &criteria_policy())
+        .await
+        .expect("completion");
+    assert_eq!(response.model, "model/backup");
+    assert_eq!(metadata.served_model.as_deref(), Some("model/backup"));
+    catalog.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_fallback_returns_primary_error_when_catalog_fails() {
+    let mut server = mock_server().await;
+    let _catalog = server
+        .mock("GET", "/api/v1/models/catalog")
+        .with_status(500)
+        .with_body("catalog unavailable")
+        .create_async()
+        .await;
+    let _primary = server
+        .mock("POST", "/api/v1/chat/completions")
+        .with_status(503)
+        .with_body("upstream down")
+        .expect(1)
+        .create_async()
+        .await;
+    let client = mock_client(&server);
+
+    let request = ChatCompletionRequest::new("model/primary", vec![ChatMessage::user("hi")]);
+    match client
+        .chat_completion_with_fallback(request, &criteria_policy())
+        .await
+    {
+        Err(RainyError::Api {
+            status_code,
+            message,
+            ..
+        }) => {
+            assert_eq!(status_code, 503);
+            assert_eq!(message, "upstream down");
+        }
+        other => panic!("expected the primary error, got {other:?}"),
+    }
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use common::{catalog_body, catalog_item, mock_client, mock_server};
+use rainy_sdk::{
+    supports_image_output, ChatCompletionResponse, GeneratedImage, ImageData, ModelArchitecture,
+    ModelCatalogItem, OpenAIChatCompletionResponse, RainyError, ResponsesApiResponse,
+};
+use serde_json::json;
+
+// "hi" base64-encoded.
+const DATA_URL: &str = "data:image/png;base64,aGk=";
+
+#[test]
+fn test_generated_image_parses_data_and_hosted_urls() {
+    let inline = GeneratedImage::from_url(DATA_URL);
+    assert_eq!(inline.mime_type, "image/png");
+    assert!(inline.is_inline());
+    assert_eq!(inline.bytes().unwrap(), b"hi");
+    assert_eq!(inline.url(), DATA_URL);
+
+    let hosted = GeneratedImage::from_url("https://cdn.example.com/out.webp?sig=1");
+    assert_eq!(hosted.mime_type, "image/webp");
+    assert_eq!(
+        hosted.data,
+        ImageData::Url("https://cdn.example.com/out.webp?sig=1".to_string())
+    );
+    match hosted.bytes().unwrap_err() {
+        RainyError::InvalidRequest { code, .. } => assert_eq!(code, "IMAGE_NOT_INLINE"),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn test_chat_responses_expose_images_from_messages_and_text() {
+    let response: OpenAIChatCompletionResponse = serde_json::from_value(json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 1,
+        "model": "gemini-3-pro-image-preview",
+        "choices": [{
+            "index": 0,
+            "finish_reason": "stop",
+            "message": {
+                "role": "assistant",
+                "content": [
+                    {"type": "text", "text": "Here you go."},
+                    {"type": "image_url", "image_url": {"url": "https://cdn.example.com/a.png"}}
+                ],
+                "images": [{"type": "image_url", "image_url": {"url": DATA_URL}}]
+            }
+        }]
+    }))
+    .unwrap();
+    let images = response.generated_images();
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0].bytes().unwrap(), b"hi");
+    assert!(!images[1].is_inline());
+
+    let plain: ChatCompletionResponse = serde_json::from_value(json!({
+        "id": "chatcmpl-2",
+        "object": "chat.completion",
+        "created": 1,
+        "model": "gemini-3-pro-image-preview",
+        "choices": [{
+            "index": 0,
+            "finish_reason": "stop",
+            "message": {"role": "assistant", "content": format!("A cat: ![cat]({DATA_URL})")}
+        }]
+    }))
+    .unwrap();
+    assert_eq!(
+        plain.generated_images(),
+        vec![GeneratedImage::from_url(DATA_URL)]
+    );
+}
+
+#[test]
+fn test_responses_output_items_yield_images() {
+    let response: ResponsesApiResponse = serde_json::from_value(json!({
+        "id": "resp_1",
+        "output": [
+            {"type": "image_generation_call", "result": "aGk=", "output_format": "jpeg"},
+            {"type": "message", "role": "assistant", "content": [
+                {"type": "output_text", "text": "Done."},
+                {"type": "output_image", "image_url": DATA_URL}
+            ]}
+        ]
+    }))
+    .unwrap();
+    let images = response.generated_images();
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0].mime_type, "image/jpeg");
+    assert_eq!(images[0].bytes().unwrap(), b"hi");
+    assert_eq!(images[1].mime_type, "image/png");
+}
+
+#[test]
+fn test_image_output_support_reads_catalog_modalities() {
+    let v2 = catalog_item("google/gemini-3-pro-image-preview")
+        .outputs(&["text", "image"])
+        .build();
+    let text_only = ModelCatalogItem {
+        id: "openai/gpt-5".to_string(),
+        architecture: Some(ModelArchitecture {
+            output_modalities: Some(vec!["text".to_string()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    assert!(supports_image_output(&v2));
+    assert!(!supports_image_output(&text_only));
+    assert!(!supports_image_output(&ModelCatalogItem::default()));
+}
+
+#[tokio::test]
+async fn test_generate_image_checks_the_catalog_but_tolerates_its_absence() {
+    let mut server = mock_server().await;
+    let catalog = server
+        .mock("GET", "/api/v1/models/catalog")
+        .with_status(503)
+        .with_body("catalog unavailable")
+        .create_async()
+        .await;
+    let chat = server
+        .mock("POST", "/api/v1/chat/completions")
+        .with_body(
+            json!({
+                "id": "chatcmpl-1",
+                "object": "chat.completion",
+                "created": 1,
+                "model": "google/gemini-3-pro-image-preview",
+                "choices": [{
+                    "index": 0,
+                    "finish_reason": "stop",
+                    "message": {
+                        "role": "assistant",
+                        "content": "",
+                        "images": [{"type": "image_url", "image_url": {"url": DATA_URL}}]
+                    }
+                }]
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    // An unreachable catalog is treated like a missing entry.
+    let images = mock_client(&server)
+        .generate_image("a cat", "google/gemini-3-pro-image-preview")
+        .await
+        .unwrap();
+    assert_eq!(images[0].bytes().unwrap(), b"hi");
+    catalog.remove_async().await;
+
+    server
+        .mock("GET", "/api/v1/models/catalog")
+        .with_body(catalog_body(&[catalog_item("openai/gpt-5")
+            .outputs(&["text"])
+            .build()]))
+        .create_async()
+        .await;
+    let err = mock_client(&server)
+        .generate_image("a cat", "gpt-5")
+        .await
+        .unwrap_err();
+    match err {
+        RainyError::InvalidRequest { code, .. } => assert_eq!(code, "UNSUPPORTED_OUTPUT_MODALITY"),
+        other => panic!("unexpected error: {other:?}"),
+    }
+    chat.assert_async().await;
+}
@@ -0,0 +1,151 @@
+use rainy_sdk::{
+    aggregate, reconcile_usage_stats, CsvLedger, JsonlLedger, LedgerEntry, LedgerGroupBy,
+    LedgerSink, MemoryLedger, RainyError, RequestMetadata, Usage, UsageStatsResponse,
+};
+use std::path::PathBuf;
+
+fn entry(model: &str, provider: &str, user: &str, credits: f64) -> LedgerEntry {
+    let metadata = RequestMetadata {
+        provider: Some(provider.to_string()),
+        request_id: Some("req_123".to_string()),
+        rainy_credits_charged: Some(credits),
+        rainy_markup_percent: Some(5.0),
+        response_time: Some(120),
+        ..Default::default()
+    };
+    LedgerEntry::from_metadata("/chat/completions", Some(model), Some(user), &metadata).with_usage(
+        Some(&Usage {
+            prompt_tokens: 10,
+            completion_tokens: 20,
+            total_tokens: 30,
+            ..Default::default()
+        }),
+    )
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rainy-ledger-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn test_memory_ledger_aggregates_by_model_provider_and_user() {
+    let ledger = MemoryLedger::new();
+    ledger
+        .record(&entry("gpt-4o", "openai", "alice", 0.5))
+        .unwrap();
+    ledger
+        .record(&entry("gpt-4o", "openai", "bob", 0.25))
+        .unwrap();
+    ledger
+        .record(&entry("gemini-3-flash", "google", "alice", 0.1))
+        .unwrap();
+    let error = RainyError::RateLimit {
+        code: "RATE_LIMIT_EXCEEDED".to_string(),
+        message: "slow down".to_string(),
+        retry_after: None,
+        current_usage: None,
+    };
+    ledger
+        .record(&LedgerEntry::from_error(
+            "/responses",
+            Some("gpt-4o"),
+            Some("alice"),
+            &error,
+            Some(40),
+        ))
+        .unwrap();
+
+    let by_model = ledger.aggregate(LedgerGroupBy::Model);
+    let gpt = &by_model["gpt-4o"];
+    assert_eq!(gpt.requests, 3);
+    assert_eq!(gpt.errors, 1);
+    assert_eq!(gpt.total_tokens, 60);
+    assert!((gpt.credits - 0.75).abs() < 1e-12);
+
+    let by_provider = aggregate(&ledger.entries(), LedgerGroupBy::Provider);
+    assert_eq!(by_provider["google"].requests, 1);
+    assert_eq!(by_provider["unknown"].errors, 1);
+
+    let by_user = ledger.aggregate(LedgerGroupBy::User);
+    assert_eq!(by_user["alice"].requests, 3);
+    assert_eq!(by_user["bob"].prompt_tokens, 10);
+
+    assert_eq!(ledger.drain().len(), 4);
+    assert!(ledger.entries().is_empty());
+}
+
+#[test]
+fn test_jsonl_ledger_round_trips_entries() {
+    let path = temp_path("entries.jsonl");
+    {
+        let ledger = JsonlLedger::open(&path).unwrap();
+        ledger
+            .record(&entry("gpt-4o", "openai", "alice", 0.5))
+            .unwrap();
+        ledger
+            .record(&entry("gpt-4o", "openai", "bob", 0.25))
+            .unwrap();
+    }
+
+    let entries = JsonlLedger::read(&path).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].user.as_deref(), Some("bob"));
+    assert_eq!(entries[0].markup_percent, Some(5.0));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_csv_ledger_writes_header_once_and_escapes_fields() {
+    let path = temp_path("entries.csv");
+    {
+        let ledger = CsvLedger::open(&path).unwrap();
+        ledger
+            .record(&entry("gpt-4o", "openai", "team, \"core\"", 0.5))
+            .unwrap();
+    }
+    {
+        let ledger = CsvLedger::open(&path).unwrap();
+        ledger
+            .record(&entry("gpt-4o", "openai", "bob", 0.25))
+            .unwrap();
+    }
+
+    let raw = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = raw.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], rainy_sdk::ledger::CSV_HEADER);
+    assert!(lines[1].contains(",\"team, \"\"core\"\"\","));
+    assert!(lines[2].ends_with(",bob,10,20,30,0.25,5,120,"));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_reconcile_ledger_with_usage_stats() {
+    let entries = vec![
+        entry("gpt-4o", "openai", "alice", 0.5),
+        entry("gpt-4o", "openai", "bob", 0.25),
+    ];
+    let stats: UsageStatsResponse = serde_json::from_value(serde_json::json!({
+        "periodDays": 7,
+        "totalRequests": 2,
+        "totalCreditsDeducted": 0.75
+    }))
+    .unwrap();
+
+    let reconciliation = reconcile_usage_stats(&entries, &stats);
+    assert_eq!(reconciliation.ledger_requests, 2);
+    assert!(reconciliation.matches(1e-9));
+
+    let stats: UsageStatsResponse = serde_json::from_value(serde_json::json!({
+        "periodDays": 7,
+        "totalRequests": 3,
+        "totalCreditsDeducted": 1.0
+    }))
+    .unwrap();
+    let reconciliation = reconcile_usage_stats(&entries, &stats);
+    assert_eq!(reconciliation.request_difference, 1);
+    assert!((reconciliation.credit_difference - 0.25).abs() < 1e-12);
+    assert!(!reconciliation.matches(1e-9));
+}
@@ -0,0 +1,266 @@
+#![cfg(feature = "mcp")]
+
+use rainy_sdk::{
+    McpBridge, McpClient, McpServerConfig, OpenAIMessageContent, OpenAIToolCall, RainyError,
+    ResponsesRequest, ToolErrorKind, ToolOutcome, DEFAULT_MCP_REQUEST_TIMEOUT,
+};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// In-process MCP server: answers `initialize`, pages `tools` two per `tools/list` response,
+/// and answers `tools/call` with `handler`.
+async fn stub_server(
+    name: &str,
+    tools: Vec<Value>,
+    handler: fn(&str, &Value) -> Value,
+) -> McpClient {
+    let (client_side, server_side) = tokio::io::duplex(64 * 1024);
+    let (server_read, mut server_write) = tokio::io::split(server_side);
+    let server_name = name.to_string();
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(server_read).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let request: Value = serde_json::from_str(&line).unwrap();
+            let Some(id) = request.get("id").cloned() else {
+                continue;
+            };
+            let params = &request["params"];
+            let result = match request["method"].as_str().unwrap() {
+                "initialize" => json!({
+                    "protocolVersion": params["protocolVersion"],
+                    "capabilities": {"tools": {}},
+                    "serverInfo": {"name": server_name, "version": "1.0.0"},
+                    "instructions": "stub server",
+                }),
+                "tools/list" => {
+                    let start: usize = params["cursor"]
+                        .as_str()
+                        .map_or(0, |cursor| cursor.parse().unwrap());
+                    let page: Vec<Value> = tools.iter().skip(start).take(2).cloned().collect();
+                    let mut result = json!({ "tools": page });
+                    if start + 2 < tools.len() {
+                        result["nextCursor"] = json!((start + 2).to_string());
+                    }
+                    result
+                }
+                "tools/call" => handler(params["name"].as_str().unwrap(), &params["arguments"]),
+                other => {
+                    let reply = json!({"jsonrpc": "2.0", "id": id, "error": {"code": -32601, "message": format!("unknown method {other}")}});
+                    server_write
+                        .write_all(format!("{reply}\n").as_bytes())
+                        .await
+                        .unwrap();
+                    continue;
+                }
+            };
+            let reply = json!({"jsonrpc": "2.0", "id": id, "result": result});
+            server_write
+                .write_all(format!("{reply}\n").as_bytes())
+                .await
+                .unwrap();
+        }
+    });
+    let (client_read, client_write) = tokio::io::split(client_side);
+    McpClient::connect(name, client_read, client_write, DEFAULT_MCP_REQUEST_TIMEOUT)
+        .await
+        .unwrap()
+}
+
+fn tool(name: &str) -> Value {
+    json!({
+        "name": name,
+        "description": format!("The {name} tool"),
+        "inputSchema": {"type": "object", "properties": {"path": {"type": "string"}}},
+        "annotations": {"readOnlyHint": true},
+    })
+}
+
+fn files_handler(name: &str, arguments: &Value) -> Value {
+    match name {
+        "read_file" => json!({
+            "content": [{"type": "text", "text": format!("contents of {}", arguments["path"].as_str().unwrap())}]
+        }),
+        _ => json!({"content": [{"type": "text", "text": "permission denied"}], "isError": true}),
+    }
+}
+
+fn search_handler(_: &str, arguments: &Value) -> Value {
+    json!({
+        "content": [{"type": "text", "text": "1 hit"}],
+        "structuredContent": {"hits": [arguments["query"]]},
+    })
+}
+
+fn call(id: &str, name: &str, arguments: Value) -> OpenAIToolCall {
+    serde_json::from_value(json!({
+        "id": id,
+        "type": "function",
+        "function": {"name": name, "arguments": arguments.to_string()}
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_client_initializes_and_lists_paginated_tools() {
+    let tools = vec![tool("a"), tool("b"), tool("c")];
+    let client = stub_server("files", tools, files_handler).await;
+    assert_eq!(client.server_info().name, "files");
+    assert_eq!(client.instructions(), Some("stub server"));
+
+    let listed = client.list_tools().await.unwrap();
+    let names: Vec<&str> = listed.iter().map(|tool| tool.name.as_str()).collect();
+    assert_eq!(names, vec!["a", "b", "c"]);
+    assert!(listed[0].extra.contains_key("annotations"));
+
+    let definition = listed[0].to_tool();
+    assert_eq!(definition.function.name, "a");
+    assert_eq!(
+        definition.function.parameters.unwrap()["properties"]["path"]["type"],
+        "string"
+    );
+
+    let err = client
+        .request("resources/list", json!({}))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Some("-32601"));
+}
+
+#[tokio::test]
+async fn test_bridge_routes_calls_to_owning_server() {
+    let mut bridge = McpBridge::new();
+    bridge
+        .add_server(
+            stub_server(
+                "files",
+                vec![tool("read_file"), tool("delete_file")],
+                files_handler,
+            )
+            .await,
+        )
+        .await
+        .unwrap();
+    bridge
+        .add_server(stub_server("search", vec![tool("search")], search_handler).await)
+        .await
+        .unwrap();
+    assert_eq!(bridge.server_for("search"), Some("search"));
+    assert_eq!(bridge.server_for("read_file"), Some("files"));
+    assert_eq!(bridge.tools().len(), 3);
+
+    let calls = vec![
+        call("call_1", "search", json!({"query": "rust"})),
+        call("call_2", "read_file", json!({"path": "/etc/motd"})),
+        call("call_3", "delete_file", json!({"path": "/"})),
+    ];
+    let executions = bridge.executor().execute(&calls).await;
+    assert_eq!(
+        executions[0].outcome,
+        ToolOutcome::Success {
+            output: json!({"hits": ["rust"]})
+        }
+    );
+    let message = executions[1].to_message();
+    assert!(matches!(
+        message.content,
+        Some(OpenAIMessageContent::Text(ref text)) if text == "contents of /etc/motd"
+    ));
+    assert!(matches!(
+        executions[2].outcome,
+        ToolOutcome::Error { kind: ToolErrorKind::Failed, ref message } if message == "permission denied"
+    ));
+
+    let err = bridge.call("missing", json!({})).await.unwrap_err();
+    assert_eq!(err.code(), Some("UNKNOWN_TOOL"));
+}
+
+#[tokio::test]
+async fn test_namespacing_resolves_conflicts_and_feeds_responses() {
+    let mut plain = McpBridge::new();
+    plain
+        .add_server(stub_server("one", vec![tool("search")], search_handler).await)
+        .await
+        .unwrap();
+    let err = plain
+        .add_server(stub_server("two", vec![tool("search")], search_handler).await)
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Some("MCP_TOOL_CONFLICT"));
+
+    let mut bridge = McpBridge::new().with_namespacing(true);
+    for name in ["one", "two"] {
+        bridge
+            .add_server(stub_server(name, vec![tool("search")], search_handler).await)
+            .await
+            .unwrap();
+    }
+    let names: Vec<&str> = bridge.tool_names().collect();
+    assert_eq!(names, vec!["one__search", "two__search"]);
+    assert_eq!(bridge.server_for("two__search"), Some("two"));
+    let result = bridge
+        .call("two__search", json!({"query": "q"}))
+        .await
+        .unwrap();
+    assert_eq!(result.text(), "1 hit");
+
+    let request = bridge.apply_to_responses(ResponsesRequest::new("gpt-5", json!("hi")));
+    let tools = request.tools.unwrap();
+    assert_eq!(tools.len(), 2);
+    assert_eq!(tools[0]["type"], "function");
+    assert_eq!(tools[0]["name"], "one__search");
+    assert_eq!(tools[0]["description"], "The search tool");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_spawns_stdio_server_process() {
+    // Replies to initialize (id 1), skips the initialized notification, then answers
+    // tools/list (id 2) and tools/call (id 3).
+    let script = r#"
+read line; echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2025-06-18","capabilities":{},"serverInfo":{"name":"echo","version":"0.1"}}}'
+read line
+read line; echo '{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"echo","inputSchema":{"type":"object"}}]}}'
+read line; echo '{"jsonrpc":"2.0","id":3,"result":{"content":[{"type":"text","text":"pong"}]}}'
+read line
+"#;
+    let config = McpServerConfig::new("echo", "sh").with_args(["-c", script]);
+    let mut bridge = McpBridge::new();
+    bridge.spawn_server(config).await.unwrap();
+    let server = bridge.servers().next().unwrap();
+    assert_eq!(server.server_info().version, "0.1");
+
+    let result = bridge.call("echo", json!({})).await.unwrap();
+    assert_eq!(result.into_output(), Ok(json!("pong")));
+
+    let missing = McpClient::spawn(McpServerConfig::new("nope", "/nonexistent/mcp-server"))
+        .await
+        .unwrap_err();
+    assert!(missing
+        .to_string()
+        .contains("failed to start MCP server 'nope'"));
+}
+
+#[tokio::test]
+async fn test_connect_applies_the_timeout_to_initialize() {
+    // The server side never answers.
+    let (client_side, _server_side) = tokio::io::duplex(1024);
+    let (client_read, client_write) = tokio::io::split(client_side);
+    let start = Instant::now();
+    let err = McpClient::connect(
+        "silent",
+        client_read,
+        client_write,
+        Duration::from_millis(50),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        RainyError::Timeout {
+            duration_ms: 50,
+            ..
+        }
+    ));
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,177 @@
+mod common;
+
+use common::{catalog_body, catalog_item, chat_body, mock_client, mock_server};
+use rainy_sdk::{
+    check_input_modalities, sniff_mime, MediaOptions, Modality, ModelCatalogItem,
+    MultimodalRequest, OpenAIChatCompletionRequest, OpenAIChatMessage, OpenAIContentPart,
+    OpenAIMessageContent, RainyError, ResponsesRequest,
+};
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01";
+const WAV: &[u8] = b"RIFF\x24\0\0\0WAVEfmt \x10\0\0\0";
+
+fn error_code(err: RainyError) -> String {
+    match err {
+        RainyError::InvalidRequest { code, .. } => code,
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn test_sniffs_common_media_types() {
+    assert_eq!(sniff_mime(PNG), Some("image/png"));
+    assert_eq!(sniff_mime(b"\xFF\xD8\xFF\xE0"), Some("image/jpeg"));
+    assert_eq!(sniff_mime(WAV), Some("audio/wav"));
+    assert_eq!(sniff_mime(b"%PDF-1.7"), Some("application/pdf"));
+    assert_eq!(sniff_mime(b"ID3\x04"), Some("audio/mpeg"));
+    assert_eq!(sniff_mime(b"plain text"), None);
+}
+
+#[test]
+fn test_image_parts_enforce_size_and_apply_downscaler() {
+    let part = OpenAIContentPart::image_from_bytes(PNG, None).unwrap();
+    let OpenAIContentPart::ImageUrl { image_url } = &part else {
+        panic!("expected image part");
+    };
+    assert!(image_url
+        .url
+        .starts_with("data:image/png;base64,iVBORw0KGgo"));
+
+    let small = MediaOptions::new().with_max_bytes(8);
+    let err = OpenAIContentPart::image_from_bytes_with(PNG, None, &small).unwrap_err();
+    assert_eq!(error_code(err), "MEDIA_TOO_LARGE");
+
+    let shrinking = small
+        .with_detail("low")
+        .with_downscaler(|_, _| Ok((b"\xFF\xD8\xFF\xE0".to_vec(), "image/jpeg".to_string())));
+    let OpenAIContentPart::ImageUrl { image_url } =
+        OpenAIContentPart::image_from_bytes_with(PNG, None, &shrinking).unwrap()
+    else {
+        panic!("expected image part");
+    };
+    assert!(image_url.url.starts_with("data:image/jpeg;base64,"));
+    assert_eq!(image_url.detail.as_deref(), Some("low"));
+
+    let err = OpenAIContentPart::image_from_bytes(b"not an image", None).unwrap_err();
+    assert_eq!(error_code(err), "UNSUPPORTED_MEDIA_TYPE");
+}
+
+#[test]
+fn test_audio_and_file_parts_from_paths_serialize_for_both_apis() {
+    let dir = std::env::temp_dir().join(format!("rainy-multimodal-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let audio_path = dir.join("clip.wav");
+    let doc_path = dir.join("notes.md");
+    std::fs::write(&audio_path, WAV).unwrap();
+    std::fs::write(&doc_path, "# Notes").unwrap();
+
+    let audio = OpenAIContentPart::audio_from_path(&audio_path).unwrap();
+    let file = OpenAIContentPart::file_from_path(&doc_path).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let audio_json = serde_json::to_value(&audio).unwrap();
+    assert_eq!(audio_json["type"], "input_audio");
+    assert_eq!(audio_json["input_audio"]["format"], "wav");
+
+    let file_json = serde_json::to_value(&file).unwrap();
+    assert_eq!(file_json["type"], "file");
+    assert_eq!(file_json["file"]["filename"], "notes.md");
+    assert!(file_json["file"]["file_data"]
+        .as_str()
+        .unwrap()
+        .starts_with("data:text/markdown;base64,"));
+
+    let request =
+        ResponsesRequest::from_parts("gpt-5", &[OpenAIContentPart::text("Hi"), audio, file]);
+    let content = &request.input[0]["content"];
+    assert_eq!(content[1]["type"], "input_audio");
+    assert_eq!(content[2]["type"], "input_file");
+    assert_eq!(content[2]["filename"], "notes.md");
+    assert_eq!(
+        request.input_modalities(),
+        vec![Modality::Text, Modality::Audio, Modality::File]
+    );
+}
+
+#[test]
+fn test_input_modalities_are_checked_against_catalog_capabilities() {
+    let request = OpenAIChatCompletionRequest::new(
+        "vision/model",
+        vec![OpenAIChatMessage::user(OpenAIMessageContent::Parts(vec![
+            OpenAIContentPart::text("Transcribe and describe."),
+            OpenAIContentPart::image_from_bytes(PNG, None).unwrap(),
+            OpenAIContentPart::input_audio("UklGRg==", "wav"),
+        ]))],
+    );
+
+    assert!(check_input_modalities(
+        &request,
+        &catalog_item("vision/model")
+            .inputs(&["text", "image", "audio"])
+            .build()
+    )
+    .is_ok());
+    assert!(check_input_modalities(&request, &ModelCatalogItem::default()).is_ok());
+
+    let err = check_input_modalities(
+        &request,
+        &catalog_item("vision/model")
+            .inputs(&["text", "image"])
+            .build(),
+    )
+    .unwrap_err();
+    match err {
+        RainyError::InvalidRequest { code, message, .. } => {
+            assert_eq!(code, "UNSUPPORTED_INPUT_MODALITY");
+            assert!(message.contains("audio"));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_send_path_rejects_media_the_model_cannot_read() {
+    let mut server = mock_server().await;
+    let catalog = server
+        .mock("GET", "/api/v1/models/catalog")
+        .with_body(catalog_body(&[catalog_item("text/model")
+            .inputs(&["text"])
+            .build()]))
+        .expect(1)
+        .create_async()
+        .await;
+    let chat = server
+        .mock("POST", "/api/v1/chat/completions")
+        .with_body(chat_body("text/model", "ok", 1, 1))
+        .expect(1)
+        .create_async()
+        .await;
+    let client = mock_client(&server);
+
+    let image = OpenAIChatCompletionRequest::new(
+        "text/model",
+        vec![OpenAIChatMessage::user(OpenAIMessageContent::Parts(vec![
+            OpenAIContentPart::text("Describe this."),
+            OpenAIContentPart::image_from_bytes(PNG, None).unwrap(),
+        ]))],
+    );
+    let err = client
+        .create_openai_chat_completion(image.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(err), "UNSUPPORTED_INPUT_MODALITY");
+    // The cached catalog serves the second check.
+    assert!(client.create_openai_chat_completion(image).await.is_err());
+
+    // Text-only requests go straight through.
+    let text = OpenAIChatCompletionRequest::new(
+        "text/model",
+        vec![OpenAIChatMessage::user(OpenAIMessageContent::Text(
+            "hi".to_string(),
+        ))],
+    );
+    client.create_openai_chat_completion(text).await.unwrap();
+
+    catalog.assert_async().await;
+    chat.assert_async().await;
+}
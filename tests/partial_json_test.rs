@@ -0,0 +1,191 @@
+use futures::StreamExt;
+use rainy_sdk::{
+    parse_partial_json, ChatCompletionStreamResponse, PartialJsonParser, ResponseFormat,
+    StructuredEvent,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+fn content_chunk(content: &str) -> ChatCompletionStreamResponse {
+    serde_json::from_value(json!({
+        "id": "chatcmpl_1",
+        "object": "chat.completion.chunk",
+        "created": 1741171200u64,
+        "model": "gpt-5",
+        "choices": [{"index": 0, "delta": {"content": content}, "finish_reason": null}]
+    }))
+    .unwrap()
+}
+
+fn recipe_format() -> ResponseFormat {
+    ResponseFormat::JsonSchema {
+        json_schema: json!({
+            "name": "recipe",
+            "strict": true,
+            "schema": {
+                "type": "object",
+                "properties": {
+                    "title": {"type": "string"},
+                    "steps": {"type": "array", "items": {"type": "string"}},
+                    "minutes": {"type": "integer"}
+                },
+                "required": ["title", "steps", "minutes"]
+            }
+        }),
+    }
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Recipe {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    steps: Vec<String>,
+    #[serde(default)]
+    minutes: Option<u32>,
+}
+
+#[test]
+fn test_truncated_documents_parse_to_longest_valid_prefix() {
+    assert_eq!(parse_partial_json(""), None);
+    assert_eq!(parse_partial_json("{"), Some(json!({})));
+    assert_eq!(
+        parse_partial_json(r#"{"title": "Pan"#),
+        Some(json!({"title": "Pan"}))
+    );
+    assert_eq!(
+        parse_partial_json(r#"{"title": "Pancakes", "ste"#),
+        Some(json!({"title": "Pancakes"}))
+    );
+    assert_eq!(
+        parse_partial_json(r#"{"steps": ["Mix", "Fry\n"#),
+        Some(json!({"steps": ["Mix", "Fry\n"]}))
+    );
+    assert_eq!(parse_partial_json(r#"{"done": tr"#), Some(json!({})));
+    assert_eq!(
+        parse_partial_json(r#"{"minutes": 1"#),
+        Some(json!({"minutes": 1}))
+    );
+    assert_eq!(parse_partial_json(r#"{"minutes": -"#), Some(json!({})));
+    assert_eq!(
+        parse_partial_json(r#"{"a": "é\u00"#),
+        Some(json!({"a": "é"}))
+    );
+    assert_eq!(parse_partial_json("not json"), None);
+    assert_eq!(
+        parse_partial_json(r#"[1, {"a": [null, fals"#),
+        Some(json!([1, {"a": [null]}]))
+    );
+    assert_eq!(
+        parse_partial_json(r#"{"a": 1} trailing"#),
+        Some(json!({"a": 1}))
+    );
+    assert_eq!(parse_partial_json(r#"{"a": 1]"#), None);
+}
+
+#[test]
+fn test_surrogate_pairs_combine_across_deltas() {
+    let mut parser = PartialJsonParser::new();
+    parser.push(r#"{"emoji": "a"#);
+    // A high surrogate alone is held back until its pair arrives.
+    assert_eq!(parser.push(r#"\ud83d"#), None);
+    assert_eq!(parser.push(r#"\ude0"#), None);
+    assert_eq!(parser.push("0"), Some(json!({"emoji": "a\u{1F600}"})));
+    assert_eq!(parse_partial_json(r#"["\ud83d x"#), Some(json!([" x"])));
+    parser.push(r#"b"}"#);
+    assert_eq!(parser.finish().unwrap(), json!({"emoji": "a\u{1F600}b"}));
+}
+
+#[test]
+fn test_parser_emits_only_changed_snapshots_and_validates_final_value() {
+    let mut parser = PartialJsonParser::for_response_format(&recipe_format());
+    assert_eq!(parser.push(r#"{"title""#), Some(json!({})));
+    assert_eq!(parser.push(": "), None);
+    assert_eq!(
+        parser.push(r#""Pancakes""#),
+        Some(json!({"title": "Pancakes"}))
+    );
+    parser.push(r#", "steps": []}"#);
+
+    let err = parser.finish().unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("missing required property 'minutes'"));
+
+    let mut parser = PartialJsonParser::for_response_format(&recipe_format());
+    parser.push(r#"{"title": "Pancakes", "steps": ["Mix"], "minutes": 15}"#);
+    assert_eq!(parser.finish().unwrap()["minutes"], 15);
+    assert!(PartialJsonParser::new()
+        .finish()
+        .unwrap_err()
+        .to_string()
+        .contains("not valid JSON"));
+}
+
+#[tokio::test]
+async fn test_chat_stream_yields_typed_partials_and_complete_value() {
+    let deltas = [
+        r#"{"title": "Pan"#,
+        r#"cakes", "steps": ["Mix""#,
+        r#", "Fry"], "minutes": 15}"#,
+    ];
+    let chunks = deltas
+        .iter()
+        .map(|delta| Ok(content_chunk(delta)))
+        .collect::<Vec<_>>();
+    let events: Vec<StructuredEvent<Recipe>> =
+        PartialJsonParser::for_response_format(&recipe_format())
+            .stream(futures::stream::iter(chunks))
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+    assert_eq!(events.len(), 4);
+    let StructuredEvent::Partial(first) = &events[0] else {
+        panic!("expected a partial snapshot");
+    };
+    assert_eq!(first.snapshot, json!({"title": "Pan"}));
+    assert_eq!(first.value.as_ref().unwrap().title.as_deref(), Some("Pan"));
+    assert_eq!(
+        events[3],
+        StructuredEvent::Complete(Recipe {
+            title: Some("Pancakes".to_string()),
+            steps: vec!["Mix".to_string(), "Fry".to_string()],
+            minutes: Some(15),
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_responses_stream_uses_output_text_deltas() {
+    let events = vec![
+        Ok(json!({"type": "response.created", "response": {"id": "resp_1"}})),
+        Ok(
+            json!({"type": "response.output_text.delta", "delta": "{\"title\": \"Toast\", \"steps\": []"}),
+        ),
+        Ok(json!({"type": "response.output_text.delta", "delta": "}"})),
+        Ok(json!({"type": "response.completed"})),
+    ];
+    let mut stream = PartialJsonParser::for_response_format(&recipe_format())
+        .stream::<serde_json::Value, _>(futures::stream::iter(events));
+
+    let mut snapshots = Vec::new();
+    let mut outcome = None;
+    while let Some(event) = stream.next().await {
+        match event {
+            Ok(StructuredEvent::Partial(partial)) => snapshots.push(partial.snapshot),
+            Ok(StructuredEvent::Complete(value)) => outcome = Some(Ok(value)),
+            Err(err) => outcome = Some(Err(err)),
+        }
+    }
+    assert_eq!(snapshots, vec![json!({"title": "Toast", "steps": []})]);
+    assert!(outcome
+        .unwrap()
+        .unwrap_err()
+        .to_string()
+        .contains("minutes"));
+    assert_eq!(
+        stream.parser().buffer(),
+        r#"{"title": "Toast", "steps": []}"#
+    );
+}
@@ -0,0 +1,292 @@
+#![cfg(feature = "proxy")]
+
+mod common;
+
+use common::{catalog_body, catalog_item, chat_body, mock_client, mock_server, sse_body, API_KEY};
+use rainy_sdk::{openai_error_body, Budget, ProxyLogEntry, RainyClient, RainyError, RainyProxy};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+/// Starts a proxy whose client has a zero budget, so no request reaches the network.
+async fn start(token: Option<&str>) -> (String, Arc<Mutex<Vec<ProxyLogEntry>>>) {
+    let client = RainyClient::with_api_key(API_KEY)
+        .unwrap()
+        .with_budget(Budget::new().with_client_limit(0.0));
+    let mut proxy = RainyProxy::new(client);
+    if let Some(token) = token {
+        proxy = proxy.with_access_token(token);
+    }
+    serve(proxy).await
+}
+
+/// Serves `proxy` on a free local port and collects its log entries.
+async fn serve(proxy: RainyProxy) -> (String, Arc<Mutex<Vec<ProxyLogEntry>>>) {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let sink = log.clone();
+    let proxy =
+        proxy.on_request(move |entry: &ProxyLogEntry| sink.lock().unwrap().push(entry.clone()));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(proxy.serve(listener));
+    (format!("http://{addr}"), log)
+}
+
+async fn send(
+    request: rainy_sdk::reqwest::RequestBuilder,
+) -> (u16, rainy_sdk::reqwest::header::HeaderMap, Value) {
+    let response = request.send().await.unwrap();
+    let status = response.status().as_u16();
+    let headers = response.headers().clone();
+    (status, headers, response.json().await.unwrap())
+}
+
+#[test]
+fn test_maps_errors_to_openai_shape() {
+    let budget = RainyError::BudgetExceeded {
+        code: "BUDGET_EXCEEDED".to_string(),
+        message: "limit reached".to_string(),
+        scope: "client".to_string(),
+        limit: 1.0,
+        spent: 1.0,
+    };
+    let (status, body) = openai_error_body(&budget);
+    assert_eq!(status, 429);
+    assert_eq!(body["error"]["type"], "insufficient_quota");
+    assert_eq!(body["error"]["code"], "BUDGET_EXCEEDED");
+
+    let upstream = RainyError::Api {
+        code: "MODEL_NOT_FOUND".to_string(),
+        message: "no such model".to_string(),
+        status_code: 404,
+        retryable: false,
+        request_id: None,
+    };
+    let (status, body) = openai_error_body(&upstream);
+    assert_eq!(status, 404);
+    assert_eq!(body["error"]["type"], "invalid_request_error");
+
+    let timeout = RainyError::Timeout {
+        message: "slow".to_string(),
+        duration_ms: 30_000,
+    };
+    assert_eq!(openai_error_body(&timeout).0, 504);
+}
+
+#[tokio::test]
+async fn test_unknown_routes_and_methods() {
+    let (base, log) = start(None).await;
+    let http = rainy_sdk::reqwest::Client::new();
+
+    let (status, headers, body) = send(http.get(format!("{base}/v1/embeddings"))).await;
+    assert_eq!(status, 404);
+    assert_eq!(headers["content-type"], "application/json");
+    assert_eq!(body["error"]["code"], "NOT_FOUND");
+
+    let (status, _, body) = send(http.get(format!("{base}/v1/chat/completions"))).await;
+    assert_eq!(status, 405);
+    assert_eq!(body["error"]["type"], "invalid_request_error");
+
+    let (status, _, body) = send(
+        http.post(format!("{base}/v1/chat/completions"))
+            .body("{\"model\": "),
+    )
+    .await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"]["code"], "INVALID_REQUEST_BODY");
+
+    let statuses: Vec<u16> = log.lock().unwrap().iter().map(|e| e.status).collect();
+    assert_eq!(statuses, vec![404, 405, 400]);
+}
+
+#[tokio::test]
+async fn test_enforces_local_access_token() {
+    let (base, log) = start(Some("local-secret")).await;
+    let http = rainy_sdk::reqwest::Client::new();
+    let chat = json!({"model": "gpt-5", "messages": [{"role": "user", "content": "hi"}]});
+
+    let (status, _, body) = send(
+        http.post(format!("{base}/v1/chat/completions"))
+            .bearer_auth("wrong")
+            .json(&chat),
+    )
+    .await;
+    assert_eq!(status, 401);
+    assert_eq!(body["error"]["code"], "INVALID_PROXY_TOKEN");
+
+    // With the right token the request reaches the client and its budget.
+    let (status, _, body) = send(
+        http.post(format!("{base}/v1/chat/completions"))
+            .bearer_auth("local-secret")
+            .json(&chat),
+    )
+    .await;
+    assert_eq!(status, 429);
+    assert_eq!(body["error"]["type"], "insufficient_quota");
+
+    let log = log.lock().unwrap();
+    assert_eq!(log[0].model, None);
+    assert_eq!(log[1].model.as_deref(), Some("gpt-5"));
+    assert_eq!(log[1].error_code.as_deref(), Some("BUDGET_EXCEEDED"));
+    assert!(log[1].metadata.is_none());
+}
+
+#[tokio::test]
+async fn test_budget_applies_to_streaming_and_responses() {
+    let (base, log) = start(None).await;
+    let http = rainy_sdk::reqwest::Client::new();
+
+    let (status, _, body) = send(
+        http.post(format!("{base}/v1/chat/completions"))
+            .json(&json!({
+                "model": "gpt-5",
+                "messages": [{"role": "user", "content": "hi"}],
+                "stream": true,
+                "stream_options": {"include_usage": true},
+            })),
+    )
+    .await;
+    assert_eq!(status, 429);
+    assert_eq!(body["error"]["code"], "BUDGET_EXCEEDED");
+
+    let (status, _, _) = send(
+        http.post(format!("{base}/v1/responses"))
+            .json(&json!({"model": "gpt-5", "input": "hi"})),
+    )
+    .await;
+    assert_eq!(status, 429);
+
+    let log = log.lock().unwrap();
+    assert!(log[0].stream);
+    assert_eq!(log[1].path, "/v1/responses");
+    assert!(!log[1].stream);
+}
+
+#[tokio::test]
+async fn test_forwards_chat_completions_with_unmodelled_fields() {
+    let mut server = mock_server().await;
+    let upstream = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_body(mockito::Matcher::PartialJson(
+            json!({"model": "openai/gpt-5", "seed": 7}),
+        ))
+        .with_header("content-type", "application/json")
+        .with_header("x-request-id", "req-proxy")
+        .with_header("x-credits-used", "0.25")
+        .with_body(chat_body("openai/gpt-5", "Hello!", 3, 2))
+        .create_async()
+        .await;
+    let (base, log) = serve(RainyProxy::new(mock_client(&server))).await;
+    let http = rainy_sdk::reqwest::Client::new();
+
+    let (status, headers, body) = send(http.post(format!("{base}/v1/chat/completions")).json(
+        &json!({
+            "model": "openai/gpt-5",
+            "messages": [{"role": "user", "content": "hi"}],
+            "seed": 7,
+        }),
+    ))
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(headers["x-request-id"], "req-proxy");
+    assert_eq!(headers["x-rainy-credits-used"], "0.25");
+    assert_eq!(body["choices"][0]["message"]["content"], "Hello!");
+    assert_eq!(body["usage"]["total_tokens"], 5);
+    upstream.assert_async().await;
+
+    let log = log.lock().unwrap();
+    assert_eq!(log[0].status, 200);
+    assert!(!log[0].stream);
+    let metadata = log[0].metadata.as_ref().expect("metadata");
+    assert_eq!(metadata.request_id.as_deref(), Some("req-proxy"));
+}
+
+#[tokio::test]
+async fn test_relays_chat_streams_and_meters_their_usage() {
+    let mut server = mock_server().await;
+    let _catalog = server
+        .mock("GET", "/api/v1/models/catalog")
+        .with_header("content-type", "application/json")
+        .with_body(catalog_body(&[catalog_item("openai/gpt-5")
+            .context(100_000)
+            .pricing("0.001", "0.002")
+            .build()]))
+        .create_async()
+        .await;
+    let chunk = |choices: Value, usage: Value| {
+        json!({
+            "id": "chatcmpl-mock",
+            "object": "chat.completion.chunk",
+            "created": 1,
+            "model": "openai/gpt-5",
+            "choices": choices,
+            "usage": usage,
+        })
+    };
+    let upstream = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_body(mockito::Matcher::PartialJson(
+            json!({"stream": true, "stream_options": {"include_usage": true}}),
+        ))
+        .with_header("content-type", "text/event-stream")
+        .with_header("x-request-id", "req-stream")
+        .with_body(sse_body(&[
+            chunk(
+                json!([{"index": 0, "delta": {"role": "assistant", "content": "Hi"}}]),
+                json!(null),
+            ),
+            chunk(
+                json!([]),
+                json!({"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}),
+            ),
+        ]))
+        .expect(2)
+        .create_async()
+        .await;
+    let proxy =
+        RainyProxy::new(mock_client(&server).with_budget(Budget::new().with_client_limit(1.0)));
+    let (base, log) = serve(proxy.clone()).await;
+    let http = rainy_sdk::reqwest::Client::new();
+    let relay = |stream_options: Value| {
+        let request = http
+            .post(format!("{base}/v1/chat/completions"))
+            .json(&json!({
+                "model": "openai/gpt-5",
+                "messages": [{"role": "user", "content": "hi"}],
+                "max_tokens": 100,
+                "stream": true,
+                "stream_options": stream_options,
+            }));
+        async move {
+            let response = request.send().await.unwrap();
+            assert_eq!(response.headers()["content-type"], "text/event-stream");
+            response.text().await.unwrap()
+        }
+    };
+
+    // Usage is requested upstream for metering but not relayed to a caller that did not ask.
+    let body = relay(json!({})).await;
+    assert!(body.contains("\"content\":\"Hi\""));
+    assert!(!body.contains("prompt_tokens"));
+    assert!(body.ends_with("data: [DONE]\n\n"));
+
+    let body = relay(json!({"include_usage": true})).await;
+    assert!(body.contains("\"total_tokens\":15"));
+    assert!(body.ends_with("data: [DONE]\n\n"));
+    upstream.assert_async().await;
+
+    // 10 prompt tokens at 0.001 plus 5 completion tokens at 0.002, for each stream.
+    let snapshot = proxy.client().budget_snapshot().unwrap();
+    assert!((snapshot.total_spent - 0.04).abs() < 1e-9);
+    assert_eq!(snapshot.reserved, 0.0);
+
+    let log = log.lock().unwrap();
+    assert_eq!(log.len(), 2);
+    for entry in log.iter() {
+        assert!(entry.stream);
+        assert_eq!(entry.status, 200);
+        let metadata = entry.metadata.as_ref().expect("metadata");
+        assert_eq!(metadata.request_id.as_deref(), Some("req-stream"));
+        assert_eq!(metadata.tokens_used, Some(15));
+    }
+}
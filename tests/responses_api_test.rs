@@ -1,7 +1,10 @@
+mod common;
+
+use common::catalog_item;
 use rainy_sdk::{
     build_reasoning_config, model_constants::OPENAI_GPT_5, select_models, CapabilityFlag,
     ModelCatalogItem, ModelPricing, ModelSelectionCriteria, RainyCapabilities, RainyCapabilitiesV2,
-    RainyClient, ReasoningMode, ReasoningPreference, ResponsesRequest,
+    RainyClient, ReasoningMode, ReasoningPreference, ResponsesRequest, SelectionWeights,
 };
 
 #[test]
@@ -255,3 +258,156 @@ fn test_build_reasoning_config_returns_none_without_confirmed_profile() {
 
     assert!(payload.is_none());
 }
+
+fn extended_catalog() -> Vec<ModelCatalogItem> {
+    vec![
+        catalog_item("google/gemini-3-flash-preview")
+            .pricing("0.0000005", "0.000003")
+            .context(1_048_576)
+            .tokenizer("Gemini")
+            .accepted(&["tools", "response_format", "seed"])
+            .build(),
+        catalog_item("openai/gpt-4o-mini")
+            .pricing("0.00000015", "0.0000006")
+            .context(128_000)
+            .tokenizer("GPT")
+            .accepted(&["tools", "response_format", "seed", "logprobs"])
+            .build(),
+        catalog_item("anthropic/claude-sonnet-4")
+            .pricing("0.000003", "0.000015")
+            .context(200_000)
+            .tokenizer("Claude")
+            .accepted(&["tools"])
+            .build(),
+    ]
+}
+
+fn ids(models: &[ModelCatalogItem]) -> Vec<&str> {
+    models.iter().map(|m| m.id.as_str()).collect()
+}
+
+#[test]
+fn test_select_models_price_caps_context_and_parameters() {
+    let catalog = extended_catalog();
+
+    let capped = select_models(
+        &catalog,
+        &ModelSelectionCriteria {
+            max_prompt_price: Some(0.000001),
+            max_completion_price: Some(0.000005),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        ids(&capped),
+        vec!["openai/gpt-4o-mini", "google/gemini-3-flash-preview"]
+    );
+
+    let long_context = select_models(
+        &catalog,
+        &ModelSelectionCriteria {
+            min_context_length: Some(150_000),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        ids(&long_context),
+        vec!["google/gemini-3-flash-preview", "anthropic/claude-sonnet-4"]
+    );
+
+    let with_params = select_models(
+        &catalog,
+        &ModelSelectionCriteria {
+            required_parameters: vec!["seed".to_string(), "logprobs".to_string()],
+            ..Default::default()
+        },
+    );
+    assert_eq!(ids(&with_params), vec!["openai/gpt-4o-mini"]);
+
+    let tokenizer = select_models(
+        &catalog,
+        &ModelSelectionCriteria {
+            tokenizer: Some("gemini".to_string()),
+            ..Default::default()
+        },
+    );
+    assert_eq!(ids(&tokenizer), vec!["google/gemini-3-flash-preview"]);
+}
+
+#[test]
+fn test_select_models_provider_allow_and_deny_lists() {
+    let catalog = extended_catalog();
+    assert_eq!(catalog[2].provider(), Some("anthropic"));
+
+    let allowed = select_models(
+        &catalog,
+        &ModelSelectionCriteria {
+            allowed_providers: vec!["Google".to_string(), "anthropic".to_string()],
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        ids(&allowed),
+        vec!["google/gemini-3-flash-preview", "anthropic/claude-sonnet-4"]
+    );
+
+    let denied = select_models(
+        &catalog,
+        &ModelSelectionCriteria {
+            denied_providers: vec!["openai".to_string()],
+            ..Default::default()
+        },
+    );
+    assert!(!ids(&denied).contains(&"openai/gpt-4o-mini"));
+    assert_eq!(denied.len(), 2);
+}
+
+#[test]
+fn test_select_models_weighted_scoring() {
+    let catalog = extended_catalog();
+
+    let by_context = select_models(
+        &catalog,
+        &ModelSelectionCriteria {
+            weights: Some(SelectionWeights {
+                cost: 0.2,
+                context: 1.0,
+                capability: 0.0,
+            }),
+            ..Default::default()
+        },
+    );
+    assert_eq!(by_context[0].id, "google/gemini-3-flash-preview");
+
+    let by_capability = select_models(
+        &catalog,
+        &ModelSelectionCriteria {
+            weights: Some(SelectionWeights {
+                cost: 0.0,
+                context: 0.0,
+                capability: 1.0,
+            }),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        ids(&by_capability),
+        vec![
+            "openai/gpt-4o-mini",
+            "google/gemini-3-flash-preview",
+            "anthropic/claude-sonnet-4"
+        ]
+    );
+
+    let default_weights = select_models(
+        &catalog,
+        &ModelSelectionCriteria {
+            weights: Some(SelectionWeights::default()),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        ids(&default_weights),
+        ids(&select_models(&catalog, &ModelSelectionCriteria::default()))
+    );
+}
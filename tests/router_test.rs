@@ -0,0 +1,239 @@
+mod common;
+
+use common::{catalog_body, catalog_item, mock_client, mock_server};
+use rainy_sdk::{
+    FunctionDefinition, ModelCatalogItem, ModelRouter, ModelSelectionCriteria, ModelSpec,
+    OpenAIChatCompletionRequest, OpenAIChatMessage, OpenAIContentPart, OpenAIImageUrl,
+    OpenAIMessageContent, RainyClient, RequestNeeds, ResponsesRequest, RoutableRequest,
+    RoutingStrategy, Tool, ToolType,
+};
+
+fn catalog() -> Vec<ModelCatalogItem> {
+    vec![
+        catalog_item("model/cheap-text")
+            .pricing("0.000001", "0.000002")
+            .context(32_000)
+            .inputs(&["text"])
+            .build(),
+        catalog_item("model/vision-tools")
+            .pricing("0.000003", "0.000002")
+            .context(128_000)
+            .inputs(&["text", "image"])
+            .accepted(&["tools"])
+            .build(),
+        catalog_item("model/long")
+            .pricing("0.000005", "0.000002")
+            .context(1_000_000)
+            .inputs(&["text", "image"])
+            .accepted(&["tools", "response_format"])
+            .build(),
+    ]
+}
+
+fn router(strategy: RoutingStrategy) -> ModelRouter {
+    ModelRouter::new(
+        ModelSpec::criteria(ModelSelectionCriteria {
+            required_input_modalities: vec!["text".to_string()],
+            ..Default::default()
+        }),
+        strategy,
+    )
+}
+
+fn weather_tool() -> Tool {
+    Tool {
+        r#type: ToolType::Function,
+        function: FunctionDefinition {
+            name: "get_weather".to_string(),
+            description: None,
+            parameters: None,
+        },
+    }
+}
+
+#[test]
+fn test_request_needs_detection() {
+    let text = OpenAIChatCompletionRequest::new("auto", vec![OpenAIChatMessage::user("hi")]);
+    assert_eq!(text.needs(), RequestNeeds::default());
+
+    let mut vision =
+        OpenAIChatCompletionRequest::new("auto", vec![OpenAIChatMessage::user("what is this?")]);
+    vision.messages[0].content = Some(OpenAIMessageContent::Parts(vec![
+        OpenAIContentPart::Text {
+            text: "what is this?".to_string(),
+        },
+        OpenAIContentPart::ImageUrl {
+            image_url: OpenAIImageUrl {
+                url: "https://example.com/cat.png".to_string(),
+                detail: None,
+            },
+        },
+    ]));
+    vision.tools = Some(vec![weather_tool()]);
+    let needs = vision.needs();
+    assert!(needs.images && needs.tools && !needs.response_format);
+
+    let responses = ResponsesRequest::new(
+        "auto",
+        serde_json::json!([{ "role": "user", "content": [
+            { "type": "input_text", "text": "describe" },
+            { "type": "input_image", "image_url": "https://example.com/dog.png" }
+        ]}]),
+    );
+    assert!(responses.needs().images);
+
+    let mut audio =
+        OpenAIChatCompletionRequest::new("auto", vec![OpenAIChatMessage::user("transcribe")]);
+    audio.messages[0].content = Some(OpenAIMessageContent::Parts(vec![
+        OpenAIContentPart::input_audio("UklGRg==", "wav"),
+    ]));
+    let needs = audio.needs();
+    assert!(needs.audio && !needs.images && !needs.files);
+
+    let document = ResponsesRequest::new(
+        "auto",
+        serde_json::json!([{ "role": "user", "content": [
+            { "type": "input_file", "file_id": "file-123" }
+        ]}]),
+    );
+    assert!(document.needs().files);
+}
+
+#[test]
+fn test_strategies_respect_request_needs() {
+    let catalog = catalog();
+    let none = RequestNeeds::default();
+    let vision_tools = RequestNeeds {
+        images: true,
+        tools: true,
+        ..Default::default()
+    };
+    let structured = RequestNeeds {
+        response_format: true,
+        ..Default::default()
+    };
+
+    let cheapest = router(RoutingStrategy::Cheapest);
+    assert_eq!(cheapest.select(&catalog, none).unwrap(), "model/cheap-text");
+    assert_eq!(
+        cheapest.select(&catalog, vision_tools).unwrap(),
+        "model/vision-tools"
+    );
+    assert_eq!(cheapest.select(&catalog, structured).unwrap(), "model/long");
+
+    let largest = router(RoutingStrategy::LargestContext);
+    assert_eq!(largest.select(&catalog, none).unwrap(), "model/long");
+
+    let round_robin = router(RoutingStrategy::RoundRobin);
+    let picks: Vec<String> = (0..4)
+        .map(|_| round_robin.select(&catalog, vision_tools).unwrap())
+        .collect();
+    assert_eq!(
+        picks,
+        vec![
+            "model/vision-tools",
+            "model/long",
+            "model/vision-tools",
+            "model/long"
+        ]
+    );
+
+    let err = cheapest
+        .select(
+            &catalog[..1],
+            RequestNeeds {
+                images: true,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err.code(), Some("NO_MATCHING_MODEL"));
+
+    // No catalog model lists audio or file input, so neither is routed to a text model.
+    for needs in [
+        RequestNeeds {
+            audio: true,
+            ..Default::default()
+        },
+        RequestNeeds {
+            files: true,
+            ..Default::default()
+        },
+    ] {
+        let err = cheapest.select(&catalog, needs).unwrap_err();
+        assert_eq!(err.code(), Some("NO_MATCHING_MODEL"));
+    }
+}
+
+#[test]
+fn test_lowest_latency_prefers_unmeasured_then_fastest() {
+    let catalog = catalog();
+    let router = router(RoutingStrategy::LowestLatency);
+    router.record_latency("model/cheap-text", 900);
+    router.record_latency("model/vision-tools", 300);
+    assert_eq!(
+        router.select(&catalog, RequestNeeds::default()).unwrap(),
+        "model/long"
+    );
+
+    router.record_latency("model/long", 500);
+    router.record_latency("model/long", 700);
+    assert_eq!(router.average_latency_ms("model/long"), Some(600.0));
+    assert_eq!(
+        router.select(&catalog, RequestNeeds::default()).unwrap(),
+        "model/vision-tools"
+    );
+}
+
+#[tokio::test]
+async fn test_route_uses_cached_catalog_and_retargets_request() {
+    let client =
+        RainyClient::with_api_key("ra-0123456789abcdef0123456789abcdef0123456789abcdef").unwrap();
+    let router = router(RoutingStrategy::Cheapest).with_catalog(catalog());
+
+    let mut request = OpenAIChatCompletionRequest::new("auto", vec![OpenAIChatMessage::user("hi")]);
+    request.tools = Some(vec![weather_tool()]);
+    let model = router.route(&client, &mut request).await.unwrap();
+    assert_eq!(model, "model/vision-tools");
+    assert_eq!(request.model, "model/vision-tools");
+
+    let fixed = ModelRouter::new(
+        ModelSpec::Fixed("model/pinned".to_string()),
+        RoutingStrategy::Cheapest,
+    );
+    let mut request = ResponsesRequest::text("auto", "hi")
+        .with_reasoning(serde_json::json!({ "effort": "high" }));
+    assert_eq!(
+        fixed.route(&client, &mut request).await.unwrap(),
+        "model/pinned"
+    );
+    assert_eq!(request.model, "model/pinned");
+    // The pinned model is not in any catalog, but the caller's reasoning is kept.
+    assert_eq!(
+        request.reasoning,
+        Some(serde_json::json!({ "effort": "high" }))
+    );
+}
+
+#[tokio::test]
+async fn test_route_shares_the_client_catalog_cache() {
+    let mut server = mock_server().await;
+    let catalog_mock = server
+        .mock("GET", "/api/v1/models/catalog")
+        .with_header("content-type", "application/json")
+        .with_body(catalog_body(&catalog()))
+        .expect(1)
+        .create_async()
+        .await;
+    let client = mock_client(&server);
+    let router = router(RoutingStrategy::Cheapest);
+
+    let mut request = OpenAIChatCompletionRequest::new("auto", vec![OpenAIChatMessage::user("hi")]);
+    assert_eq!(
+        router.route(&client, &mut request).await.unwrap(),
+        "model/cheap-text"
+    );
+    // The client's capability checks reuse the catalog the router loaded.
+    assert!(client.catalog_entry("model/long").await.unwrap().is_some());
+    catalog_mock.assert_async().await;
+}
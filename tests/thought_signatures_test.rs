@@ -0,0 +1,221 @@
+mod common;
+
+use common::{mock_client, mock_server};
+use rainy_sdk::{
+    missing_thought_signatures, ChatCompletionStreamResponse, OpenAIChatCompletionRequest,
+    OpenAIChatCompletionResponse, OpenAIChatMessage, OpenAIFunctionCall, OpenAIToolCall,
+    RainyClient, SignatureScope, StreamAccumulator, ThoughtSignatureCache,
+};
+use serde_json::json;
+
+fn call(id: &str) -> OpenAIToolCall {
+    OpenAIToolCall {
+        id: id.to_string(),
+        r#type: "function".to_string(),
+        extra_content: None,
+        function: OpenAIFunctionCall {
+            name: "list_files".to_string(),
+            arguments: r#"{"path":"."}"#.to_string(),
+        },
+    }
+}
+
+fn history(first: OpenAIToolCall) -> Vec<OpenAIChatMessage> {
+    vec![
+        OpenAIChatMessage::user("List the workspace and the docs folder."),
+        OpenAIChatMessage::assistant_with_tool_calls(vec![first, call("call_2")]),
+        OpenAIChatMessage::tool("call_1", "[\"src\"]"),
+        OpenAIChatMessage::tool("call_2", "[\"guide.md\"]"),
+    ]
+}
+
+#[test]
+fn test_signature_accessors_keep_other_extra_content() {
+    let mut tool_call = call("call_1");
+    tool_call.extra_content = Some(json!({"google": {"other": 1}, "vendor": true}));
+    tool_call.set_thought_signature("sig_a");
+
+    assert_eq!(tool_call.thought_signature(), Some("sig_a"));
+    assert_eq!(
+        tool_call.extra_content,
+        Some(json!({"google": {"other": 1, "thought_signature": "sig_a"}, "vendor": true}))
+    );
+}
+
+#[test]
+fn test_validation_checks_first_call_of_current_turn_only() {
+    let missing = OpenAIChatCompletionRequest::new("gemini-3-pro-preview", history(call("call_1")));
+    assert_eq!(
+        missing_thought_signatures(&missing.messages),
+        vec!["call_1"]
+    );
+    let err = missing.validate_openai_compatibility().unwrap_err();
+    assert!(err.contains("call_1"), "{err}");
+
+    let signed = OpenAIChatCompletionRequest::new(
+        "gemini-3-pro-preview",
+        history(call("call_1").with_thought_signature("sig")),
+    );
+    assert!(signed.validate_thought_signatures().is_ok());
+
+    // Earlier turns and models without signatures are not validated.
+    let mut next_turn = missing.messages.clone();
+    next_turn.push(OpenAIChatMessage::user("Thanks!"));
+    assert!(missing_thought_signatures(&next_turn).is_empty());
+    let other = OpenAIChatCompletionRequest::new("gemini-2.5-flash", history(call("call_1")));
+    assert!(other.validate_thought_signatures().is_ok());
+}
+
+#[test]
+fn test_cache_captures_and_reattaches_signatures() {
+    let cache = ThoughtSignatureCache::with_capacity(2);
+    let response: OpenAIChatCompletionResponse = serde_json::from_value(json!({
+        "id": "c1", "object": "chat.completion", "created": 1, "model": "gemini-3-pro-preview",
+        "choices": [{"index": 0, "finish_reason": "tool_calls", "message": {
+            "role": "assistant",
+            "tool_calls": [{
+                "id": "call_1", "type": "function",
+                "extra_content": {"google": {"thought_signature": "sig_1"}},
+                "function": {"name": "list_files", "arguments": "{}"}
+            }]
+        }}]
+    }))
+    .unwrap();
+    let mut messages = history(call("call_1"));
+    assert_eq!(cache.capture_response(&messages[..1], &response), 1);
+
+    assert_eq!(cache.reattach(&mut messages), 1);
+    let calls = messages[1].tool_calls.as_ref().unwrap();
+    assert_eq!(calls[0].thought_signature(), Some("sig_1"));
+    assert_eq!(calls[1].thought_signature(), None);
+
+    let scope = SignatureScope::of(&messages[..1]);
+    assert_eq!(cache.get(scope, "call_1").as_deref(), Some("sig_1"));
+    cache.insert(scope, "call_2", "sig_2");
+    cache.insert(scope, "call_3", "sig_3");
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(scope, "call_1"), None);
+}
+
+#[test]
+fn test_cache_does_not_leak_signatures_between_conversations() {
+    let cache = ThoughtSignatureCache::new();
+    let mut first = history(call("call_1"));
+    let scope = SignatureScope::of(&first[..1]);
+    cache.insert(scope, "call_1", "sig_first");
+
+    // Another conversation reusing the same tool call ID gets nothing.
+    let mut other = history(call("call_1"));
+    other[0] = OpenAIChatMessage::user("Something else entirely.");
+    assert_eq!(cache.reattach(&mut other), 0);
+
+    // The same history hashes the same with or without signatures attached.
+    first[1].tool_calls.as_mut().unwrap()[1].set_thought_signature("sig_other");
+    assert_eq!(cache.reattach(&mut first), 1);
+    assert_eq!(SignatureScope::of(&first[..2]), {
+        let mut stripped = history(call("call_1"));
+        stripped.truncate(2);
+        SignatureScope::of(&stripped)
+    });
+}
+
+#[test]
+fn test_stream_signatures_survive_accumulation() {
+    let chunk: ChatCompletionStreamResponse = serde_json::from_value(json!({
+        "id": "c1", "object": "chat.completion.chunk", "created": 1, "model": "gemini-3-pro-preview",
+        "choices": [{"index": 0, "delta": {"tool_calls": [{
+            "index": 0, "id": "call_1", "type": "function",
+            "extra_content": {"google": {"thought_signature": "sig_1"}},
+            "function": {"name": "list_files", "arguments": "{}"}
+        }]}}]
+    }))
+    .unwrap();
+
+    let mut accumulator = StreamAccumulator::new();
+    accumulator.push(&chunk);
+    let message = accumulator.message();
+    assert_eq!(
+        message.tool_calls.unwrap()[0].thought_signature(),
+        Some("sig_1")
+    );
+}
+
+#[tokio::test]
+async fn test_client_rejects_unsigned_gemini_3_history_locally() {
+    let client =
+        RainyClient::with_api_key("ra-0123456789abcdef0123456789abcdef0123456789abcdef").unwrap();
+    let request = OpenAIChatCompletionRequest::new("gemini-3-pro-preview", history(call("call_1")));
+
+    let err = client
+        .create_openai_chat_completion(request)
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Some("MISSING_THOUGHT_SIGNATURE"));
+    assert!(client.thought_signatures().is_empty());
+}
+
+#[tokio::test]
+async fn test_client_replays_captured_signature_against_mock_server() {
+    let mut server = mock_server().await;
+    let question = OpenAIChatMessage::user("List the workspace and the docs folder.");
+    let _first = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_body(mockito::Matcher::PartialJson(
+            json!({"messages": [question]}),
+        ))
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!({
+                "id": "c1", "object": "chat.completion", "created": 1,
+                "model": "gemini-3-pro-preview",
+                "choices": [{"index": 0, "finish_reason": "tool_calls", "message": {
+                    "role": "assistant",
+                    "tool_calls": [{
+                        "id": "call_1", "type": "function",
+                        "extra_content": {"google": {"thought_signature": "sig_1"}},
+                        "function": {"name": "list_files", "arguments": "{}"}
+                    }]
+                }}]
+            })
+            .to_string(),
+        )
+        .create_async()
+        .await;
+    let replay = server
+        .mock("POST", "/api/v1/chat/completions")
+        .match_body(mockito::Matcher::PartialJson(json!({"messages": [
+            {"role": "user"},
+            {"tool_calls": [{"extra_content": {"google": {"thought_signature": "sig_1"}}}]},
+        ]})))
+        .with_header("content-type", "application/json")
+        .with_body(common::chat_body("gemini-3-pro-preview", "Done.", 10, 2))
+        .expect(1)
+        .create_async()
+        .await;
+    let client = mock_client(&server);
+
+    client
+        .create_openai_chat_completion(OpenAIChatCompletionRequest::new(
+            "gemini-3-pro-preview",
+            vec![question.clone()],
+        ))
+        .await
+        .expect("tool call");
+
+    // Replay the history with the signature stripped; the client restores it.
+    let mut unsigned = call("call_1");
+    unsigned.function.arguments = "{}".to_string();
+    let request = OpenAIChatCompletionRequest::new(
+        "gemini-3-pro-preview",
+        vec![
+            question,
+            OpenAIChatMessage::assistant_with_tool_calls(vec![unsigned]),
+            OpenAIChatMessage::tool("call_1", "[\"src\"]"),
+        ],
+    );
+    client
+        .create_openai_chat_completion(request)
+        .await
+        .expect("replay");
+    replay.assert_async().await;
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use common::catalog_item;
+use rainy_sdk::{
+    ChatCompletionRequest, ChatMessage, OpenAIChatMessage, OpenAIContentPart, OpenAIFunctionCall,
+    OpenAIMessageContent, OpenAIToolCall, ResponsesRequest, TokenCounter, TokenizerFamily,
+};
+use serde_json::json;
+
+#[test]
+fn test_family_from_tokenizer_name_and_model_id() {
+    assert_eq!(
+        TokenizerFamily::from_name("Claude"),
+        TokenizerFamily::Claude
+    );
+    assert_eq!(TokenizerFamily::from_name("Llama3"), TokenizerFamily::Llama);
+    assert_eq!(TokenizerFamily::from_name("Qwen3"), TokenizerFamily::Qwen);
+    assert_eq!(
+        TokenizerFamily::from_name("Router"),
+        TokenizerFamily::Generic
+    );
+
+    assert_eq!(
+        TokenizerFamily::from_model_id("openai/gpt-4o-mini"),
+        TokenizerFamily::O200k
+    );
+    assert_eq!(
+        TokenizerFamily::from_model_id("openai/gpt-3.5-turbo"),
+        TokenizerFamily::Cl100k
+    );
+    assert_eq!(
+        TokenizerFamily::from_model_id("mistralai/mixtral-8x7b"),
+        TokenizerFamily::Mistral
+    );
+
+    // `GPT` is disambiguated by the id; unknown names fall back to the id.
+    assert_eq!(
+        TokenizerFamily::for_model(&catalog_item("openai/gpt-4-turbo").tokenizer("GPT").build()),
+        TokenizerFamily::Cl100k
+    );
+    assert_eq!(
+        TokenizerFamily::for_model(&catalog_item("openai/gpt-5").tokenizer("GPT").build()),
+        TokenizerFamily::O200k
+    );
+    assert_eq!(
+        TokenizerFamily::for_model(
+            &catalog_item("google/gemini-2.5-pro")
+                .tokenizer("Other")
+                .build()
+        ),
+        TokenizerFamily::Gemini
+    );
+    assert_eq!(
+        TokenizerFamily::for_model(&catalog_item("x/unknown").build()),
+        TokenizerFamily::Generic
+    );
+}
+
+#[test]
+fn test_heuristic_counts_are_plausible() {
+    let counter = TokenCounter::new(TokenizerFamily::Claude);
+    assert_eq!(counter.count_text(""), 0);
+
+    let prose = "The quick brown fox jumps over the lazy dog.";
+    let tokens = counter.count_text(prose);
+    assert!((9..=14).contains(&tokens), "prose: {tokens}");
+
+    // CJK text is far denser than Latin text of the same length.
+    let cjk = "今日はとても良い天気ですね";
+    assert!(counter.count_text(cjk) >= cjk.chars().count() as u32);
+
+    // Families that split digits individually count long numbers as more tokens.
+    let number = "1234567890";
+    assert!(
+        TokenCounter::new(TokenizerFamily::Gemini).count_text(number)
+            > TokenCounter::new(TokenizerFamily::Generic).count_text(number)
+    );
+}
+
+#[test]
+fn test_message_counts_include_overhead_tool_calls_and_images() {
+    let counter = TokenCounter::new(TokenizerFamily::Gemini);
+    let plain = counter.count_chat_message(&ChatMessage::user("hello"));
+    assert_eq!(
+        plain,
+        counter.message_overhead() + counter.count_text("hello")
+    );
+
+    let request = ChatCompletionRequest::new(
+        "google/gemini-2.5-flash",
+        vec![ChatMessage::system("Be brief."), ChatMessage::user("hello")],
+    );
+    assert_eq!(
+        counter.count_chat_request(&request),
+        counter.count_chat_message(&request.messages[0]) + plain + counter.request_overhead()
+    );
+
+    let call = OpenAIChatMessage::assistant_with_tool_calls(vec![OpenAIToolCall {
+        id: "call_1".to_string(),
+        r#type: "function".to_string(),
+        extra_content: None,
+        function: OpenAIFunctionCall {
+            name: "get_weather".to_string(),
+            arguments: r#"{"city":"Lima"}"#.to_string(),
+        },
+    }]);
+    assert!(counter.count_openai_message(&call) > counter.message_overhead() + 5);
+
+    let image = OpenAIChatMessage::user(OpenAIMessageContent::Parts(vec![
+        OpenAIContentPart::text("What is this?"),
+        OpenAIContentPart::image_url("https://example.com/cat.png"),
+    ]));
+    let low = OpenAIChatMessage::user(OpenAIMessageContent::Parts(vec![
+        OpenAIContentPart::text("What is this?"),
+        OpenAIContentPart::image_url_with_detail("https://example.com/cat.png", "low"),
+    ]));
+    assert_eq!(
+        counter.count_openai_message(&image) - counter.count_openai_message(&low),
+        258 - 85
+    );
+}
+
+#[test]
+fn test_responses_input_items() {
+    let counter = TokenCounter::new(TokenizerFamily::O200k);
+    let text = ResponsesRequest::text("openai/gpt-5", "Summarize the report");
+    let items = ResponsesRequest::new(
+        "openai/gpt-5",
+        json!([
+            {"role": "user", "content": [{"type": "input_text", "text": "Summarize the report"}]},
+            {"type": "function_call", "call_id": "c1", "name": "fetch", "arguments": "{}"},
+            {"type": "function_call_output", "call_id": "c1", "output": "report body"},
+        ]),
+    );
+
+    let single = counter.count_responses_request(&text);
+    assert_eq!(
+        single,
+        counter.message_overhead()
+            + counter.count_text("Summarize the report")
+            + counter.request_overhead()
+    );
+    assert!(counter.count_responses_request(&items) > single + 2 * counter.message_overhead());
+}
+
+#[cfg(feature = "tiktoken")]
+#[test]
+fn test_exact_bpe_counts() {
+    let counter = TokenCounter::new(TokenizerFamily::Cl100k);
+    assert!(counter.family().is_exact());
+    assert_eq!(counter.count_text("hello world"), 2);
+    assert_eq!(
+        TokenCounter::new(TokenizerFamily::O200k).count_text("tiktoken is great!"),
+        6
+    );
+}
@@ -0,0 +1,206 @@
+use rainy_sdk::{
+    validate_json_schema, ChatCompletionStreamResponse, FunctionDefinition, MalformedReason,
+    RainyError, Tool, ToolCallAssembler, ToolCallEvent, ToolType,
+};
+use serde_json::json;
+
+fn weather_tool() -> Tool {
+    Tool {
+        r#type: ToolType::Function,
+        function: FunctionDefinition {
+            name: "get_weather".to_string(),
+            description: None,
+            parameters: Some(json!({
+                "type": "object",
+                "properties": {
+                    "city": {"type": "string", "minLength": 1},
+                    "unit": {"enum": ["celsius", "fahrenheit"]}
+                },
+                "required": ["city"],
+                "additionalProperties": false
+            })),
+        },
+    }
+}
+
+fn chunk(
+    tool_calls: serde_json::Value,
+    finish_reason: Option<&str>,
+) -> ChatCompletionStreamResponse {
+    serde_json::from_value(json!({
+        "id": "chatcmpl_1",
+        "object": "chat.completion.chunk",
+        "created": 1741171200u64,
+        "model": "gpt-5",
+        "choices": [{"index": 0, "delta": {"tool_calls": tool_calls}, "finish_reason": finish_reason}]
+    }))
+    .unwrap()
+}
+
+fn fragment(index: u32, arguments: &str) -> serde_json::Value {
+    json!([{"index": index, "function": {"arguments": arguments}}])
+}
+
+#[test]
+fn test_emits_started_deltas_and_ready_as_arguments_close() {
+    let mut assembler = ToolCallAssembler::new(&[weather_tool()]);
+    let header = chunk(
+        json!([{"index": 0, "id": "call_1", "type": "function",
+                "function": {"name": "get_weather", "arguments": ""}}]),
+        None,
+    );
+    assert!(assembler.push(&header).is_empty());
+
+    let events = assembler.push(&chunk(fragment(0, r#"{"city": "Par"#), None));
+    assert_eq!(
+        events[0],
+        ToolCallEvent::ToolCallStarted {
+            index: 0,
+            id: "call_1".to_string(),
+            name: "get_weather".to_string()
+        }
+    );
+    assert!(
+        matches!(&events[1], ToolCallEvent::ArgumentsDelta { delta, .. } if delta == r#"{"city": "Par"#)
+    );
+
+    let events = assembler.push(&chunk(fragment(0, r#"is}"}"#), None));
+    match &events[1] {
+        ToolCallEvent::ToolCallReady {
+            index,
+            call,
+            arguments,
+        } => {
+            assert_eq!(*index, 0);
+            assert_eq!(call.id, "call_1");
+            assert_eq!(call.function.arguments, r#"{"city": "Paris}"}"#);
+            assert_eq!(arguments, &json!({"city": "Paris}"}));
+        }
+        other => panic!("unexpected event: {other:?}"),
+    }
+    assert!(assembler
+        .push(&chunk(json!([]), Some("tool_calls")))
+        .is_empty());
+    assert_eq!(assembler.tool_calls().len(), 1);
+}
+
+#[test]
+fn test_repeated_names_on_later_deltas_are_not_appended() {
+    let mut assembler = ToolCallAssembler::new(&[weather_tool()]);
+    assembler.push(&chunk(
+        json!([{"index": 0, "id": "call_1", "function": {"name": "get_weather", "arguments": ""}}]),
+        None,
+    ));
+    let events = assembler.push(&chunk(
+        json!([{"index": 0, "function": {"name": "get_weather", "arguments": r#"{"city": "Paris"}"#}}]),
+        None,
+    ));
+    assert!(matches!(
+        events.last(),
+        Some(ToolCallEvent::ToolCallReady { call, .. }) if call.function.name == "get_weather"
+    ));
+    assert_eq!(assembler.tool_calls()[0].function.name, "get_weather");
+}
+
+#[test]
+fn test_reports_malformed_calls_with_index_and_name() {
+    let mut assembler = ToolCallAssembler::new(&[weather_tool()]);
+    assembler.push(&chunk(
+        json!([
+            {"index": 0, "id": "call_a", "function": {"name": "get_weather", "arguments": ""}},
+            {"index": 1, "id": "call_b", "function": {"name": "get_weather", "arguments": ""}},
+            {"index": 2, "id": "call_c", "function": {"name": "get_time", "arguments": ""}}
+        ]),
+        None,
+    ));
+
+    let events = assembler.push(&chunk(fragment(0, r#"{"city": ]"#), None));
+    let ToolCallEvent::ToolCallMalformed(error) = &events[2] else {
+        panic!("expected malformed call, got {events:?}");
+    };
+    assert_eq!((error.index, error.name.as_str()), (0, "get_weather"));
+    assert!(matches!(error.reason, MalformedReason::InvalidJson(_)));
+    assert!(error.to_string().starts_with("tool call #0 (get_weather)"));
+
+    let events = assembler.push(&chunk(fragment(1, r#"{"city": "", "days": 3}"#), None));
+    let ToolCallEvent::ToolCallMalformed(error) = &events[2] else {
+        panic!("expected schema failure, got {events:?}");
+    };
+    let MalformedReason::SchemaViolations(violations) = &error.reason else {
+        panic!("unexpected reason {:?}", error.reason);
+    };
+    assert_eq!(violations.len(), 2);
+    assert_eq!(violations[0].path, "/city");
+
+    let events = assembler.push(&chunk(fragment(2, "{}"), None));
+    let ToolCallEvent::ToolCallMalformed(error) = &events[2] else {
+        panic!("expected unknown tool, got {events:?}");
+    };
+    assert_eq!(error.reason, MalformedReason::UnknownTool);
+    let error: RainyError = error.clone().into();
+    assert_eq!(error.code(), Some("MALFORMED_TOOL_CALL"));
+}
+
+#[tokio::test]
+async fn test_stream_adapter_finalizes_open_calls_at_end() {
+    use futures::StreamExt;
+
+    let chunks = vec![
+        Ok(chunk(
+            json!([
+                {"index": 0, "id": "call_1", "function": {"name": "get_weather", "arguments": ""}},
+                {"index": 1, "id": "call_2", "function": {"name": "get_weather", "arguments": "{\"ci"}}
+            ]),
+            None,
+        )),
+        Ok(chunk(json!([]), None)),
+    ];
+    let events: Vec<ToolCallEvent> = ToolCallAssembler::default()
+        .stream(futures::stream::iter(chunks))
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+    assert!(matches!(
+        &events[0],
+        ToolCallEvent::ToolCallStarted { index: 1, .. }
+    ));
+    assert!(matches!(
+        &events[1],
+        ToolCallEvent::ArgumentsDelta { index: 1, .. }
+    ));
+    assert!(matches!(
+        &events[3],
+        ToolCallEvent::ToolCallReady { index: 0, arguments, .. } if arguments == &json!({})
+    ));
+    assert!(matches!(
+        &events[4],
+        ToolCallEvent::ToolCallMalformed(error) if error.index == 1 && error.reason == MalformedReason::Incomplete
+    ));
+}
+
+#[test]
+fn test_schema_validation_covers_common_keywords() {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "items": {"type": "array", "items": {"$ref": "#/$defs/item"}, "maxItems": 2},
+            "limit": {"type": "integer", "minimum": 1, "exclusiveMaximum": 100},
+            "mode": {"anyOf": [{"const": "fast"}, {"type": "null"}]}
+        },
+        "$defs": {"item": {"type": "object", "required": ["id"]}}
+    });
+
+    assert!(validate_json_schema(
+        &schema,
+        &json!({"items": [{"id": 1}], "limit": 5, "mode": null})
+    )
+    .is_empty());
+
+    let violations = validate_json_schema(
+        &schema,
+        &json!({"items": [{"id": 1}, {}, {"id": 3}], "limit": 100.5, "mode": "slow"}),
+    );
+    let paths: Vec<&str> = violations.iter().map(|v| v.path.as_str()).collect();
+    assert_eq!(paths, vec!["/items", "/items/1", "/limit", "/mode"]);
+}
@@ -0,0 +1,130 @@
+use rainy_sdk::{
+    ChatCompletionRequest, ChatMessage, FunctionDefinition, OpenAIChatCompletionRequest,
+    OpenAIChatMessage, ResponsesRequest, Tool, ToolChoice, ToolType,
+};
+use serde_json::json;
+
+fn weather_tool() -> Tool {
+    Tool {
+        r#type: ToolType::Function,
+        function: FunctionDefinition {
+            name: "get_weather".to_string(),
+            description: Some("Current weather for a city".to_string()),
+            parameters: Some(json!({
+                "type": "object",
+                "properties": {"city": {"type": "string"}},
+                "required": ["city"]
+            })),
+        },
+    }
+}
+
+#[test]
+fn test_tool_choice_round_trips_openai_wire_format() {
+    let cases = [
+        (ToolChoice::None, json!("none")),
+        (ToolChoice::Auto, json!("auto")),
+        (ToolChoice::Required, json!("required")),
+        (
+            ToolChoice::function("get_weather"),
+            json!({"type": "function", "function": {"name": "get_weather"}}),
+        ),
+    ];
+    for (choice, wire) in cases {
+        assert_eq!(serde_json::to_value(&choice).unwrap(), wire);
+        assert_eq!(serde_json::from_value::<ToolChoice>(wire).unwrap(), choice);
+    }
+
+    let responses_shape: ToolChoice =
+        serde_json::from_value(json!({"type": "function", "name": "get_weather"})).unwrap();
+    assert_eq!(responses_shape, ToolChoice::function("get_weather"));
+    assert!(serde_json::from_value::<ToolChoice>(json!("sometimes")).is_err());
+}
+
+#[test]
+fn test_recorded_chat_request_round_trips() {
+    let recorded = json!({
+        "model": "gpt-5",
+        "messages": [{"role": "user", "content": "Weather in Paris?"}],
+        "tools": [{
+            "type": "function",
+            "function": {
+                "name": "get_weather",
+                "description": "Current weather for a city",
+                "parameters": {
+                    "type": "object",
+                    "properties": {"city": {"type": "string"}},
+                    "required": ["city"]
+                }
+            }
+        }],
+        "tool_choice": "required",
+        "parallel_tool_calls": false
+    });
+
+    let request: OpenAIChatCompletionRequest = serde_json::from_value(recorded.clone()).unwrap();
+    assert_eq!(request.tool_choice, Some(ToolChoice::Required));
+    assert_eq!(request.parallel_tool_calls, Some(false));
+    assert_eq!(serde_json::to_value(&request).unwrap(), recorded);
+
+    let built = OpenAIChatCompletionRequest::new(
+        "gpt-5",
+        vec![OpenAIChatMessage::user("Weather in Paris?")],
+    )
+    .with_tools(vec![weather_tool()])
+    .with_tool_choice(ToolChoice::Required)
+    .with_parallel_tool_calls(false);
+    assert_eq!(serde_json::to_value(&built).unwrap(), recorded);
+}
+
+#[test]
+fn test_responses_request_uses_responses_tool_choice_shape() {
+    let request = ResponsesRequest::text("gpt-5", "Weather in Paris?")
+        .with_tool_choice(ToolChoice::function("get_weather"))
+        .with_parallel_tool_calls(true);
+    let json = serde_json::to_value(&request).unwrap();
+    assert_eq!(
+        json["tool_choice"],
+        json!({"type": "function", "name": "get_weather"})
+    );
+    assert_eq!(json["parallel_tool_calls"], true);
+    assert_eq!(
+        request.typed_tool_choice(),
+        Some(ToolChoice::function("get_weather"))
+    );
+    let hosted = ResponsesRequest {
+        tool_choice: Some(json!({"type": "web_search_preview"})),
+        ..request
+    };
+    assert_eq!(hosted.typed_tool_choice(), None);
+
+    let chat = OpenAIChatCompletionRequest::new("gpt-5", vec![OpenAIChatMessage::user("Hi")])
+        .with_tools(vec![weather_tool()])
+        .with_tool_choice(ToolChoice::Auto)
+        .with_parallel_tool_calls(false);
+    let (converted, _) = chat.to_responses();
+    assert_eq!(converted.tool_choice, Some(json!("auto")));
+    assert_eq!(converted.parallel_tool_calls, Some(false));
+}
+
+#[test]
+fn test_validation_rejects_tool_choice_without_matching_tool() {
+    let request = ChatCompletionRequest::new("gpt-5", vec![ChatMessage::user("Hi")])
+        .with_tool_choice(ToolChoice::Required);
+    assert!(request.validate_openai_compatibility().is_err());
+
+    let request = request
+        .with_tools(vec![weather_tool()])
+        .with_tool_choice(ToolChoice::function("get_time"));
+    let err = request.validate_openai_compatibility().unwrap_err();
+    assert!(err.contains("get_time"));
+
+    let request = request
+        .with_tool_choice(ToolChoice::function("get_weather"))
+        .with_parallel_tool_calls(true);
+    assert!(request.validate_openai_compatibility().is_ok());
+    assert_eq!(
+        serde_json::to_value(&request).unwrap()["parallel_tool_calls"],
+        true
+    );
+}
@@ -0,0 +1,164 @@
+use rainy_sdk::{OpenAIMessageContent, OpenAIToolCall, ToolErrorKind, ToolExecutor, ToolOutcome};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn call(id: &str, name: &str, arguments: &str) -> OpenAIToolCall {
+    serde_json::from_value(json!({
+        "id": id,
+        "type": "function",
+        "function": {"name": name, "arguments": arguments}
+    }))
+    .unwrap()
+}
+
+fn sleeper() -> ToolExecutor {
+    ToolExecutor::new().with_tool("sleep", |args: Value| async move {
+        let ms = args["ms"].as_u64().unwrap_or(0);
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+        Ok(json!({ "slept": ms }))
+    })
+}
+
+#[tokio::test]
+async fn test_runs_concurrently_and_keeps_call_order() {
+    let calls = vec![
+        call("call_1", "sleep", r#"{"ms": 300}"#),
+        call("call_2", "sleep", r#"{"ms": 100}"#),
+        call("call_3", "sleep", r#"{"ms": 200}"#),
+    ];
+    let start = Instant::now();
+    let executions = sleeper().execute(&calls).await;
+    assert!(start.elapsed() < Duration::from_millis(550));
+
+    let ids: Vec<&str> = executions.iter().map(|e| e.tool_call_id.as_str()).collect();
+    assert_eq!(ids, vec!["call_1", "call_2", "call_3"]);
+    assert_eq!(
+        executions[1].outcome,
+        ToolOutcome::Success {
+            output: json!({"slept": 100})
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_concurrency_cap_limits_calls_in_flight() {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let (current, max) = (in_flight.clone(), peak.clone());
+    let executor =
+        ToolExecutor::new()
+            .with_concurrency(2)
+            .with_tool("work", move |_args: Value| {
+                let (current, max) = (current.clone(), max.clone());
+                async move {
+                    let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+                    max.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(30)).await;
+                    current.fetch_sub(1, Ordering::SeqCst);
+                    Ok(Value::Null)
+                }
+            });
+    let calls: Vec<OpenAIToolCall> = (0..6)
+        .map(|i| call(&format!("call_{i}"), "work", ""))
+        .collect();
+    let executions = executor.execute(&calls).await;
+    assert!(executions.iter().all(|e| e.is_success()));
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_failures_become_structured_tool_errors() {
+    let executor = sleeper()
+        .with_tool_timeout("sleep", Duration::from_millis(50))
+        .with_tool(
+            "fail",
+            |_args: Value| async move { Err("disk full".into()) },
+        )
+        .with_tool("boom", |_args: Value| async move {
+            if true {
+                panic!("handler exploded");
+            }
+            Ok(Value::Null)
+        });
+    let calls = vec![
+        call("a", "missing", "{}"),
+        call("b", "fail", "{not json"),
+        call("c", "fail", "{}"),
+        call("d", "boom", "{}"),
+        call("e", "sleep", r#"{"ms": 5000}"#),
+    ];
+    let executions = executor.execute(&calls).await;
+    let kinds: Vec<ToolErrorKind> = executions
+        .iter()
+        .map(|e| match &e.outcome {
+            ToolOutcome::Error { kind, .. } => *kind,
+            other => panic!("unexpected outcome {other:?}"),
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ToolErrorKind::UnknownTool,
+            ToolErrorKind::InvalidArguments,
+            ToolErrorKind::Failed,
+            ToolErrorKind::Panicked,
+            ToolErrorKind::Timeout,
+        ]
+    );
+
+    let payload: Value = serde_json::from_str(&executions[3].content()).unwrap();
+    assert_eq!(payload["error"]["type"], "panicked");
+    assert!(payload["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("handler exploded"));
+    assert!(executions[2].content().contains("disk full"));
+}
+
+#[tokio::test]
+async fn test_messages_answer_each_call() {
+    let executor =
+        ToolExecutor::new().with_tool(
+            "echo",
+            |args: Value| async move { Ok(args["text"].clone()) },
+        );
+    let messages = executor
+        .execute_messages(&[
+            call("call_1", "echo", r#"{"text": "hi"}"#),
+            call("call_2", "echo", r#"{"text": {"nested": true}}"#),
+        ])
+        .await;
+
+    assert_eq!(messages[0].tool_call_id.as_deref(), Some("call_1"));
+    assert_eq!(
+        messages[0].content,
+        Some(OpenAIMessageContent::Text("hi".to_string()))
+    );
+    assert_eq!(
+        messages[1].content,
+        Some(OpenAIMessageContent::Text(r#"{"nested":true}"#.to_string()))
+    );
+    assert!(executor.has_tool("echo"));
+}
+
+#[tokio::test]
+async fn test_cancelling_execute_aborts_running_tools() {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let counter = finished.clone();
+    let executor = ToolExecutor::new().with_tool("slow", move |_args: Value| {
+        let counter = counter.clone();
+        async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(Value::Null)
+        }
+    });
+    let calls = vec![call("call_1", "slow", ""), call("call_2", "slow", "")];
+
+    let cancelled = tokio::time::timeout(Duration::from_millis(20), executor.execute(&calls)).await;
+    assert!(cancelled.is_err());
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert_eq!(finished.load(Ordering::SeqCst), 0);
+}